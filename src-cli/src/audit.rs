@@ -0,0 +1,156 @@
+//! Reading the engine's audit log (`flowstt audit`).
+//!
+//! The log is a local file, so these commands work without the engine.
+
+use colored::Colorize;
+use flowstt_common::logging::{audit_log_path, rotated_audit_log_path, AuditEntry};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// How often `--follow` checks the log for new entries
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Print the last `count` audit entries, then new ones as they are written
+/// if `follow` is set.
+pub async fn tail(count: usize, follow: bool, json: bool) -> Result<(), String> {
+    let path = audit_log_path();
+    let mut lines = read_lines(&rotated_audit_log_path(1))?;
+    lines.extend(read_lines(&path)?);
+
+    if lines.is_empty() && !follow {
+        if !json {
+            println!("No audit entries in {:?}", path);
+        }
+        return Ok(());
+    }
+    for line in last(&lines, count) {
+        print_line(line, json);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    loop {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        let len = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if len < offset {
+            // Rotated: the new file starts from scratch
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+
+        let mut file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| e.to_string())?;
+        let mut new = String::new();
+        file.read_to_string(&mut new).map_err(|e| e.to_string())?;
+        // Leave a partially written last line for the next round
+        let Some(end) = new.rfind('\n') else {
+            continue;
+        };
+        for line in new[..end].lines() {
+            print_line(line, json);
+        }
+        offset += end as u64 + 1;
+    }
+}
+
+fn read_lines(path: &Path) -> Result<Vec<String>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.lines().map(str::to_string).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {:?}: {}", path, e)),
+    }
+}
+
+fn last(lines: &[String], count: usize) -> &[String] {
+    &lines[lines.len().saturating_sub(count)..]
+}
+
+fn print_line(line: &str, json: bool) {
+    if json {
+        println!("{}", line);
+        return;
+    }
+    match serde_json::from_str::<AuditEntry>(line) {
+        Ok(entry) => println!("{}", format_entry(&entry)),
+        // Keep entries written by other versions visible
+        Err(_) => println!("{}", line.dimmed()),
+    }
+}
+
+fn format_entry(entry: &AuditEntry) -> String {
+    let client = match (entry.pid, &entry.executable) {
+        (Some(pid), Some(exe)) => format!("pid {} {}", pid, exe.display()),
+        (Some(pid), None) => format!("pid {}", pid),
+        (None, Some(exe)) => exe.display().to_string(),
+        (None, None) => "-".to_string(),
+    };
+    let outcome = if entry.success {
+        "ok".green().to_string()
+    } else {
+        let code = entry
+            .error_code
+            .and_then(|c| serde_json::to_value(c).ok())
+            .and_then(|v| v.as_str().map(|c| format!(" ({})", c)))
+            .unwrap_or_default();
+        format!(
+            "{}{}: {}",
+            "failed".red(),
+            code,
+            entry.error.as_deref().unwrap_or("")
+        )
+    };
+    format!(
+        "{} {:<4} {} {} {}",
+        entry.timestamp.dimmed(),
+        entry.transport,
+        entry.request.bold(),
+        client.cyan(),
+        outcome
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::ipc::ErrorCode;
+
+    #[test]
+    fn entries_are_formatted() {
+        colored::control::set_override(false);
+        let mut entry = AuditEntry {
+            timestamp: "2026-01-01T00:00:00+00:00".into(),
+            transport: "ipc".into(),
+            pid: Some(42),
+            executable: Some("/usr/bin/flowstt".into()),
+            request: "set_sources".into(),
+            success: true,
+            error_code: None,
+            error: None,
+        };
+        assert_eq!(
+            format_entry(&entry),
+            "2026-01-01T00:00:00+00:00 ipc  set_sources pid 42 /usr/bin/flowstt ok"
+        );
+
+        entry.transport = "http".into();
+        entry.pid = None;
+        entry.executable = None;
+        entry.success = false;
+        entry.error_code = Some(ErrorCode::DeviceNotFound);
+        entry.error = Some("Device not found: hw:9".into());
+        assert_eq!(
+            format_entry(&entry),
+            "2026-01-01T00:00:00+00:00 http set_sources - failed (device_not_found): Device not found: hw:9"
+        );
+
+        let lines: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(last(&lines, 2), ["b", "c"]);
+        assert_eq!(last(&lines, 5).len(), 3);
+    }
+}
@@ -1,16 +1,33 @@
 //! IPC client for communicating with the FlowSTT application.
 
-use flowstt_common::ipc::{get_socket_path, read_json, write_json, IpcError, Request, Response};
+use flowstt_common::ipc::{
+    get_socket_path, read_json, write_json, EventTopic, IpcError, Request, RequestEnvelope,
+    Response, ResponseEnvelope, PROTOCOL_VERSION,
+};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::time::Duration;
+use tokio::io::AsyncRead;
+
+#[cfg(unix)]
+type Stream = tokio::net::UnixStream;
+#[cfg(windows)]
+type Stream = tokio::net::windows::named_pipe::NamedPipeClient;
 
 /// IPC client for communicating with the FlowSTT application.
+///
+/// Requests and events share one connection: when the engine supports
+/// request IDs, events that arrive while waiting for a response are queued
+/// for `read_event()`.
 pub struct Client {
-    #[cfg(unix)]
-    stream: Option<tokio::net::UnixStream>,
-    #[cfg(windows)]
-    stream: Option<tokio::net::windows::named_pipe::NamedPipeClient>,
+    stream: Option<Stream>,
+    /// Whether the engine echoes request IDs (from the `Hello` capabilities)
+    request_ids: bool,
+    /// ID of the next request
+    next_request_id: u64,
+    /// Events received while waiting for a response
+    pending_events: VecDeque<Response>,
 }
 
 impl Client {
@@ -18,33 +35,72 @@ impl Client {
     pub fn new() -> Self {
         Self {
             stream: None,
+            request_ids: false,
+            next_request_id: 1,
+            pending_events: VecDeque::new(),
         }
     }
 
-    /// Connect to the application.
+    /// Connect to the application and check that it speaks our protocol version.
     pub async fn connect(&mut self) -> Result<(), IpcError> {
+        self.open().await?;
+        self.hello().await
+    }
+
+    /// Open the socket without the handshake.
+    async fn open(&mut self) -> Result<(), IpcError> {
         let socket_path = get_socket_path();
 
         #[cfg(unix)]
-        {
-            let stream = tokio::net::UnixStream::connect(&socket_path)
-                .await
-                .map_err(IpcError::Io)?;
-            self.stream = Some(stream);
-        }
+        let stream = tokio::net::UnixStream::connect(&socket_path)
+            .await
+            .map_err(IpcError::Io)?;
 
         #[cfg(windows)]
-        {
-            use tokio::net::windows::named_pipe::ClientOptions;
-            let stream = ClientOptions::new()
-                .open(&socket_path)
-                .map_err(IpcError::Io)?;
-            self.stream = Some(stream);
-        }
+        let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+            .open(&socket_path)
+            .map_err(IpcError::Io)?;
 
+        self.stream = Some(stream);
+        self.request_ids = false;
+        self.pending_events.clear();
         Ok(())
     }
 
+    /// Exchange `Hello` messages and check protocol compatibility.
+    async fn hello(&mut self) -> Result<(), IpcError> {
+        let request = Request::Hello {
+            protocol_version: PROTOCOL_VERSION,
+            client_name: "flowstt-cli".into(),
+        };
+        match self.request(request).await {
+            Ok(Response::Hello {
+                protocol_version,
+                capabilities,
+            }) if protocol_version == PROTOCOL_VERSION => {
+                self.request_ids = capabilities.iter().any(|c| c == "request_ids");
+                Ok(())
+            }
+            Ok(Response::Hello {
+                protocol_version, ..
+            }) => Err(IpcError::IncompatibleVersion {
+                local: PROTOCOL_VERSION,
+                remote: protocol_version,
+            }),
+            // Engines from before the handshake close the connection on
+            // requests they cannot parse
+            Ok(Response::UnsupportedRequest { .. }) | Err(IpcError::ConnectionClosed) => {
+                Err(IpcError::IncompatibleVersion {
+                    local: PROTOCOL_VERSION,
+                    remote: 0,
+                })
+            }
+            Ok(Response::Error { message, .. }) => Err(IpcError::ParseError(message)),
+            Ok(_) => Err(IpcError::ParseError("Unexpected response".into())),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Check if the application is running.
     #[allow(dead_code)]
     pub async fn is_app_running() -> bool {
@@ -55,9 +111,10 @@ impl Client {
     /// Try to connect, spawning the application in headless mode if needed.
     /// Returns Ok if connected, Err if connection/spawn failed.
     pub async fn connect_or_spawn(&mut self) -> Result<(), IpcError> {
-        // First try to connect
-        if self.connect().await.is_ok() {
-            return Ok(());
+        // First try to connect. A running application that fails the
+        // handshake is reported rather than replaced.
+        if self.open().await.is_ok() {
+            return self.hello().await;
         }
 
         // Application not running, try to spawn it in headless mode
@@ -67,8 +124,8 @@ impl Client {
         // Wait for application to be ready (up to 5 seconds)
         for _ in 0..50 {
             tokio::time::sleep(Duration::from_millis(100)).await;
-            if self.connect().await.is_ok() {
-                return Ok(());
+            if self.open().await.is_ok() {
+                return self.hello().await;
             }
         }
 
@@ -77,70 +134,85 @@ impl Client {
         ))
     }
 
-    /// Send a request and receive a response.
-    pub async fn request(&mut self, request: Request) -> Result<Response, IpcError> {
-        #[cfg(unix)]
-        {
-            let stream = self
-                .stream
-                .as_mut()
-                .ok_or_else(|| IpcError::ParseError("Not connected".into()))?;
-            let (mut reader, mut writer) = stream.split();
-            write_json(&mut writer, &request).await?;
-            read_json(&mut reader).await
-        }
+    fn stream(&mut self) -> Result<&mut Stream, IpcError> {
+        self.stream
+            .as_mut()
+            .ok_or_else(|| IpcError::ParseError("Not connected".into()))
+    }
 
-        #[cfg(windows)]
-        {
-            let stream = self
-                .stream
-                .as_mut()
-                .ok_or_else(|| IpcError::ParseError("Not connected".into()))?;
-            let (mut reader, mut writer) = tokio::io::split(stream);
-            write_json(&mut writer, &request).await?;
-            read_json(&mut reader).await
-        }
+    /// Send a request and receive its response.
+    pub async fn request(&mut self, request: Request) -> Result<Response, IpcError> {
+        let request_id = self.request_ids.then(|| {
+            self.next_request_id += 1;
+            self.next_request_id - 1
+        });
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| IpcError::ParseError("Not connected".into()))?;
+        write_json(
+            stream,
+            &RequestEnvelope {
+                request_id,
+                request,
+            },
+        )
+        .await?;
+        read_response(stream, request_id, &mut self.pending_events).await
     }
 
     /// Ping the application.
     pub async fn ping(&mut self) -> Result<bool, IpcError> {
         match self.request(Request::Ping).await? {
             Response::Pong => Ok(true),
-            Response::Error { message } => Err(IpcError::ParseError(message)),
+            Response::Error { message, .. } => Err(IpcError::ParseError(message)),
             _ => Err(IpcError::ParseError("Unexpected response".into())),
         }
     }
 
-    /// Subscribe to events. After this, use `read_event()` to read events.
-    pub async fn subscribe_events(&mut self) -> Result<(), IpcError> {
-        let response = self.request(Request::SubscribeEvents).await?;
+    /// Subscribe to events of the given topics (all if empty). After this,
+    /// use `read_event()` to read events.
+    pub async fn subscribe_events(&mut self, topics: Vec<EventTopic>) -> Result<(), IpcError> {
+        let response = self
+            .request(Request::SubscribeEvents {
+                topics,
+                max_rate_hz: None,
+            })
+            .await?;
         match response {
             Response::Subscribed => Ok(()),
-            Response::Error { message } => Err(IpcError::ParseError(message)),
+            Response::Error { message, .. } => Err(IpcError::ParseError(message)),
             _ => Err(IpcError::ParseError("Failed to subscribe to events".into())),
         }
     }
 
     /// Read the next event from the stream (blocking).
     pub async fn read_event(&mut self) -> Result<Response, IpcError> {
-        #[cfg(unix)]
-        {
-            let stream = self
-                .stream
-                .as_mut()
-                .ok_or_else(|| IpcError::ParseError("Not connected".into()))?;
-            let (mut reader, _) = stream.split();
-            read_json(&mut reader).await
+        if let Some(event) = self.pending_events.pop_front() {
+            return Ok(event);
         }
+        let message: ResponseEnvelope = read_json(self.stream()?).await?;
+        Ok(message.response)
+    }
+}
 
-        #[cfg(windows)]
-        {
-            let stream = self
-                .stream
-                .as_mut()
-                .ok_or_else(|| IpcError::ParseError("Not connected".into()))?;
-            let (mut reader, _) = tokio::io::split(stream);
-            read_json(&mut reader).await
+/// Read messages until the response to `request_id` arrives, queueing events
+/// that arrive first. Without an ID, the next message that is not an event is
+/// the response.
+async fn read_response<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    request_id: Option<u64>,
+    events: &mut VecDeque<Response>,
+) -> Result<Response, IpcError> {
+    loop {
+        let message: ResponseEnvelope = read_json(reader).await?;
+        match message.response {
+            event @ Response::Event { .. } if message.request_id.is_none() => {
+                events.push_back(event)
+            }
+            response if message.request_id == request_id => return Ok(response),
+            // Response to an earlier request that was abandoned
+            _ => {}
         }
     }
 }
@@ -200,3 +272,66 @@ fn spawn_app() -> Result<Child, IpcError> {
             ))
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::ipc::EventType;
+
+    #[tokio::test]
+    async fn responses_are_matched_by_request_id() {
+        let messages = [
+            (
+                None,
+                Response::Event {
+                    event: EventType::SpeechStarted,
+                },
+            ),
+            (Some(1), Response::Pong),
+            (
+                None,
+                Response::Event {
+                    event: EventType::PttPressed,
+                },
+            ),
+            (Some(2), Response::Subscribed),
+            (None, Response::Ok),
+        ];
+        let mut data = Vec::new();
+        for (request_id, response) in messages {
+            write_json(
+                &mut data,
+                &ResponseEnvelope {
+                    request_id,
+                    response,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let mut reader = std::io::Cursor::new(data);
+        let mut events = VecDeque::new();
+        let response = read_response(&mut reader, Some(2), &mut events).await;
+        assert!(matches!(response, Ok(Response::Subscribed)));
+        assert!(matches!(
+            events.make_contiguous(),
+            [
+                Response::Event {
+                    event: EventType::SpeechStarted
+                },
+                Response::Event {
+                    event: EventType::PttPressed
+                }
+            ]
+        ));
+
+        // Without IDs the next non-event message is the response
+        let response = read_response(&mut reader, None, &mut events).await;
+        assert!(matches!(response, Ok(Response::Ok)));
+        assert!(matches!(
+            read_response(&mut reader, None, &mut events).await,
+            Err(IpcError::ConnectionClosed)
+        ));
+    }
+}
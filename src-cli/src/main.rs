@@ -3,13 +3,14 @@
 //! This is the command-line interface for FlowSTT voice transcription.
 //! It communicates with the background service via IPC.
 
+mod audit;
 mod client;
 
 use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use flowstt_common::config::Config;
-use flowstt_common::ipc::{EventType, Request, Response};
-use flowstt_common::{runtime_mode, AudioSourceType, ConfigValues, HotkeyCombination, KeyCode, RecordingMode, TranscriptionMode};
+use flowstt_common::ipc::{ErrorCode, EventTopic, EventType, Request, Response};
+use flowstt_common::{runtime_mode, AudioSourceType, ConfigValues, HotkeyCombination, KeyCode, RecordingMode, SegmentationConfig, SourceSettings, TranscriptionMode};
 
 use client::Client;
 
@@ -65,9 +66,33 @@ enum Commands {
         #[arg(long)]
         aec: bool,
 
-        /// Recording mode (mix or echo-cancel)
+        /// Recording mode (mixed, echo-cancel or speaker-separated)
         #[arg(short, long, default_value = "mixed")]
         mode: RecordingModeArg,
+
+        /// Gain for the primary source in dB
+        #[arg(long, value_name = "DB", allow_hyphen_values = true)]
+        source1_gain: Option<f32>,
+
+        /// Device channels to record from the primary source (1-based, e.g. "2" or "1,2")
+        #[arg(long, value_name = "LIST", value_delimiter = ',')]
+        source1_channels: Vec<u16>,
+
+        /// Mute the primary source
+        #[arg(long)]
+        source1_mute: bool,
+
+        /// Gain for the secondary source in dB
+        #[arg(long, value_name = "DB", allow_hyphen_values = true)]
+        source2_gain: Option<f32>,
+
+        /// Device channels to record from the secondary source (1-based, e.g. "2" or "1,2")
+        #[arg(long, value_name = "LIST", value_delimiter = ',')]
+        source2_channels: Vec<u16>,
+
+        /// Mute the secondary source
+        #[arg(long)]
+        source2_mute: bool,
     },
 
     /// Get current transcription status
@@ -104,6 +129,12 @@ enum Commands {
     /// Stop the background service
     Shutdown,
 
+    /// Inspect the audit log of state-changing client requests
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+
     /// Show version information
     Version,
 }
@@ -118,6 +149,8 @@ enum SourceFilter {
 enum RecordingModeArg {
     Mixed,
     EchoCancel,
+    /// Transcribe source1 ("me") and source2 ("remote") separately
+    SpeakerSeparated,
 }
 
 #[derive(Subcommand)]
@@ -126,6 +159,20 @@ enum ModelAction {
     Download,
 }
 
+#[derive(Subcommand)]
+enum AuditAction {
+    /// Show the most recent audit entries
+    Tail {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value = "20")]
+        lines: usize,
+
+        /// Keep printing new entries as they are written
+        #[arg(short, long)]
+        follow: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Display all persisted configuration values
@@ -133,22 +180,29 @@ enum ConfigAction {
 
     /// Get the value of a configuration key
     Get {
-        /// Configuration key (transcription_mode, ptt_hotkeys)
+        /// Configuration key (transcription_mode, ptt_hotkeys, auto_toggle_hotkeys, segmentation)
         key: String,
     },
 
     /// Set the value of a configuration key
     Set {
-        /// Configuration key (transcription_mode, ptt_hotkeys)
+        /// Configuration key (transcription_mode, ptt_hotkeys, auto_toggle_hotkeys, segmentation)
         key: String,
 
-        /// Value to set (e.g. "automatic", "push_to_talk", or JSON for ptt_hotkeys)
+        /// Value to set (e.g. "automatic", "push_to_talk", "toggle_to_talk", or JSON for ptt_hotkeys)
         value: String,
     },
 }
 
 /// Valid configuration key names.
-const VALID_CONFIG_KEYS: &[&str] = &["transcription_mode", "ptt_hotkeys", "auto_toggle_hotkeys"];
+const VALID_CONFIG_KEYS: &[&str] = &[
+    "transcription_mode",
+    "ptt_hotkeys",
+    "auto_toggle_hotkeys",
+    "toggle_talk_hotkeys",
+    "toggle_talk_silence_timeout_ms",
+    "segmentation",
+];
 
 /// Error with an associated exit code.
 struct CliError {
@@ -171,6 +225,23 @@ impl CliError {
     fn usage(message: impl Into<String>) -> Self {
         Self::new(message, 64)
     }
+
+    /// Error reported by the engine, with a sysexits-style exit code for
+    /// the kinds of errors scripts may want to handle.
+    fn engine(code: ErrorCode, message: impl Into<String>) -> Self {
+        let exit_code = match code {
+            ErrorCode::Internal | ErrorCode::AlreadyExists => 1,
+            ErrorCode::InvalidArgument => 65,
+            ErrorCode::NoSource
+            | ErrorCode::ModelMissing
+            | ErrorCode::DeviceNotFound
+            | ErrorCode::NotFound => 66,
+            ErrorCode::Unavailable => 69,
+            ErrorCode::QueueFull => 75,
+            ErrorCode::PermissionDenied => 77,
+        };
+        Self::new(message, exit_code)
+    }
 }
 
 impl From<String> for CliError {
@@ -209,6 +280,15 @@ async fn run(cli: Cli) -> Result<(), CliError> {
         return handle_config(&mut client, action, &cli).await;
     }
 
+    // Audit log is a local file (works offline)
+    if let Commands::Audit {
+        action: AuditAction::Tail { lines, follow },
+    } = cli.command
+    {
+        let json = matches!(cli.format, OutputFormat::Json);
+        return Ok(audit::tail(lines, follow, json).await?);
+    }
+
     // Handle setup command
     if matches!(cli.command, Commands::Setup) {
         return handle_setup(&mut client, &cli).await;
@@ -264,7 +344,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         }
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -274,6 +354,12 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             source2,
             aec,
             mode,
+            source1_gain,
+            source1_channels,
+            source1_mute,
+            source2_gain,
+            source2_channels,
+            source2_mute,
         } => {
             if source1.is_none() && source2.is_none() {
                 return Err(
@@ -285,6 +371,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             let recording_mode = match mode {
                 RecordingModeArg::Mixed => RecordingMode::Mixed,
                 RecordingModeArg::EchoCancel => RecordingMode::EchoCancel,
+                RecordingModeArg::SpeakerSeparated => RecordingMode::SpeakerSeparated,
             };
 
             // Set AEC and recording mode first
@@ -304,6 +391,16 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                 .request(Request::SetSources {
                     source1_id: source1.clone(),
                     source2_id: source2.clone(),
+                    source1_settings: source_settings_arg(
+                        *source1_gain,
+                        source1_channels.clone(),
+                        *source1_mute,
+                    ),
+                    source2_settings: source_settings_arg(
+                        *source2_gain,
+                        source2_channels.clone(),
+                        *source2_mute,
+                    ),
                 })
                 .await
                 .map_err(|e| e.to_string())?;
@@ -315,15 +412,9 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         println!("Press Ctrl+C to stop, or run 'flowstt stop'");
                     }
 
-                    // Create a dedicated event client (separate connection)
-                    let mut event_client = Client::new();
-                    event_client
-                        .connect_or_spawn()
-                        .await
-                        .map_err(|e| format!("Failed to connect event client: {}", e))?;
-
-                    event_client
-                        .subscribe_events()
+                    // Events arrive on the same connection as requests
+                    client
+                        .subscribe_events(vec![EventTopic::Transcription, EventTopic::State])
                         .await
                         .map_err(|e| format!("Failed to subscribe: {}", e))?;
 
@@ -340,17 +431,22 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                                 }
                                 break;
                             }
-                            event_result = event_client.read_event() => {
+                            event_result = client.read_event() => {
                                 match event_result {
                                     Ok(Response::Event { event }) => {
                                         match event {
                                             EventType::TranscriptionComplete(result) => {
                                                 if matches!(cli.format, OutputFormat::Json) {
                                                     println!("{}", serde_json::to_string(&result).unwrap());
+                                                } else if let Some(speaker) = result.speaker {
+                                                    println!("[{}] {}", speaker.label(), result.text);
                                                 } else {
                                                     println!("{}", result.text);
                                                 }
                                             }
+                                            EventType::TranscriptionError { error } => {
+                                                eprintln!("{}: {}", "Transcription error".red(), error);
+                                            }
                                             EventType::SpeechStarted => {
                                                 if cli.verbose {
                                                     eprintln!("{}", "[speech started]".dimmed());
@@ -393,7 +489,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         }
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -419,6 +515,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         let mode_str = match status.transcription_mode {
                             TranscriptionMode::Automatic => "automatic",
                             TranscriptionMode::PushToTalk => "push-to-talk",
+                            TranscriptionMode::ToggleToTalk => "toggle-to-talk",
                         };
                         println!("Mode: {}", mode_str);
 
@@ -450,7 +547,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         }
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -461,6 +558,8 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                 .request(Request::SetSources {
                     source1_id: None,
                     source2_id: None,
+                    source1_settings: None,
+                    source2_settings: None,
                 })
                 .await
                 .map_err(|e| e.to_string())?;
@@ -471,7 +570,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         println!("{}", "Capture stopped".green());
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -494,12 +593,14 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                                 println!("{}", "Model download started".green());
                             }
                         }
-                        Response::Error { message } => {
-                            if message.contains("already downloaded") {
-                                println!("{}", "Model already downloaded".yellow());
-                            } else {
-                                return Err(message.into());
-                            }
+                        Response::Error {
+                            code: ErrorCode::AlreadyExists,
+                            ..
+                        } => {
+                            println!("{}", "Model already downloaded".yellow());
+                        }
+                        Response::Error { code, message } => {
+                            return Err(CliError::engine(code, message));
                         }
                         _ => return Err("Unexpected response".into()),
                     }
@@ -532,7 +633,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                                 }
                             }
                         }
-                        Response::Error { message } => return Err(message.into()),
+                        Response::Error { code, message } => return Err(CliError::engine(code, message)),
                         _ => return Err("Unexpected response".into()),
                     }
                 }
@@ -568,7 +669,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         println!("  {}", status.system_info.dimmed());
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -597,7 +698,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         println!("{}", "Service shutdown initiated".green());
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -619,6 +720,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         let mode_str = match status.mode {
                             TranscriptionMode::Automatic => "Automatic",
                             TranscriptionMode::PushToTalk => "Push-to-Talk",
+                            TranscriptionMode::ToggleToTalk => "Toggle-to-Talk",
                         };
                         if !cli.quiet {
                             println!("{} transcription mode: {}", "Toggled".green().bold(), mode_str);
@@ -634,7 +736,7 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
                         }
                     }
                 }
-                Response::Error { message } => return Err(message.into()),
+                Response::Error { code, message } => return Err(CliError::engine(code, message)),
                 _ => return Err("Unexpected response".into()),
             }
         }
@@ -649,6 +751,11 @@ async fn run_command(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
             unreachable!()
         }
 
+        Commands::Audit { .. } => {
+            // Already handled above
+            unreachable!()
+        }
+
         Commands::Version => {
             // Already handled above
             unreachable!()
@@ -681,7 +788,7 @@ async fn get_config_values(client: &mut Client) -> Result<ConfigValues, CliError
             .map_err(|e| e.to_string())?;
         match response {
             Response::ConfigValues(values) => return Ok(values),
-            Response::Error { message } => return Err(CliError::general(message)),
+            Response::Error { code, message } => return Err(CliError::engine(code, message)),
             _ => return Err(CliError::general("Unexpected response from service")),
         }
     }
@@ -692,8 +799,38 @@ async fn get_config_values(client: &mut Client) -> Result<ConfigValues, CliError
         transcription_mode: config.transcription_mode,
         ptt_hotkeys: config.ptt_hotkeys,
         auto_toggle_hotkeys: config.auto_toggle_hotkeys,
+        toggle_talk_hotkeys: config.toggle_talk_hotkeys,
+        toggle_talk_silence_timeout_ms: config.toggle_talk_silence_timeout_ms,
         auto_paste_enabled: config.auto_paste_enabled,
         auto_paste_delay_ms: config.auto_paste_delay_ms,
+        agc_enabled: config.agc_enabled,
+        agc_target_level_db: config.agc_target_level_db,
+        long_form_enabled: config.long_form_enabled,
+        segmentation: config.segmentation,
+        wake_word_enabled: config.wake_word_enabled,
+        wake_word: config.wake_word,
+        voice_commands_enabled: config.voice_commands_enabled,
+        voice_commands: config.voice_commands,
+        text_processing: config.text_processing,
+        hooks: config.hooks,
+        http_api_enabled: config.http_api_enabled,
+        http_api_port: config.http_api_port,
+    })
+}
+
+/// Build per-source settings from CLI flags (None when all flags are defaults).
+fn source_settings_arg(
+    gain_db: Option<f32>,
+    channels: Vec<u16>,
+    muted: bool,
+) -> Option<SourceSettings> {
+    if gain_db.is_none() && channels.is_empty() && !muted {
+        return None;
+    }
+    Some(SourceSettings {
+        gain_db: gain_db.unwrap_or(0.0),
+        muted,
+        channels,
     })
 }
 
@@ -723,6 +860,28 @@ fn format_hotkeys_display(hotkeys: &[HotkeyCombination]) -> String {
     }
 }
 
+/// Config value of a transcription mode.
+fn transcription_mode_key(mode: TranscriptionMode) -> &'static str {
+    match mode {
+        TranscriptionMode::Automatic => "automatic",
+        TranscriptionMode::PushToTalk => "push_to_talk",
+        TranscriptionMode::ToggleToTalk => "toggle_to_talk",
+    }
+}
+
+/// Format segmentation limits for human-readable display.
+fn format_segmentation_display(config: &SegmentationConfig) -> String {
+    format!(
+        "ring_buffer_secs={} max_segment_duration_ms={} word_break_grace_ms={} min_segment_duration_ms={} min_audio_rms={} max_queue_size={}",
+        config.ring_buffer_secs,
+        config.max_segment_duration_ms,
+        config.word_break_grace_ms,
+        config.min_segment_duration_ms,
+        config.min_audio_rms,
+        config.max_queue_size
+    )
+}
+
 /// Handle `config show` -- display all config values.
 async fn handle_config_show(client: &mut Client, cli: &Cli) -> Result<(), CliError> {
     let values = get_config_values(client).await?;
@@ -733,11 +892,11 @@ async fn handle_config_show(client: &mut Client, cli: &Cli) -> Result<(), CliErr
             serde_json::to_string_pretty(&values).map_err(|e| e.to_string())?
         );
     } else {
-        let mode_str = match values.transcription_mode {
-            TranscriptionMode::Automatic => "automatic",
-            TranscriptionMode::PushToTalk => "push_to_talk",
-        };
-        println!("{}: {}", "transcription_mode".bold(), mode_str);
+        println!(
+            "{}: {}",
+            "transcription_mode".bold(),
+            transcription_mode_key(values.transcription_mode)
+        );
         println!(
             "{}: {}",
             "ptt_hotkeys".bold(),
@@ -748,6 +907,21 @@ async fn handle_config_show(client: &mut Client, cli: &Cli) -> Result<(), CliErr
             "auto_toggle_hotkeys".bold(),
             format_hotkeys_display(&values.auto_toggle_hotkeys)
         );
+        println!(
+            "{}: {}",
+            "toggle_talk_hotkeys".bold(),
+            format_hotkeys_display(&values.toggle_talk_hotkeys)
+        );
+        println!(
+            "{}: {}",
+            "toggle_talk_silence_timeout_ms".bold(),
+            values.toggle_talk_silence_timeout_ms
+        );
+        println!(
+            "{}: {}",
+            "segmentation".bold(),
+            format_segmentation_display(&values.segmentation)
+        );
     }
 
     Ok(())
@@ -772,11 +946,7 @@ async fn handle_config_get(
                         .map_err(|e| e.to_string())?
                 );
             } else {
-                let mode_str = match values.transcription_mode {
-                    TranscriptionMode::Automatic => "automatic",
-                    TranscriptionMode::PushToTalk => "push_to_talk",
-                };
-                println!("{}", mode_str);
+                println!("{}", transcription_mode_key(values.transcription_mode));
             }
         }
         "ptt_hotkeys" => {
@@ -801,6 +971,31 @@ async fn handle_config_get(
                 println!("{}", format_hotkeys_display(&values.auto_toggle_hotkeys));
             }
         }
+        "toggle_talk_hotkeys" => {
+            if matches!(cli.format, OutputFormat::Json) {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&values.toggle_talk_hotkeys)
+                        .map_err(|e| e.to_string())?
+                );
+            } else {
+                println!("{}", format_hotkeys_display(&values.toggle_talk_hotkeys));
+            }
+        }
+        "toggle_talk_silence_timeout_ms" => {
+            println!("{}", values.toggle_talk_silence_timeout_ms);
+        }
+        "segmentation" => {
+            if matches!(cli.format, OutputFormat::Json) {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&values.segmentation)
+                        .map_err(|e| e.to_string())?
+                );
+            } else {
+                println!("{}", format_segmentation_display(&values.segmentation));
+            }
+        }
         _ => unreachable!(), // validate_config_key already checked
     }
 
@@ -824,9 +1019,10 @@ async fn handle_config_set(
             let mode = match value {
                 "automatic" => TranscriptionMode::Automatic,
                 "push_to_talk" => TranscriptionMode::PushToTalk,
+                "toggle_to_talk" => TranscriptionMode::ToggleToTalk,
                 _ => {
                     return Err(CliError::usage(format!(
-                        "Invalid value '{}' for transcription_mode. Expected: automatic, push_to_talk",
+                        "Invalid value '{}' for transcription_mode. Expected: automatic, push_to_talk, toggle_to_talk",
                         value
                     )));
                 }
@@ -839,7 +1035,7 @@ async fn handle_config_set(
                     .map_err(|e| e.to_string())?;
                 match response {
                     Response::Ok => {}
-                    Response::Error { message } => return Err(CliError::general(message)),
+                    Response::Error { code, message } => return Err(CliError::engine(code, message)),
                     _ => return Err(CliError::general("Unexpected response")),
                 }
             } else {
@@ -878,7 +1074,7 @@ async fn handle_config_set(
                     .map_err(|e| e.to_string())?;
                 match response {
                     Response::Ok => {}
-                    Response::Error { message } => return Err(CliError::general(message)),
+                    Response::Error { code, message } => return Err(CliError::engine(code, message)),
                     _ => return Err(CliError::general("Unexpected response")),
                 }
             } else {
@@ -920,7 +1116,7 @@ async fn handle_config_set(
                     .map_err(|e| e.to_string())?;
                 match response {
                     Response::Ok => {}
-                    Response::Error { message } => return Err(CliError::general(message)),
+                    Response::Error { code, message } => return Err(CliError::engine(code, message)),
                     _ => return Err(CliError::general("Unexpected response")),
                 }
             } else {
@@ -940,6 +1136,139 @@ async fn handle_config_set(
                 );
             }
         }
+        "toggle_talk_hotkeys" => {
+            let hotkeys: Vec<HotkeyCombination> = if value == "null" || value == "none" || value == "[]" {
+                vec![]
+            } else {
+                serde_json::from_str(value).map_err(|e| {
+                    CliError::usage(format!(
+                        "Invalid JSON for toggle_talk_hotkeys: {}\nExpected format: {} or []",
+                        e,
+                        r#"[{"keys":["f15"]}]"#
+                    ))
+                })?
+            };
+
+            if service_available {
+                let response = client
+                    .request(Request::SetToggleTalkHotkeys {
+                        hotkeys: hotkeys.clone(),
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match response {
+                    Response::Ok => {}
+                    Response::Error { code, message } => return Err(CliError::engine(code, message)),
+                    _ => return Err(CliError::general("Unexpected response")),
+                }
+            } else {
+                // Offline: write directly to config file
+                let mut config = Config::load();
+                config.toggle_talk_hotkeys = hotkeys.clone();
+                config
+                    .save()
+                    .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+            }
+
+            if !cli.quiet {
+                println!(
+                    "{} toggle_talk_hotkeys = {}",
+                    "Set".green().bold(),
+                    format_hotkeys_display(&hotkeys)
+                );
+            }
+        }
+        "toggle_talk_silence_timeout_ms" => {
+            let timeout_ms: u32 = value.parse().map_err(|_| {
+                CliError::usage(format!(
+                    "Invalid value '{}' for toggle_talk_silence_timeout_ms. Expected milliseconds (0 disables)",
+                    value
+                ))
+            })?;
+            let request = Request::SetToggleTalkSilenceTimeout { timeout_ms };
+            request.validate().map_err(CliError::usage)?;
+
+            if service_available {
+                let response = client.request(request).await.map_err(|e| e.to_string())?;
+                match response {
+                    Response::Ok => {}
+                    Response::Error { code, message } => return Err(CliError::engine(code, message)),
+                    _ => return Err(CliError::general("Unexpected response")),
+                }
+            } else {
+                // Offline: write directly to config file
+                let mut config = Config::load();
+                config.toggle_talk_silence_timeout_ms = timeout_ms;
+                config
+                    .save()
+                    .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+            }
+
+            if !cli.quiet {
+                println!(
+                    "{} toggle_talk_silence_timeout_ms = {}",
+                    "Set".green().bold(),
+                    timeout_ms
+                );
+            }
+        }
+        "segmentation" => {
+            // Fields given in the JSON object override the current limits
+            let overrides: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(value).map_err(|e| {
+                    CliError::usage(format!(
+                        "Invalid JSON for segmentation: {}\nExpected format: {}",
+                        e,
+                        r#"'{"ring_buffer_secs":120,"max_queue_size":20}'"#
+                    ))
+                })?;
+            let current = get_config_values(client).await?.segmentation;
+            let mut merged = serde_json::to_value(&current).map_err(|e| e.to_string())?;
+            if let Some(fields) = merged.as_object_mut() {
+                for (key, field) in overrides {
+                    if !fields.contains_key(&key) {
+                        return Err(CliError::usage(format!(
+                            "Unknown segmentation field '{}'. Valid fields: {}",
+                            key,
+                            fields.keys().cloned().collect::<Vec<_>>().join(", ")
+                        )));
+                    }
+                    fields.insert(key, field);
+                }
+            }
+            let segmentation: SegmentationConfig = serde_json::from_value(merged)
+                .map_err(|e| CliError::usage(format!("Invalid segmentation value: {}", e)))?;
+            segmentation.validate().map_err(CliError::usage)?;
+
+            if service_available {
+                let response = client
+                    .request(Request::SetSegmentationConfig {
+                        config: segmentation.clone(),
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match response {
+                    Response::Ok => {}
+                    Response::Error { code, message } => return Err(CliError::engine(code, message)),
+                    _ => return Err(CliError::general("Unexpected response")),
+                }
+            } else {
+                // Offline: write directly to config file
+                let mut config = Config::load();
+                config.segmentation = segmentation.clone();
+                config
+                    .save()
+                    .map_err(|e| CliError::general(format!("Failed to save config: {}", e)))?;
+            }
+
+            if !cli.quiet {
+                println!(
+                    "{} segmentation = {}",
+                    "Set".green().bold(),
+                    format_segmentation_display(&segmentation)
+                );
+            }
+        }
         _ => unreachable!(), // validate_config_key already checked
     }
 
@@ -1024,7 +1353,7 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
                             stdout.flush().unwrap();
                         }
                     }
-                    Response::Error { message } => {
+                    Response::Error { message, .. } => {
                         println!("  Download failed: {}", message.red());
                     }
                     _ => {}
@@ -1078,27 +1407,39 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
         "  2: {} - hold a key to transcribe (default)",
         "Push-to-Talk".cyan()
     );
-    print!("  Select mode [1-2, default=2]: ");
+    println!(
+        "  3: {} - tap a key to start, tap again (or pause) to stop",
+        "Toggle-to-Talk".cyan()
+    );
+    print!("  Select mode [1-3, default=2]: ");
     stdout.flush().unwrap();
     let mut answer = String::new();
     stdin.lock().read_line(&mut answer).unwrap();
 
     let mode = match answer.trim() {
         "1" => TranscriptionMode::Automatic,
+        "3" => TranscriptionMode::ToggleToTalk,
         _ => TranscriptionMode::PushToTalk,
     };
 
     let mode_name = match mode {
         TranscriptionMode::Automatic => "Automatic",
         TranscriptionMode::PushToTalk => "Push-to-Talk",
+        TranscriptionMode::ToggleToTalk => "Toggle-to-Talk",
     };
     println!("  Selected: {}", mode_name.green());
 
     let mut hotkey = HotkeyCombination::single(KeyCode::default());
 
-    if mode == TranscriptionMode::PushToTalk {
+    if mode.is_hotkey_driven() {
+        let key_label = if mode == TranscriptionMode::ToggleToTalk {
+            "Toggle-to-talk key"
+        } else {
+            "PTT key"
+        };
         print!(
-            "  PTT key [default=RightAlt, or type key name e.g. f5, left_control]: "
+            "  {} [default=RightAlt, or type key name e.g. f5, left_control]: ",
+            key_label
         );
         stdout.flush().unwrap();
         let mut key_answer = String::new();
@@ -1110,7 +1451,7 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
             match serde_json::from_str::<KeyCode>(&key_json) {
                 Ok(key) => {
                     hotkey = HotkeyCombination::single(key);
-                    println!("  PTT key: {}", key_str.green());
+                    println!("  {}: {}", key_label, key_str.green());
                 }
                 Err(_) => {
                     println!(
@@ -1121,7 +1462,7 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
                 }
             }
         } else {
-            println!("  PTT key: {}", "RightAlt".green());
+            println!("  {}: {}", key_label, "RightAlt".green());
         }
     }
 
@@ -1160,11 +1501,18 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
 
     // --- Save config ---
     println!("\n{}", "Saving configuration...".bold());
+    let defaults = Config::default_with_hotkeys();
+    let (ptt_hotkeys, toggle_talk_hotkeys) = if mode == TranscriptionMode::ToggleToTalk {
+        (defaults.ptt_hotkeys.clone(), vec![hotkey])
+    } else {
+        (vec![hotkey], vec![])
+    };
     let config = Config {
         transcription_mode: mode,
-        ptt_hotkeys: vec![hotkey],
+        ptt_hotkeys,
         auto_toggle_hotkeys: toggle_hotkeys,
-        ..Config::default_with_hotkeys()
+        toggle_talk_hotkeys,
+        ..defaults
     };
     config
         .save()
@@ -1176,6 +1524,17 @@ async fn handle_setup(client: &mut Client, _cli: &Cli) -> Result<(), CliError> {
             .request(Request::SetSources {
                 source1_id: Some(device_id.clone()),
                 source2_id: None,
+                source1_settings: None,
+                source2_settings: None,
+            })
+            .await;
+    }
+
+    // Toggle-to-talk needs its hotkey on the service before the mode
+    if mode == TranscriptionMode::ToggleToTalk {
+        let _ = client
+            .request(Request::SetToggleTalkHotkeys {
+                hotkeys: config.toggle_talk_hotkeys.clone(),
             })
             .await;
     }
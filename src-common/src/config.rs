@@ -10,7 +10,13 @@ use std::fs;
 use std::io;
 use std::path::PathBuf;
 
-use crate::types::{HotkeyCombination, KeyCode, TranscriptionMode};
+use crate::types::{
+    default_voice_commands, validate_hooks, validate_voice_commands, HotkeyCombination, KeyCode,
+    SegmentationConfig, SourceSettings, TextProcessingConfig, TranscriptionHook, TranscriptionMode,
+    VoiceCommand, DEFAULT_AGC_TARGET_LEVEL_DB, DEFAULT_HTTP_API_PORT,
+    DEFAULT_TOGGLE_TALK_SILENCE_TIMEOUT_MS, DEFAULT_WAKE_WORD, MAX_AGC_TARGET_LEVEL_DB,
+    MAX_TOGGLE_TALK_SILENCE_TIMEOUT_MS, MAX_WAKE_WORD_LEN, MIN_AGC_TARGET_LEVEL_DB,
+};
 
 /// Theme mode for the application UI.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -25,7 +31,7 @@ pub enum ThemeMode {
 /// Service configuration that persists across restarts.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Config {
-    /// Current transcription mode (Automatic or PushToTalk)
+    /// Current transcription mode (Automatic, PushToTalk or ToggleToTalk)
     #[serde(default)]
     pub transcription_mode: TranscriptionMode,
     /// Configured push-to-talk hotkey combinations (new format)
@@ -34,6 +40,13 @@ pub struct Config {
     /// Configured auto-mode toggle hotkeys
     #[serde(default = "default_auto_toggle_hotkeys")]
     pub auto_toggle_hotkeys: Vec<HotkeyCombination>,
+    /// Configured toggle-to-talk hotkey combinations
+    #[serde(default)]
+    pub toggle_talk_hotkeys: Vec<HotkeyCombination>,
+    /// Silence in milliseconds after which a toggle-to-talk recording ends
+    /// (0 disables the timeout)
+    #[serde(default = "default_toggle_talk_silence_timeout_ms")]
+    pub toggle_talk_silence_timeout_ms: u32,
     /// Whether auto-paste into the foreground application is enabled
     #[serde(default = "default_auto_paste_enabled")]
     pub auto_paste_enabled: bool,
@@ -43,12 +56,67 @@ pub struct Config {
     /// UI theme mode: auto (follow OS), light, or dark
     #[serde(default)]
     pub theme_mode: ThemeMode,
+    /// Whether automatic gain control and segment normalization are enabled
+    #[serde(default = "default_agc_enabled")]
+    pub agc_enabled: bool,
+    /// Target RMS level in dBFS for gain control and normalization
+    #[serde(default = "default_agc_target_level_db")]
+    pub agc_target_level_db: f32,
+    /// Whether long speech in Automatic mode is transcribed as stitched
+    /// ~30s windows instead of ~4s segments
+    #[serde(default)]
+    pub long_form_enabled: bool,
+    /// Ring buffer and segment limits
+    #[serde(default)]
+    pub segmentation: SegmentationConfig,
+    /// Whether Automatic mode text is only pasted after the wake phrase
+    #[serde(default)]
+    pub wake_word_enabled: bool,
+    /// Wake phrase that must start an Automatic mode transcript
+    #[serde(default = "default_wake_word")]
+    pub wake_word: String,
+    /// Whether spoken commands ("comma", "new line", ...) are interpreted
+    #[serde(default)]
+    pub voice_commands_enabled: bool,
+    /// Voice command grammar
+    #[serde(default = "default_voice_commands")]
+    pub voice_commands: Vec<VoiceCommand>,
+    /// Text post-processing pipeline
+    #[serde(default)]
+    pub text_processing: TextProcessingConfig,
+    /// External commands run for each completed transcription
+    #[serde(default)]
+    pub hooks: Vec<TranscriptionHook>,
+    /// Whether the localhost HTTP/WebSocket API is enabled
+    #[serde(default)]
+    pub http_api_enabled: bool,
+    /// Localhost port of the HTTP/WebSocket API
+    #[serde(default = "default_http_api_port")]
+    pub http_api_port: u16,
+    /// Gain, mute and channel selection for the primary source
+    #[serde(default)]
+    pub source1_settings: SourceSettings,
+    /// Gain, mute and channel selection for the secondary source
+    #[serde(default)]
+    pub source2_settings: SourceSettings,
 }
 
 fn default_auto_toggle_hotkeys() -> Vec<HotkeyCombination> {
     vec![]
 }
 
+fn default_toggle_talk_silence_timeout_ms() -> u32 {
+    DEFAULT_TOGGLE_TALK_SILENCE_TIMEOUT_MS
+}
+
+fn default_wake_word() -> String {
+    DEFAULT_WAKE_WORD.to_string()
+}
+
+fn default_http_api_port() -> u16 {
+    DEFAULT_HTTP_API_PORT
+}
+
 fn default_auto_paste_enabled() -> bool {
     true
 }
@@ -57,6 +125,14 @@ fn default_auto_paste_delay_ms() -> u32 {
     50
 }
 
+fn default_agc_enabled() -> bool {
+    true
+}
+
+fn default_agc_target_level_db() -> f32 {
+    DEFAULT_AGC_TARGET_LEVEL_DB
+}
+
 /// Legacy configuration format for backward-compatible loading.
 #[derive(Debug, Deserialize)]
 struct LegacyConfig {
@@ -70,12 +146,44 @@ struct LegacyConfig {
     auto_toggle_hotkey: Option<HotkeyCombination>,
     /// New multi auto-toggle hotkeys field
     auto_toggle_hotkeys: Option<Vec<HotkeyCombination>>,
+    /// Toggle-to-talk hotkeys (may be absent in old configs)
+    toggle_talk_hotkeys: Option<Vec<HotkeyCombination>>,
+    /// Toggle-to-talk silence timeout (may be absent in old configs)
+    toggle_talk_silence_timeout_ms: Option<u32>,
     /// Whether auto-paste is enabled (may be absent in old configs)
     auto_paste_enabled: Option<bool>,
     /// Auto-paste delay in ms (may be absent in old configs)
     auto_paste_delay_ms: Option<u32>,
     /// UI theme mode (may be absent in old configs)
     theme_mode: Option<ThemeMode>,
+    /// Whether gain control is enabled (may be absent in old configs)
+    agc_enabled: Option<bool>,
+    /// Gain control target level (may be absent in old configs)
+    agc_target_level_db: Option<f32>,
+    /// Whether long-form segmentation is enabled (may be absent in old configs)
+    long_form_enabled: Option<bool>,
+    /// Segmentation limits (may be absent in old configs)
+    segmentation: Option<SegmentationConfig>,
+    /// Whether the wake-word gate is enabled (may be absent in old configs)
+    wake_word_enabled: Option<bool>,
+    /// Wake phrase (may be absent in old configs)
+    wake_word: Option<String>,
+    /// Whether voice commands are enabled (may be absent in old configs)
+    voice_commands_enabled: Option<bool>,
+    /// Voice command grammar (may be absent in old configs)
+    voice_commands: Option<Vec<VoiceCommand>>,
+    /// Text post-processing pipeline (may be absent in old configs)
+    text_processing: Option<TextProcessingConfig>,
+    /// Transcription hooks (may be absent in old configs)
+    hooks: Option<Vec<TranscriptionHook>>,
+    /// Whether the HTTP API is enabled (may be absent in old configs)
+    http_api_enabled: Option<bool>,
+    /// HTTP API port (may be absent in old configs)
+    http_api_port: Option<u16>,
+    /// Primary source mixing settings (may be absent in old configs)
+    source1_settings: Option<SourceSettings>,
+    /// Secondary source mixing settings (may be absent in old configs)
+    source2_settings: Option<SourceSettings>,
 }
 
 impl Config {
@@ -149,9 +257,25 @@ impl Config {
             transcription_mode: TranscriptionMode::default(),
             ptt_hotkeys: vec![HotkeyCombination::single(KeyCode::default())],
             auto_toggle_hotkeys: vec![],
+            toggle_talk_hotkeys: vec![],
+            toggle_talk_silence_timeout_ms: DEFAULT_TOGGLE_TALK_SILENCE_TIMEOUT_MS,
             auto_paste_enabled: true,
             auto_paste_delay_ms: 50,
             theme_mode: ThemeMode::default(),
+            agc_enabled: default_agc_enabled(),
+            agc_target_level_db: default_agc_target_level_db(),
+            long_form_enabled: false,
+            segmentation: SegmentationConfig::default(),
+            wake_word_enabled: false,
+            wake_word: default_wake_word(),
+            voice_commands_enabled: false,
+            voice_commands: default_voice_commands(),
+            text_processing: TextProcessingConfig::default(),
+            hooks: Vec::new(),
+            http_api_enabled: false,
+            http_api_port: DEFAULT_HTTP_API_PORT,
+            source1_settings: SourceSettings::default(),
+            source2_settings: SourceSettings::default(),
         }
     }
 
@@ -187,9 +311,60 @@ impl Config {
             transcription_mode: legacy.transcription_mode,
             ptt_hotkeys,
             auto_toggle_hotkeys,
+            toggle_talk_hotkeys: legacy.toggle_talk_hotkeys.unwrap_or_default(),
+            toggle_talk_silence_timeout_ms: legacy
+                .toggle_talk_silence_timeout_ms
+                .filter(|ms| *ms <= MAX_TOGGLE_TALK_SILENCE_TIMEOUT_MS)
+                .unwrap_or_else(default_toggle_talk_silence_timeout_ms),
             auto_paste_enabled: legacy.auto_paste_enabled.unwrap_or(true),
             auto_paste_delay_ms: legacy.auto_paste_delay_ms.unwrap_or(50),
             theme_mode: legacy.theme_mode.unwrap_or_default(),
+            agc_enabled: legacy.agc_enabled.unwrap_or_else(default_agc_enabled),
+            agc_target_level_db: legacy
+                .agc_target_level_db
+                .filter(|db| (MIN_AGC_TARGET_LEVEL_DB..=MAX_AGC_TARGET_LEVEL_DB).contains(db))
+                .unwrap_or_else(default_agc_target_level_db),
+            long_form_enabled: legacy.long_form_enabled.unwrap_or(false),
+            // Hand-edited limits that fail validation fall back to defaults
+            segmentation: legacy
+                .segmentation
+                .filter(|s| s.validate().is_ok())
+                .unwrap_or_default(),
+            wake_word_enabled: legacy.wake_word_enabled.unwrap_or(false),
+            wake_word: legacy
+                .wake_word
+                .filter(|w| !w.trim().is_empty() && w.chars().count() <= MAX_WAKE_WORD_LEN)
+                .unwrap_or_else(default_wake_word),
+            voice_commands_enabled: legacy.voice_commands_enabled.unwrap_or(false),
+            // A hand-edited grammar that fails validation falls back to the default
+            voice_commands: legacy
+                .voice_commands
+                .filter(|c| validate_voice_commands(c).is_ok())
+                .unwrap_or_else(default_voice_commands),
+            text_processing: legacy
+                .text_processing
+                .filter(|t| t.validate().is_ok())
+                .unwrap_or_default(),
+            // Invalid hooks are dropped rather than run with bad settings
+            hooks: legacy
+                .hooks
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|h| validate_hooks(std::slice::from_ref(h)).is_ok())
+                .collect(),
+            http_api_enabled: legacy.http_api_enabled.unwrap_or(false),
+            http_api_port: legacy
+                .http_api_port
+                .filter(|port| *port != 0)
+                .unwrap_or_else(default_http_api_port),
+            source1_settings: legacy
+                .source1_settings
+                .filter(|s| s.validate().is_ok())
+                .unwrap_or_default(),
+            source2_settings: legacy
+                .source2_settings
+                .filter(|s| s.validate().is_ok())
+                .unwrap_or_default(),
         }
     }
 }
@@ -277,4 +452,153 @@ mod tests {
 
         assert_eq!(config.auto_toggle_hotkeys.len(), 2);
     }
+
+    #[test]
+    fn test_invalid_segmentation_falls_back_to_defaults() {
+        let json = r#"{"segmentation": {"ring_buffer_secs": 120, "max_queue_size": 0}}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert_eq!(config.segmentation, SegmentationConfig::default());
+
+        let json = r#"{"segmentation": {"ring_buffer_secs": 120}}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert_eq!(config.segmentation.ring_buffer_secs, 120);
+        assert_eq!(config.segmentation.max_queue_size, 10);
+    }
+
+    #[test]
+    fn test_source_settings_fields() {
+        let json = r#"{"source1_settings": {"gain_db": 6.0, "channels": [2]}, "source2_settings": {"gain_db": 99.0}}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert_eq!(config.source1_settings.gain_db, 6.0);
+        assert_eq!(config.source1_settings.channels, vec![2]);
+        assert_eq!(config.source2_settings, SourceSettings::default());
+
+        let legacy: LegacyConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            Config::from_legacy(legacy).source1_settings,
+            SourceSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_toggle_to_talk_fields() {
+        let json = r#"{"transcription_mode": "toggle_to_talk", "toggle_talk_hotkeys": [{"keys": ["f15"]}]}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+
+        assert_eq!(config.transcription_mode, TranscriptionMode::ToggleToTalk);
+        assert_eq!(config.toggle_talk_hotkeys[0].keys, vec![KeyCode::F15]);
+        assert_eq!(
+            config.toggle_talk_silence_timeout_ms,
+            DEFAULT_TOGGLE_TALK_SILENCE_TIMEOUT_MS
+        );
+    }
+
+    #[test]
+    fn test_legacy_missing_agc_fields() {
+        let json = r#"{"transcription_mode": "automatic"}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+
+        assert!(config.agc_enabled);
+        assert_eq!(config.agc_target_level_db, -20.0);
+
+        // Out-of-range hand-edited targets fall back to the default
+        for (target, expected) in [("-12.5", -12.5), ("0.0", -20.0), ("-90", -20.0)] {
+            let json = format!(r#"{{"agc_target_level_db": {}}}"#, target);
+            let legacy: LegacyConfig = serde_json::from_str(&json).unwrap();
+            assert_eq!(Config::from_legacy(legacy).agc_target_level_db, expected);
+        }
+    }
+
+    #[test]
+    fn test_wake_word_fields() {
+        let json = r#"{"transcription_mode": "automatic"}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert!(!config.wake_word_enabled);
+        assert_eq!(config.wake_word, DEFAULT_WAKE_WORD);
+
+        let json = r#"{"wake_word_enabled": true, "wake_word": "  "}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert!(config.wake_word_enabled);
+        assert_eq!(config.wake_word, DEFAULT_WAKE_WORD);
+    }
+
+    #[test]
+    fn test_voice_commands_grammar() {
+        let json = r#"{"voice_commands_enabled": true, "voice_commands": [
+            {"phrase": "next line", "action": "insert", "text": "\n"},
+            {"phrase": "send it", "action": "key", "key": "enter"},
+            {"phrase": "oops", "action": "delete_that"}
+        ]}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert!(config.voice_commands_enabled);
+        assert_eq!(config.voice_commands.len(), 3);
+        assert_eq!(
+            config.voice_commands[1].action,
+            crate::types::VoiceCommandAction::Key {
+                key: crate::types::EditKey::Enter
+            }
+        );
+
+        // Duplicate phrases are rejected in favour of the default grammar
+        let json = r#"{"voice_commands": [
+            {"phrase": "Comma", "action": "punctuation", "text": ","},
+            {"phrase": "comma.", "action": "punctuation", "text": ";"}
+        ]}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert!(!config.voice_commands_enabled);
+        assert_eq!(config.voice_commands, default_voice_commands());
+    }
+
+    #[test]
+    fn test_text_processing_profiles() {
+        let json = r#"{"text_processing": {
+            "stages": [{"stage": "capitalization"}, {"stage": "filler_removal"}],
+            "profiles": [{"mode": "push_to_talk", "stages": [{"stage": "number_formatting"}]}]
+        }}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        let text = &config.text_processing;
+        assert_eq!(
+            text.stages_for(TranscriptionMode::PushToTalk),
+            &[crate::types::TextStage::NumberFormatting]
+        );
+        assert_eq!(text.stages_for(TranscriptionMode::Automatic).len(), 2);
+        assert_eq!(
+            text.stages[1],
+            crate::types::TextStage::FillerRemoval {
+                words: crate::types::default_filler_words()
+            }
+        );
+
+        // Old configs keep the built-in FlowSTT spelling fix
+        let legacy: LegacyConfig = serde_json::from_str("{}").unwrap();
+        let config = Config::from_legacy(legacy);
+        assert_eq!(config.text_processing, TextProcessingConfig::default());
+    }
+
+    #[test]
+    fn test_hooks_defaults_and_invalid_hooks() {
+        let json = r#"{"hooks": [
+            {"name": "log", "command": "logger"},
+            {"name": "broken", "command": " "},
+            {"name": "slow", "command": "fmt", "timeout_ms": 600000, "replace_text": true}
+        ]}"#;
+        let legacy: LegacyConfig = serde_json::from_str(json).unwrap();
+        let config = Config::from_legacy(legacy);
+        assert_eq!(config.hooks.len(), 1);
+        let hook = &config.hooks[0];
+        assert_eq!(hook.name, "log");
+        assert!(hook.args.is_empty());
+        assert_eq!(hook.timeout_ms, crate::types::DEFAULT_HOOK_TIMEOUT_MS);
+        assert!(hook.enabled && !hook.replace_text);
+    }
 }
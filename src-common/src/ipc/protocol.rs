@@ -3,8 +3,38 @@
 use std::path::PathBuf;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
-/// Maximum IPC message size (64 KB)
-pub const MAX_MESSAGE_SIZE: usize = 65536;
+/// Maximum size of one frame (64 KB). Larger messages are split into
+/// continuation frames.
+pub const MAX_FRAME_SIZE: usize = 65536;
+
+/// Maximum IPC message size across all of its frames (64 MB). Only
+/// `TranscribeAudio` requests may be larger than [`MAX_REQUEST_SIZE`].
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Maximum size of a request other than `TranscribeAudio` (1 MB)
+pub const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+
+/// Bit of the length prefix marking a frame that is followed by another frame
+/// of the same message
+const CONTINUATION_FLAG: u32 = 1 << 31;
+
+/// IPC protocol version exchanged in the `Hello` handshake.
+///
+/// Only incremented for breaking changes to the message format or to existing
+/// requests; additions are advertised through [`CAPABILITIES`] instead.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Optional protocol features supported by this build, reported to clients in
+/// `Response::Hello`.
+pub const CAPABILITIES: &[&str] = &[
+    "subscribe_events",
+    "request_ids",
+    "event_topics",
+    "chunked_messages",
+    "http_api",
+    "openai_transcriptions",
+    "transcribe_audio",
+];
 
 /// Error type for IPC operations.
 #[derive(Debug)]
@@ -15,8 +45,13 @@ pub enum IpcError {
     MessageTooLarge { size: usize, max: usize },
     /// JSON parsing failed
     ParseError(String),
+    /// The framing of a message is malformed
+    InvalidFrame(String),
     /// Connection closed
     ConnectionClosed,
+    /// The peer speaks a different protocol version (`remote` is 0 for
+    /// engines from before the `Hello` handshake)
+    IncompatibleVersion { local: u32, remote: u32 },
 }
 
 impl std::fmt::Display for IpcError {
@@ -27,7 +62,14 @@ impl std::fmt::Display for IpcError {
                 write!(f, "Message too large: {} bytes (max {})", size, max)
             }
             IpcError::ParseError(e) => write!(f, "Parse error: {}", e),
+            IpcError::InvalidFrame(e) => write!(f, "Invalid frame: {}", e),
             IpcError::ConnectionClosed => write!(f, "Connection closed"),
+            IpcError::IncompatibleVersion { local, remote } => write!(
+                f,
+                "Incompatible protocol version: FlowSTT speaks v{}, this client speaks v{}; \
+                 restart FlowSTT or update the client",
+                remote, local
+            ),
         }
     }
 }
@@ -67,37 +109,76 @@ pub fn get_socket_path() -> PathBuf {
     }
 }
 
+/// Get the path of the per-user token file that authenticates clients of the
+/// localhost HTTP/WebSocket API.
+///
+/// The file lives next to the config file and is created by the engine with
+/// owner-only permissions:
+/// - Linux: ~/.config/flowstt/api-token
+/// - macOS: ~/Library/Application Support/flowstt/api-token
+/// - Windows: %APPDATA%\flowstt\api-token
+pub fn get_api_token_path() -> PathBuf {
+    directories::BaseDirs::new()
+        .map(|d| d.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("flowstt")
+        .join("api-token")
+}
+
 /// Read a length-prefixed message with size validation.
 ///
-/// Message format:
+/// Frame format:
 /// ```text
 /// ┌──────────────────┬─────────────────────────────────┐
 /// │ Length (4 bytes) │ JSON Payload (variable length)  │
 /// │ Little-endian    │ Max 65,536 bytes                │
 /// └──────────────────┴─────────────────────────────────┘
 /// ```
+///
+/// A message larger than [`MAX_FRAME_SIZE`] is sent as several frames. The
+/// high bit of the length is set on every frame but the last, and the
+/// payloads are concatenated. Each frame and the running total are checked
+/// before anything is allocated for them.
 pub async fn read_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, IpcError> {
-    // Read 4-byte length prefix
-    let mut len_buf = [0u8; 4];
-    reader.read_exact(&mut len_buf).await?;
-    let len = u32::from_le_bytes(len_buf) as usize;
+    let mut buf = Vec::new();
+    loop {
+        // Read 4-byte length prefix
+        let mut len_buf = [0u8; 4];
+        reader.read_exact(&mut len_buf).await?;
+        let prefix = u32::from_le_bytes(len_buf);
+        let more = prefix & CONTINUATION_FLAG != 0;
+        let len = (prefix & !CONTINUATION_FLAG) as usize;
 
-    // Validate size BEFORE allocating
-    if len > MAX_MESSAGE_SIZE {
-        return Err(IpcError::MessageTooLarge {
-            size: len,
-            max: MAX_MESSAGE_SIZE,
-        });
-    }
+        // Validate size BEFORE allocating
+        if len > MAX_FRAME_SIZE {
+            return Err(IpcError::MessageTooLarge {
+                size: len,
+                max: MAX_FRAME_SIZE,
+            });
+        }
+        if buf.len() + len > MAX_MESSAGE_SIZE {
+            return Err(IpcError::MessageTooLarge {
+                size: buf.len() + len,
+                max: MAX_MESSAGE_SIZE,
+            });
+        }
+        if more && len == 0 {
+            return Err(IpcError::InvalidFrame("empty continuation frame".into()));
+        }
 
-    // Read payload
-    let mut buf = vec![0u8; len];
-    reader.read_exact(&mut buf).await?;
+        // Read payload
+        let start = buf.len();
+        buf.resize(start + len, 0);
+        reader.read_exact(&mut buf[start..]).await?;
 
-    Ok(buf)
+        if !more {
+            return Ok(buf);
+        }
+    }
 }
 
-/// Write a length-prefixed message.
+/// Write a length-prefixed message, split into frames if it is larger than
+/// [`MAX_FRAME_SIZE`].
 pub async fn write_message<W: AsyncWrite + Unpin>(
     writer: &mut W,
     data: &[u8],
@@ -109,12 +190,22 @@ pub async fn write_message<W: AsyncWrite + Unpin>(
         });
     }
 
-    // Write 4-byte length prefix
-    let len = data.len() as u32;
-    writer.write_all(&len.to_le_bytes()).await?;
+    let mut frames = data.chunks(MAX_FRAME_SIZE).peekable();
+    if frames.peek().is_none() {
+        // Empty message
+        writer.write_all(&0u32.to_le_bytes()).await?;
+    }
+    while let Some(frame) = frames.next() {
+        // Write 4-byte length prefix
+        let mut prefix = frame.len() as u32;
+        if frames.peek().is_some() {
+            prefix |= CONTINUATION_FLAG;
+        }
+        writer.write_all(&prefix.to_le_bytes()).await?;
 
-    // Write payload
-    writer.write_all(data).await?;
+        // Write payload
+        writer.write_all(frame).await?;
+    }
     writer.flush().await?;
 
     Ok(())
@@ -165,4 +256,120 @@ mod tests {
         let result = write_message(&mut buf, &oversized).await;
         assert!(matches!(result, Err(IpcError::MessageTooLarge { .. })));
     }
+
+    /// Deterministic xorshift generator for the fuzz-style tests
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    fn frame(len: u32, more: bool, payload: &[u8]) -> Vec<u8> {
+        let prefix = if more { len | CONTINUATION_FLAG } else { len };
+        let mut frame = prefix.to_le_bytes().to_vec();
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[tokio::test]
+    async fn test_chunked_roundtrip() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for size in [
+            0,
+            1,
+            MAX_FRAME_SIZE - 1,
+            MAX_FRAME_SIZE,
+            MAX_FRAME_SIZE + 1,
+            3 * MAX_FRAME_SIZE + 7,
+        ] {
+            let original: Vec<u8> = (0..size).map(|_| rng.next() as u8).collect();
+            let mut buf = Vec::new();
+            write_message(&mut buf, &original).await.unwrap();
+            write_message(&mut buf, b"next").await.unwrap();
+
+            let frames = size.div_ceil(MAX_FRAME_SIZE).max(1);
+            assert_eq!(buf.len(), size + 4 * frames + 8);
+
+            let mut cursor = Cursor::new(buf);
+            assert_eq!(read_message(&mut cursor).await.unwrap(), original);
+            assert_eq!(read_message(&mut cursor).await.unwrap(), b"next");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_frames() {
+        let oversized_frame = frame(MAX_FRAME_SIZE as u32 + 1, false, &[]);
+        let empty_continuation = frame(0, true, &[]);
+        let truncated_prefix = vec![1, 0];
+        let truncated_payload = frame(10, false, b"abc");
+        let missing_continuation = frame(3, true, b"abc");
+
+        let result = read_message(&mut Cursor::new(oversized_frame)).await;
+        assert!(matches!(result, Err(IpcError::MessageTooLarge { .. })));
+        let result = read_message(&mut Cursor::new(empty_continuation)).await;
+        assert!(matches!(result, Err(IpcError::InvalidFrame(_))));
+        for data in [truncated_prefix, truncated_payload, missing_continuation] {
+            let result = read_message(&mut Cursor::new(data)).await;
+            assert!(matches!(result, Err(IpcError::ConnectionClosed)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_total_size_checked_before_reading() {
+        // Continuation frames up to the limit, then a frame header without
+        // its payload: the reader must reject that frame from its length alone
+        // rather than wait for (or allocate) the payload.
+        let full = vec![0u8; MAX_FRAME_SIZE];
+        let mut data = Vec::new();
+        for _ in 0..MAX_MESSAGE_SIZE / MAX_FRAME_SIZE {
+            data.extend(frame(MAX_FRAME_SIZE as u32, true, &full));
+        }
+        data.extend(frame(1, false, &[]));
+
+        let result = read_message(&mut Cursor::new(data)).await;
+        assert!(matches!(
+            result,
+            Err(IpcError::MessageTooLarge { size, max: MAX_MESSAGE_SIZE }) if size == MAX_MESSAGE_SIZE + 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fuzzed_frames_never_panic() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut valid = Vec::new();
+        let message: Vec<u8> = (0..2 * MAX_FRAME_SIZE + 100).map(|i| i as u8).collect();
+        write_message(&mut valid, &message).await.unwrap();
+
+        for _ in 0..500 {
+            let mut data = valid.clone();
+            match rng.below(3) {
+                // Flip random bytes, which hits prefixes as well as payloads
+                0 => {
+                    for _ in 0..1 + rng.below(8) {
+                        let i = rng.below(data.len());
+                        data[i] ^= rng.next() as u8 | 1;
+                    }
+                }
+                // Truncate anywhere
+                1 => data.truncate(rng.below(data.len())),
+                // Random garbage
+                _ => {
+                    let len = rng.below(64);
+                    data = (0..len).map(|_| rng.next() as u8).collect();
+                }
+            }
+            if let Ok(read) = read_message(&mut Cursor::new(data)).await {
+                assert!(read.len() <= MAX_MESSAGE_SIZE);
+            }
+        }
+    }
 }
@@ -2,12 +2,35 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::types::{AudioSourceType, HotkeyCombination, RecordingMode, TranscriptionMode};
+use super::EventTopic;
+
+use crate::types::{
+    validate_hooks, validate_voice_commands, AudioBytes, AudioEncoding, AudioSourceType,
+    HotkeyCombination, RecordingMode, SegmentationConfig, SourceSettings, TextProcessingConfig,
+    TranscriptionHook, TranscriptionMode, VoiceCommand, MAX_AGC_TARGET_LEVEL_DB,
+    MAX_TOGGLE_TALK_SILENCE_TIMEOUT_MS, MAX_WAKE_WORD_LEN, MIN_AGC_TARGET_LEVEL_DB,
+};
+
+/// Lowest sample rate accepted by `Request::TranscribeAudio`
+pub const MIN_AUDIO_SAMPLE_RATE: u32 = 8000;
+/// Highest sample rate accepted by `Request::TranscribeAudio`
+pub const MAX_AUDIO_SAMPLE_RATE: u32 = 192_000;
+/// Most channels accepted by `Request::TranscribeAudio`
+pub const MAX_AUDIO_CHANNELS: u16 = 8;
 
 /// IPC request from client to service.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Request {
+    // === Handshake ===
+    /// Announce the client and its protocol version (answered with `Response::Hello`)
+    Hello {
+        /// The client's `PROTOCOL_VERSION`
+        protocol_version: u32,
+        /// Client name for logging (e.g. "flowstt-cli")
+        client_name: String,
+    },
+
     // === Device Enumeration ===
     /// List all audio devices
     ListDevices {
@@ -25,6 +48,12 @@ pub enum Request {
         /// Secondary audio source ID (system audio for mixing/AEC)
         #[serde(skip_serializing_if = "Option::is_none")]
         source2_id: Option<String>,
+        /// Gain, mute and channel selection for the primary source (unchanged if omitted)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source1_settings: Option<SourceSettings>,
+        /// Gain, mute and channel selection for the secondary source (unchanged if omitted)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source2_settings: Option<SourceSettings>,
     },
 
     // === Audio Settings ===
@@ -37,7 +66,31 @@ pub enum Request {
     /// Get current transcription status
     GetStatus,
     /// Subscribe to real-time events (visualization, transcription results)
-    SubscribeEvents,
+    SubscribeEvents {
+        /// Topics to receive; all topics if empty. `Shutdown` is always sent.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        topics: Vec<EventTopic>,
+        /// Maximum rate of visualization events per second (unlimited if omitted)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_rate_hz: Option<f32>,
+    },
+
+    // === Client Audio ===
+    /// Transcribe raw PCM audio sent by the client. Answered with
+    /// `Response::TranscriptionQueued`; the result follows as an
+    /// `AudioTranscriptionComplete` event with the same job ID, sent only to
+    /// this connection, so subscribe to the transcription topic first. Large
+    /// payloads are split into continuation frames by the protocol.
+    TranscribeAudio {
+        /// Sample rate in Hz
+        sample_rate: u32,
+        /// Number of interleaved channels
+        channels: u16,
+        /// Sample format of `data`
+        encoding: AudioEncoding,
+        /// Interleaved samples, base64-encoded in JSON
+        data: AudioBytes,
+    },
 
     // === Model Management ===
     /// Get Whisper model status
@@ -52,7 +105,7 @@ pub enum Request {
     GetConfig,
 
     // === Transcription Mode Control ===
-    /// Set the transcription mode (Automatic or PushToTalk)
+    /// Set the transcription mode (Automatic, PushToTalk or ToggleToTalk)
     SetTranscriptionMode {
         /// The transcription mode to set
         mode: TranscriptionMode,
@@ -73,6 +126,16 @@ pub enum Request {
     GetAutoToggleHotkeys,
     /// Toggle between Automatic and PushToTalk modes
     ToggleAutoMode,
+    /// Set the toggle-to-talk hotkey combinations
+    SetToggleTalkHotkeys {
+        /// The hotkey combinations that start and stop a recording
+        hotkeys: Vec<HotkeyCombination>,
+    },
+    /// Set how long a toggle-to-talk recording continues without speech
+    SetToggleTalkSilenceTimeout {
+        /// Silence timeout in milliseconds (0 disables the timeout)
+        timeout_ms: u32,
+    },
 
     // === Clipboard / Auto-Paste ===
     /// Enable or disable automatic paste after transcription
@@ -81,6 +144,76 @@ pub enum Request {
         enabled: bool,
     },
 
+    // === Gain Control ===
+    /// Configure automatic gain control and segment loudness normalization
+    SetAutoGain {
+        /// Whether gain control should be enabled
+        enabled: bool,
+        /// Target RMS level in dBFS (-40.0 to -6.0)
+        target_level_db: f32,
+    },
+
+    // === Segmentation ===
+    /// Enable or disable long-form segmentation in Automatic mode (long
+    /// speech transcribed as overlapping ~30s windows stitched into one result)
+    SetLongFormSegmentation {
+        /// Whether long-form segmentation should be enabled
+        enabled: bool,
+    },
+
+    /// Get the ring buffer and segment limits
+    GetSegmentationConfig,
+
+    /// Set the ring buffer and segment limits
+    SetSegmentationConfig {
+        /// New limits (validated as a whole)
+        config: SegmentationConfig,
+    },
+
+    /// Configure the wake-word gate: in Automatic mode, only transcripts that
+    /// start with the wake phrase are pasted, with the phrase removed
+    SetWakeWord {
+        /// Whether the wake-word gate should be enabled
+        enabled: bool,
+        /// Wake phrase (e.g. "hey flow")
+        phrase: String,
+    },
+
+    // === Text Processing ===
+    /// Set the text post-processing pipeline
+    SetTextProcessing {
+        /// New pipeline (validated as a whole)
+        config: TextProcessingConfig,
+    },
+
+    // === Hooks ===
+    /// Set the external commands run for each completed transcription
+    SetTranscriptionHooks {
+        /// New hooks (replace the current ones)
+        hooks: Vec<TranscriptionHook>,
+    },
+
+    // === Voice Commands ===
+    /// Configure spoken commands ("comma", "new line", "press enter", ...)
+    /// that are turned into text edits and keystrokes when pasting
+    SetVoiceCommands {
+        /// Whether voice commands should be interpreted
+        enabled: bool,
+        /// New grammar (None keeps the current one)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        commands: Option<Vec<VoiceCommand>>,
+    },
+
+    // === HTTP API ===
+    /// Enable or disable the localhost HTTP/WebSocket API
+    SetHttpApi {
+        /// Whether the API server should run
+        enabled: bool,
+        /// Port to listen on (None keeps the current one)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        port: Option<u16>,
+    },
+
     // === History Management ===
     /// Get all transcription history entries
     GetHistory,
@@ -119,13 +252,182 @@ pub enum Request {
     GetRuntimeMode,
 }
 
+/// Why a message could not be parsed as a [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestParseError {
+    /// The request type is not known to this version
+    Unsupported(String),
+    /// The message is not valid JSON or has invalid fields
+    Invalid(String),
+}
+
+/// A request as sent on the wire.
+///
+/// The optional `request_id` is chosen by the client and echoed in the
+/// response, so a client can pipeline requests and tell responses apart from
+/// events on the same connection.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestEnvelope {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u64>,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+impl RequestEnvelope {
+    /// Parse a request message. The ID is returned even when the request is
+    /// invalid, so the error response can still be correlated.
+    pub fn parse(data: &[u8]) -> (Option<u64>, Result<Request, RequestParseError>) {
+        let mut value: serde_json::Value = match serde_json::from_slice(data) {
+            Ok(value) => value,
+            Err(e) => return (None, Err(RequestParseError::Invalid(e.to_string()))),
+        };
+        let request_id = value
+            .as_object_mut()
+            .and_then(|fields| fields.remove("request_id"))
+            .and_then(|id| id.as_u64());
+        (request_id, Request::from_value(value))
+    }
+}
+
 impl Request {
+    /// Parse a request from a JSON message.
+    pub fn from_json(data: &[u8]) -> Result<Self, RequestParseError> {
+        let value =
+            serde_json::from_slice(data).map_err(|e| RequestParseError::Invalid(e.to_string()))?;
+        Self::from_value(value)
+    }
+
+    /// Every request's `type` tag on the wire (see [`Self::type_name`]).
+    pub const TYPE_NAMES: &'static [&'static str] = &[
+        "hello",
+        "list_devices",
+        "set_sources",
+        "set_aec_enabled",
+        "set_recording_mode",
+        "get_status",
+        "subscribe_events",
+        "transcribe_audio",
+        "get_model_status",
+        "download_model",
+        "get_cuda_status",
+        "get_config",
+        "set_transcription_mode",
+        "set_push_to_talk_hotkeys",
+        "get_ptt_status",
+        "set_auto_toggle_hotkeys",
+        "get_auto_toggle_hotkeys",
+        "toggle_auto_mode",
+        "set_toggle_talk_hotkeys",
+        "set_toggle_talk_silence_timeout",
+        "set_auto_paste",
+        "set_auto_gain",
+        "set_long_form_segmentation",
+        "get_segmentation_config",
+        "set_segmentation_config",
+        "set_wake_word",
+        "set_text_processing",
+        "set_transcription_hooks",
+        "set_voice_commands",
+        "set_http_api",
+        "get_history",
+        "delete_history_entry",
+        "test_audio_device",
+        "stop_test_audio_device",
+        "check_accessibility_permission",
+        "request_accessibility_permission",
+        "ping",
+        "shutdown",
+        "get_runtime_mode",
+    ];
+
+    /// Parse a request from a JSON value, telling unknown request types apart
+    /// from malformed requests.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, RequestParseError> {
+        match value.get("type").and_then(|t| t.as_str()) {
+            Some(t) if !Self::TYPE_NAMES.contains(&t) => {
+                return Err(RequestParseError::Unsupported(t.to_string()))
+            }
+            _ => {}
+        }
+        serde_json::from_value(value).map_err(|e| RequestParseError::Invalid(e.to_string()))
+    }
+
+    /// The request's `type` tag on the wire (e.g. "set_sources").
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Request::Hello { .. } => "hello",
+            Request::ListDevices { .. } => "list_devices",
+            Request::SetSources { .. } => "set_sources",
+            Request::SetAecEnabled { .. } => "set_aec_enabled",
+            Request::SetRecordingMode { .. } => "set_recording_mode",
+            Request::GetStatus => "get_status",
+            Request::SubscribeEvents { .. } => "subscribe_events",
+            Request::TranscribeAudio { .. } => "transcribe_audio",
+            Request::GetModelStatus => "get_model_status",
+            Request::DownloadModel => "download_model",
+            Request::GetCudaStatus => "get_cuda_status",
+            Request::GetConfig => "get_config",
+            Request::SetTranscriptionMode { .. } => "set_transcription_mode",
+            Request::SetPushToTalkHotkeys { .. } => "set_push_to_talk_hotkeys",
+            Request::GetPttStatus => "get_ptt_status",
+            Request::SetAutoToggleHotkeys { .. } => "set_auto_toggle_hotkeys",
+            Request::GetAutoToggleHotkeys => "get_auto_toggle_hotkeys",
+            Request::ToggleAutoMode => "toggle_auto_mode",
+            Request::SetToggleTalkHotkeys { .. } => "set_toggle_talk_hotkeys",
+            Request::SetToggleTalkSilenceTimeout { .. } => "set_toggle_talk_silence_timeout",
+            Request::SetAutoPaste { .. } => "set_auto_paste",
+            Request::SetAutoGain { .. } => "set_auto_gain",
+            Request::SetLongFormSegmentation { .. } => "set_long_form_segmentation",
+            Request::GetSegmentationConfig => "get_segmentation_config",
+            Request::SetSegmentationConfig { .. } => "set_segmentation_config",
+            Request::SetWakeWord { .. } => "set_wake_word",
+            Request::SetTextProcessing { .. } => "set_text_processing",
+            Request::SetTranscriptionHooks { .. } => "set_transcription_hooks",
+            Request::SetVoiceCommands { .. } => "set_voice_commands",
+            Request::SetHttpApi { .. } => "set_http_api",
+            Request::GetHistory => "get_history",
+            Request::DeleteHistoryEntry { .. } => "delete_history_entry",
+            Request::TestAudioDevice { .. } => "test_audio_device",
+            Request::StopTestAudioDevice => "stop_test_audio_device",
+            Request::CheckAccessibilityPermission => "check_accessibility_permission",
+            Request::RequestAccessibilityPermission => "request_accessibility_permission",
+            Request::Ping => "ping",
+            Request::Shutdown => "shutdown",
+            Request::GetRuntimeMode => "get_runtime_mode",
+        }
+    }
+
+    /// Whether the request changes engine state, configuration or data, or
+    /// starts work on the engine. Such requests are recorded in the audit log.
+    pub fn is_state_changing(&self) -> bool {
+        !matches!(
+            self,
+            Request::Hello { .. }
+                | Request::ListDevices { .. }
+                | Request::GetStatus
+                | Request::SubscribeEvents { .. }
+                | Request::GetModelStatus
+                | Request::GetCudaStatus
+                | Request::GetConfig
+                | Request::GetPttStatus
+                | Request::GetAutoToggleHotkeys
+                | Request::GetSegmentationConfig
+                | Request::GetHistory
+                | Request::CheckAccessibilityPermission
+                | Request::Ping
+                | Request::GetRuntimeMode
+        )
+    }
+
     /// Validate all parameters in this request.
     pub fn validate(&self) -> Result<(), String> {
         match self {
             Request::SetSources {
                 source1_id,
                 source2_id,
+                source1_settings,
+                source2_settings,
             } => {
                 // Validate source ID format (basic check)
                 if let Some(id) = source1_id {
@@ -138,6 +440,16 @@ impl Request {
                         return Err("source2_id cannot be empty".to_string());
                     }
                 }
+                if let Some(settings) = source1_settings {
+                    settings
+                        .validate()
+                        .map_err(|e| format!("source1_settings.{}", e))?;
+                }
+                if let Some(settings) = source2_settings {
+                    settings
+                        .validate()
+                        .map_err(|e| format!("source2_settings.{}", e))?;
+                }
                 Ok(())
             }
             Request::TestAudioDevice { device_id } => {
@@ -146,8 +458,273 @@ impl Request {
                 }
                 Ok(())
             }
+            Request::SetToggleTalkSilenceTimeout { timeout_ms } => {
+                if *timeout_ms > MAX_TOGGLE_TALK_SILENCE_TIMEOUT_MS {
+                    return Err(format!(
+                        "timeout_ms must be at most {}, got {}",
+                        MAX_TOGGLE_TALK_SILENCE_TIMEOUT_MS, timeout_ms
+                    ));
+                }
+                Ok(())
+            }
+            Request::SetAutoGain {
+                target_level_db, ..
+            } => {
+                if !(MIN_AGC_TARGET_LEVEL_DB..=MAX_AGC_TARGET_LEVEL_DB).contains(target_level_db) {
+                    return Err(format!(
+                        "target_level_db must be between {:.1} and {:.1} dBFS, got {}",
+                        MIN_AGC_TARGET_LEVEL_DB, MAX_AGC_TARGET_LEVEL_DB, target_level_db
+                    ));
+                }
+                Ok(())
+            }
+            Request::SetSegmentationConfig { config } => config.validate(),
+            Request::SetWakeWord { phrase, .. } => {
+                if !phrase.chars().any(char::is_alphanumeric) {
+                    return Err("phrase must contain at least one word".to_string());
+                }
+                if phrase.chars().count() > MAX_WAKE_WORD_LEN {
+                    return Err(format!(
+                        "phrase must be at most {} characters",
+                        MAX_WAKE_WORD_LEN
+                    ));
+                }
+                Ok(())
+            }
+            Request::SetTextProcessing { config } => config.validate(),
+            Request::SetTranscriptionHooks { hooks } => validate_hooks(hooks),
+            Request::SetHttpApi { port: Some(0), .. } => Err("port cannot be 0".to_string()),
+            Request::SubscribeEvents {
+                max_rate_hz: Some(rate),
+                ..
+            } if !(rate.is_finite() && *rate > 0.0) => {
+                Err(format!("max_rate_hz must be positive, got {}", rate))
+            }
+            Request::TranscribeAudio {
+                sample_rate,
+                channels,
+                encoding,
+                data,
+            } => {
+                if !(MIN_AUDIO_SAMPLE_RATE..=MAX_AUDIO_SAMPLE_RATE).contains(sample_rate) {
+                    return Err(format!(
+                        "sample_rate must be between {} and {} Hz, got {}",
+                        MIN_AUDIO_SAMPLE_RATE, MAX_AUDIO_SAMPLE_RATE, sample_rate
+                    ));
+                }
+                if !(1..=MAX_AUDIO_CHANNELS).contains(channels) {
+                    return Err(format!(
+                        "channels must be between 1 and {}, got {}",
+                        MAX_AUDIO_CHANNELS, channels
+                    ));
+                }
+                let frame_size = encoding.bytes_per_sample() * *channels as usize;
+                if data.0.is_empty() || data.0.len() % frame_size != 0 {
+                    return Err(format!(
+                        "data must be a non-empty multiple of {} bytes, got {}",
+                        frame_size,
+                        data.0.len()
+                    ));
+                }
+                Ok(())
+            }
+            Request::SetVoiceCommands {
+                commands: Some(commands),
+                ..
+            } => validate_voice_commands(commands),
             // Other requests have no parameters to validate
             _ => Ok(()),
         }
     }
 }
+
+/// Validate per-source mixing settings.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_request_types_are_unsupported() {
+        assert!(matches!(
+            Request::from_json(br#"{"type":"hello","protocol_version":1,"client_name":"test"}"#),
+            Ok(Request::Hello {
+                protocol_version: 1,
+                ..
+            })
+        ));
+        assert_eq!(
+            Request::from_json(br#"{"type":"from_the_future","x":1}"#).unwrap_err(),
+            RequestParseError::Unsupported("from_the_future".into())
+        );
+        assert!(matches!(
+            Request::from_json(br#"{"type":"set_aec_enabled"}"#),
+            Err(RequestParseError::Invalid(_))
+        ));
+        // A field holding an unknown variant is a malformed known request
+        assert!(matches!(
+            Request::from_json(br#"{"type":"set_recording_mode","mode":"ping"}"#),
+            Err(RequestParseError::Invalid(_))
+        ));
+        assert!(matches!(
+            Request::from_json(br#"{"type":7}"#),
+            Err(RequestParseError::Invalid(_))
+        ));
+        assert!(matches!(
+            Request::from_json(b"not json"),
+            Err(RequestParseError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn request_ids_round_trip() {
+        let envelope = RequestEnvelope {
+            request_id: Some(7),
+            request: Request::DeleteHistoryEntry { id: "abc".into() },
+        };
+        let data = serde_json::to_vec(&envelope).unwrap();
+        let (request_id, request) = RequestEnvelope::parse(&data);
+        assert_eq!(request_id, Some(7));
+        assert!(matches!(request, Ok(Request::DeleteHistoryEntry { id }) if id == "abc"));
+
+        let (request_id, request) = RequestEnvelope::parse(br#"{"type":"ping"}"#);
+        assert_eq!(request_id, None);
+        assert!(matches!(request, Ok(Request::Ping)));
+
+        let (request_id, request) = RequestEnvelope::parse(br#"{"type":"nope","request_id":3}"#);
+        assert_eq!(request_id, Some(3));
+        assert!(matches!(request, Err(RequestParseError::Unsupported(_))));
+    }
+
+    #[test]
+    fn transcribe_audio_is_base64_and_validated() {
+        let request = Request::TranscribeAudio {
+            sample_rate: 16000,
+            channels: 2,
+            encoding: AudioEncoding::PcmS16le,
+            data: AudioBytes(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["data"], "AQIDBAUGBwg=");
+        assert_eq!(json["encoding"], "pcm_s16le");
+        let parsed = Request::from_value(json).unwrap();
+        assert_eq!(format!("{:?}", parsed).matches("<8 bytes>").count(), 1);
+        assert!(parsed.validate().is_ok());
+
+        let with = |sample_rate, channels, len| Request::TranscribeAudio {
+            sample_rate,
+            channels,
+            encoding: AudioEncoding::PcmF32le,
+            data: AudioBytes(vec![0; len]),
+        };
+        assert!(with(16000, 1, 4).validate().is_ok());
+        assert!(with(16000, 1, 6).validate().is_err());
+        assert!(with(16000, 1, 0).validate().is_err());
+        assert!(with(16000, 0, 4).validate().is_err());
+        assert!(with(1000, 1, 4).validate().is_err());
+
+        assert!(matches!(
+            Request::from_json(br#"{"type":"transcribe_audio","sample_rate":16000,"channels":1,"encoding":"pcm_s16le","data":"not base64!"}"#),
+            Err(RequestParseError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn type_names_match_serde_tags() {
+        // One of every variant
+        let requests = [
+            Request::Hello {
+                protocol_version: 1,
+                client_name: "test".into(),
+            },
+            Request::ListDevices { source_type: None },
+            Request::SetSources {
+                source1_id: None,
+                source2_id: None,
+                source1_settings: None,
+                source2_settings: None,
+            },
+            Request::SetAecEnabled { enabled: true },
+            Request::SetRecordingMode {
+                mode: RecordingMode::default(),
+            },
+            Request::GetStatus,
+            Request::SubscribeEvents {
+                topics: Vec::new(),
+                max_rate_hz: None,
+            },
+            Request::TranscribeAudio {
+                sample_rate: 16000,
+                channels: 1,
+                encoding: AudioEncoding::PcmS16le,
+                data: AudioBytes::default(),
+            },
+            Request::GetModelStatus,
+            Request::DownloadModel,
+            Request::GetCudaStatus,
+            Request::GetConfig,
+            Request::SetTranscriptionMode {
+                mode: TranscriptionMode::default(),
+            },
+            Request::SetPushToTalkHotkeys {
+                hotkeys: Vec::new(),
+            },
+            Request::GetPttStatus,
+            Request::SetAutoToggleHotkeys {
+                hotkeys: Vec::new(),
+            },
+            Request::GetAutoToggleHotkeys,
+            Request::ToggleAutoMode,
+            Request::SetToggleTalkHotkeys {
+                hotkeys: Vec::new(),
+            },
+            Request::SetToggleTalkSilenceTimeout { timeout_ms: 1000 },
+            Request::SetAutoPaste { enabled: true },
+            Request::SetAutoGain {
+                enabled: true,
+                target_level_db: -20.0,
+            },
+            Request::SetLongFormSegmentation { enabled: true },
+            Request::GetSegmentationConfig,
+            Request::SetSegmentationConfig {
+                config: SegmentationConfig::default(),
+            },
+            Request::SetWakeWord {
+                enabled: true,
+                phrase: "hey flow".into(),
+            },
+            Request::SetTextProcessing {
+                config: TextProcessingConfig::default(),
+            },
+            Request::SetTranscriptionHooks { hooks: Vec::new() },
+            Request::SetVoiceCommands {
+                enabled: true,
+                commands: None,
+            },
+            Request::SetHttpApi {
+                enabled: false,
+                port: None,
+            },
+            Request::GetHistory,
+            Request::DeleteHistoryEntry { id: "abc".into() },
+            Request::TestAudioDevice {
+                device_id: "hw:0".into(),
+            },
+            Request::StopTestAudioDevice,
+            Request::CheckAccessibilityPermission,
+            Request::RequestAccessibilityPermission,
+            Request::Ping,
+            Request::Shutdown,
+            Request::GetRuntimeMode,
+        ];
+        let mut names = std::collections::HashSet::new();
+        for request in &requests {
+            let json = serde_json::to_value(request).unwrap();
+            assert_eq!(json["type"], request.type_name());
+            assert!(names.insert(request.type_name()));
+        }
+        assert_eq!(names.len(), Request::TYPE_NAMES.len());
+        assert!(Request::TYPE_NAMES.iter().all(|t| names.contains(t)));
+        assert!(Request::Shutdown.is_state_changing());
+        assert!(!Request::GetConfig.is_state_changing());
+    }
+}
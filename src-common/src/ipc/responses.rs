@@ -3,8 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::types::{
-    AudioDevice, ConfigValues, CudaStatus, HistoryEntry, ModelStatus, PttStatus, TranscribeStatus,
-    TranscriptionResult, VisualizationData,
+    AecMetrics, AudioDevice, ConfigValues, CudaStatus, HistoryEntry, ModelStatus, PttStatus,
+    SegmentationConfig, TranscribeStatus, TranscriptionResult, VisualizationData,
 };
 
 /// IPC response from service to client.
@@ -12,6 +12,14 @@ use crate::types::{
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Response {
     // === Success Responses ===
+    /// Handshake reply with the engine's protocol version and capabilities
+    Hello {
+        /// The engine's `PROTOCOL_VERSION`
+        protocol_version: u32,
+        /// Optional protocol features the engine supports
+        capabilities: Vec<String>,
+    },
+
     /// List of audio devices
     Devices { devices: Vec<AudioDevice> },
 
@@ -30,6 +38,9 @@ pub enum Response {
     /// Persisted configuration values
     ConfigValues(ConfigValues),
 
+    /// Ring buffer and segment limits
+    SegmentationConfig(SegmentationConfig),
+
     /// Accessibility permission status
     AccessibilityPermission {
         /// Whether the service process has Accessibility permission
@@ -42,6 +53,11 @@ pub enum Response {
     /// Subscribed to events
     Subscribed,
 
+    /// Audio from `TranscribeAudio` was queued; its result arrives as an
+    /// `AudioTranscriptionComplete` event with this job ID, on the connection
+    /// that sent the request
+    TranscriptionQueued { job_id: u64 },
+
     /// Generic success
     Ok,
 
@@ -53,13 +69,85 @@ pub enum Response {
 
     // === Error Response ===
     /// Error occurred
-    Error { message: String },
+    Error {
+        /// What kind of error this is, for clients that react to specific errors
+        #[serde(default)]
+        code: ErrorCode,
+        /// Human-readable description
+        message: String,
+    },
+
+    /// The request type is not known to this engine version
+    UnsupportedRequest { request_type: String },
 
     // === Event Responses (after Subscribe) ===
     /// Real-time event
     Event { event: EventType },
 }
 
+/// Machine-readable kind of a `Response::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A request parameter is invalid
+    InvalidArgument,
+    /// No primary audio source is configured
+    NoSource,
+    /// The Whisper model has not been downloaded
+    ModelMissing,
+    /// The engine lacks an OS permission (e.g. macOS Accessibility)
+    PermissionDenied,
+    /// The audio device does not exist
+    DeviceNotFound,
+    /// The transcription queue is full
+    QueueFull,
+    /// The requested item (e.g. a history entry) does not exist
+    NotFound,
+    /// What the request would create already exists (e.g. the model is
+    /// already downloaded)
+    AlreadyExists,
+    /// A subsystem the request needs is not running or not available
+    Unavailable,
+    /// Unexpected failure inside the engine (also used for codes unknown to
+    /// this version)
+    #[default]
+    #[serde(other)]
+    Internal,
+}
+
+/// A response or event as sent on the wire, with the `request_id` of the
+/// request it answers (absent for events and for requests sent without one).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseEnvelope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u64>,
+    #[serde(flatten)]
+    pub response: Response,
+}
+
+/// Group of related events that clients can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    /// Transcription results, speech start/end and hook failures
+    Transcription,
+    /// Capture, transcription mode and hotkey state changes
+    State,
+    /// High-rate waveform/spectrogram data, AEC metrics and device test levels
+    Visualization,
+    /// Model download progress
+    Model,
+    /// History changes
+    History,
+}
+
+impl EventTopic {
+    /// Parse a topic name as used on the wire (e.g. "transcription").
+    pub fn parse(name: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+}
+
 /// Event types streamed to subscribed clients.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "event", rename_all = "snake_case")]
@@ -67,9 +155,19 @@ pub enum EventType {
     /// Visualization data update
     VisualizationData(VisualizationData),
 
+    /// Echo cancellation convergence metrics (emitted periodically while AEC runs)
+    AecMetrics(AecMetrics),
+
     /// Transcription result for a segment
     TranscriptionComplete(TranscriptionResult),
 
+    /// A captured segment could not be transcribed (queue full or
+    /// transcription failed)
+    TranscriptionError {
+        /// What went wrong
+        error: String,
+    },
+
     /// Speech started (segment recording began)
     SpeechStarted,
 
@@ -105,6 +203,37 @@ pub enum EventType {
     /// Push-to-talk key released
     PttReleased,
 
+    /// Toggle-to-talk recording started by a hotkey tap
+    ToggleTalkStarted,
+
+    /// Toggle-to-talk recording ended and its segment was submitted
+    ToggleTalkStopped {
+        /// What ended the recording
+        reason: crate::types::ToggleTalkStopReason,
+        /// Duration of the recording in milliseconds
+        duration_ms: u64,
+    },
+
+    /// Audio submitted with `TranscribeAudio` was transcribed (or failed)
+    AudioTranscriptionComplete {
+        /// The job ID from `Response::TranscriptionQueued`
+        job_id: u64,
+        /// The transcription, if it succeeded
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<TranscriptionResult>,
+        /// Why transcription failed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+
+    /// A transcription hook failed, timed out or could not be started
+    HookFailed {
+        /// Name of the hook
+        hook: String,
+        /// What went wrong
+        error: String,
+    },
+
     /// Transcription mode changed (Auto vs PTT)
     TranscriptionModeChanged {
         /// The new transcription mode
@@ -127,10 +256,46 @@ pub enum EventType {
     Shutdown,
 }
 
+impl EventType {
+    /// The topic this event belongs to. `Shutdown` counts as a state event but
+    /// is sent to every subscriber.
+    pub fn topic(&self) -> EventTopic {
+        match self {
+            EventType::TranscriptionComplete(_)
+            | EventType::TranscriptionError { .. }
+            | EventType::SpeechStarted
+            | EventType::SpeechEnded { .. }
+            | EventType::AudioTranscriptionComplete { .. }
+            | EventType::HookFailed { .. } => EventTopic::Transcription,
+            EventType::CaptureStateChanged { .. }
+            | EventType::PttPressed
+            | EventType::PttReleased
+            | EventType::ToggleTalkStarted
+            | EventType::ToggleTalkStopped { .. }
+            | EventType::TranscriptionModeChanged { .. }
+            | EventType::AutoModeToggled { .. }
+            | EventType::Shutdown => EventTopic::State,
+            EventType::VisualizationData(_)
+            | EventType::AecMetrics(_)
+            | EventType::AudioLevelUpdate { .. } => EventTopic::Visualization,
+            EventType::ModelDownloadProgress { .. } | EventType::ModelDownloadComplete { .. } => {
+                EventTopic::Model
+            }
+            EventType::HistoryEntryDeleted { .. } => EventTopic::History,
+        }
+    }
+}
+
 impl Response {
-    /// Create an error response.
+    /// Create an error response for an unexpected failure.
     pub fn error(message: impl Into<String>) -> Self {
+        Self::error_with_code(ErrorCode::Internal, message)
+    }
+
+    /// Create an error response with a specific code.
+    pub fn error_with_code(code: ErrorCode, message: impl Into<String>) -> Self {
         Response::Error {
+            code,
             message: message.into(),
         }
     }
@@ -142,6 +307,65 @@ impl Response {
 
     /// Check if this response indicates an error.
     pub fn is_error(&self) -> bool {
-        matches!(self, Response::Error { .. })
+        matches!(
+            self,
+            Response::Error { .. } | Response::UnsupportedRequest { .. }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_envelopes_round_trip() {
+        let envelope = ResponseEnvelope {
+            request_id: Some(42),
+            response: Response::Status(TranscribeStatus::default()),
+        };
+        let json = serde_json::to_string(&envelope).unwrap();
+        let parsed: ResponseEnvelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.request_id, Some(42));
+        assert!(matches!(parsed.response, Response::Status(_)));
+
+        // Events carry no ID and parse as plain responses too
+        let event = Response::Event {
+            event: EventType::SpeechEnded { duration_ms: 1200 },
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        let parsed: ResponseEnvelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.request_id, None);
+        assert!(matches!(
+            parsed.response,
+            Response::Event {
+                event: EventType::SpeechEnded { duration_ms: 1200 }
+            }
+        ));
+    }
+
+    #[test]
+    fn error_codes() {
+        let json = serde_json::to_string(&Response::error_with_code(
+            ErrorCode::NoSource,
+            "No primary audio source configured",
+        ))
+        .unwrap();
+        assert!(json.contains(r#""code":"no_source""#));
+
+        // Engines from before error codes, and codes from newer engines
+        for json in [
+            r#"{"type":"error","message":"x"}"#,
+            r#"{"type":"error","code":"from_the_future","message":"x"}"#,
+        ] {
+            let response: Response = serde_json::from_str(json).unwrap();
+            assert!(matches!(
+                response,
+                Response::Error {
+                    code: ErrorCode::Internal,
+                    ..
+                }
+            ));
+        }
     }
 }
@@ -1,6 +1,9 @@
-//! Platform-specific logging directory resolution.
+//! Platform-specific logging directory resolution and audit log records.
 
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::ipc::ErrorCode;
 
 /// Returns the platform-appropriate directory for log files.
 ///
@@ -58,3 +61,45 @@ pub fn ensure_log_dir() -> Result<(), std::io::Error> {
 pub fn app_log_path() -> PathBuf {
     log_dir().join("flowstt-app.log")
 }
+
+/// Returns the path to the audit log of client requests (JSON lines).
+pub fn audit_log_path() -> PathBuf {
+    log_dir().join("audit.log")
+}
+
+/// Returns the path to a rotated audit log; `1` is the most recent.
+pub fn rotated_audit_log_path(generation: u32) -> PathBuf {
+    rotated_log_path(&audit_log_path(), generation)
+}
+
+/// Returns the path a log file is rotated to (`<path>.<generation>`).
+pub fn rotated_log_path(path: &Path, generation: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", generation));
+    PathBuf::from(name)
+}
+
+/// One line of the audit log: a state-changing request or a rejected client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// RFC 3339 time the request was handled
+    pub timestamp: String,
+    /// How the client connected ("ipc" or "http")
+    pub transport: String,
+    /// Process ID of the client, when known (IPC only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i32>,
+    /// Executable of the client, when known (IPC only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<PathBuf>,
+    /// Request type (e.g. "set_sources"), or "connect" for rejected clients
+    pub request: String,
+    /// Whether the request succeeded
+    pub success: bool,
+    /// Error code of a failed request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ErrorCode>,
+    /// Error message of a failed request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -1,8 +1,10 @@
 //! Security modules for IPC authentication and validation.
 
 pub mod peer_verify;
+pub mod trust_policy;
 
-/// Executable names permitted to connect to the IPC server.
+/// Executable names permitted to connect to the IPC server. Other clients
+/// must be allowed in the [`trust_policy::TrustPolicy`] file.
 pub const TRUSTED_EXECUTABLES: &[&str] = &["flowstt", "flowstt-app"];
 
 /// Trusted installation directories (Linux).
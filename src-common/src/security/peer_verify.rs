@@ -1,13 +1,17 @@
 //! Peer credential verification for IPC connections.
 //!
-//! This module provides platform-specific mechanisms to verify that
-//! connecting processes are trusted FlowSTT binaries.
+//! This module provides platform-specific mechanisms to identify the process
+//! on the other end of a connection ([`identify_peer`]) and to verify that it
+//! is a trusted FlowSTT binary or a client allowed by the user's
+//! [`TrustPolicy`] ([`verify_executable`]). Verification may hash the
+//! executable, so async callers should run it on a blocking thread.
 
 use std::path::{Path, PathBuf};
 
+use super::trust_policy::{file_sha256, is_cargo_target_dir, TrustPolicy};
 use super::{TRUSTED_DIRECTORIES, TRUSTED_EXECUTABLES};
 
-/// Information about a verified peer process.
+/// Information about a connected peer process.
 #[derive(Debug, Clone)]
 pub struct PeerInfo {
     /// Process ID of the peer
@@ -23,10 +27,14 @@ pub enum PeerVerifyError {
     CredentialsFailed(String),
     /// Process with given PID not found
     ProcessNotFound(i32),
-    /// Executable is not in the trusted list
-    UntrustedExecutable(PathBuf),
-    /// Executable is not in a trusted directory
-    UntrustedDirectory(PathBuf),
+    /// Executable is neither a trusted FlowSTT binary nor allowed by the
+    /// trust policy
+    Untrusted {
+        pid: i32,
+        executable: PathBuf,
+        /// Digest of the executable, if it could be read
+        sha256: Option<String>,
+    },
     /// Peer UID doesn't match current user (Unix only)
     #[cfg(unix)]
     UidMismatch { peer: u32, current: u32 },
@@ -39,12 +47,17 @@ impl std::fmt::Display for PeerVerifyError {
                 write!(f, "Failed to get peer credentials: {}", e)
             }
             PeerVerifyError::ProcessNotFound(pid) => write!(f, "Process {} not found", pid),
-            PeerVerifyError::UntrustedExecutable(path) => {
-                write!(f, "Untrusted executable: {:?}", path)
-            }
-            PeerVerifyError::UntrustedDirectory(path) => {
-                write!(f, "Executable not in trusted directory: {:?}", path)
-            }
+            PeerVerifyError::Untrusted {
+                pid,
+                executable,
+                sha256,
+            } => write!(
+                f,
+                "Untrusted executable {:?} (pid {}, sha256 {})",
+                executable,
+                pid,
+                sha256.as_deref().unwrap_or("unavailable")
+            ),
             #[cfg(unix)]
             PeerVerifyError::UidMismatch { peer, current } => {
                 write!(f, "UID mismatch: peer={}, current={}", peer, current)
@@ -57,15 +70,37 @@ impl std::error::Error for PeerVerifyError {}
 
 /// Verify that an executable path is trusted.
 ///
-/// The executable must:
-/// 1. Have a filename matching one of TRUSTED_EXECUTABLES
-/// 2. Be located in a TRUSTED_DIRECTORY OR the same directory as the service
-fn verify_executable(exe_path: &Path) -> Result<(), PeerVerifyError> {
+/// The executable is trusted if any of these hold:
+/// 1. Its filename matches one of TRUSTED_EXECUTABLES and it is located in a
+///    TRUSTED_DIRECTORY or the same directory as the service
+/// 2. Its filename matches and it is in a cargo target directory, when the
+///    policy sets `allow_dev_builds`
+/// 3. The policy lists its path or SHA-256 digest
+pub fn verify_executable(peer: &PeerInfo, policy: &TrustPolicy) -> Result<(), PeerVerifyError> {
+    let exe_path = peer.executable.as_path();
+    if is_trusted_flowstt_binary(exe_path, policy.allow_dev_builds) || policy.allows_path(exe_path)
+    {
+        return Ok(());
+    }
+
+    let sha256 = file_sha256(exe_path).ok();
+    if sha256.as_deref().is_some_and(|h| policy.allows_hash(h)) {
+        return Ok(());
+    }
+
+    Err(PeerVerifyError::Untrusted {
+        pid: peer.pid,
+        executable: exe_path.to_path_buf(),
+        sha256,
+    })
+}
+
+/// Whether the executable is one of our own binaries in a trusted location.
+fn is_trusted_flowstt_binary(exe_path: &Path, allow_dev_builds: bool) -> bool {
     // Get executable name
-    let exe_name = exe_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| PeerVerifyError::UntrustedExecutable(exe_path.to_path_buf()))?;
+    let Some(exe_name) = exe_path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
 
     // Check name (handle .exe suffix on Windows)
     #[cfg(windows)]
@@ -76,14 +111,10 @@ fn verify_executable(exe_path: &Path) -> Result<(), PeerVerifyError> {
     #[cfg(not(windows))]
     let name_matches = TRUSTED_EXECUTABLES.contains(&exe_name);
 
-    if !name_matches {
-        return Err(PeerVerifyError::UntrustedExecutable(exe_path.to_path_buf()));
-    }
-
     // Get executable directory
-    let exe_dir = exe_path
-        .parent()
-        .ok_or_else(|| PeerVerifyError::UntrustedDirectory(exe_path.to_path_buf()))?;
+    let Some(exe_dir) = exe_path.parent().filter(|_| name_matches) else {
+        return false;
+    };
 
     // Check if in trusted directory OR same directory as self
     let self_dir = std::env::current_exe()
@@ -93,23 +124,17 @@ fn verify_executable(exe_path: &Path) -> Result<(), PeerVerifyError> {
     let in_trusted_dir = TRUSTED_DIRECTORIES.iter().any(|d| exe_dir == Path::new(d));
     let same_as_self = self_dir.as_ref().map(|d| exe_dir == d).unwrap_or(false);
 
-    // During development, also allow cargo target directories
-    let exe_dir_str = exe_dir.to_string_lossy();
-    let in_target_dir = exe_dir_str.contains("target")
-        && (exe_dir_str.contains("debug") || exe_dir_str.contains("release"));
-
-    if !in_trusted_dir && !same_as_self && !in_target_dir {
-        return Err(PeerVerifyError::UntrustedDirectory(exe_path.to_path_buf()));
-    }
+    // Cargo target directories only when the policy opts in
+    let in_target_dir = allow_dev_builds && is_cargo_target_dir(exe_dir);
 
-    Ok(())
+    in_trusted_dir || same_as_self || in_target_dir
 }
 
-/// Verify connecting peer on Linux using SO_PEERCRED.
+/// Identify the connecting peer on Linux using SO_PEERCRED.
 #[cfg(target_os = "linux")]
-pub fn verify_peer(stream: &std::os::unix::net::UnixStream) -> Result<PeerInfo, PeerVerifyError> {
-    use std::os::unix::io::AsRawFd;
-
+pub fn identify_peer(
+    stream: &impl std::os::unix::io::AsRawFd,
+) -> Result<PeerInfo, PeerVerifyError> {
     let fd = stream.as_raw_fd();
 
     // Get peer credentials via SO_PEERCRED
@@ -145,20 +170,17 @@ pub fn verify_peer(stream: &std::os::unix::net::UnixStream) -> Result<PeerInfo,
     let exe_path = std::fs::read_link(format!("/proc/{}/exe", creds.pid))
         .map_err(|_| PeerVerifyError::ProcessNotFound(creds.pid))?;
 
-    // Verify executable is trusted
-    verify_executable(&exe_path)?;
-
     Ok(PeerInfo {
         pid: creds.pid,
         executable: exe_path,
     })
 }
 
-/// Verify connecting peer on macOS using LOCAL_PEERPID.
+/// Identify the connecting peer on macOS using LOCAL_PEERPID.
 #[cfg(target_os = "macos")]
-pub fn verify_peer(stream: &std::os::unix::net::UnixStream) -> Result<PeerInfo, PeerVerifyError> {
-    use std::os::unix::io::AsRawFd;
-
+pub fn identify_peer(
+    stream: &impl std::os::unix::io::AsRawFd,
+) -> Result<PeerInfo, PeerVerifyError> {
     let fd = stream.as_raw_fd();
 
     // macOS constants not in libc
@@ -208,18 +230,17 @@ pub fn verify_peer(stream: &std::os::unix::net::UnixStream) -> Result<PeerInfo,
         )
     };
 
-    // Verify executable is trusted
-    verify_executable(&exe_path)?;
-
     Ok(PeerInfo {
         pid,
         executable: exe_path,
     })
 }
 
-/// Verify connecting peer on Windows using GetNamedPipeClientProcessId.
+/// Identify the connecting peer on Windows using GetNamedPipeClientProcessId.
 #[cfg(target_os = "windows")]
-pub fn verify_peer(pipe: windows::Win32::Foundation::HANDLE) -> Result<PeerInfo, PeerVerifyError> {
+pub fn identify_peer(
+    pipe: windows::Win32::Foundation::HANDLE,
+) -> Result<PeerInfo, PeerVerifyError> {
     use windows::Win32::Foundation::CloseHandle;
     use windows::Win32::System::Pipes::GetNamedPipeClientProcessId;
     use windows::Win32::System::Threading::{
@@ -255,11 +276,58 @@ pub fn verify_peer(pipe: windows::Win32::Foundation::HANDLE) -> Result<PeerInfo,
         PathBuf::from(String::from_utf16_lossy(&buf[..len as usize]))
     };
 
-    // Verify executable is trusted
-    verify_executable(&exe_path)?;
-
     Ok(PeerInfo {
         pid: pid as i32,
         executable: exe_path,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(executable: PathBuf) -> PeerInfo {
+        PeerInfo {
+            pid: 42,
+            executable,
+        }
+    }
+
+    #[test]
+    fn executables_are_verified_against_policy() {
+        let dir = std::env::temp_dir().join(format!("flowstt-peer-{}", std::process::id()));
+        let dev_dir = dir.join("target").join("debug");
+        std::fs::create_dir_all(&dev_dir).unwrap();
+        let dev_build = dev_dir.join(TRUSTED_EXECUTABLES[0]);
+        let other = dir.join("dictate");
+        std::fs::write(&dev_build, b"dev build").unwrap();
+        std::fs::write(&other, b"third-party client").unwrap();
+        let other_sha256 = file_sha256(&other).unwrap();
+
+        // Dev builds only with allow_dev_builds
+        let mut policy = TrustPolicy::default();
+        assert!(matches!(
+            verify_executable(&peer(dev_build.clone()), &policy),
+            Err(PeerVerifyError::Untrusted { pid: 42, .. })
+        ));
+        policy.allow_dev_builds = true;
+        assert!(verify_executable(&peer(dev_build.clone()), &policy).is_ok());
+
+        // A bundled name elsewhere is not enough
+        let renamed = dir.join(TRUSTED_EXECUTABLES[0]);
+        std::fs::write(&renamed, b"not ours").unwrap();
+        assert!(verify_executable(&peer(renamed), &policy).is_err());
+
+        // Other clients by digest alone, reported with their digest otherwise
+        match verify_executable(&peer(other.clone()), &policy) {
+            Err(PeerVerifyError::Untrusted { sha256, .. }) => {
+                assert_eq!(sha256.as_deref(), Some(other_sha256.as_str()))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        policy.sha256.push(other_sha256.to_uppercase());
+        assert!(verify_executable(&peer(other), &policy).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
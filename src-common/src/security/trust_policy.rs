@@ -0,0 +1,170 @@
+//! Client trust policy for IPC peer verification.
+//!
+//! Besides the bundled FlowSTT binaries, the user can allow other clients in
+//! a JSON policy file next to the config file:
+//!
+//! ```json
+//! {
+//!   "enforce": true,
+//!   "executables": ["/home/me/bin/flowstt-obsidian"],
+//!   "sha256": ["9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"],
+//!   "allow_dev_builds": false
+//! }
+//! ```
+//!
+//! Untrusted clients are only logged until the policy sets `enforce`, so
+//! existing setups (development builds, scripts, `cargo install`ed CLIs) keep
+//! working while the user writes their policy.
+//!
+//! The file is read on every connection, so edits apply without a restart.
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/// Clients the user allows in addition to the built-in trusted binaries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrustPolicy {
+    /// Refuse clients the policy does not trust instead of only logging them
+    pub enforce: bool,
+    /// Absolute paths of allowed executables
+    pub executables: Vec<PathBuf>,
+    /// Hex SHA-256 digests of allowed executables
+    pub sha256: Vec<String>,
+    /// Also allow FlowSTT binaries built in a cargo `target/{debug,release}`
+    /// directory (development only)
+    pub allow_dev_builds: bool,
+}
+
+impl TrustPolicy {
+    /// Get the path of the policy file.
+    ///
+    /// Returns platform-specific path:
+    /// - Linux: ~/.config/flowstt/trusted-clients.json
+    /// - macOS: ~/Library/Application Support/flowstt/trusted-clients.json
+    /// - Windows: %APPDATA%\flowstt\trusted-clients.json
+    pub fn path() -> PathBuf {
+        directories::BaseDirs::new()
+            .map(|d| d.config_dir().to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("flowstt")
+            .join("trusted-clients.json")
+    }
+
+    /// Load the policy file. A missing file is an empty, non-enforcing
+    /// policy; a malformed one is an error so that a typo never silently
+    /// widens access. The engine then ignores the whole file and enforces
+    /// the built-in clients only.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents).map_err(|e| format!("{:?}: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read {:?}: {}", path, e)),
+        }
+    }
+
+    /// Parse and validate policy JSON.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let policy: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if let Some(path) = policy.executables.iter().find(|p| !p.is_absolute()) {
+            return Err(format!(
+                "executables must be absolute paths, got {:?}",
+                path
+            ));
+        }
+        if let Some(hash) = policy
+            .sha256
+            .iter()
+            .find(|h| h.len() != 64 || !h.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(format!(
+                "sha256 entries must be 64 hex digits, got '{}'",
+                hash
+            ));
+        }
+        Ok(policy)
+    }
+
+    /// Whether the policy lists this executable path.
+    pub fn allows_path(&self, exe_path: &Path) -> bool {
+        self.executables.iter().any(|allowed| {
+            allowed == exe_path || std::fs::canonicalize(allowed).is_ok_and(|p| p == exe_path)
+        })
+    }
+
+    /// Whether the policy lists this SHA-256 digest (hex, any case).
+    pub fn allows_hash(&self, sha256: &str) -> bool {
+        self.sha256.iter().any(|h| h.eq_ignore_ascii_case(sha256))
+    }
+}
+
+/// Hex SHA-256 digest of a file.
+pub fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Whether a directory is a cargo build output directory
+/// (`.../target/debug` or `.../target/release`, optionally with a target
+/// triple in between).
+pub fn is_cargo_target_dir(dir: &Path) -> bool {
+    let components: Vec<_> = dir.components().map(|c| c.as_os_str()).collect();
+    let is_profile = |c: &std::ffi::OsStr| c == "debug" || c == "release";
+    match components.as_slice() {
+        [.., target, profile] if *target == "target" => is_profile(profile),
+        [.., target, _, profile] if *target == "target" => is_profile(profile),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_is_validated() {
+        let policy = TrustPolicy::parse(
+            r#"{"executables":["/opt/tools/dictate"],"sha256":["9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08"]}"#,
+        )
+        .unwrap();
+        assert!(policy.allows_path(Path::new("/opt/tools/dictate")));
+        assert!(!policy.allows_path(Path::new("/opt/tools/other")));
+        assert!(
+            policy.allows_hash("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")
+        );
+        assert!(!policy.allow_dev_builds);
+        assert!(!policy.enforce);
+        assert!(TrustPolicy::parse(r#"{"enforce":true}"#).unwrap().enforce);
+
+        assert_eq!(TrustPolicy::parse("{}"), Ok(TrustPolicy::default()));
+        assert!(TrustPolicy::parse(r#"{"executables":["dictate"]}"#).is_err());
+        assert!(TrustPolicy::parse(r#"{"sha256":["abc"]}"#).is_err());
+        assert!(TrustPolicy::parse(r#"{"executable":["/opt/tools/dictate"]}"#).is_err());
+    }
+
+    #[test]
+    fn cargo_target_dirs() {
+        assert!(is_cargo_target_dir(Path::new("/src/flowstt/target/debug")));
+        assert!(is_cargo_target_dir(Path::new(
+            "/src/flowstt/target/x86_64-unknown-linux-gnu/release"
+        )));
+        assert!(!is_cargo_target_dir(Path::new(
+            "/home/target-practice/debug"
+        )));
+        assert!(!is_cargo_target_dir(Path::new("/tmp/target/debug/extra")));
+        assert!(!is_cargo_target_dir(Path::new("/usr/bin")));
+    }
+}
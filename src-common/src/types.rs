@@ -27,6 +27,64 @@ pub enum RecordingMode {
     Mixed,
     /// Echo cancellation mode - output only echo-cancelled primary source
     EchoCancel,
+    /// Keep the primary source (microphone) and secondary source (system audio)
+    /// on separate channels and transcribe each speaker independently
+    SpeakerSeparated,
+}
+
+/// Speaker label for transcriptions made in speaker-separated recording mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Speaker {
+    /// Local user, captured from the primary source (microphone)
+    Me,
+    /// Remote participants, captured from the secondary source (system audio)
+    Remote,
+}
+
+impl Speaker {
+    /// Label used when displaying transcriptions
+    pub fn label(&self) -> &'static str {
+        match self {
+            Speaker::Me => "me",
+            Speaker::Remote => "remote",
+        }
+    }
+}
+
+/// Per-source mixing settings applied by the audio backends before sources are mixed.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceSettings {
+    /// Gain applied to the source in dB (-60.0 to +24.0)
+    #[serde(default)]
+    pub gain_db: f32,
+    /// Whether the source is muted
+    #[serde(default)]
+    pub muted: bool,
+    /// Device channels to record (1-based). Empty uses all channels.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<u16>,
+}
+
+impl SourceSettings {
+    /// Check the gain range and channel numbers.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-60.0..=24.0).contains(&self.gain_db) {
+            return Err(format!(
+                "gain_db must be between -60.0 and 24.0 dB, got {}",
+                self.gain_db
+            ));
+        }
+        for (i, channel) in self.channels.iter().enumerate() {
+            if *channel == 0 {
+                return Err("channels are 1-based; 0 is not valid".to_string());
+            }
+            if self.channels[..i].contains(channel) {
+                return Err(format!("channels contains {} twice", channel));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Transcription mode - determines how speech segment boundaries are identified.
@@ -38,6 +96,29 @@ pub enum TranscriptionMode {
     /// Manual key-controlled - hotkey press/release determines segment boundaries
     #[default]
     PushToTalk,
+    /// Hands-free key-controlled - one hotkey tap starts the segment, and a
+    /// second tap or a silence timeout ends it
+    ToggleToTalk,
+}
+
+impl TranscriptionMode {
+    /// Whether segments are started and ended by a hotkey (rather than VAD)
+    pub fn is_hotkey_driven(&self) -> bool {
+        matches!(
+            self,
+            TranscriptionMode::PushToTalk | TranscriptionMode::ToggleToTalk
+        )
+    }
+}
+
+/// Why a toggle-to-talk recording ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToggleTalkStopReason {
+    /// The toggle-to-talk hotkey was tapped again
+    Hotkey,
+    /// No speech was detected for the configured silence timeout
+    SilenceTimeout,
 }
 
 /// Runtime mode - determines behavior for service lifecycle management.
@@ -452,19 +533,98 @@ impl Default for HotkeyCombination {
 /// Persisted configuration values returned by the GetConfig IPC request.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ConfigValues {
-    /// Current transcription mode (Automatic or PushToTalk)
+    /// Current transcription mode (Automatic, PushToTalk or ToggleToTalk)
     pub transcription_mode: TranscriptionMode,
     /// Configured push-to-talk hotkey combinations
     pub ptt_hotkeys: Vec<HotkeyCombination>,
     /// Configured auto-mode toggle hotkeys
     #[serde(default)]
     pub auto_toggle_hotkeys: Vec<HotkeyCombination>,
+    /// Configured toggle-to-talk hotkey combinations
+    #[serde(default)]
+    pub toggle_talk_hotkeys: Vec<HotkeyCombination>,
+    /// Silence in milliseconds after which a toggle-to-talk recording ends
+    /// (0 disables the timeout)
+    #[serde(default = "default_toggle_talk_silence_timeout_ms")]
+    pub toggle_talk_silence_timeout_ms: u32,
     /// Whether auto-paste into the foreground application is enabled
     #[serde(default = "default_auto_paste_enabled")]
     pub auto_paste_enabled: bool,
     /// Delay in milliseconds between clipboard write and paste simulation
     #[serde(default = "default_auto_paste_delay_ms")]
     pub auto_paste_delay_ms: u32,
+    /// Whether automatic gain control and segment normalization are enabled
+    #[serde(default = "default_agc_enabled")]
+    pub agc_enabled: bool,
+    /// Target RMS level in dBFS for gain control and normalization
+    #[serde(default = "default_agc_target_level_db")]
+    pub agc_target_level_db: f32,
+    /// Whether long-form segmentation is enabled in Automatic mode
+    #[serde(default)]
+    pub long_form_enabled: bool,
+    /// Ring buffer and segment limits
+    #[serde(default)]
+    pub segmentation: SegmentationConfig,
+    /// Whether Automatic mode text is only pasted after the wake phrase
+    #[serde(default)]
+    pub wake_word_enabled: bool,
+    /// Wake phrase that must start an Automatic mode transcript
+    #[serde(default = "default_wake_word")]
+    pub wake_word: String,
+    /// Whether spoken commands ("comma", "new line", ...) are interpreted
+    #[serde(default)]
+    pub voice_commands_enabled: bool,
+    /// Voice command grammar
+    #[serde(default = "default_voice_commands")]
+    pub voice_commands: Vec<VoiceCommand>,
+    /// Text post-processing pipeline
+    #[serde(default)]
+    pub text_processing: TextProcessingConfig,
+    /// External commands run for each completed transcription
+    #[serde(default)]
+    pub hooks: Vec<TranscriptionHook>,
+    /// Whether the localhost HTTP/WebSocket API is enabled
+    #[serde(default)]
+    pub http_api_enabled: bool,
+    /// Localhost port of the HTTP/WebSocket API
+    #[serde(default = "default_http_api_port")]
+    pub http_api_port: u16,
+}
+
+/// Default AGC and normalization target RMS level in dBFS
+pub const DEFAULT_AGC_TARGET_LEVEL_DB: f32 = -20.0;
+
+/// Lowest accepted AGC target level in dBFS
+pub const MIN_AGC_TARGET_LEVEL_DB: f32 = -40.0;
+
+/// Highest accepted AGC target level in dBFS
+pub const MAX_AGC_TARGET_LEVEL_DB: f32 = -6.0;
+
+/// Default toggle-to-talk silence timeout
+pub const DEFAULT_TOGGLE_TALK_SILENCE_TIMEOUT_MS: u32 = 5000;
+
+/// Longest accepted toggle-to-talk silence timeout
+pub const MAX_TOGGLE_TALK_SILENCE_TIMEOUT_MS: u32 = 600_000;
+
+/// Default wake phrase for Automatic mode
+pub const DEFAULT_WAKE_WORD: &str = "hey flow";
+
+/// Longest accepted wake phrase, in characters
+pub const MAX_WAKE_WORD_LEN: usize = 64;
+
+/// Default localhost port of the HTTP/WebSocket API
+pub const DEFAULT_HTTP_API_PORT: u16 = 47825;
+
+fn default_http_api_port() -> u16 {
+    DEFAULT_HTTP_API_PORT
+}
+
+fn default_toggle_talk_silence_timeout_ms() -> u32 {
+    DEFAULT_TOGGLE_TALK_SILENCE_TIMEOUT_MS
+}
+
+fn default_wake_word() -> String {
+    DEFAULT_WAKE_WORD.to_string()
 }
 
 fn default_auto_paste_enabled() -> bool {
@@ -475,6 +635,395 @@ fn default_auto_paste_delay_ms() -> u32 {
     50
 }
 
+fn default_agc_enabled() -> bool {
+    true
+}
+
+fn default_agc_target_level_db() -> f32 {
+    DEFAULT_AGC_TARGET_LEVEL_DB
+}
+
+/// Limits that control how captured audio is buffered and split into segments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SegmentationConfig {
+    /// Seconds of audio held by the capture ring buffer (5 to 600).
+    /// An automatic-mode segment is cut before it outgrows the buffer;
+    /// hotkey recordings are buffered separately and are not limited by it.
+    pub ring_buffer_secs: u32,
+    /// Segment duration after which a word break is sought (1000 ms or more)
+    pub max_segment_duration_ms: u64,
+    /// Grace period after `max_segment_duration_ms` before a segment is cut
+    /// without a word break (0 to 5000 ms)
+    pub word_break_grace_ms: u64,
+    /// Segments shorter than this are not transcribed (0 to 5000 ms)
+    pub min_segment_duration_ms: u64,
+    /// Segments quieter than this RMS amplitude are not transcribed (0.0 to 0.5)
+    pub min_audio_rms: f32,
+    /// Maximum number of segments waiting for transcription (1 to 100)
+    pub max_queue_size: u32,
+}
+
+impl Default for SegmentationConfig {
+    fn default() -> Self {
+        Self {
+            ring_buffer_secs: 30,
+            max_segment_duration_ms: 4000,
+            word_break_grace_ms: 750,
+            min_segment_duration_ms: 500,
+            min_audio_rms: 0.01,
+            max_queue_size: 10,
+        }
+    }
+}
+
+impl SegmentationConfig {
+    /// Check that all limits are within range and consistent with each other.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(5..=600).contains(&self.ring_buffer_secs) {
+            return Err(format!(
+                "ring_buffer_secs must be between 5 and 600, got {}",
+                self.ring_buffer_secs
+            ));
+        }
+        if self.max_segment_duration_ms < 1000 {
+            return Err(format!(
+                "max_segment_duration_ms must be at least 1000, got {}",
+                self.max_segment_duration_ms
+            ));
+        }
+        if self.word_break_grace_ms > 5000 {
+            return Err(format!(
+                "word_break_grace_ms must be at most 5000, got {}",
+                self.word_break_grace_ms
+            ));
+        }
+        // Segments are extracted before the ring buffer reaches 90% full
+        let usable_ms = self.ring_buffer_secs as u64 * 900;
+        if self.max_segment_duration_ms + self.word_break_grace_ms > usable_ms {
+            return Err(format!(
+                "max_segment_duration_ms + word_break_grace_ms ({} ms) must fit in 90% of the ring buffer ({} ms)",
+                self.max_segment_duration_ms + self.word_break_grace_ms,
+                usable_ms
+            ));
+        }
+        if self.min_segment_duration_ms > 5000 {
+            return Err(format!(
+                "min_segment_duration_ms must be at most 5000, got {}",
+                self.min_segment_duration_ms
+            ));
+        }
+        if self.min_segment_duration_ms >= self.max_segment_duration_ms {
+            return Err(format!(
+                "min_segment_duration_ms ({}) must be less than max_segment_duration_ms ({})",
+                self.min_segment_duration_ms, self.max_segment_duration_ms
+            ));
+        }
+        if !(0.0..=0.5).contains(&self.min_audio_rms) {
+            return Err(format!(
+                "min_audio_rms must be between 0.0 and 0.5, got {}",
+                self.min_audio_rms
+            ));
+        }
+        if !(1..=100).contains(&self.max_queue_size) {
+            return Err(format!(
+                "max_queue_size must be between 1 and 100, got {}",
+                self.max_queue_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Key or shortcut pressed by a voice command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKey {
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    /// Ctrl+A / Cmd+A
+    SelectAll,
+    /// Ctrl+Z / Cmd+Z
+    Undo,
+}
+
+/// What a spoken voice command does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum VoiceCommandAction {
+    /// Insert punctuation attached to the preceding word, replacing any
+    /// punctuation Whisper placed there
+    Punctuation { text: String },
+    /// Insert text as-is (e.g. line breaks)
+    Insert { text: String },
+    /// Press a key or shortcut
+    Key { key: EditKey },
+    /// Delete the phrase dictated before the command (or the previous
+    /// transcript when spoken on its own)
+    DeleteThat,
+}
+
+/// A spoken phrase and the action it triggers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceCommand {
+    /// Words that trigger the command (case and punctuation-insensitive)
+    pub phrase: String,
+    /// Action performed when the phrase is heard
+    #[serde(flatten)]
+    pub action: VoiceCommandAction,
+}
+
+impl VoiceCommand {
+    fn new(phrase: &str, action: VoiceCommandAction) -> Self {
+        Self {
+            phrase: phrase.to_string(),
+            action,
+        }
+    }
+
+    /// The phrase as normalized words, as matched against transcripts.
+    pub fn phrase_words(&self) -> Vec<String> {
+        self.phrase
+            .split_whitespace()
+            .map(normalize_word)
+            .filter(|w| !w.is_empty())
+            .collect()
+    }
+}
+
+/// Normalize a transcript word for matching: lowercase, without punctuation
+/// other than apostrophes. Shared by voice commands, text processing, the
+/// wake-word gate and window stitching.
+pub fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Built-in voice command grammar.
+pub fn default_voice_commands() -> Vec<VoiceCommand> {
+    use VoiceCommandAction::*;
+    let punctuation = |text: &str| Punctuation {
+        text: text.to_string(),
+    };
+    let insert = |text: &str| Insert {
+        text: text.to_string(),
+    };
+    vec![
+        VoiceCommand::new("comma", punctuation(",")),
+        VoiceCommand::new("period", punctuation(".")),
+        VoiceCommand::new("full stop", punctuation(".")),
+        VoiceCommand::new("question mark", punctuation("?")),
+        VoiceCommand::new("exclamation mark", punctuation("!")),
+        VoiceCommand::new("colon", punctuation(":")),
+        VoiceCommand::new("semicolon", punctuation(";")),
+        VoiceCommand::new("new line", insert("\n")),
+        VoiceCommand::new("new paragraph", insert("\n\n")),
+        VoiceCommand::new(
+            "press enter",
+            Key {
+                key: EditKey::Enter,
+            },
+        ),
+        VoiceCommand::new("press tab", Key { key: EditKey::Tab }),
+        VoiceCommand::new(
+            "press escape",
+            Key {
+                key: EditKey::Escape,
+            },
+        ),
+        VoiceCommand::new(
+            "select all",
+            Key {
+                key: EditKey::SelectAll,
+            },
+        ),
+        VoiceCommand::new("undo that", Key { key: EditKey::Undo }),
+        VoiceCommand::new("delete that", DeleteThat),
+        VoiceCommand::new("scratch that", DeleteThat),
+    ]
+}
+
+/// Check a voice command grammar: every phrase needs a word and phrases must
+/// be distinct.
+pub fn validate_voice_commands(commands: &[VoiceCommand]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for command in commands {
+        let words = command.phrase_words();
+        if words.is_empty() {
+            return Err(format!(
+                "voice command phrase {:?} has no words",
+                command.phrase
+            ));
+        }
+        if !seen.insert(words) {
+            return Err(format!(
+                "voice command phrase {:?} is defined twice",
+                command.phrase
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// One stage of the text post-processing pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum TextStage {
+    /// Capitalize sentence starts and the pronoun "I"
+    Capitalization,
+    /// Remove filler words such as "um" and "uh"
+    FillerRemoval {
+        /// Words to remove (case-insensitive)
+        #[serde(default = "default_filler_words")]
+        words: Vec<String>,
+    },
+    /// Write spelled-out numbers as digits ("twenty five" -> "25")
+    NumberFormatting,
+    /// Typographic quotes, dashes and ellipses
+    SmartPunctuation,
+    /// Replace every occurrence of `find` with `replace`
+    Replace { find: String, replace: String },
+}
+
+/// Filler words removed by default
+pub fn default_filler_words() -> Vec<String> {
+    ["um", "uh", "uhm", "erm", "er", "ah", "hmm", "mm"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect()
+}
+
+/// Text post-processing stages used for recordings made in one mode, or with
+/// one hotkey binding of that mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextProfile {
+    /// Mode the profile applies to
+    pub mode: TranscriptionMode,
+    /// Hotkey binding of `mode` the profile applies to; all of the mode's
+    /// recordings when omitted. Binding profiles take precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<HotkeyCombination>,
+    /// Stages applied in order, instead of the default stages
+    pub stages: Vec<TextStage>,
+}
+
+/// Ordered text post-processing applied to each transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextProcessingConfig {
+    /// Stages applied in order to every transcript
+    pub stages: Vec<TextStage>,
+    /// Per-mode and per-hotkey stage lists that replace `stages`
+    pub profiles: Vec<TextProfile>,
+}
+
+impl Default for TextProcessingConfig {
+    fn default() -> Self {
+        Self {
+            stages: vec![TextStage::Replace {
+                find: "Flow STT".to_string(),
+                replace: "FlowSTT".to_string(),
+            }],
+            profiles: Vec::new(),
+        }
+    }
+}
+
+impl TextProcessingConfig {
+    /// Stages to apply to a recording made in `mode`.
+    pub fn stages_for(&self, mode: TranscriptionMode) -> &[TextStage] {
+        self.profiles
+            .iter()
+            .find(|p| p.mode == mode)
+            .map(|p| p.stages.as_slice())
+            .unwrap_or(&self.stages)
+    }
+
+    /// Check that every stage is usable and each mode has at most one profile.
+    pub fn validate(&self) -> Result<(), String> {
+        let all_stages = self
+            .stages
+            .iter()
+            .chain(self.profiles.iter().flat_map(|p| p.stages.iter()));
+        for stage in all_stages {
+            if let TextStage::Replace { find, .. } = stage {
+                if find.is_empty() {
+                    return Err("replace stage needs a non-empty find string".to_string());
+                }
+            }
+        }
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if self.profiles[..i].iter().any(|p| p.mode == profile.mode) {
+                return Err(format!(
+                    "more than one text profile for {:?} mode",
+                    profile.mode
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Default time a transcription hook may run before it is killed
+pub const DEFAULT_HOOK_TIMEOUT_MS: u64 = 5000;
+
+/// Longest accepted transcription hook timeout
+pub const MAX_HOOK_TIMEOUT_MS: u64 = 60_000;
+
+/// External command run for each completed transcription.
+///
+/// The transcript is written to the command's stdin and its metadata is
+/// passed in `FLOWSTT_*` environment variables. The command is run directly,
+/// not through a shell.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionHook {
+    /// Name used in logs and failure events
+    pub name: String,
+    /// Program to run
+    pub command: String,
+    /// Arguments passed to the program
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Time in milliseconds after which the command is killed
+    #[serde(default = "default_hook_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Whether the command's stdout replaces the text before it is pasted
+    #[serde(default)]
+    pub replace_text: bool,
+    /// Whether the hook runs
+    #[serde(default = "default_hook_enabled")]
+    pub enabled: bool,
+}
+
+fn default_hook_timeout_ms() -> u64 {
+    DEFAULT_HOOK_TIMEOUT_MS
+}
+
+fn default_hook_enabled() -> bool {
+    true
+}
+
+/// Check transcription hooks: each needs a command and a timeout of at most
+/// `MAX_HOOK_TIMEOUT_MS`.
+pub fn validate_hooks(hooks: &[TranscriptionHook]) -> Result<(), String> {
+    for hook in hooks {
+        if hook.command.trim().is_empty() {
+            return Err(format!("hook {:?} has no command", hook.name));
+        }
+        if hook.timeout_ms == 0 || hook.timeout_ms > MAX_HOOK_TIMEOUT_MS {
+            return Err(format!(
+                "hook {:?} timeout_ms must be between 1 and {}, got {}",
+                hook.name, MAX_HOOK_TIMEOUT_MS, hook.timeout_ms
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// Push-to-talk status information.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PttStatus {
@@ -490,6 +1039,12 @@ pub struct PttStatus {
     pub auto_mode_active: bool,
     /// Whether PTT key is currently pressed
     pub is_active: bool,
+    /// Configured toggle-to-talk hotkey combinations
+    #[serde(default)]
+    pub toggle_talk_hotkeys: Vec<HotkeyCombination>,
+    /// Whether a toggle-to-talk recording is in progress
+    #[serde(default)]
+    pub toggle_talk_active: bool,
     /// Whether PTT is available on this platform
     pub available: bool,
     /// Error message if PTT is unavailable (e.g., missing permissions)
@@ -600,6 +1155,30 @@ pub struct SpeechMetrics {
     pub is_lookback_speech: bool,
     /// Whether this is a word break
     pub is_word_break: bool,
+    /// Gain currently applied by automatic gain control, in dB
+    #[serde(default)]
+    pub applied_gain_db: f32,
+}
+
+/// Acoustic echo cancellation convergence metrics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AecMetrics {
+    /// Sample rate of the capture/render streams in Hz
+    pub sample_rate: u32,
+    /// Sample rate the echo canceller runs at in Hz
+    pub processing_rate: u32,
+    /// Estimated render-to-capture delay in milliseconds
+    pub estimated_delay_ms: Option<u32>,
+    /// Confidence of the delay estimate (normalized correlation, 0.0-1.0)
+    pub delay_confidence: f32,
+    /// Smoothed echo return loss enhancement in dB (capture vs. processed)
+    pub erle_db: f32,
+    /// Smoothed echo return loss in dB (render vs. capture)
+    pub echo_return_loss_db: f32,
+    /// Whether the render (reference) stream currently carries signal
+    pub render_active: bool,
+    /// Whether the canceller is considered converged
+    pub converged: bool,
 }
 
 /// A single entry in the transcription history.
@@ -614,6 +1193,12 @@ pub struct HistoryEntry {
     /// Path to the cached WAV file, if it still exists
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wav_path: Option<String>,
+    /// Speaker, when recorded in speaker-separated mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<Speaker>,
+    /// Diarized speaker (1-based, numbered per session and source)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<u32>,
 }
 
 /// Transcription result for a speech segment.
@@ -630,4 +1215,59 @@ pub struct TranscriptionResult {
     /// Path to the saved audio file (if saved)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audio_path: Option<String>,
+    /// Speaker, when recorded in speaker-separated mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<Speaker>,
+    /// Diarized speaker (1-based, numbered per session and source)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<u32>,
+}
+
+/// Sample format of raw PCM audio submitted by clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioEncoding {
+    /// Signed 16-bit little-endian integers
+    PcmS16le,
+    /// 32-bit little-endian floats in [-1.0, 1.0]
+    PcmF32le,
+}
+
+impl AudioEncoding {
+    /// Size of one sample in bytes.
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            AudioEncoding::PcmS16le => 2,
+            AudioEncoding::PcmF32le => 4,
+        }
+    }
+}
+
+/// Binary data, sent as a base64 string in JSON.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct AudioBytes(pub Vec<u8>);
+
+// Only the length is printed so logged requests stay readable
+impl fmt::Debug for AudioBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes>", self.0.len())
+    }
+}
+
+impl Serialize for AudioBytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use base64::Engine;
+        serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for AudioBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use base64::Engine;
+        let encoded = String::deserialize(deserializer)?;
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map(AudioBytes)
+            .map_err(serde::de::Error::custom)
+    }
 }
@@ -0,0 +1,370 @@
+//! Audio pipeline wakeup benchmark.
+//!
+//! Compares the consumer patterns used by the audio and transcription loops:
+//!
+//! - `polling`: `try_recv()` with a 1ms sleep when empty (audio loop) and a
+//!   50ms sleep when the transcription queue is empty.
+//! - `blocking`: a bounded `sync_channel` drained with `recv_timeout()` (audio
+//!   loop) and a `Condvar` signalled on enqueue (transcription queue).
+//!
+//! For each pattern it reports the CPU time consumed by the consumer threads
+//! while no audio is flowing, and the latency from a 10ms audio chunk being
+//! produced to it being handled, and from a segment being queued to the worker
+//! picking it up.
+//!
+//! It then runs the engine itself on synthetic speech: `AudioPipeline::process`
+//! feeding a `TranscribeState` in Automatic mode, whose segments go through the
+//! `TranscriptionQueue` worker. No whisper model is available to the worker, so
+//! transcription fails straight away and the figures cover the engine's own
+//! overhead: time spent in `process` per 10ms chunk, and the latency from the
+//! chunk that ends speech to the worker reporting the segment.
+//!
+//! Run with `cargo bench -p flowstt-engine --bench pipeline`.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::EventType;
+use flowstt_common::Speaker;
+use flowstt_engine::pipeline::{AudioPipeline, SegmentationStrategy};
+use flowstt_engine::platform::AudioData;
+use flowstt_engine::transcription::{TranscribeState, TranscriptionCallback, TranscriptionQueue};
+
+const IDLE_DURATION: Duration = Duration::from_secs(2);
+const CHUNK_INTERVAL: Duration = Duration::from_millis(10);
+const CHUNK_COUNT: usize = 300;
+const SEGMENT_INTERVAL: Duration = Duration::from_millis(100);
+const SEGMENT_COUNT: usize = 30;
+const RECV_TIMEOUT: Duration = Duration::from_millis(50);
+const ENGINE_SAMPLE_RATE: u32 = 48000;
+const ENGINE_CHANNELS: u16 = 2;
+const UTTERANCE_COUNT: usize = 10;
+const UTTERANCE_DURATION: Duration = Duration::from_millis(1500);
+const PAUSE_DURATION: Duration = Duration::from_millis(1500);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pattern {
+    Polling,
+    Blocking,
+}
+
+impl Pattern {
+    fn name(self) -> &'static str {
+        match self {
+            Pattern::Polling => "polling",
+            Pattern::Blocking => "blocking",
+        }
+    }
+}
+
+/// CPU time consumed by the calling thread.
+#[cfg(unix)]
+fn thread_cpu_time() -> Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: clock_gettime only writes to the provided timespec
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts);
+    }
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Duration {
+    Duration::ZERO
+}
+
+/// Audio consumer: returns (cpu time, per-chunk latencies).
+fn run_audio_consumer(
+    pattern: Pattern,
+    chunks: usize,
+    idle: Duration,
+) -> (Duration, Vec<Duration>) {
+    let running = Arc::new(AtomicBool::new(true));
+    let (tx, rx) = mpsc::sync_channel::<Instant>(64);
+
+    let consumer_running = Arc::clone(&running);
+    let consumer = thread::spawn(move || {
+        let start_cpu = thread_cpu_time();
+        let mut latencies = Vec::with_capacity(chunks);
+        while consumer_running.load(Ordering::SeqCst) {
+            let chunk = match pattern {
+                Pattern::Polling => {
+                    let chunk = rx.try_recv().ok();
+                    if chunk.is_none() {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                    chunk
+                }
+                Pattern::Blocking => rx.recv_timeout(RECV_TIMEOUT).ok(),
+            };
+            if let Some(sent_at) = chunk {
+                latencies.push(sent_at.elapsed());
+            }
+        }
+        (thread_cpu_time() - start_cpu, latencies)
+    });
+
+    if chunks == 0 {
+        thread::sleep(idle);
+    } else {
+        for _ in 0..chunks {
+            thread::sleep(CHUNK_INTERVAL);
+            let _ = tx.try_send(Instant::now());
+        }
+        thread::sleep(CHUNK_INTERVAL);
+    }
+
+    running.store(false, Ordering::SeqCst);
+    consumer.join().unwrap()
+}
+
+/// Transcription worker: returns (cpu time, per-segment latencies).
+fn run_queue_worker(
+    pattern: Pattern,
+    segments: usize,
+    idle: Duration,
+) -> (Duration, Vec<Duration>) {
+    let queue: Arc<(Mutex<VecDeque<Instant>>, Condvar)> =
+        Arc::new((Mutex::new(VecDeque::new()), Condvar::new()));
+    let active = Arc::new(AtomicBool::new(true));
+
+    let worker_queue = Arc::clone(&queue);
+    let worker_active = Arc::clone(&active);
+    let worker = thread::spawn(move || {
+        let (lock, available) = &*worker_queue;
+        let start_cpu = thread_cpu_time();
+        let mut latencies = Vec::with_capacity(segments);
+        loop {
+            let segment = {
+                let mut q = lock.lock().unwrap();
+                if pattern == Pattern::Blocking {
+                    while q.is_empty() && worker_active.load(Ordering::SeqCst) {
+                        q = available.wait(q).unwrap();
+                    }
+                }
+                q.pop_front()
+            };
+            match segment {
+                Some(queued_at) => latencies.push(queued_at.elapsed()),
+                None if !worker_active.load(Ordering::SeqCst) => break,
+                None => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+        (thread_cpu_time() - start_cpu, latencies)
+    });
+
+    let (lock, available) = &*queue;
+    if segments == 0 {
+        thread::sleep(idle);
+    } else {
+        for _ in 0..segments {
+            thread::sleep(SEGMENT_INTERVAL);
+            lock.lock().unwrap().push_back(Instant::now());
+            available.notify_one();
+        }
+        thread::sleep(SEGMENT_INTERVAL);
+    }
+
+    {
+        let _q = lock.lock().unwrap();
+        active.store(false, Ordering::SeqCst);
+        available.notify_all();
+    }
+    worker.join().unwrap()
+}
+
+fn summarize(latencies: &mut [Duration]) -> (f64, f64) {
+    if latencies.is_empty() {
+        return (0.0, 0.0);
+    }
+    latencies.sort();
+    let mean = latencies.iter().sum::<Duration>().as_secs_f64() / latencies.len() as f64;
+    let p99 = latencies[(latencies.len() * 99 / 100).min(latencies.len() - 1)];
+    (mean * 1000.0, p99.as_secs_f64() * 1000.0)
+}
+
+/// Reports when the transcription worker is done with a segment.
+struct SegmentDone(Mutex<mpsc::Sender<Instant>>);
+
+impl TranscriptionCallback for SegmentDone {
+    fn on_transcription_started(&self) {}
+
+    fn on_transcription_complete(
+        &self,
+        _text: String,
+        _wav_path: Option<String>,
+        _speaker: Option<Speaker>,
+        _speaker_id: Option<u32>,
+        _paste: bool,
+    ) {
+        let _ = self.0.lock().unwrap().send(Instant::now());
+    }
+
+    fn on_transcription_error(&self, _error: String) {
+        let _ = self.0.lock().unwrap().send(Instant::now());
+    }
+
+    fn on_transcription_finished(&self) {}
+
+    fn on_queue_update(&self, _depth: usize) {}
+}
+
+/// Point the user data, config and cache directories at a scratch directory
+/// so recorded segments are not written to the real recordings directory and
+/// no installed whisper model is picked up.
+fn isolate_user_dirs() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("flowstt-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for var in ["HOME", "XDG_DATA_HOME", "XDG_CONFIG_HOME", "XDG_CACHE_HOME"] {
+        std::env::set_var(var, &dir);
+    }
+    dir
+}
+
+/// One 10ms chunk of synthetic audio: a voiced 150 Hz tone with harmonics at
+/// a syllable-rate loudness while `speaking`, faint noise otherwise.
+fn synthetic_chunk(start_frame: usize, speaking: bool, noise: &mut u32) -> Vec<f32> {
+    let frames = ENGINE_SAMPLE_RATE as usize / 100;
+    let mut samples = Vec::with_capacity(frames * ENGINE_CHANNELS as usize);
+    for i in start_frame..start_frame + frames {
+        let t = i as f32 / ENGINE_SAMPLE_RATE as f32;
+        *noise = noise.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let mut sample = (*noise >> 8) as f32 / (1u32 << 24) as f32 * 0.002 - 0.001;
+        if speaking {
+            let syllable = 0.6 + 0.4 * (2.0 * std::f32::consts::PI * 4.0 * t).sin();
+            let voiced: f32 = [(150.0, 0.5), (300.0, 0.3), (450.0, 0.2), (900.0, 0.1)]
+                .iter()
+                .map(|(freq, amp)| amp * (2.0 * std::f32::consts::PI * freq * t).sin())
+                .sum();
+            sample += 0.3 * syllable * voiced;
+        }
+        for _ in 0..ENGINE_CHANNELS {
+            samples.push(sample);
+        }
+    }
+    samples
+}
+
+/// Feed synthetic utterances through the engine pipeline in real time.
+/// Returns (per-chunk `process` times, per-segment latencies).
+fn run_engine_pipeline() -> (Vec<Duration>, Vec<Duration>) {
+    let (done_tx, done_rx) = mpsc::channel();
+    let queue = Arc::new(TranscriptionQueue::new());
+    queue.set_callback(Arc::new(SegmentDone(Mutex::new(done_tx))));
+    queue.start_worker(PathBuf::new());
+
+    let state = Mutex::new(TranscribeState::new(Arc::clone(&queue)));
+    {
+        let mut state = state.lock().unwrap();
+        state.init_for_capture(ENGINE_SAMPLE_RATE, ENGINE_CHANNELS);
+        state.activate();
+    }
+    let mut pipeline = AudioPipeline::new(ENGINE_SAMPLE_RATE, SegmentationStrategy::Vad);
+
+    let chunks_per = |d: Duration| (d.as_millis() / CHUNK_INTERVAL.as_millis()) as usize;
+    let utterance_chunks = chunks_per(UTTERANCE_DURATION);
+    let cycle_chunks = utterance_chunks + chunks_per(PAUSE_DURATION);
+    let frames_per_chunk = ENGINE_SAMPLE_RATE as usize / 100;
+
+    let mut noise = 1;
+    let mut process_times = Vec::with_capacity(UTTERANCE_COUNT * cycle_chunks);
+    let mut ended_at = Vec::new();
+    let mut next_chunk = Instant::now();
+    for chunk in 0..UTTERANCE_COUNT * cycle_chunks {
+        let speaking = chunk % cycle_chunks < utterance_chunks;
+        let mut data = AudioData {
+            samples: synthetic_chunk(chunk * frames_per_chunk, speaking, &mut noise),
+            channels: ENGINE_CHANNELS,
+            sample_rate: ENGINE_SAMPLE_RATE,
+        };
+
+        let start = Instant::now();
+        let event = pipeline.process(&mut data, &state);
+        process_times.push(start.elapsed());
+        if let Some(EventType::SpeechEnded { .. }) = event {
+            ended_at.push(Instant::now());
+        }
+
+        // Deliver chunks at the capture rate so the worker runs concurrently
+        next_chunk += CHUNK_INTERVAL;
+        thread::sleep(next_chunk.saturating_duration_since(Instant::now()));
+    }
+
+    let segment_latencies = ended_at
+        .iter()
+        .filter_map(|&ended| {
+            let done = done_rx.recv_timeout(Duration::from_secs(5)).ok()?;
+            Some(done.saturating_duration_since(ended))
+        })
+        .collect();
+    queue.stop_worker();
+    (process_times, segment_latencies)
+}
+
+fn main() {
+    if cfg!(not(unix)) {
+        println!("note: thread CPU time is only measured on Unix");
+    }
+
+    println!(
+        "{:<10} {:>16} {:>16} {:>14} {:>14} {:>14} {:>14}",
+        "pattern",
+        "audio idle cpu",
+        "queue idle cpu",
+        "chunk mean",
+        "chunk p99",
+        "segment mean",
+        "segment p99"
+    );
+
+    for pattern in [Pattern::Polling, Pattern::Blocking] {
+        let (audio_idle_cpu, _) = run_audio_consumer(pattern, 0, IDLE_DURATION);
+        let (queue_idle_cpu, _) = run_queue_worker(pattern, 0, IDLE_DURATION);
+        let (_, mut chunk_latencies) = run_audio_consumer(pattern, CHUNK_COUNT, Duration::ZERO);
+        let (_, mut segment_latencies) = run_queue_worker(pattern, SEGMENT_COUNT, Duration::ZERO);
+
+        let idle_pct = |cpu: Duration| 100.0 * cpu.as_secs_f64() / IDLE_DURATION.as_secs_f64();
+        let (chunk_mean, chunk_p99) = summarize(&mut chunk_latencies);
+        let (segment_mean, segment_p99) = summarize(&mut segment_latencies);
+
+        println!(
+            "{:<10} {:>15.3}% {:>15.3}% {:>12.3}ms {:>12.3}ms {:>12.3}ms {:>12.3}ms",
+            pattern.name(),
+            idle_pct(audio_idle_cpu),
+            idle_pct(queue_idle_cpu),
+            chunk_mean,
+            chunk_p99,
+            segment_mean,
+            segment_p99
+        );
+    }
+
+    let scratch_dir = isolate_user_dirs();
+    let (mut process_times, mut segment_latencies) = run_engine_pipeline();
+    std::fs::remove_dir_all(&scratch_dir).ok();
+
+    let chunk_count = process_times.len();
+    let segment_count = segment_latencies.len();
+    let (process_mean, process_p99) = summarize(&mut process_times);
+    let (segment_mean, segment_p99) = summarize(&mut segment_latencies);
+    println!();
+    println!(
+        "engine: {} chunks, process mean/p99 {:.3}/{:.3}ms ({:.1}% of real time); \
+         {} of {} segments, speech end to worker done mean/p99 {:.3}/{:.3}ms",
+        chunk_count,
+        process_mean,
+        process_p99,
+        100.0 * process_mean / CHUNK_INTERVAL.as_secs_f64() / 1000.0,
+        segment_count,
+        UTTERANCE_COUNT,
+        segment_mean,
+        segment_p99
+    );
+}
@@ -3,7 +3,8 @@
 //! This module contains audio-related types and utilities shared across
 //! the service, including format conversion and recording.
 
-use std::path::PathBuf;
+use flowstt_common::AudioEncoding;
+use std::path::{Path, PathBuf};
 
 /// Raw recorded audio data before processing
 pub struct RawRecordedAudio {
@@ -22,14 +23,30 @@ pub fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
 
 /// Process raw recorded audio into format suitable for transcription
 /// This is CPU-intensive and should be called in a separate thread/task
-pub fn process_recorded_audio(raw: RawRecordedAudio) -> Result<Vec<f32>, String> {
+///
+/// When `normalize_target_db` is set, the mono segment is loudness-normalized
+/// towards that RMS level (peak-limited) before resampling.
+pub fn process_recorded_audio(
+    raw: RawRecordedAudio,
+    normalize_target_db: Option<f32>,
+) -> Result<Vec<f32>, String> {
     // Convert to mono if stereo
-    let mono_samples = if raw.channels > 1 {
+    let mut mono_samples = if raw.channels > 1 {
         convert_to_mono(&raw.samples, raw.channels as usize)
     } else {
         raw.samples
     };
 
+    // Normalize segment loudness
+    if let Some(target_db) = normalize_target_db {
+        let gain_db = crate::gain::normalize_segment(&mut mono_samples, target_db);
+        tracing::debug!(
+            "Segment normalized by {:+.1} dB (target {:.1} dBFS)",
+            gain_db,
+            target_db
+        );
+    }
+
     // Resample to 16kHz for Whisper
     resample_to_16khz(&mono_samples, raw.sample_rate)
 }
@@ -76,31 +93,189 @@ pub fn save_to_wav(
     samples: &[f32],
     sample_rate: u32,
     channels: u16,
-    output_path: &PathBuf,
+    output_path: &Path,
 ) -> Result<(), String> {
-    use hound::{SampleFormat, WavSpec, WavWriter};
+    let mut recorder = WavRecorder::create(output_path.to_path_buf(), sample_rate, channels)?;
+    recorder.write(samples)?;
+    recorder.finalize()?;
+    Ok(())
+}
 
-    let spec = WavSpec {
-        channels,
-        sample_rate,
-        bits_per_sample: 32,
-        sample_format: SampleFormat::Float,
+/// WAV file written as audio arrives, for recordings too long to hold in memory
+pub struct WavRecorder {
+    path: PathBuf,
+    writer: hound::WavWriter<std::io::BufWriter<std::fs::File>>,
+}
+
+impl WavRecorder {
+    /// Create the file, in the same format as `save_to_wav`
+    pub fn create(path: PathBuf, sample_rate: u32, channels: u16) -> Result<Self, String> {
+        use hound::{SampleFormat, WavSpec, WavWriter};
+
+        let spec = WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let writer = WavWriter::create(&path, spec)
+            .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+        Ok(Self { path, writer })
+    }
+
+    /// Append interleaved samples
+    pub fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        for &sample in samples {
+            self.writer
+                .write_sample(sample)
+                .map_err(|e| format!("Failed to write sample: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Complete the file and return its path
+    pub fn finalize(self) -> Result<PathBuf, String> {
+        self.writer
+            .finalize()
+            .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
+        Ok(self.path)
+    }
+}
+
+/// Decode a WAV file held in memory (integer or float samples)
+#[cfg(test)]
+pub fn read_wav(bytes: &[u8]) -> Result<RawRecordedAudio, String> {
+    use hound::{SampleFormat, WavReader};
+
+    let reader = WavReader::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Invalid WAV file: {}", e))?;
+    let spec = reader.spec();
+
+    let samples: Result<Vec<f32>, _> = match spec.sample_format {
+        SampleFormat::Float => reader.into_samples::<f32>().collect(),
+        SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|s| s as f32 * scale))
+                .collect()
+        }
     };
 
-    let mut writer = WavWriter::create(output_path, spec)
-        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+    Ok(RawRecordedAudio {
+        samples: samples.map_err(|e| format!("Invalid WAV file: {}", e))?,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+    })
+}
 
-    for &sample in samples {
-        writer
-            .write_sample(sample)
-            .map_err(|e| format!("Failed to write sample: {}", e))?;
+/// Longest audio file decoded by `decode_audio_file`
+pub const MAX_DECODED_AUDIO_SECS: u64 = 15 * 60;
+
+/// Decode a compressed or PCM audio file held in memory (WAV, FLAC, MP3,
+/// Ogg Vorbis, MP4/M4A AAC, ...) to mono. `extension` of the original file
+/// name helps detect the container. Files longer than
+/// `MAX_DECODED_AUDIO_SECS` are rejected rather than held in memory.
+pub fn decode_audio_file(
+    bytes: Vec<u8>,
+    extension: Option<&str>,
+) -> Result<RawRecordedAudio, String> {
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+    use symphonia::core::errors::Error;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::probe::Hint;
+
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
     }
+    let source = MediaSourceStream::new(Box::new(std::io::Cursor::new(bytes)), Default::default());
+    let mut format = symphonia::default::get_probe()
+        .format(&hint, source, &Default::default(), &Default::default())
+        .map_err(|e| format!("Unrecognized audio file: {}", e))?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("Audio file has no audio track")?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported audio codec: {}", e))?;
 
-    writer
-        .finalize()
-        .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
+    let mut samples = Vec::new();
+    let mut sample_rate = 0;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("Invalid audio file: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // Skip corrupt packets like players do
+            Err(Error::DecodeError(_)) => continue,
+            Err(e) => return Err(format!("Invalid audio file: {}", e)),
+        };
 
-    Ok(())
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend(convert_to_mono(buffer.samples(), spec.channels.count()));
+        if samples.len() as u64 > MAX_DECODED_AUDIO_SECS * sample_rate as u64 {
+            return Err(format!(
+                "Audio is longer than {} minutes",
+                MAX_DECODED_AUDIO_SECS / 60
+            ));
+        }
+    }
+
+    if samples.is_empty() {
+        return Err("Audio file contains no audio".into());
+    }
+    Ok(RawRecordedAudio {
+        samples,
+        sample_rate,
+        channels: 1,
+    })
+}
+
+/// Decode interleaved little-endian PCM sent by a client (float samples are
+/// clamped, non-finite ones become silence)
+pub fn decode_pcm(
+    data: &[u8],
+    encoding: AudioEncoding,
+    sample_rate: u32,
+    channels: u16,
+) -> RawRecordedAudio {
+    let samples = match encoding {
+        AudioEncoding::PcmS16le => data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect(),
+        AudioEncoding::PcmF32le => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .map(|s| {
+                if s.is_finite() {
+                    s.clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect(),
+    };
+    RawRecordedAudio {
+        samples,
+        sample_rate,
+        channels,
+    }
 }
 
 /// Generate a timestamped filename for recording
@@ -109,3 +284,65 @@ pub fn generate_recording_filename() -> String {
     let now = Utc::now();
     format!("flowstt-{}.wav", now.format("%Y%m%d-%H%M%S"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcm_is_decoded() {
+        let audio = decode_pcm(&[0x00, 0x40, 0x00, 0x80], AudioEncoding::PcmS16le, 16000, 1);
+        assert_eq!(audio.samples, [0.5, -1.0]);
+
+        let data: Vec<u8> = [0.25f32, 2.0, f32::NAN]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let audio = decode_pcm(&data, AudioEncoding::PcmF32le, 48000, 2);
+        assert_eq!(audio.samples, [0.25, 1.0, 0.0]);
+        assert_eq!((audio.sample_rate, audio.channels), (48000, 2));
+    }
+
+    #[test]
+    fn wav_is_recorded_incrementally() {
+        let path = std::env::temp_dir().join(format!("flowstt-wav-{}.wav", std::process::id()));
+        let mut recorder = WavRecorder::create(path.clone(), 16000, 2).unwrap();
+        recorder.write(&[0.5, -0.5]).unwrap();
+        recorder.write(&[0.25, 0.0, 1.0, -1.0]).unwrap();
+        assert_eq!(recorder.finalize().unwrap(), path);
+
+        let audio = read_wav(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(audio.samples, [0.5, -0.5, 0.25, 0.0, 1.0, -1.0]);
+        assert_eq!((audio.sample_rate, audio.channels), (16000, 2));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn wav_files_are_decoded() {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(&mut bytes, spec).unwrap();
+        for sample in [16384i16, -32768, 0, 32767] {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let audio = read_wav(bytes.get_ref()).unwrap();
+        assert_eq!((audio.sample_rate, audio.channels), (44100, 2));
+        assert_eq!(audio.samples[..3], [0.5, -1.0, 0.0]);
+        assert!(read_wav(b"not a wav file").is_err());
+
+        // The upload decoder downmixes to mono
+        let audio = decode_audio_file(bytes.into_inner(), Some("wav")).unwrap();
+        assert_eq!((audio.sample_rate, audio.channels), (44100, 1));
+        assert_eq!(audio.samples.len(), 2);
+        assert_eq!(audio.samples[0], -0.25);
+        assert!((audio.samples[1] - 0.5).abs() < 1e-4);
+        assert!(decode_audio_file(b"not audio".to_vec(), Some("mp3")).is_err());
+    }
+}
@@ -1,30 +1,39 @@
 //! Audio processing loop for the service.
 //!
-//! This module connects the platform audio backend to the speech detection
-//! and transcription systems. In Automatic mode, uses VAD to trigger transcription.
-//! In PTT mode, the PTT controller manages transcription triggers.
+//! This module connects the platform audio backend to the shared audio
+//! pipeline. In Automatic mode, VAD triggers transcription. In PTT and
+//! toggle-to-talk modes, the PTT controller manages transcription triggers and
+//! the loop only records (and reports toggle-to-talk silence timeouts).
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
 use flowstt_common::ipc::{EventType, Response};
-use flowstt_common::{TranscriptionResult, VisualizationData};
+use flowstt_common::{Speaker, TranscriptionResult};
 use tracing::{debug, error, info};
 
+use crate::hooks::HookMetadata;
 use crate::ipc::broadcast_event;
+use crate::pipeline::{AudioPipeline, SegmentationStrategy, VisualizationBroadcaster};
 use crate::platform;
-use crate::processor::{
-    SpeechDetector, SpeechEventCallback, SpeechEventPayload, SpeechStateChange,
-    VisualizationCallback, VisualizationPayload, VisualizationProcessor, WordBreakEvent,
-    WordBreakPayload,
-};
 use crate::transcription::{TranscribeState, TranscriptionCallback, TranscriptionQueue};
+use crate::voice_commands::VoiceCommandParser;
+
+/// How long the audio loops block waiting for audio before re-checking their
+/// stop flags. Audio normally arrives every ~10ms, so this only bounds how
+/// quickly an idle loop notices it has been stopped.
+pub(crate) const AUDIO_RECV_TIMEOUT: Duration = Duration::from_millis(50);
 
 /// Global audio processing thread control
 static AUDIO_LOOP_ACTIVE: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
 
+/// Incremented every time a loop starts. A loop thread exits as soon as a
+/// newer loop supersedes it, so a quick stop/start never leaves two threads
+/// competing for the backend's audio channel.
+static AUDIO_LOOP_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 fn get_loop_active() -> Arc<AtomicBool> {
     AUDIO_LOOP_ACTIVE
         .get_or_init(|| Arc::new(AtomicBool::new(false)))
@@ -36,10 +45,11 @@ pub fn is_audio_loop_active() -> bool {
     get_loop_active().load(Ordering::SeqCst)
 }
 
-/// Start the audio processing loop
+/// Start the audio processing loop with the given segmentation strategy
 pub fn start_audio_loop(
     _transcription_queue: Arc<TranscriptionQueue>,
-    transcribe_state: Arc<std::sync::Mutex<TranscribeState>>,
+    transcribe_state: Arc<Mutex<TranscribeState>>,
+    strategy: SegmentationStrategy,
 ) -> Result<(), String> {
     if is_audio_loop_active() {
         return Err("Audio loop already running".into());
@@ -47,6 +57,7 @@ pub fn start_audio_loop(
 
     let loop_active = get_loop_active();
     loop_active.store(true, Ordering::SeqCst);
+    let generation = AUDIO_LOOP_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
 
     // Get sample rate from backend
     let sample_rate = platform::get_backend()
@@ -54,21 +65,19 @@ pub fn start_audio_loop(
         .unwrap_or(48000);
 
     thread::spawn(move || {
-        tracing::info!("[AudioLoop] Starting audio processing loop");
-
-        // Create speech detector
-        let mut speech_detector = SpeechDetector::new(sample_rate);
-        speech_detector.set_callback(Arc::new(SpeechEventBroadcaster));
-
-        // Create visualization processor
-        let mut viz_processor = VisualizationProcessor::new(sample_rate, 256);
-        viz_processor.set_callback(Arc::new(VisualizationBroadcaster));
+        info!(
+            "[AudioLoop] Starting audio processing loop ({:?})",
+            strategy
+        );
 
-        let loop_active = get_loop_active();
+        let mut pipeline = AudioPipeline::new(sample_rate, strategy);
+        pipeline.set_visualization_callback(Arc::new(VisualizationBroadcaster));
 
         loop {
-            // Check if we should stop
-            if !loop_active.load(Ordering::SeqCst) {
+            // Check if we should stop or have been superseded
+            if !loop_active.load(Ordering::SeqCst)
+                || AUDIO_LOOP_GENERATION.load(Ordering::SeqCst) != generation
+            {
                 break;
             }
 
@@ -77,73 +86,26 @@ pub fn start_audio_loop(
                 break;
             }
 
-            // Try to receive audio from backend
-            let audio_data = platform::get_backend().and_then(|b| b.try_recv());
-
-            if let Some(data) = audio_data {
-                // Convert to mono for processing
-                let mono_samples = convert_to_mono(&data.samples, data.channels as usize);
-
-                // Process through speech detector (always run for visualization)
-                speech_detector.process(&mono_samples);
-
-                // Get speech metrics for visualization
-                let speech_metrics = speech_detector.get_metrics();
-                viz_processor.set_speech_metrics(speech_metrics);
-
-                // Process visualization
-                viz_processor.process(&mono_samples);
-
-                // Handle speech state changes for transcribe mode
-                let state_change = speech_detector.take_state_change();
-                let word_break = speech_detector.take_word_break_event();
-
-                // Update transcribe state if active
-                // Note: In Automatic mode, VAD triggers segments
-                // In PTT mode, PTT controller triggers segments (not audio_loop)
-                if let Ok(mut transcribe) = transcribe_state.try_lock() {
-                    if transcribe.is_active {
-                        // Write samples to ring buffer
-                        transcribe.process_samples(&data.samples);
-
-                        // Use speech detection events to trigger segments
-                        match state_change {
-                            SpeechStateChange::Started { lookback_samples } => {
-                                transcribe.on_speech_started(lookback_samples);
-
-                                // Broadcast speech started event
-                                broadcast_event(Response::Event {
-                                    event: EventType::SpeechStarted,
-                                });
-                            }
-                            SpeechStateChange::Ended { duration_ms } => {
-                                transcribe.on_speech_ended();
-
-                                // Broadcast speech ended event
-                                broadcast_event(Response::Event {
-                                    event: EventType::SpeechEnded { duration_ms },
-                                });
-                            }
-                            SpeechStateChange::None => {}
-                        }
+            // Block until the backend delivers audio (no busy polling)
+            let audio_data = match platform::get_backend() {
+                Some(backend) => backend.recv_timeout(AUDIO_RECV_TIMEOUT),
+                None => {
+                    thread::sleep(AUDIO_RECV_TIMEOUT);
+                    None
+                }
+            };
 
-                        // Handle word breaks for timed segment submission
-                        if let Some(WordBreakEvent {
-                            offset_ms,
-                            gap_duration_ms,
-                        }) = word_break
-                        {
-                            transcribe.on_word_break(offset_ms, gap_duration_ms);
-                        }
-                    }
+            if let Some(mut data) = audio_data {
+                if let Some(event) = pipeline.process(&mut data, &transcribe_state) {
+                    broadcast_event(Response::Event { event });
+                }
+                if pipeline.take_silence_timeout() {
+                    crate::ptt_controller::request_toggle_talk_stop();
                 }
-            } else {
-                // No data available, sleep briefly
-                thread::sleep(Duration::from_millis(1));
             }
         }
 
-        tracing::info!("[AudioLoop] Audio processing loop stopped");
+        info!("[AudioLoop] Audio processing loop stopped");
     });
 
     Ok(())
@@ -154,62 +116,23 @@ pub fn stop_audio_loop() {
     get_loop_active().store(false, Ordering::SeqCst);
 }
 
-/// Convert multi-channel audio to mono
-fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        return samples.to_vec();
-    }
-    samples
-        .chunks(channels)
-        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-        .collect()
-}
-
-/// Broadcaster for speech events
-struct SpeechEventBroadcaster;
-
-impl SpeechEventCallback for SpeechEventBroadcaster {
-    fn on_speech_started(&self, _payload: SpeechEventPayload) {
-        // Handled in the main loop
-    }
-
-    fn on_speech_ended(&self, _payload: SpeechEventPayload) {
-        // Handled in the main loop
-    }
-
-    fn on_word_break(&self, _payload: WordBreakPayload) {
-        // Handled in the main loop
-    }
-}
-
-/// Broadcaster for visualization events
-struct VisualizationBroadcaster;
-
-impl VisualizationCallback for VisualizationBroadcaster {
-    fn on_visualization_data(&self, payload: VisualizationPayload) {
-        // Convert processor payload to common VisualizationData
-        let data = VisualizationData {
-            waveform: payload.waveform,
-            spectrogram: payload
-                .spectrogram
-                .map(|s| flowstt_common::SpectrogramColumn { colors: s.colors }),
-            speech_metrics: payload
-                .speech_metrics
-                .map(|m| flowstt_common::SpeechMetrics {
-                    amplitude_db: m.amplitude_db,
-                    zcr: m.zcr,
-                    centroid_hz: m.centroid_hz,
-                    is_speaking: m.is_speaking,
-                    voiced_onset_pending: m.is_voiced_pending,
-                    whisper_onset_pending: m.is_whisper_pending,
-                    is_transient: m.is_transient,
-                    is_lookback_speech: m.is_lookback_speech,
-                    is_word_break: m.is_word_break,
-                }),
-        };
-        broadcast_event(Response::Event {
-            event: EventType::VisualizationData(data),
-        });
+/// Copy a transcription to the clipboard and optionally paste it into the
+/// foreground app, applying voice commands when enabled.
+fn paste_transcription(config: &crate::config::Config, text: &str) {
+    if config.voice_commands_enabled {
+        // History keeps the transcript as spoken; only typing is edited
+        let edits = VoiceCommandParser::new(&config.voice_commands).parse(text);
+        crate::clipboard::type_edits(
+            &edits,
+            config.auto_paste_enabled,
+            config.auto_paste_delay_ms,
+        );
+    } else {
+        crate::clipboard::copy_and_paste(
+            text,
+            config.auto_paste_enabled,
+            config.auto_paste_delay_ms,
+        );
     }
 }
 
@@ -221,37 +144,68 @@ impl TranscriptionCallback for TranscriptionEventBroadcaster {
         debug!("[Transcription] Started");
     }
 
-    fn on_transcription_complete(&self, text: String, wav_path: Option<String>) {
-        info!("[Transcription] Complete: {}", text);
+    fn on_transcription_complete(
+        &self,
+        text: String,
+        wav_path: Option<String>,
+        speaker: Option<Speaker>,
+        speaker_id: Option<u32>,
+        paste: bool,
+    ) {
+        match (speaker, speaker_id) {
+            (Some(speaker), _) => info!("[Transcription] Complete ({}): {}", speaker.label(), text),
+            (None, Some(id)) => info!("[Transcription] Complete (speaker {}): {}", id, text),
+            (None, None) => info!("[Transcription] Complete: {}", text),
+        }
 
         // Add to persistent history and get the enriched entry
         let history = crate::history::get_history();
         let entry = {
             let mut h = history.lock().unwrap();
-            h.add_entry(text.clone(), wav_path)
+            h.add_entry(text.clone(), wav_path, speaker, speaker_id)
         };
 
         broadcast_event(Response::Event {
             event: EventType::TranscriptionComplete(TranscriptionResult {
-                id: Some(entry.id),
+                id: Some(entry.id.clone()),
                 text: entry.text.clone(),
-                timestamp: Some(entry.timestamp),
-                audio_path: entry.wav_path,
+                timestamp: Some(entry.timestamp.clone()),
+                audio_path: entry.wav_path.clone(),
+                speaker: entry.speaker,
+                speaker_id: entry.speaker_id,
             }),
         });
 
-        // Copy to clipboard and optionally paste into the foreground app.
-        // Config is loaded from disk so runtime changes take effect immediately.
-        let config = crate::config::Config::load();
-        crate::clipboard::copy_and_paste(
-            &entry.text,
-            config.auto_paste_enabled,
-            config.auto_paste_delay_ms,
-        );
+        // Config is loaded from disk so runtime changes take effect immediately
+        let mut config = crate::config::Config::load();
+        if crate::clipboard::is_no_speech(&entry.text) {
+            return;
+        }
+
+        // Only the local user's own speech is pasted; remote participants'
+        // speech is recorded in history for meeting notes
+        let paste = paste && speaker != Some(Speaker::Remote);
+        if !paste {
+            crate::clipboard::forget_typed();
+        }
+        let metadata = HookMetadata {
+            id: entry.id,
+            timestamp: entry.timestamp,
+            audio_path: entry.wav_path,
+            speaker: entry.speaker,
+            speaker_id: entry.speaker_id,
+        };
+        let hooks = std::mem::take(&mut config.hooks);
+        crate::hooks::dispatch(&hooks, &entry.text, metadata, paste, move |text| {
+            paste_transcription(&config, text)
+        });
     }
 
     fn on_transcription_error(&self, error: String) {
         error!("[Transcription] Error: {}", error);
+        broadcast_event(Response::Event {
+            event: EventType::TranscriptionError { error },
+        });
     }
 
     fn on_transcription_finished(&self) {
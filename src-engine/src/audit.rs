@@ -0,0 +1,213 @@
+//! Audit log of state-changing client requests.
+//!
+//! Every request that changes engine state or configuration is appended to
+//! `audit.log` in the log directory as one JSON line recording the client
+//! (PID and executable for IPC clients), the request type and its outcome.
+//! Clients rejected by peer verification are recorded as well. The file is
+//! rotated by size, keeping [`AUDIT_LOG_GENERATIONS`] older files.
+//!
+//! Entries are written by a dedicated thread so that recording a request
+//! does not wait for file I/O unless the writer falls
+//! [`AUDIT_QUEUE_CAPACITY`] entries behind. [`flush`] waits for the queued
+//! entries to be written and is called when the engine shuts down.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use flowstt_common::ipc::Response;
+use flowstt_common::logging::{audit_log_path, ensure_log_dir, rotated_log_path, AuditEntry};
+use flowstt_common::security::peer_verify::{PeerInfo, PeerVerifyError};
+use tracing::warn;
+
+/// Size at which the audit log is rotated
+const MAX_AUDIT_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Number of rotated audit logs kept (`audit.log.1` ...)
+const AUDIT_LOG_GENERATIONS: u32 = 5;
+
+/// Number of entries queued for the writer before recording blocks
+const AUDIT_QUEUE_CAPACITY: usize = 1024;
+
+/// How long [`flush`] waits for the writer
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Message for the writer thread
+enum WriterMessage {
+    Entry(AuditEntry),
+    /// Acknowledge once every earlier entry is written
+    Flush(mpsc::Sender<()>),
+}
+
+/// Queue of messages for the writer thread, started on first use
+static AUDIT_WRITER: OnceLock<mpsc::SyncSender<WriterMessage>> = OnceLock::new();
+
+/// Record a state-changing request and its response.
+///
+/// `peer` is the verified IPC client; HTTP API clients have none.
+pub fn record_request(
+    transport: &str,
+    peer: Option<&PeerInfo>,
+    request_type: &str,
+    response: &Response,
+) {
+    let (error_code, error) = match response {
+        Response::Error { code, message } => (Some(*code), Some(message.clone())),
+        _ => (None, None),
+    };
+    append(AuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        transport: transport.to_string(),
+        pid: peer.map(|p| p.pid),
+        executable: peer.map(|p| p.executable.clone()),
+        request: request_type.to_string(),
+        success: error.is_none(),
+        error_code,
+        error,
+    });
+}
+
+/// Record a client that failed peer verification.
+pub fn record_rejected_peer(error: &PeerVerifyError) {
+    let (pid, executable) = match error {
+        PeerVerifyError::Untrusted {
+            pid, executable, ..
+        } => (Some(*pid), Some(executable.clone())),
+        PeerVerifyError::ProcessNotFound(pid) => (Some(*pid), None),
+        _ => (None, None),
+    };
+    append(AuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        transport: "ipc".to_string(),
+        pid,
+        executable,
+        request: "connect".to_string(),
+        success: false,
+        error_code: None,
+        error: Some(error.to_string()),
+    });
+}
+
+/// Wait until every recorded entry is written to the audit log, so entries
+/// recorded just before the engine exits (such as the `shutdown` request
+/// itself) are not lost. Gives up after [`FLUSH_TIMEOUT`].
+pub fn flush() {
+    let Some(writer) = AUDIT_WRITER.get() else {
+        return;
+    };
+    let (ack_tx, ack_rx) = mpsc::channel();
+    if writer.send(WriterMessage::Flush(ack_tx)).is_err() {
+        return;
+    }
+    if ack_rx.recv_timeout(FLUSH_TIMEOUT).is_err() {
+        warn!("Timed out flushing the audit log");
+    }
+}
+
+fn append(entry: AuditEntry) {
+    let writer = AUDIT_WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::sync_channel(AUDIT_QUEUE_CAPACITY);
+        if let Err(e) = std::thread::Builder::new()
+            .name("audit-log".into())
+            .spawn(move || write_entries(rx))
+        {
+            warn!("Failed to start audit log writer: {}", e);
+        }
+        tx
+    });
+    if writer.send(WriterMessage::Entry(entry)).is_err() {
+        warn!("Audit log writer is not running, entry dropped");
+    }
+}
+
+/// Writer thread: append queued entries until the engine exits.
+fn write_entries(messages: mpsc::Receiver<WriterMessage>) {
+    for message in messages {
+        match message {
+            WriterMessage::Entry(entry) => {
+                let result = ensure_log_dir().and_then(|()| write_entry(&audit_log_path(), &entry));
+                if let Err(e) = result {
+                    warn!("Failed to write audit log: {}", e);
+                }
+            }
+            WriterMessage::Flush(ack) => {
+                let _ = ack.send(());
+            }
+        }
+    }
+}
+
+fn write_entry(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if std::fs::metadata(path).is_ok_and(|m| m.len() >= MAX_AUDIT_LOG_BYTES) {
+        rotate(path)?;
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Shift `audit.log` to `audit.log.1`, `audit.log.1` to `audit.log.2` and so
+/// on, dropping the oldest.
+fn rotate(path: &Path) -> std::io::Result<()> {
+    for generation in (1..AUDIT_LOG_GENERATIONS).rev() {
+        let from = rotated_log_path(path, generation);
+        if from.exists() {
+            std::fs::rename(&from, rotated_log_path(path, generation + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_log_path(path, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(request: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: "2026-01-01T00:00:00+00:00".into(),
+            transport: "ipc".into(),
+            pid: Some(42),
+            executable: Some("/usr/bin/flowstt".into()),
+            request: request.into(),
+            success: true,
+            error_code: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn audit_log_is_rotated() {
+        let dir = std::env::temp_dir().join(format!("flowstt-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+
+        write_entry(&path, &entry("set_sources")).unwrap();
+        write_entry(&path, &entry("shutdown")).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let requests: Vec<String> = contents
+            .lines()
+            .map(|l| serde_json::from_str::<AuditEntry>(l).unwrap().request)
+            .collect();
+        assert_eq!(requests, ["set_sources", "shutdown"]);
+
+        for _ in 0..AUDIT_LOG_GENERATIONS + 2 {
+            std::fs::write(&path, vec![b'x'; MAX_AUDIT_LOG_BYTES as usize]).unwrap();
+            write_entry(&path, &entry("set_aec_enabled")).unwrap();
+        }
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("set_aec_enabled"));
+        assert!(rotated_log_path(&path, AUDIT_LOG_GENERATIONS).exists());
+        assert!(!rotated_log_path(&path, AUDIT_LOG_GENERATIONS + 1).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
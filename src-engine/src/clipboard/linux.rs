@@ -4,8 +4,10 @@
 //! - Clipboard: `xclip` (X11) or `wl-copy` (Wayland)
 //! - Foreground: `xdotool getactivewindow getwindowpid` (X11) or best-effort
 //! - Paste: `xdotool key ctrl+v` (X11) or `wtype -M ctrl -k v` (Wayland)
+//! - Keys: `xdotool key` (X11) or `wtype -k` (Wayland)
 
 use super::ClipboardPaster;
+use flowstt_common::EditKey;
 use std::process::Command;
 use tracing::{debug, warn};
 
@@ -31,6 +33,18 @@ impl ClipboardPaster for LinuxClipboardPaster {
         }
     }
 
+    fn foreground_window(&self) -> Option<String> {
+        if is_wayland() {
+            return None;
+        }
+        let output = Command::new("xdotool")
+            .arg("getactivewindow")
+            .output()
+            .ok()?;
+        let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !id.is_empty()).then_some(id)
+    }
+
     fn simulate_paste(&self) -> Result<(), String> {
         if is_wayland() {
             let status = Command::new("wtype")
@@ -54,6 +68,52 @@ impl ClipboardPaster for LinuxClipboardPaster {
             Ok(())
         }
     }
+
+    fn simulate_key(&self, key: EditKey, count: usize) -> Result<(), String> {
+        // X keysym names, understood by both xdotool and wtype
+        let (ctrl, keysym) = match key {
+            EditKey::Enter => (false, "Return"),
+            EditKey::Tab => (false, "Tab"),
+            EditKey::Backspace => (false, "BackSpace"),
+            EditKey::Escape => (false, "Escape"),
+            EditKey::SelectAll => (true, "a"),
+            EditKey::Undo => (true, "z"),
+        };
+
+        let (cmd, args) = if is_wayland() {
+            let mut args = Vec::new();
+            for _ in 0..count {
+                if ctrl {
+                    args.extend(["-M", "ctrl", "-k", keysym, "-m", "ctrl"]);
+                } else {
+                    args.extend(["-k", keysym]);
+                }
+            }
+            ("wtype", args.into_iter().map(String::from).collect())
+        } else {
+            let name = if ctrl {
+                format!("ctrl+{}", keysym)
+            } else {
+                keysym.to_string()
+            };
+            let args = vec![
+                "key".to_string(),
+                "--repeat".to_string(),
+                count.to_string(),
+                name,
+            ];
+            ("xdotool", args)
+        };
+
+        let status = Command::new(cmd)
+            .args(&args)
+            .status()
+            .map_err(|e| format!("Failed to run {}: {} (is {} installed?)", cmd, e, cmd))?;
+        if !status.success() {
+            return Err(format!("{} exited with status {}", cmd, status));
+        }
+        Ok(())
+    }
 }
 
 /// Detect whether we're running under Wayland.
@@ -4,8 +4,10 @@
 //! - `NSPasteboard` for clipboard write
 //! - `NSWorkspace.shared.frontmostApplication` for foreground detection
 //! - `CGEvent` for Cmd+V paste simulation
+//! - `System Events` key codes for voice command keystrokes
 
 use super::ClipboardPaster;
+use flowstt_common::EditKey;
 use std::process::Command;
 use tracing::debug;
 
@@ -53,6 +55,17 @@ impl ClipboardPaster for MacOSClipboardPaster {
         }
     }
 
+    fn foreground_window(&self) -> Option<String> {
+        // Process ID and title of the frontmost window
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(r#"tell application "System Events" to tell (first process whose frontmost is true) to get {unix id, name of front window}"#)
+            .output()
+            .ok()?;
+        let window = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !window.is_empty()).then_some(window)
+    }
+
     fn simulate_paste(&self) -> Result<(), String> {
         // Use osascript to send Cmd+V keystroke.
         // This requires Accessibility permission (which FlowSTT already needs
@@ -68,4 +81,30 @@ impl ClipboardPaster for MacOSClipboardPaster {
         }
         Ok(())
     }
+
+    fn simulate_key(&self, key: EditKey, count: usize) -> Result<(), String> {
+        let keystroke = match key {
+            EditKey::Enter => "key code 36",
+            EditKey::Tab => "key code 48",
+            EditKey::Backspace => "key code 51",
+            EditKey::Escape => "key code 53",
+            EditKey::SelectAll => r#"keystroke "a" using command down"#,
+            EditKey::Undo => r#"keystroke "z" using command down"#,
+        };
+        let script = format!(
+            "tell application \"System Events\"\nrepeat {} times\n{}\nend repeat\nend tell",
+            count, keystroke
+        );
+
+        let status = Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .status()
+            .map_err(|e| format!("Failed to run osascript for key press: {}", e))?;
+
+        if !status.success() {
+            return Err(format!("osascript key press exited with status {}", status));
+        }
+        Ok(())
+    }
 }
@@ -3,7 +3,8 @@
 //! After each transcription segment completes, this module copies the text to
 //! the system clipboard and optionally simulates a paste keystroke into the
 //! active foreground application. Paste simulation is suppressed when a FlowSTT
-//! window is in the foreground.
+//! window is in the foreground. When voice commands are enabled, the text is
+//! typed as a sequence of pastes and keystrokes instead (see [`type_edits`]).
 //!
 //! Platform-specific implementations live in submodules following the same
 //! backend-trait pattern used by `crate::hotkey`.
@@ -17,9 +18,54 @@ mod macos;
 #[cfg(target_os = "linux")]
 mod linux;
 
+use std::sync::Mutex;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
+use flowstt_common::EditKey;
+
+use crate::voice_commands::{edits_text, EditOp};
+
+/// Text pasted for the most recent transcript, removed by a "delete that"
+/// spoken on its own. Cleared whenever a transcript is not typed.
+static LAST_TYPED: Mutex<Option<TypedText>> = Mutex::new(None);
+
+/// Characters typed into a window by the most recent transcript.
+#[derive(Debug, Clone, PartialEq)]
+struct TypedText {
+    chars: usize,
+    /// Foreground window the text was typed into, if the platform reports it
+    window: Option<String>,
+}
+
+/// Record the characters typed for the current transcript.
+fn remember_typed(chars: usize, window: Option<String>) {
+    *LAST_TYPED.lock().unwrap() = (chars > 0).then_some(TypedText { chars, window });
+}
+
+/// Forget the previous transcript so a later "delete that" removes nothing.
+pub fn forget_typed() {
+    *LAST_TYPED.lock().unwrap() = None;
+}
+
+/// Take the number of characters "delete that" may remove from `window`.
+///
+/// Nothing is removed once focus has moved to another window, or when the
+/// platform cannot report the focused window (Wayland).
+fn take_typed(window: &Option<String>) -> usize {
+    match LAST_TYPED.lock().unwrap().take() {
+        Some(typed) if typed.window.is_some() && typed.window == *window => typed.chars,
+        Some(typed) => {
+            debug!(
+                "[Clipboard] Focus changed since the last transcript, not deleting {} characters",
+                typed.chars
+            );
+            0
+        }
+        None => 0,
+    }
+}
+
 /// Platform-agnostic clipboard and paste backend.
 pub trait ClipboardPaster: Send + Sync {
     /// Write plain text to the system clipboard.
@@ -28,8 +74,14 @@ pub trait ClipboardPaster: Send + Sync {
     /// Check whether the current foreground window belongs to FlowSTT.
     fn is_flowstt_foreground(&self) -> bool;
 
+    /// Identify the current foreground window, or `None` if it is unknown.
+    fn foreground_window(&self) -> Option<String>;
+
     /// Simulate a paste keystroke (Ctrl+V / Cmd+V) into the foreground window.
     fn simulate_paste(&self) -> Result<(), String>;
+
+    /// Simulate pressing a key or shortcut `count` times.
+    fn simulate_key(&self, key: EditKey, count: usize) -> Result<(), String>;
 }
 
 /// Create the platform-specific backend.
@@ -55,6 +107,12 @@ fn create_backend() -> Box<dyn ClipboardPaster> {
     }
 }
 
+/// Whether a transcript is empty or a "no speech" placeholder.
+pub fn is_no_speech(text: &str) -> bool {
+    let trimmed = text.trim();
+    trimmed.is_empty() || trimmed == "(No speech detected)"
+}
+
 /// Perform the full clipboard-copy-and-paste flow for a transcription result.
 ///
 /// 1. Skip if the text is empty or a "no speech" placeholder.
@@ -62,11 +120,15 @@ fn create_backend() -> Box<dyn ClipboardPaster> {
 /// 3. If `auto_paste` is enabled and the foreground window is not FlowSTT,
 ///    wait `delay` and simulate a paste keystroke.
 pub fn copy_and_paste(text: &str, auto_paste_enabled: bool, delay_ms: u32) {
+    // Nothing typed below may be removed by a later "delete that" unless the
+    // paste succeeds
+    forget_typed();
+
     // Skip empty / no-speech results
-    let trimmed = text.trim();
-    if trimmed.is_empty() || trimmed == "(No speech detected)" {
+    if is_no_speech(text) {
         return;
     }
+    let trimmed = text.trim();
 
     let backend = create_backend();
 
@@ -97,5 +159,92 @@ pub fn copy_and_paste(text: &str, auto_paste_enabled: bool, delay_ms: u32) {
         warn!("[Clipboard] Failed to simulate paste: {}", e);
     } else {
         debug!("[Clipboard] Paste simulated into foreground application");
+        remember_typed(trimmed.chars().count(), backend.foreground_window());
+    }
+}
+
+/// Type the edits produced by voice commands into the foreground application.
+///
+/// Text is pasted through the clipboard and keys are simulated in order.
+/// Without auto-paste, or when FlowSTT is in the foreground, only the text is
+/// copied to the clipboard.
+pub fn type_edits(edits: &[EditOp], auto_paste_enabled: bool, delay_ms: u32) {
+    if edits.is_empty() {
+        return;
+    }
+
+    let backend = create_backend();
+    if !auto_paste_enabled || backend.is_flowstt_foreground() {
+        forget_typed();
+        let text = edits_text(edits);
+        if !text.trim().is_empty() {
+            if let Err(e) = backend.write_clipboard(&text) {
+                warn!("[Clipboard] Failed to write clipboard: {}", e);
+            }
+        }
+        return;
+    }
+
+    // Characters typed for this transcript since the last keystroke
+    let window = backend.foreground_window();
+    let mut typed = 0;
+    for op in edits {
+        let result = match op {
+            EditOp::Text(text) => backend.write_clipboard(text).and_then(|_| {
+                if delay_ms > 0 {
+                    std::thread::sleep(Duration::from_millis(delay_ms as u64));
+                }
+                typed += text.chars().count();
+                backend.simulate_paste()
+            }),
+            EditOp::Key(key) => {
+                typed = 0;
+                backend.simulate_key(*key, 1)
+            }
+            EditOp::DeletePrevious => {
+                let count = take_typed(&window);
+                if count == 0 {
+                    Ok(())
+                } else {
+                    backend.simulate_key(EditKey::Backspace, count)
+                }
+            }
+        };
+        if let Err(e) = result {
+            warn!("[Clipboard] Failed to type {:?}: {}", op, e);
+            // How much of the text reached the window is unknown
+            typed = 0;
+            break;
+        }
+    }
+    remember_typed(typed, window);
+    debug!(
+        "[Clipboard] Typed {} edits into foreground application",
+        edits.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_text_is_only_deleted_in_the_same_window() {
+        let editor = Some("editor".to_string());
+        remember_typed(5, editor.clone());
+        assert_eq!(take_typed(&Some("terminal".to_string())), 0);
+        // Taken even when focus moved, so it cannot be deleted later
+        assert_eq!(take_typed(&editor), 0);
+
+        remember_typed(5, editor.clone());
+        assert_eq!(take_typed(&editor), 5);
+        assert_eq!(take_typed(&editor), 0);
+
+        remember_typed(5, None);
+        assert_eq!(take_typed(&None), 0);
+
+        remember_typed(5, editor.clone());
+        forget_typed();
+        assert_eq!(take_typed(&editor), 0);
     }
 }
@@ -4,8 +4,10 @@
 //! - Clipboard: `OpenClipboard` / `EmptyClipboard` / `SetClipboardData` / `CloseClipboard`
 //! - Foreground: `GetForegroundWindow` / `GetWindowThreadProcessId`
 //! - Paste sim: `SendInput` with `INPUT_KEYBOARD` for Ctrl+V
+//! - Keys: `SendInput` for voice command keystrokes
 
 use super::ClipboardPaster;
+use flowstt_common::EditKey;
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
 use tracing::debug;
@@ -19,7 +21,7 @@ use windows::Win32::System::Threading::{
 };
 use windows::Win32::UI::Input::KeyboardAndMouse::{
     SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
-    VIRTUAL_KEY, VK_CONTROL, VK_V,
+    VIRTUAL_KEY, VK_A, VK_BACK, VK_CONTROL, VK_ESCAPE, VK_RETURN, VK_TAB, VK_V, VK_Z,
 };
 use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
 
@@ -37,9 +39,18 @@ impl ClipboardPaster for WindowsClipboardPaster {
         is_flowstt_foreground_window()
     }
 
+    fn foreground_window(&self) -> Option<String> {
+        let hwnd = unsafe { GetForegroundWindow() };
+        (!hwnd.0.is_null()).then(|| format!("{:p}", hwnd.0))
+    }
+
     fn simulate_paste(&self) -> Result<(), String> {
         simulate_ctrl_v()
     }
+
+    fn simulate_key(&self, key: EditKey, count: usize) -> Result<(), String> {
+        simulate_edit_key(key, count)
+    }
 }
 
 /// Write UTF-16 text to the Windows clipboard.
@@ -154,6 +165,40 @@ fn simulate_ctrl_v() -> Result<(), String> {
     Ok(())
 }
 
+/// Simulate a voice command key press `count` times via `SendInput`.
+fn simulate_edit_key(key: EditKey, count: usize) -> Result<(), String> {
+    let (ctrl, vk) = match key {
+        EditKey::Enter => (false, VK_RETURN),
+        EditKey::Tab => (false, VK_TAB),
+        EditKey::Backspace => (false, VK_BACK),
+        EditKey::Escape => (false, VK_ESCAPE),
+        EditKey::SelectAll => (true, VK_A),
+        EditKey::Undo => (true, VK_Z),
+    };
+
+    let mut inputs = Vec::with_capacity(count * 4);
+    for _ in 0..count {
+        if ctrl {
+            inputs.push(make_key_input(VK_CONTROL, false));
+        }
+        inputs.push(make_key_input(vk, false));
+        inputs.push(make_key_input(vk, true));
+        if ctrl {
+            inputs.push(make_key_input(VK_CONTROL, true));
+        }
+    }
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent != inputs.len() as u32 {
+        return Err(format!(
+            "SendInput sent {} of {} events",
+            sent,
+            inputs.len()
+        ));
+    }
+    Ok(())
+}
+
 /// Build an `INPUT` struct for a single keyboard event.
 fn make_key_input(vk: VIRTUAL_KEY, key_up: bool) -> INPUT {
     let flags = if key_up {
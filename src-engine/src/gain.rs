@@ -0,0 +1,315 @@
+//! Automatic gain control and loudness normalization.
+//!
+//! This module provides:
+//! - `AutomaticGainControl`: Smoothed level tracking applied to live capture
+//!   so quiet microphones reach the VAD/segment thresholds and hot microphones
+//!   are pulled back before they clip
+//! - `normalize_segment`: Per-segment loudness normalization with a peak
+//!   ceiling, applied just before audio is handed to Whisper
+
+use flowstt_common::DEFAULT_AGC_TARGET_LEVEL_DB;
+use std::sync::{Arc, Mutex};
+
+/// Maximum gain the AGC may apply (boost for quiet microphones)
+const AGC_MAX_GAIN_DB: f32 = 24.0;
+
+/// Minimum gain the AGC may apply (attenuation for hot microphones)
+const AGC_MIN_GAIN_DB: f32 = -12.0;
+
+/// Input level below which the AGC holds its current gain instead of boosting
+/// (prevents the noise floor from being amplified during silence)
+const AGC_NOISE_GATE_DB: f32 = -60.0;
+
+/// Time constant for reducing gain when the signal gets louder
+const AGC_ATTACK_MS: f32 = 20.0;
+
+/// Time constant for increasing gain when the signal gets quieter
+const AGC_RELEASE_MS: f32 = 800.0;
+
+/// Output ceiling for the soft limiter (approximately -1 dBFS)
+const PEAK_CEILING: f32 = 0.89;
+
+/// Maximum gain applied by segment normalization
+const NORMALIZE_MAX_GAIN_DB: f32 = 30.0;
+
+/// Segments quieter than this RMS are left untouched by normalization
+const NORMALIZE_MIN_RMS: f32 = 1e-5;
+
+/// Convert decibels to a linear gain factor
+pub fn db_to_linear(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+/// Convert a linear gain factor to decibels
+pub fn linear_to_db(linear: f32) -> f32 {
+    if linear <= 0.0 {
+        return -100.0;
+    }
+    20.0 * linear.log10()
+}
+
+/// Calculate RMS amplitude of samples
+fn calculate_rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
+    (sum_squares / samples.len() as f32).sqrt()
+}
+
+/// Soft-limit a sample so it never exceeds the peak ceiling
+fn soft_limit(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= PEAK_CEILING {
+        return sample;
+    }
+    // Compress everything above the ceiling into the remaining headroom
+    let headroom = 1.0 - PEAK_CEILING;
+    let excess = magnitude - PEAK_CEILING;
+    let limited = PEAK_CEILING + headroom * (1.0 - (-excess / headroom).exp());
+    limited.copysign(sample)
+}
+
+// ============================================================================
+// Gain Settings
+// ============================================================================
+
+/// Runtime gain settings shared between the IPC handlers and the audio loops.
+#[derive(Debug, Clone, Copy)]
+pub struct GainSettings {
+    /// Whether AGC and segment normalization are applied
+    pub enabled: bool,
+    /// Target RMS level in dBFS
+    pub target_level_db: f32,
+}
+
+impl Default for GainSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_level_db: DEFAULT_AGC_TARGET_LEVEL_DB,
+        }
+    }
+}
+
+/// Global gain settings
+static GAIN_SETTINGS: std::sync::OnceLock<Arc<Mutex<GainSettings>>> = std::sync::OnceLock::new();
+
+/// Get the global gain settings.
+pub fn get_gain_settings() -> Arc<Mutex<GainSettings>> {
+    GAIN_SETTINGS
+        .get_or_init(|| Arc::new(Mutex::new(GainSettings::default())))
+        .clone()
+}
+
+/// Get a snapshot of the current gain settings.
+pub fn current_gain_settings() -> GainSettings {
+    *get_gain_settings().lock().unwrap()
+}
+
+/// Replace the global gain settings.
+pub fn set_gain_settings(settings: GainSettings) {
+    *get_gain_settings().lock().unwrap() = settings;
+}
+
+// ============================================================================
+// Automatic Gain Control
+// ============================================================================
+
+/// Block-based automatic gain control for the live capture stream.
+///
+/// Tracks the block RMS level, moves the gain towards the value that would
+/// bring the signal to the target level (fast attack, slow release), and
+/// interpolates the gain across each block to avoid zipper noise. A soft
+/// limiter keeps the boosted output below full scale.
+pub struct AutomaticGainControl {
+    /// Sample rate for time constant calculations
+    sample_rate: u32,
+    /// Target RMS level in dBFS
+    target_level_db: f32,
+    /// Current gain in dB
+    gain_db: f32,
+}
+
+impl AutomaticGainControl {
+    /// Create a new AGC with the specified sample rate and target level.
+    pub fn new(sample_rate: u32, target_level_db: f32) -> Self {
+        Self {
+            sample_rate,
+            target_level_db,
+            gain_db: 0.0,
+        }
+    }
+
+    /// Update the target level (takes effect from the next block).
+    pub fn set_target_level_db(&mut self, target_level_db: f32) {
+        self.target_level_db = target_level_db;
+    }
+
+    /// Get the gain currently applied, in dB.
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Reset the gain to unity.
+    pub fn reset(&mut self) {
+        self.gain_db = 0.0;
+    }
+
+    /// Apply gain to a block of interleaved samples in place.
+    pub fn process(&mut self, samples: &mut [f32], channels: usize) {
+        if samples.is_empty() {
+            return;
+        }
+
+        let previous_gain = db_to_linear(self.gain_db);
+        let input_db = linear_to_db(calculate_rms(samples));
+
+        if input_db > AGC_NOISE_GATE_DB {
+            let desired_gain_db =
+                (self.target_level_db - input_db).clamp(AGC_MIN_GAIN_DB, AGC_MAX_GAIN_DB);
+
+            // Time elapsed for this block determines how far the gain may move
+            let frames = samples.len() / channels.max(1);
+            let block_ms = frames as f32 * 1000.0 / self.sample_rate as f32;
+            let time_constant_ms = if desired_gain_db < self.gain_db {
+                AGC_ATTACK_MS
+            } else {
+                AGC_RELEASE_MS
+            };
+            let coeff = 1.0 - (-block_ms / time_constant_ms).exp();
+            self.gain_db += (desired_gain_db - self.gain_db) * coeff;
+        }
+
+        let target_gain = db_to_linear(self.gain_db);
+        let step = (target_gain - previous_gain) / samples.len() as f32;
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let gain = previous_gain + step * (i + 1) as f32;
+            *sample = soft_limit(*sample * gain);
+        }
+    }
+}
+
+// ============================================================================
+// Segment Normalization
+// ============================================================================
+
+/// Normalize a mono segment towards the target RMS level.
+///
+/// The applied gain is capped so the segment peak stays below the limiter
+/// ceiling, so hot segments are pulled down and quiet ones are lifted without
+/// clipping. Returns the gain that was applied, in dB.
+pub fn normalize_segment(samples: &mut [f32], target_level_db: f32) -> f32 {
+    let rms = calculate_rms(samples);
+    if rms < NORMALIZE_MIN_RMS {
+        return 0.0;
+    }
+
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    let loudness_gain = db_to_linear(target_level_db) / rms;
+    let peak_gain = PEAK_CEILING / peak;
+    let gain = loudness_gain
+        .min(peak_gain)
+        .min(db_to_linear(NORMALIZE_MAX_GAIN_DB));
+
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+
+    linear_to_db(gain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 2.0 * std::f32::consts::PI * 440.0 / 16000.0).sin())
+            .collect()
+    }
+
+    fn level_db(samples: &[f32]) -> f32 {
+        linear_to_db(calculate_rms(samples))
+    }
+
+    /// Run the AGC over 10 ms blocks and return the level of the last block
+    fn run_agc(agc: &mut AutomaticGainControl, amplitude: f32, blocks: usize) -> f32 {
+        let mut last = Vec::new();
+        for _ in 0..blocks {
+            last = sine(amplitude, 160);
+            agc.process(&mut last, 1);
+        }
+        level_db(&last)
+    }
+
+    #[test]
+    fn agc_converges_to_target() {
+        // Quiet input (about -43 dBFS) is lifted to the target
+        let mut agc = AutomaticGainControl::new(16000, -20.0);
+        let level = run_agc(&mut agc, 0.01, 1000);
+        assert!((level + 20.0).abs() < 1.0, "level {}", level);
+        assert!((agc.gain_db() - 23.0).abs() < 1.0);
+
+        // Hot input is pulled down quickly (attack is much faster than release)
+        let mut agc = AutomaticGainControl::new(16000, -20.0);
+        let level = run_agc(&mut agc, 0.3, 20);
+        assert!((level + 20.0).abs() < 1.0, "level {}", level);
+
+        // Attenuation is capped at the minimum gain
+        let mut agc = AutomaticGainControl::new(16000, -20.0);
+        run_agc(&mut agc, 0.9, 100);
+        assert!((agc.gain_db() - AGC_MIN_GAIN_DB).abs() < 0.1);
+
+        // Boost is capped at the maximum gain
+        let mut agc = AutomaticGainControl::new(16000, -20.0);
+        run_agc(&mut agc, 0.0005, 2000);
+        assert!(agc.gain_db() <= AGC_MAX_GAIN_DB + 1e-3);
+    }
+
+    #[test]
+    fn agc_holds_gain_below_noise_gate() {
+        let mut agc = AutomaticGainControl::new(16000, -20.0);
+        run_agc(&mut agc, 0.01, 1000);
+        let gain = agc.gain_db();
+        run_agc(&mut agc, 0.0001, 1000);
+        assert_eq!(agc.gain_db(), gain);
+
+        agc.reset();
+        assert_eq!(agc.gain_db(), 0.0);
+    }
+
+    #[test]
+    fn agc_output_never_exceeds_ceiling() {
+        let mut agc = AutomaticGainControl::new(16000, -6.0);
+        run_agc(&mut agc, 0.05, 1000);
+        let mut block = sine(1.0, 160);
+        agc.process(&mut block, 1);
+        assert!(block.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn segments_are_normalized() {
+        // Quiet segment is lifted to the target
+        let mut samples = sine(0.01, 16000);
+        let gain = normalize_segment(&mut samples, -20.0);
+        assert!((level_db(&samples) + 20.0).abs() < 0.1);
+        assert!((gain - 23.0).abs() < 0.1);
+
+        // A loud peak limits the gain so the segment does not clip
+        let mut samples = sine(0.01, 16000);
+        samples[100] = 0.5;
+        normalize_segment(&mut samples, -20.0);
+        let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - PEAK_CEILING).abs() < 1e-4);
+
+        // Hot segments are attenuated
+        let mut samples = sine(0.8, 16000);
+        assert!(normalize_segment(&mut samples, -20.0) < 0.0);
+
+        // Silence is left alone
+        let mut samples = vec![0.0; 1600];
+        assert_eq!(normalize_segment(&mut samples, -20.0), 0.0);
+        assert!(samples.iter().all(|s| *s == 0.0));
+    }
+}
@@ -4,6 +4,7 @@
 //! cached WAV recordings in the OS-standard application data directory.
 
 use chrono::Utc;
+use flowstt_common::Speaker;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -22,6 +23,12 @@ pub struct HistoryEntry {
     pub timestamp: String,
     /// Path to the cached WAV file, if it still exists
     pub wav_path: Option<String>,
+    /// Speaker, when recorded in speaker-separated mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<Speaker>,
+    /// Diarized speaker within the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<u32>,
 }
 
 /// Manages persistent transcription history.
@@ -101,12 +108,20 @@ impl TranscriptionHistory {
     }
 
     /// Add a new entry to the history and save.
-    pub fn add_entry(&mut self, text: String, wav_path: Option<String>) -> HistoryEntry {
+    pub fn add_entry(
+        &mut self,
+        text: String,
+        wav_path: Option<String>,
+        speaker: Option<Speaker>,
+        speaker_id: Option<u32>,
+    ) -> HistoryEntry {
         let entry = HistoryEntry {
             id: generate_id(),
             text,
             timestamp: Utc::now().to_rfc3339(),
             wav_path,
+            speaker,
+            speaker_id,
         };
         self.entries.push(entry.clone());
         if let Err(e) = self.save() {
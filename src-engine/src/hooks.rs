@@ -0,0 +1,316 @@
+//! External command hooks on transcription events.
+//!
+//! For each completed transcription, every enabled `Config::hooks` command is
+//! run with the text on stdin and the metadata in `FLOWSTT_*` environment
+//! variables. Hooks with `replace_text` run in order before the paste and
+//! their stdout becomes the pasted text; the others are notified in the
+//! background. Failures are logged and broadcast as `HookFailed` events.
+//!
+//! Hooks never run on the transcription worker: text-replacing hooks and the
+//! paste that follows them run on a dedicated thread (so pastes stay in
+//! transcription order), and notify-only hooks each get their own thread.
+
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::{Speaker, TranscriptionHook};
+use tracing::{debug, info, warn};
+
+use crate::ipc::broadcast_event;
+
+/// How often a running hook is checked for exit
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Metadata of a completed transcription, passed to hooks as env vars.
+#[derive(Debug, Clone, Default)]
+pub struct HookMetadata {
+    /// History entry ID
+    pub id: String,
+    /// ISO 8601 timestamp
+    pub timestamp: String,
+    /// Saved WAV file, if any
+    pub audio_path: Option<String>,
+    /// Speaker of a speaker-separated recording
+    pub speaker: Option<Speaker>,
+    /// Diarized speaker number
+    pub speaker_id: Option<u32>,
+}
+
+impl HookMetadata {
+    fn env(&self, hook: &TranscriptionHook) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("FLOWSTT_EVENT", "transcription_complete".to_string()),
+            ("FLOWSTT_HOOK", hook.name.clone()),
+            ("FLOWSTT_ID", self.id.clone()),
+            ("FLOWSTT_TIMESTAMP", self.timestamp.clone()),
+        ];
+        if let Some(ref path) = self.audio_path {
+            env.push(("FLOWSTT_AUDIO_PATH", path.clone()));
+        }
+        if let Some(speaker) = self.speaker {
+            env.push(("FLOWSTT_SPEAKER", speaker.label().to_string()));
+        }
+        if let Some(id) = self.speaker_id {
+            env.push(("FLOWSTT_SPEAKER_ID", id.to_string()));
+        }
+        env
+    }
+}
+
+/// Text-replacing hooks waiting to run before a paste.
+struct ReplaceJob {
+    hooks: Vec<TranscriptionHook>,
+    text: String,
+    metadata: HookMetadata,
+    /// Pastes the final text
+    paste: Box<dyn FnOnce(&str) + Send>,
+}
+
+/// Sender for the thread running text-replacing hooks
+static REPLACE_JOBS: OnceLock<mpsc::Sender<ReplaceJob>> = OnceLock::new();
+
+fn get_replace_jobs() -> &'static mpsc::Sender<ReplaceJob> {
+    REPLACE_JOBS.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<ReplaceJob>();
+        thread::spawn(move || {
+            for job in receiver {
+                let mut text = job.text;
+                for hook in &job.hooks {
+                    if let Some(output) = run_reporting(hook, &text, &job.metadata) {
+                        text = output.trim().to_string();
+                    }
+                }
+                if text.is_empty() {
+                    debug!("[Hooks] Hook output is empty, nothing to paste");
+                } else {
+                    (job.paste)(&text);
+                }
+            }
+        });
+        sender
+    })
+}
+
+/// Run the hooks for a completed transcription.
+///
+/// `paste` is called with the text to paste (after text-replacing hooks) or
+/// not at all when `should_paste` is false. It runs on the calling thread
+/// when no hook replaces the text.
+pub fn dispatch(
+    hooks: &[TranscriptionHook],
+    text: &str,
+    metadata: HookMetadata,
+    should_paste: bool,
+    paste: impl FnOnce(&str) + Send + 'static,
+) {
+    let (replacing, notifying): (Vec<_>, Vec<_>) = hooks
+        .iter()
+        .filter(|h| h.enabled)
+        .cloned()
+        .partition(|h| h.replace_text);
+
+    for hook in notifying {
+        let text = text.to_string();
+        let metadata = metadata.clone();
+        thread::spawn(move || {
+            run_reporting(&hook, &text, &metadata);
+        });
+    }
+
+    if !should_paste {
+        return;
+    }
+    if replacing.is_empty() {
+        paste(text);
+        return;
+    }
+
+    let job = ReplaceJob {
+        hooks: replacing,
+        text: text.to_string(),
+        metadata,
+        paste: Box::new(paste),
+    };
+    if get_replace_jobs().send(job).is_err() {
+        warn!("[Hooks] Hook thread is not running, transcription not pasted");
+    }
+}
+
+/// Run a hook, logging and broadcasting a failure. Returns its stdout on
+/// success.
+fn run_reporting(hook: &TranscriptionHook, text: &str, metadata: &HookMetadata) -> Option<String> {
+    let started = Instant::now();
+    match run_hook(hook, text, &metadata.env(hook)) {
+        Ok(output) => {
+            debug!(
+                "[Hooks] '{}' finished in {}ms",
+                hook.name,
+                started.elapsed().as_millis()
+            );
+            Some(output)
+        }
+        Err(error) => {
+            warn!("[Hooks] '{}' failed: {}", hook.name, error);
+            broadcast_event(Response::Event {
+                event: EventType::HookFailed {
+                    hook: hook.name.clone(),
+                    error,
+                },
+            });
+            None
+        }
+    }
+}
+
+/// Run a hook command with `text` on stdin, killing it (and any processes it
+/// started) after its timeout. Returns its stdout if it exits successfully.
+pub fn run_hook(
+    hook: &TranscriptionHook,
+    text: &str,
+    env: &[(&str, String)],
+) -> Result<String, String> {
+    let mut command = Command::new(&hook.command);
+    // Own process group, so a timeout also kills the commands a shell hook runs
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .args(&hook.args)
+        .envs(env.iter().map(|(k, v)| (*k, v.as_str())))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {}", hook.command, e))?;
+
+    // Feed stdin and drain the output pipes on their own threads so a hook
+    // that writes a lot before reading cannot deadlock against us
+    let stdin = child.stdin.take().map(|mut stdin| {
+        let text = text.to_string();
+        thread::spawn(move || {
+            // A hook that ignores stdin may close it early
+            let _ = stdin.write_all(text.as_bytes());
+        })
+    });
+    let stdout = child.stdout.take().map(read_pipe);
+    let stderr = child.stderr.take().map(read_pipe);
+
+    let deadline = Instant::now() + Duration::from_millis(hook.timeout_ms);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                kill_hook(&mut child);
+                return Err(format!("timed out after {}ms", hook.timeout_ms));
+            }
+            Ok(None) => thread::sleep(HOOK_POLL_INTERVAL),
+            Err(e) => return Err(format!("failed to wait for {}: {}", hook.command, e)),
+        }
+    };
+
+    if let Some(handle) = stdin {
+        let _ = handle.join();
+    }
+    let stdout = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+    let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+
+    if !status.success() {
+        let stderr = stderr.trim();
+        return Err(if stderr.is_empty() {
+            format!("exited with {}", status)
+        } else {
+            format!("exited with {}: {}", status, stderr)
+        });
+    }
+    if !stderr.trim().is_empty() {
+        info!("[Hooks] '{}' stderr: {}", hook.name, stderr.trim());
+    }
+    Ok(stdout)
+}
+
+/// Kill a hook and the processes it started.
+fn kill_hook(child: &mut Child) {
+    // SAFETY: signals the process group created for the hook at spawn
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Read a child pipe to the end on a separate thread.
+fn read_pipe(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn hook(script: &str, timeout_ms: u64) -> TranscriptionHook {
+        TranscriptionHook {
+            name: "test".into(),
+            command: "sh".into(),
+            args: vec!["-c".into(), script.into()],
+            timeout_ms,
+            replace_text: true,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn hook_reads_stdin_and_env() {
+        let metadata = HookMetadata {
+            id: "42".into(),
+            speaker_id: Some(2),
+            ..Default::default()
+        };
+        let hook = hook(
+            r#"printf '%s|%s|' "$FLOWSTT_ID" "$FLOWSTT_SPEAKER_ID"; cat"#,
+            5000,
+        );
+        let output = run_hook(&hook, "hello", &metadata.env(&hook)).unwrap();
+        assert_eq!(output, "42|2|hello");
+    }
+
+    #[test]
+    fn hook_failures_are_reported() {
+        let err = run_hook(&hook("echo broken >&2; exit 3", 5000), "", &[]).unwrap_err();
+        assert!(err.contains("broken"), "{}", err);
+
+        let started = Instant::now();
+        let err = run_hook(&hook("sleep 5", 100), "", &[]).unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        // Commands started by the hook die with it
+        let marker = std::env::temp_dir().join(format!("flowstt-hook-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        let script = format!("(sleep 1; touch '{}') & wait", marker.display());
+        let err = run_hook(&hook(&script, 100), "", &[]).unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+        thread::sleep(Duration::from_millis(1500));
+        assert!(!marker.exists());
+
+        let mut missing = hook("", 100);
+        missing.command = "/nonexistent/flowstt-hook".into();
+        assert!(run_hook(&missing, "", &[]).is_err());
+    }
+}
@@ -5,10 +5,10 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 /// Event emitted when hotkey state changes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HotkeyEvent {
-    /// PTT hotkey was pressed
-    PttPressed,
+    /// PTT hotkey was pressed, with the combination that matched
+    PttPressed(HotkeyCombination),
     /// PTT hotkey was released
     PttReleased,
     /// Toggle hotkey was pressed
@@ -712,18 +712,20 @@ fn check_combinations(context: &EventTapContext) {
         context.any_toggle_matched.store(false, Ordering::SeqCst);
     }
 
-    let now_ptt_matched = context
+    let matched_ptt = context
         .ptt_hotkeys
         .iter()
-        .any(|combo| combo.is_subset_of(&pressed));
+        .find(|combo| combo.is_subset_of(&pressed))
+        .cloned();
+    let now_ptt_matched = matched_ptt.is_some();
 
     let suppress_ptt = context.auto_mode_state.is_active();
 
-    if now_ptt_matched && !context.any_ptt_matched.load(Ordering::SeqCst) {
+    if let Some(combo) = matched_ptt.filter(|_| !context.any_ptt_matched.load(Ordering::SeqCst)) {
         context.any_ptt_matched.store(true, Ordering::SeqCst);
         if !suppress_ptt {
             info!("[PTT] Combination MATCHED - key DOWN");
-            let _ = context.sender.send(HotkeyEvent::PttPressed);
+            let _ = context.sender.send(HotkeyEvent::PttPressed(combo));
         } else {
             debug!("[PTT] PTT suppressed (auto mode active)");
         }
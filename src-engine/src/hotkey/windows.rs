@@ -654,19 +654,21 @@ unsafe fn handle_raw_input(hrawinput: HRAWINPUT) {
             }
 
             // Check if any PTT combination is now matched
-            let now_ptt_matched = context
+            let matched_ptt = context
                 .ptt_hotkeys
                 .iter()
-                .any(|combo| combo.is_subset_of(&context.pressed_keys));
+                .find(|combo| combo.is_subset_of(&context.pressed_keys))
+                .cloned();
+            let now_ptt_matched = matched_ptt.is_some();
 
             // Emit PTT events on state transitions (unless suppressed)
             let suppress_ptt = context.auto_mode_state.is_active();
 
-            if now_ptt_matched && !context.any_ptt_matched {
+            if let Some(combo) = matched_ptt.filter(|_| !context.any_ptt_matched) {
                 context.any_ptt_matched = true;
                 if !suppress_ptt {
                     info!("[PTT] Combination MATCHED - key DOWN");
-                    let _ = context.sender.send(HotkeyEvent::PttPressed);
+                    let _ = context.sender.send(HotkeyEvent::PttPressed(combo));
                 } else {
                     debug!("[PTT] PTT suppressed (auto mode active)");
                 }
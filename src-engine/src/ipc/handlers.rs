@@ -1,20 +1,35 @@
 //! IPC request handlers.
 
-use flowstt_common::ipc::{EventType, Request, Response};
-use flowstt_common::{ConfigValues, CudaStatus, ModelStatus, PttStatus, TranscriptionMode};
+use flowstt_common::ipc::{
+    ErrorCode, EventType, Request, Response, CAPABILITIES, PROTOCOL_VERSION,
+};
+use flowstt_common::{
+    ConfigValues, CudaStatus, ModelStatus, PttStatus, SegmentationConfig, TranscriptionMode,
+    TranscriptionResult,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::oneshot;
 use tracing::{info, warn};
 
 use super::broadcast_event;
 use crate::hotkey;
+use crate::pipeline::SegmentationStrategy;
 use crate::platform;
 use crate::ptt_controller;
 use crate::state::get_service_state;
-use crate::transcription::{download_model, TranscribeState, Transcriber, TranscriptionQueue};
+use crate::transcription::queue::QueuedSegment;
+use crate::transcription::{
+    download_model, TranscribeOptions, TranscribeState, Transcriber, TranscriptionJob,
+    TranscriptionQueue,
+};
 use crate::{
     is_audio_loop_active, start_audio_loop, stop_audio_loop, TranscriptionEventBroadcaster,
 };
 
+/// ID of the next `TranscribeAudio` job
+static NEXT_AUDIO_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
 /// Global transcription queue
 static TRANSCRIPTION_QUEUE: std::sync::OnceLock<Arc<TranscriptionQueue>> =
     std::sync::OnceLock::new();
@@ -53,17 +68,87 @@ pub fn init_transcription_system() {
     let model_path = transcriber.get_model_path().clone();
     queue.start_worker(model_path);
 
+    // Apply persisted segmentation settings
+    let config = crate::config::Config::load();
+    apply_segmentation_config(&config.segmentation);
+    get_transcribe_state()
+        .lock()
+        .unwrap()
+        .set_long_form(config.long_form_enabled);
+    queue.set_wake_word(config.wake_word_enabled.then_some(config.wake_word.as_str()));
+    queue.set_text_processing(&config.text_processing);
+    crate::platform::mixing::set_source_settings(
+        config.source1_settings,
+        config.source2_settings,
+    );
+
     info!("Transcription system initialized");
 }
 
+/// Apply ring buffer and segment limits to the transcription system.
+fn apply_segmentation_config(config: &SegmentationConfig) {
+    get_transcription_queue().set_max_queue_size(config.max_queue_size as usize);
+    get_transcribe_state()
+        .lock()
+        .unwrap()
+        .set_segmentation_config(config.clone());
+}
+
+/// Why audio capture could not be started.
+#[derive(Debug, Clone)]
+pub struct CaptureError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for CaptureError {
+    fn from(message: String) -> Self {
+        Self {
+            code: ErrorCode::Internal,
+            message,
+        }
+    }
+}
+
+/// Code for a failure to start hotkey monitoring, which on macOS is usually
+/// a missing Accessibility permission.
+fn hotkey_error_code() -> ErrorCode {
+    if hotkey::check_accessibility_permission() {
+        ErrorCode::Internal
+    } else {
+        ErrorCode::PermissionDenied
+    }
+}
+
+/// Check that an audio device is known to the backend. Without a backend
+/// this is left to the capture start to report.
+fn device_exists(id: &str) -> bool {
+    platform::get_backend().is_none_or(|backend| {
+        backend
+            .list_input_devices()
+            .iter()
+            .chain(backend.list_system_devices().iter())
+            .any(|device| device.id == id)
+    })
+}
+
 /// Start audio capture with current source configuration.
-/// Returns Ok if capture started, Err with message if it failed.
-pub async fn start_capture() -> Result<(), String> {
+/// Returns Ok if capture started, Err with the reason if it failed.
+pub async fn start_capture() -> Result<(), CaptureError> {
     let state_arc = get_service_state();
     let state = state_arc.lock().await;
 
     if !state.has_primary_source() {
-        return Err("No primary audio source configured".to_string());
+        return Err(CaptureError {
+            code: ErrorCode::NoSource,
+            message: "No primary audio source configured".to_string(),
+        });
     }
 
     let source1_id = state.source1_id.clone();
@@ -71,29 +156,35 @@ pub async fn start_capture() -> Result<(), String> {
     let aec_enabled = state.aec_enabled;
     let recording_mode = state.recording_mode;
     let transcription_mode = state.transcription_mode;
-    let ptt_hotkeys = state.ptt_hotkeys.clone();
+    let talk_hotkeys = state.talk_hotkeys();
     let auto_toggle_hotkeys = state.auto_toggle_hotkeys.clone();
 
     // Drop the lock before doing expensive operations
     drop(state);
 
-    if transcription_mode == TranscriptionMode::PushToTalk {
-        // PTT mode: Don't start audio capture yet, just start the PTT controller
-        // Audio will be started/stopped when the hotkey is pressed/released
-
-        // Start hotkey backend
-        if let Err(e) = hotkey::start_hotkey(ptt_hotkeys.clone(), auto_toggle_hotkeys.clone()) {
-            return Err(format!("Failed to start PTT hotkey monitoring: {}", e));
+    if transcription_mode.is_hotkey_driven() {
+        // PTT / toggle-to-talk mode: Don't start audio capture yet, just start
+        // the PTT controller. Audio will be started/stopped by the hotkey
+        // (press/release for PTT, successive taps for toggle-to-talk).
+
+        // Start hotkey backend. The backend reports the mode's talk hotkeys as
+        // PTT events; the controller interprets them for the mode.
+        if let Err(e) = hotkey::start_hotkey(talk_hotkeys.clone(), auto_toggle_hotkeys.clone()) {
+            return Err(CaptureError {
+                code: hotkey_error_code(),
+                message: format!("Failed to start PTT hotkey monitoring: {}", e),
+            });
         }
         info!(
-            "PTT hotkey monitoring started for {} combination(s)",
-            ptt_hotkeys.len()
+            "{:?} hotkey monitoring started for {} combination(s)",
+            transcription_mode,
+            talk_hotkeys.len()
         );
 
         // Start PTT controller
         if let Err(e) = ptt_controller::start_ptt_controller() {
             hotkey::stop_hotkey();
-            return Err(format!("Failed to start PTT controller: {}", e));
+            return Err(format!("Failed to start PTT controller: {}", e).into());
         }
 
         // Update state - not capturing yet, but ready
@@ -102,7 +193,7 @@ pub async fn start_capture() -> Result<(), String> {
         state.transcribe_status.capturing = false;
         state.transcribe_status.error = None;
 
-        info!("PTT mode ready - waiting for hotkey press");
+        info!("{:?} mode ready - waiting for hotkey press", transcription_mode);
 
         // Broadcast ready event
         broadcast_event(Response::Event {
@@ -153,7 +244,10 @@ pub async fn start_capture() -> Result<(), String> {
             let transcribe_state = get_transcribe_state();
             let mut transcribe = transcribe_state.lock().unwrap();
             transcribe.init_for_capture(sample_rate, 2);
+            transcribe.set_recording_mode(recording_mode);
             transcribe.activate();
+            // New automatic session - speakers are numbered from 1 again
+            transcribe.transcription_queue.reset_speakers();
         }
 
         // Start capture
@@ -163,14 +257,17 @@ pub async fn start_capture() -> Result<(), String> {
 
             backend.start_capture_sources(source1_id, source2_id)?;
         } else {
-            return Err("Audio backend not available".to_string());
+            return Err(CaptureError {
+                code: ErrorCode::Unavailable,
+                message: "Audio backend not available".to_string(),
+            });
         }
 
         // Start audio processing loop
         if !is_audio_loop_active() {
             let queue = get_transcription_queue();
             let transcribe_state = get_transcribe_state();
-            start_audio_loop(queue, transcribe_state)?;
+            start_audio_loop(queue, transcribe_state, SegmentationStrategy::Vad)?;
         }
 
         // Update state
@@ -230,10 +327,28 @@ async fn stop_capture() {
 pub async fn handle_request(request: Request) -> Response {
     // Validate request
     if let Err(e) = request.validate() {
-        return Response::error(e);
+        return Response::error_with_code(ErrorCode::InvalidArgument, e);
     }
 
     match request {
+        Request::Hello {
+            protocol_version,
+            client_name,
+        } => {
+            if protocol_version == PROTOCOL_VERSION {
+                info!("Client '{}' connected (protocol v{})", client_name, protocol_version);
+            } else {
+                warn!(
+                    "Client '{}' speaks protocol v{}, engine speaks v{}",
+                    client_name, protocol_version, PROTOCOL_VERSION
+                );
+            }
+            Response::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+            }
+        }
+
         Request::Ping => Response::Pong,
 
         Request::GetRuntimeMode => {
@@ -277,17 +392,48 @@ pub async fn handle_request(request: Request) -> Response {
         Request::SetSources {
             source1_id,
             source2_id,
+            source1_settings,
+            source2_settings,
         } => {
             let state_arc = get_service_state();
 
+            for id in source1_id.iter().chain(source2_id.iter()) {
+                if !device_exists(id) {
+                    return Response::error_with_code(
+                        ErrorCode::DeviceNotFound,
+                        format!("Audio device not found: {}", id),
+                    );
+                }
+            }
+
+            // Per-source gain/mute/channel selection is read by the backend
+            // stream callbacks, so it applies as soon as capture (re)starts.
+            // Omitted settings keep their current values.
+            if source1_settings.is_some() || source2_settings.is_some() {
+                let mut config = crate::config::Config::load();
+                if let Some(settings) = source1_settings {
+                    config.source1_settings = settings;
+                }
+                if let Some(settings) = source2_settings {
+                    config.source2_settings = settings;
+                }
+                if let Err(e) = crate::config::save_config(&config) {
+                    warn!("Failed to save config: {}", e);
+                }
+                crate::platform::mixing::set_source_settings(
+                    config.source1_settings,
+                    config.source2_settings,
+                );
+            }
+
             // Update source configuration and check if we should capture.
-            // In PTT mode the hotkey backend and controller run even while
-            // `capturing` is false (audio only flows while the key is held),
+            // In hotkey modes the hotkey backend and controller run even while
+            // `capturing` is false (audio only flows while recording),
             // so we must also check if the PTT controller is active.
             let (was_active, should_capture) = {
                 let mut state = state_arc.lock().await;
                 let was = state.transcribe_status.capturing
-                    || (state.transcription_mode == TranscriptionMode::PushToTalk
+                    || (state.transcription_mode.is_hotkey_driven()
                         && ptt_controller::is_ptt_controller_running());
                 state.source1_id = source1_id.clone();
                 state.source2_id = source2_id.clone();
@@ -311,17 +457,17 @@ pub async fn handle_request(request: Request) -> Response {
                     Err(e) => {
                         // Update error state
                         let mut state = state_arc.lock().await;
-                        state.transcribe_status.error = Some(e.clone());
+                        state.transcribe_status.error = Some(e.message.clone());
 
                         // Broadcast error
                         broadcast_event(Response::Event {
                             event: EventType::CaptureStateChanged {
                                 capturing: false,
-                                error: Some(e.clone()),
+                                error: Some(e.message.clone()),
                             },
                         });
 
-                        Response::error(e)
+                        Response::error_with_code(e.code, e.message)
                     }
                 }
             } else {
@@ -364,6 +510,12 @@ pub async fn handle_request(request: Request) -> Response {
                 }
             }
 
+            // Segments split by speaker in speaker-separated mode
+            get_transcribe_state()
+                .lock()
+                .unwrap()
+                .set_recording_mode(mode);
+
             info!("Recording mode: {:?}", mode);
             Response::Ok
         }
@@ -398,16 +550,118 @@ pub async fn handle_request(request: Request) -> Response {
                 transcription_mode: state.transcription_mode,
                 ptt_hotkeys: state.ptt_hotkeys.clone(),
                 auto_toggle_hotkeys: state.auto_toggle_hotkeys.clone(),
+                toggle_talk_hotkeys: state.toggle_talk_hotkeys.clone(),
+                toggle_talk_silence_timeout_ms: state.toggle_talk_silence_timeout_ms,
                 auto_paste_enabled: config.auto_paste_enabled,
                 auto_paste_delay_ms: config.auto_paste_delay_ms,
+                agc_enabled: config.agc_enabled,
+                agc_target_level_db: config.agc_target_level_db,
+                long_form_enabled: config.long_form_enabled,
+                segmentation: config.segmentation,
+                wake_word_enabled: config.wake_word_enabled,
+                wake_word: config.wake_word,
+                voice_commands_enabled: config.voice_commands_enabled,
+                voice_commands: config.voice_commands,
+                text_processing: config.text_processing,
+                hooks: config.hooks,
+                http_api_enabled: config.http_api_enabled,
+                http_api_port: config.http_api_port,
             })
         }
 
-        Request::SubscribeEvents => {
+        Request::SubscribeEvents { .. } => {
             // Actual subscription is handled in the server
             Response::Subscribed
         }
 
+        Request::TranscribeAudio {
+            sample_rate,
+            channels,
+            encoding,
+            data,
+        } => {
+            if !Transcriber::new().is_model_available() {
+                return Response::error_with_code(
+                    ErrorCode::ModelMissing,
+                    "Whisper model is not downloaded",
+                );
+            }
+            let queue = get_transcription_queue();
+            if !queue.is_worker_active() {
+                return Response::error_with_code(
+                    ErrorCode::Unavailable,
+                    "Transcription worker is not running",
+                );
+            }
+
+            let job_id = NEXT_AUDIO_JOB_ID.fetch_add(1, Ordering::Relaxed);
+            let bytes = data.0.len();
+            // Converting a large payload takes a while; keep it off the runtime
+            let decoded = tokio::task::spawn_blocking(move || {
+                crate::audio::decode_pcm(&data.0, encoding, sample_rate, channels)
+            })
+            .await;
+            let audio = match decoded {
+                Ok(audio) => audio,
+                Err(e) => return Response::error(format!("Failed to decode audio: {}", e)),
+            };
+            let (reply, result) = oneshot::channel();
+            let job = TranscriptionJob {
+                options: TranscribeOptions::default(),
+                reply,
+            };
+            if !queue.enqueue(QueuedSegment::for_job(audio, job)) {
+                return Response::error_with_code(
+                    ErrorCode::QueueFull,
+                    "Transcription queue is full, retry later",
+                );
+            }
+
+            info!(
+                "Queued audio transcription {} ({} bytes, {} Hz, {} channels)",
+                job_id,
+                bytes,
+                sample_rate,
+                channels
+            );
+            tokio::spawn(async move {
+                let (result, error) = match result.await {
+                    Ok(Ok(segments)) => {
+                        let text = segments
+                            .iter()
+                            .map(|s| s.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        let result = TranscriptionResult {
+                            id: None,
+                            text,
+                            timestamp: Some(chrono::Utc::now().to_rfc3339()),
+                            audio_path: None,
+                            speaker: None,
+                            speaker_id: None,
+                        };
+                        (Some(result), None)
+                    }
+                    Ok(Err(e)) => {
+                        warn!("Audio transcription {} failed: {}", job_id, e);
+                        (None, Some(e))
+                    }
+                    // The queue was cleared before the job ran
+                    Err(_) => (None, Some("Transcription was cancelled".to_string())),
+                };
+                // Only the connection that queued the job forwards it
+                broadcast_event(Response::Event {
+                    event: EventType::AudioTranscriptionComplete {
+                        job_id,
+                        result,
+                        error,
+                    },
+                });
+            });
+
+            Response::TranscriptionQueued { job_id }
+        }
+
         Request::GetModelStatus => {
             let transcriber = Transcriber::new();
             Response::ModelStatus(ModelStatus {
@@ -421,7 +675,10 @@ pub async fn handle_request(request: Request) -> Response {
             let model_path = transcriber.get_model_path().clone();
 
             if model_path.exists() {
-                return Response::error("Model already downloaded");
+                return Response::error_with_code(
+                    ErrorCode::AlreadyExists,
+                    "Model already downloaded",
+                );
             }
 
             // Download in background with streaming progress
@@ -457,8 +714,18 @@ pub async fn handle_request(request: Request) -> Response {
 
             let (old_mode, is_ready, ptt_hotkeys) = {
                 let mut state = state_arc.lock().await;
+                // Without a hotkey, toggle-to-talk could never start recording
+                if mode == TranscriptionMode::ToggleToTalk && state.toggle_talk_hotkeys.is_empty() {
+                    return Response::error_with_code(
+                        ErrorCode::InvalidArgument,
+                        "Toggle-to-talk has no hotkeys; set them with set_toggle_talk_hotkeys first",
+                    );
+                }
                 let old_mode = state.transcription_mode;
                 state.transcription_mode = mode;
+                if mode.is_hotkey_driven() {
+                    state.hotkey_mode = mode;
+                }
                 (
                     old_mode,
                     state.has_primary_source(),
@@ -532,7 +799,10 @@ pub async fn handle_request(request: Request) -> Response {
                     let mut state = state_arc.lock().await;
                     state.ptt_hotkeys = old_hotkeys.clone();
                     let _ = hotkey::start_hotkey(old_hotkeys, old_toggle);
-                    return Response::error(format!("Failed to set hotkeys: {}", e));
+                    return Response::error_with_code(
+                        hotkey_error_code(),
+                        format!("Failed to set hotkeys: {}", e),
+                    );
                 }
             }
 
@@ -565,6 +835,8 @@ pub async fn handle_request(request: Request) -> Response {
                 auto_toggle_hotkeys: state.auto_toggle_hotkeys.clone(),
                 auto_mode_active: state.auto_mode_active,
                 is_active: state.is_ptt_active,
+                toggle_talk_hotkeys: state.toggle_talk_hotkeys.clone(),
+                toggle_talk_active: state.is_toggle_talk_active,
                 available,
                 error,
                 accessibility_permission_granted: hotkey::check_accessibility_permission(),
@@ -573,14 +845,13 @@ pub async fn handle_request(request: Request) -> Response {
 
         Request::SetAutoToggleHotkeys { hotkeys } => {
             let state_arc = get_service_state();
-            let (ptt_hotkeys, _transcription_mode, is_ptt_monitoring) = {
+            let (talk_hotkeys, _transcription_mode, is_ptt_monitoring) = {
                 let mut state = state_arc.lock().await;
                 let _old_toggle = state.auto_toggle_hotkeys.clone();
                 state.auto_toggle_hotkeys = hotkeys.clone();
-                let is_ptt_monitoring =
-                    state.transcription_mode == TranscriptionMode::PushToTalk
-                        && ptt_controller::is_ptt_controller_running();
-                (state.ptt_hotkeys.clone(), state.transcription_mode, is_ptt_monitoring)
+                let is_ptt_monitoring = state.transcription_mode.is_hotkey_driven()
+                    && ptt_controller::is_ptt_controller_running();
+                (state.talk_hotkeys(), state.transcription_mode, is_ptt_monitoring)
             };
 
             info!("Auto-toggle hotkeys set: {} combination(s)", hotkeys.len());
@@ -588,7 +859,7 @@ pub async fn handle_request(request: Request) -> Response {
             // If PTT monitoring is active, restart hotkey backend with new toggle hotkeys
             if is_ptt_monitoring {
                 hotkey::stop_hotkey();
-                if let Err(e) = hotkey::start_hotkey(ptt_hotkeys, hotkeys.clone()) {
+                if let Err(e) = hotkey::start_hotkey(talk_hotkeys, hotkeys.clone()) {
                     warn!("Failed to restart hotkey with new toggle: {}", e);
                 }
             }
@@ -610,17 +881,32 @@ pub async fn handle_request(request: Request) -> Response {
                 transcription_mode: state.transcription_mode,
                 ptt_hotkeys: state.ptt_hotkeys.clone(),
                 auto_toggle_hotkeys: state.auto_toggle_hotkeys.clone(),
+                toggle_talk_hotkeys: state.toggle_talk_hotkeys.clone(),
+                toggle_talk_silence_timeout_ms: state.toggle_talk_silence_timeout_ms,
                 auto_paste_enabled: true,
                 auto_paste_delay_ms: 50,
+                agc_enabled: true,
+                agc_target_level_db: flowstt_common::DEFAULT_AGC_TARGET_LEVEL_DB,
+                long_form_enabled: false,
+                segmentation: SegmentationConfig::default(),
+                wake_word_enabled: false,
+                wake_word: flowstt_common::DEFAULT_WAKE_WORD.to_string(),
+                voice_commands_enabled: false,
+                voice_commands: flowstt_common::default_voice_commands(),
+                text_processing: Default::default(),
+                hooks: Vec::new(),
+                http_api_enabled: false,
+                http_api_port: flowstt_common::DEFAULT_HTTP_API_PORT,
             })
         }
 
         Request::ToggleAutoMode => {
             let state_arc = get_service_state();
-            let (current_mode, auto_mode_active, _ptt_hotkeys, _toggle_hotkeys) = {
+            let (current_mode, hotkey_mode, auto_mode_active, _ptt_hotkeys, _toggle_hotkeys) = {
                 let state = state_arc.lock().await;
                 (
                     state.transcription_mode,
+                    state.hotkey_mode,
                     state.auto_mode_active,
                     state.ptt_hotkeys.clone(),
                     state.auto_toggle_hotkeys.clone(),
@@ -628,7 +914,7 @@ pub async fn handle_request(request: Request) -> Response {
             };
 
             let (new_mode, new_auto_active) = if auto_mode_active {
-                (TranscriptionMode::PushToTalk, false)
+                (hotkey_mode, false)
             } else {
                 (TranscriptionMode::Automatic, true)
             };
@@ -644,9 +930,13 @@ pub async fn handle_request(request: Request) -> Response {
             // Update state
             {
                 let mut state = state_arc.lock().await;
+                if current_mode.is_hotkey_driven() {
+                    state.hotkey_mode = current_mode;
+                }
                 state.transcription_mode = new_mode;
                 state.auto_mode_active = new_auto_active;
                 state.is_ptt_active = false;
+                state.is_toggle_talk_active = false;
                 hotkey::set_auto_mode_active(new_auto_active);
             }
 
@@ -665,6 +955,75 @@ pub async fn handle_request(request: Request) -> Response {
             Response::Ok
         }
 
+        Request::SetToggleTalkHotkeys { hotkeys } => {
+            let state_arc = get_service_state();
+            let (old_hotkeys, auto_toggle_hotkeys, is_monitoring) = {
+                let mut state = state_arc.lock().await;
+                if hotkeys.is_empty() && state.hotkey_mode == TranscriptionMode::ToggleToTalk {
+                    return Response::error_with_code(
+                        ErrorCode::InvalidArgument,
+                        "Toggle-to-talk needs at least one hotkey; switch to another mode before clearing them",
+                    );
+                }
+                let old_hotkeys = state.toggle_talk_hotkeys.clone();
+                state.toggle_talk_hotkeys = hotkeys.clone();
+                let is_monitoring = state.transcription_mode == TranscriptionMode::ToggleToTalk
+                    && ptt_controller::is_ptt_controller_running();
+                (old_hotkeys, state.auto_toggle_hotkeys.clone(), is_monitoring)
+            };
+
+            info!(
+                "Toggle-to-talk hotkeys change requested: {} -> {} combinations (monitoring={})",
+                old_hotkeys.len(),
+                hotkeys.len(),
+                is_monitoring
+            );
+
+            // If toggle-to-talk monitoring is active, restart hotkey with new combinations
+            if is_monitoring {
+                hotkey::stop_hotkey();
+                if let Err(e) = hotkey::start_hotkey(hotkeys.clone(), auto_toggle_hotkeys.clone()) {
+                    // Revert on failure
+                    warn!("Failed to start hotkey with new combinations: {}", e);
+                    let mut state = state_arc.lock().await;
+                    state.toggle_talk_hotkeys = old_hotkeys.clone();
+                    let _ = hotkey::start_hotkey(old_hotkeys, auto_toggle_hotkeys);
+                    return Response::error_with_code(
+                        hotkey_error_code(),
+                        format!("Failed to set hotkeys: {}", e),
+                    );
+                }
+            }
+
+            // Save configuration to disk (load first to preserve other fields)
+            let mut config = crate::config::Config::load();
+            config.toggle_talk_hotkeys = hotkeys;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!("Toggle-to-talk hotkeys updated");
+            Response::Ok
+        }
+
+        Request::SetToggleTalkSilenceTimeout { timeout_ms } => {
+            // Applies from the next toggle-to-talk recording
+            {
+                let state_arc = get_service_state();
+                let mut state = state_arc.lock().await;
+                state.toggle_talk_silence_timeout_ms = timeout_ms;
+            }
+
+            let mut config = crate::config::Config::load();
+            config.toggle_talk_silence_timeout_ms = timeout_ms;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!("Toggle-to-talk silence timeout set to {}ms", timeout_ms);
+            Response::Ok
+        }
+
         Request::GetCudaStatus => {
             // Check build-time GPU support
             // Windows always uses CUDA binaries (auto CPU fallback when no GPU)
@@ -710,6 +1069,165 @@ pub async fn handle_request(request: Request) -> Response {
             Response::Ok
         }
 
+        Request::SetAutoGain {
+            enabled,
+            target_level_db,
+        } => {
+            crate::gain::set_gain_settings(crate::gain::GainSettings {
+                enabled,
+                target_level_db,
+            });
+
+            // Load current config, update the gain settings, and save
+            let mut config = crate::config::Config::load();
+            config.agc_enabled = enabled;
+            config.agc_target_level_db = target_level_db;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!(
+                "Automatic gain control set to {} (target {:.1} dBFS)",
+                enabled, target_level_db
+            );
+            Response::Ok
+        }
+
+        Request::SetLongFormSegmentation { enabled } => {
+            get_transcribe_state()
+                .lock()
+                .unwrap()
+                .set_long_form(enabled);
+
+            // Load current config, update the segmentation mode, and save
+            let mut config = crate::config::Config::load();
+            config.long_form_enabled = enabled;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!("Long-form segmentation set to {}", enabled);
+            Response::Ok
+        }
+
+        Request::GetSegmentationConfig => {
+            let transcribe_state = get_transcribe_state();
+            let config = transcribe_state.lock().unwrap().segmentation_config().clone();
+            Response::SegmentationConfig(config)
+        }
+
+        Request::SetSegmentationConfig { config } => {
+            apply_segmentation_config(&config);
+
+            // Load current config, update the segmentation limits, and save
+            let mut persisted = crate::config::Config::load();
+            persisted.segmentation = config;
+            if let Err(e) = crate::config::save_config(&persisted) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!("Segmentation limits updated: {:?}", persisted.segmentation);
+            Response::Ok
+        }
+
+        Request::SetWakeWord { enabled, phrase } => {
+            get_transcription_queue().set_wake_word(enabled.then_some(phrase.as_str()));
+
+            // Load current config, update the wake-word gate, and save
+            let mut config = crate::config::Config::load();
+            config.wake_word_enabled = enabled;
+            config.wake_word = phrase;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!(
+                "Wake word set to {} ({:?})",
+                if enabled { "enabled" } else { "disabled" },
+                config.wake_word
+            );
+            Response::Ok
+        }
+
+        Request::SetTextProcessing { config } => {
+            get_transcription_queue().set_text_processing(&config);
+
+            // Load current config, update the pipeline, and save
+            let mut persisted = crate::config::Config::load();
+            persisted.text_processing = config;
+            if let Err(e) = crate::config::save_config(&persisted) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!(
+                "Text processing updated: {} stages, {} profiles",
+                persisted.text_processing.stages.len(),
+                persisted.text_processing.profiles.len()
+            );
+            Response::Ok
+        }
+
+        Request::SetTranscriptionHooks { hooks } => {
+            // Hooks are read from config for each transcription
+            let mut config = crate::config::Config::load();
+            config.hooks = hooks;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!("Transcription hooks updated: {} hooks", config.hooks.len());
+            Response::Ok
+        }
+
+        Request::SetHttpApi { enabled, port } => {
+            let mut config = crate::config::Config::load();
+            let port = port.unwrap_or(config.http_api_port);
+            if port == 0 {
+                return Response::error_with_code(ErrorCode::InvalidArgument, "port cannot be 0");
+            }
+            if enabled {
+                // e.g. the port is in use or the token file is refused
+                if let Err(e) = super::start_http_api(port).await {
+                    return Response::error_with_code(ErrorCode::Unavailable, e);
+                }
+            } else {
+                super::stop_http_api().await;
+            }
+
+            // Persist only once the server is known to start
+            config.http_api_enabled = enabled;
+            config.http_api_port = port;
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!(
+                "HTTP API {} (port {})",
+                if enabled { "enabled" } else { "disabled" },
+                port
+            );
+            Response::Ok
+        }
+
+        Request::SetVoiceCommands { enabled, commands } => {
+            // The grammar is read from config for each transcript
+            let mut config = crate::config::Config::load();
+            config.voice_commands_enabled = enabled;
+            if let Some(commands) = commands {
+                config.voice_commands = commands;
+            }
+            if let Err(e) = crate::config::save_config(&config) {
+                warn!("Failed to save config: {}", e);
+            }
+
+            info!(
+                "Voice commands set to {} ({} commands)",
+                if enabled { "enabled" } else { "disabled" },
+                config.voice_commands.len()
+            );
+            Response::Ok
+        }
+
         Request::GetHistory => {
             let history = crate::history::get_history();
             let h = history.lock().unwrap();
@@ -721,6 +1239,8 @@ pub async fn handle_request(request: Request) -> Response {
                     text: e.text.clone(),
                     timestamp: e.timestamp.clone(),
                     wav_path: e.wav_path.clone(),
+                    speaker: e.speaker,
+                    speaker_id: e.speaker_id,
                 })
                 .collect();
             Response::History { entries }
@@ -740,17 +1260,27 @@ pub async fn handle_request(request: Request) -> Response {
                 });
                 Response::Ok
             } else {
-                Response::error(format!("History entry not found: {}", id))
+                Response::error_with_code(
+                    ErrorCode::NotFound,
+                    format!("History entry not found: {}", id),
+                )
             }
         }
 
         Request::TestAudioDevice { device_id } => {
+            if !device_exists(&device_id) {
+                return Response::error_with_code(
+                    ErrorCode::DeviceNotFound,
+                    format!("Audio device not found: {}", device_id),
+                );
+            }
+
             // Stop any existing test capture (handles device switching)
             crate::test_capture::stop_test_capture();
 
-            // Stop the main audio loop so it doesn't race on try_recv().
+            // Stop the main audio loop so it doesn't race on recv_timeout().
             // The audio backend is a singleton with a single mpsc channel;
-            // only one consumer can poll it at a time.
+            // only one consumer can receive from it at a time.
             if is_audio_loop_active() {
                 stop_audio_loop();
                 if let Some(backend) = platform::get_backend() {
@@ -760,7 +1290,7 @@ pub async fn handle_request(request: Request) -> Response {
 
             match crate::test_capture::start_test_capture(device_id) {
                 Ok(()) => Response::Ok,
-                Err(e) => Response::error(e),
+                Err(e) => Response::error_with_code(ErrorCode::Unavailable, e),
             }
         }
 
@@ -0,0 +1,521 @@
+//! Localhost HTTP/WebSocket API.
+//!
+//! An optional alternative to the socket IPC for clients that cannot link our
+//! crates (browser extensions, editor plugins). It listens on 127.0.0.1 only
+//! and mirrors the IPC protocol:
+//!
+//! - `POST /api/{request}` runs the [`Request`] whose `type` is `{request}`
+//!   (e.g. `/api/get_status`), taking the request's other fields from the JSON
+//!   body, and returns the [`Response`] as JSON
+//! - `GET /api/events` is a WebSocket that streams events as
+//!   `Response::Event` JSON text messages, optionally limited like
+//!   `Request::SubscribeEvents` with `topics` (comma-separated) and
+//!   `max_rate_hz` query parameters. The result of a `transcribe_audio`
+//!   request made over HTTP is sent to one stream; results of jobs queued
+//!   over the socket IPC are not.
+//! - `POST /v1/audio/transcriptions` is compatible with the OpenAI API (see
+//!   [`super::openai`])
+//!
+//! Every call must present the token stored in the per-user file at
+//! [`get_api_token_path`], either as an `Authorization: Bearer <token>` header
+//! or as a `token` query parameter (browsers cannot set WebSocket headers).
+
+use std::collections::HashSet;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::DefaultBodyLimit;
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use flowstt_common::ipc::{
+    get_api_token_path, ErrorCode, EventTopic, EventType, Request, RequestParseError, Response,
+};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tracing::{debug, error, info, warn};
+
+use super::handlers::handle_request;
+use super::openai;
+use super::server::{get_event_sender, job_result_allowed, EventFilter};
+use crate::audit;
+use crate::is_shutdown_requested;
+use crate::state::get_service_state;
+
+/// Number of random bytes in a generated API token
+const TOKEN_BYTES: usize = 32;
+
+/// A running API server.
+struct HttpApiServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// The running API server, if any
+static HTTP_API_SERVER: OnceLock<Mutex<Option<HttpApiServer>>> = OnceLock::new();
+
+fn get_http_api_server() -> &'static Mutex<Option<HttpApiServer>> {
+    HTTP_API_SERVER.get_or_init(|| Mutex::new(None))
+}
+
+/// Audio transcription jobs queued over HTTP whose results have not been sent
+/// to an event stream yet
+static AUDIO_JOBS: OnceLock<std::sync::Mutex<HashSet<u64>>> = OnceLock::new();
+
+fn get_audio_jobs() -> &'static std::sync::Mutex<HashSet<u64>> {
+    AUDIO_JOBS.get_or_init(|| std::sync::Mutex::new(HashSet::new()))
+}
+
+/// Start the API server on `port`, restarting it if it runs on another port.
+pub async fn start_http_api(port: u16) -> Result<(), String> {
+    let mut server = get_http_api_server().lock().await;
+    match server.take() {
+        Some(running) if running.port == port => {
+            *server = Some(running);
+            return Ok(());
+        }
+        Some(running) => {
+            let _ = running.shutdown.send(());
+        }
+        None => {}
+    }
+
+    let token = load_or_create_token(&get_api_token_path())?;
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+        .await
+        .map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {}", port, e))?;
+    info!("HTTP API listening on http://127.0.0.1:{}", port);
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    tokio::spawn(serve(listener, token, shutdown_rx));
+    *server = Some(HttpApiServer {
+        port,
+        shutdown: shutdown_tx,
+    });
+    Ok(())
+}
+
+/// Stop the API server if it is running.
+pub async fn stop_http_api() {
+    if let Some(running) = get_http_api_server().lock().await.take() {
+        let _ = running.shutdown.send(());
+        info!("HTTP API on port {} stopped", running.port);
+    }
+}
+
+/// Serve the API on `listener` until `shutdown` fires.
+async fn serve(listener: TcpListener, token: String, shutdown: oneshot::Receiver<()>) {
+    let result = axum::serve(listener, router(token))
+        .with_graceful_shutdown(async {
+            let _ = shutdown.await;
+        })
+        .await;
+    if let Err(e) = result {
+        error!("HTTP API server error: {}", e);
+    }
+}
+
+fn router(token: String) -> Router {
+    Router::new()
+        .route("/api/events", get(events))
+        .route("/api/{request}", post(request))
+        .route(
+            "/v1/audio/transcriptions",
+            post(openai::transcriptions).layer(DefaultBodyLimit::max(openai::MAX_UPLOAD_BYTES)),
+        )
+        .layer(middleware::from_fn_with_state(
+            Arc::from(token),
+            require_token,
+        ))
+}
+
+/// Reject calls that do not present the API token.
+async fn require_token(
+    State(token): State<Arc<str>>,
+    request: axum::extract::Request,
+    next: Next,
+) -> Result<axum::response::Response, StatusCode> {
+    let presented = bearer_token(request.headers())
+        .or_else(|| query_token(request.uri().query().unwrap_or("")));
+    match presented {
+        Some(presented) if token_matches(&token, presented) => Ok(next.run(request).await),
+        _ => {
+            warn!(
+                "HTTP API: rejected unauthenticated {}",
+                request.uri().path()
+            );
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn query_token(query: &str) -> Option<&str> {
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+}
+
+/// Compare tokens without leaking the matching prefix length through timing
+fn token_matches(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// `POST /api/{request}`
+async fn request(UrlPath(name): UrlPath<String>, body: Bytes) -> (StatusCode, Json<Response>) {
+    let request = match parse_request(&name, &body) {
+        Ok(Request::SubscribeEvents { .. }) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(Response::error(
+                    "Use the /api/events WebSocket to receive events",
+                )),
+            );
+        }
+        Ok(request) => request,
+        Err(RequestParseError::Unsupported(request_type)) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(Response::UnsupportedRequest { request_type }),
+            )
+        }
+        Err(RequestParseError::Invalid(message)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(Response::error_with_code(
+                    ErrorCode::InvalidArgument,
+                    message,
+                )),
+            )
+        }
+    };
+
+    debug!("HTTP API request: {:?}", request);
+    let audited = request.is_state_changing().then(|| request.type_name());
+    let response = handle_request(request).await;
+    if let Some(request_type) = audited {
+        audit::record_request("http", None, request_type, &response);
+    }
+    if let Response::TranscriptionQueued { job_id } = response {
+        get_audio_jobs().lock().unwrap().insert(job_id);
+    }
+    let status = match response {
+        Response::Error { code, .. } => match code {
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::NotFound | ErrorCode::DeviceNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+            ErrorCode::AlreadyExists => StatusCode::CONFLICT,
+            ErrorCode::QueueFull => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::InvalidArgument | ErrorCode::NoSource | ErrorCode::ModelMissing => {
+                StatusCode::BAD_REQUEST
+            }
+        },
+        _ => StatusCode::OK,
+    };
+    (status, Json(response))
+}
+
+/// Build a request from its type name and a JSON body holding its fields.
+fn parse_request(name: &str, body: &[u8]) -> Result<Request, RequestParseError> {
+    let mut fields = if body.iter().all(u8::is_ascii_whitespace) {
+        serde_json::Map::new()
+    } else {
+        match serde_json::from_slice(body)
+            .map_err(|e| RequestParseError::Invalid(format!("Invalid JSON body: {}", e)))?
+        {
+            serde_json::Value::Object(fields) => fields,
+            _ => {
+                return Err(RequestParseError::Invalid(
+                    "Request body must be a JSON object".to_string(),
+                ))
+            }
+        }
+    };
+    fields.insert("type".to_string(), name.into());
+    Request::from_value(serde_json::Value::Object(fields)).map_err(|e| match e {
+        RequestParseError::Invalid(message) => {
+            RequestParseError::Invalid(format!("Invalid {} request: {}", name, message))
+        }
+        unsupported => unsupported,
+    })
+}
+
+/// `GET /api/events`
+async fn events(
+    Query(query): Query<EventsQuery>,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(e) => {
+            let response = Response::error_with_code(ErrorCode::InvalidArgument, e);
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+    ws.on_upgrade(|socket| stream_events(socket, filter))
+}
+
+/// Query parameters of `GET /api/events`, matching `Request::SubscribeEvents`.
+#[derive(Deserialize)]
+struct EventsQuery {
+    /// Comma-separated topic names
+    topics: Option<String>,
+    max_rate_hz: Option<f32>,
+}
+
+impl EventsQuery {
+    fn filter(self) -> Result<EventFilter, String> {
+        let topics = self
+            .topics
+            .iter()
+            .flat_map(|topics| topics.split(','))
+            .filter(|name| !name.is_empty())
+            .map(|name| EventTopic::parse(name).ok_or(format!("Unknown event topic '{}'", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Request::SubscribeEvents {
+            topics: Vec::new(),
+            max_rate_hz: self.max_rate_hz,
+        }
+        .validate()?;
+        Ok(EventFilter::new(topics, self.max_rate_hz))
+    }
+}
+
+/// Forward broadcast events to a WebSocket client until it disconnects.
+async fn stream_events(mut socket: WebSocket, mut filter: EventFilter) {
+    info!("HTTP API: event stream opened");
+    let mut events = get_event_sender().subscribe();
+
+    // Like IPC subscribers, start with the current capture state
+    let synthetic = {
+        let state_arc = get_service_state();
+        let state = state_arc.lock().await;
+        Response::Event {
+            event: EventType::CaptureStateChanged {
+                capturing: state.transcribe_status.capturing,
+                error: state.transcribe_status.error.clone(),
+            },
+        }
+    };
+    if filter.accepts(&synthetic, Instant::now())
+        && send_json(&mut socket, &synthetic).await.is_err()
+    {
+        return;
+    }
+
+    let mut shutdown_check = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    // Jobs queued over HTTP go to the first event stream
+                    // that receives their result
+                    let deliver = filter.accepts(&event, Instant::now())
+                        && job_result_allowed(&event, &mut get_audio_jobs().lock().unwrap());
+                    if deliver && send_json(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("HTTP API: event stream lagged {} events", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                // The stream is one-way; anything but a close is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = shutdown_check.tick() => {
+                if is_shutdown_requested() {
+                    let shutdown = Response::Event {
+                        event: EventType::Shutdown,
+                    };
+                    let _ = send_json(&mut socket, &shutdown).await;
+                    break;
+                }
+            }
+        }
+    }
+    info!("HTTP API: event stream closed");
+}
+
+async fn send_json(socket: &mut WebSocket, response: &Response) -> Result<(), String> {
+    let text = serde_json::to_string(response).map_err(|e| e.to_string())?;
+    socket
+        .send(Message::Text(text.into()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read the API token, creating the file with a random token (readable by
+/// the current user only) if it is missing or empty. A token file other
+/// users can read is refused.
+fn load_or_create_token(path: &Path) -> Result<String, String> {
+    if let Ok(contents) = std::fs::read_to_string(path) {
+        let token = contents.trim();
+        if !token.is_empty() {
+            check_token_permissions(path)?;
+            return Ok(token.to_string());
+        }
+    }
+
+    let mut bytes = [0u8; TOKEN_BYTES];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate API token: {}", e))?;
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    // The mode above only applies to new files; an existing empty one may
+    // have been created with looser permissions
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {:?}: {}", path, e))?;
+    }
+    std::io::Write::write_all(&mut file, token.as_bytes())
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+    info!("Created HTTP API token file {:?}", path);
+    Ok(token)
+}
+
+/// Fail if the token file is readable or writable by the group or others.
+#[cfg(unix)]
+fn check_token_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(format!(
+            "HTTP API token file {:?} is accessible by other users (mode {:o}); \
+             run `chmod 600` on it or delete it to generate a new token",
+            path,
+            mode & 0o777
+        ));
+    }
+    Ok(())
+}
+
+/// The token file is created in the per-user data directory, which other
+/// users cannot read by default.
+#[cfg(not(unix))]
+fn check_token_permissions(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_built_from_path_and_body() {
+        assert!(matches!(parse_request("ping", b""), Ok(Request::Ping)));
+        assert!(matches!(
+            parse_request("set_auto_paste", br#"{"enabled": false}"#),
+            Ok(Request::SetAutoPaste { enabled: false })
+        ));
+        assert!(matches!(
+            parse_request("no_such_request", b""),
+            Err(RequestParseError::Unsupported(_))
+        ));
+        assert!(parse_request("set_auto_paste", b"[1]").is_err());
+    }
+
+    #[test]
+    fn token_file_is_created_once() {
+        let dir = std::env::temp_dir().join(format!("flowstt-api-token-{}", std::process::id()));
+        let path = dir.join("api-token");
+        let _ = std::fs::remove_file(&path);
+
+        let token = load_or_create_token(&path).unwrap();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert_eq!(load_or_create_token(&path).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+
+            // A token other users can read is refused, not used
+            let loose = std::fs::Permissions::from_mode(0o644);
+            std::fs::set_permissions(&path, loose).unwrap();
+            let err = load_or_create_token(&path).unwrap_err();
+            assert!(err.contains("chmod 600"), "{}", err);
+
+            // An empty file is replaced and restricted
+            std::fs::write(&path, "").unwrap();
+            let replaced = load_or_create_token(&path).unwrap();
+            assert_ne!(replaced, token);
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn requests_need_the_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/ping", listener.local_addr().unwrap());
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+        tokio::spawn(serve(listener, "secret".to_string(), shutdown_rx));
+
+        let client = reqwest::Client::new();
+        let status = |response: reqwest::Response| response.status().as_u16();
+
+        let response = client.post(&url).send().await.unwrap();
+        assert_eq!(status(response), 401);
+        let response = client.post(&url).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(status(response), 401);
+
+        let response = client
+            .post(&url)
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), r#"{"type":"pong"}"#);
+        let response = client
+            .post(format!("{}?token=secret", url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(status(response), 200);
+    }
+}
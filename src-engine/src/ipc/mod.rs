@@ -1,6 +1,9 @@
 //! IPC server for client communication.
 
 pub mod handlers;
+mod http;
+mod openai;
 mod server;
 
+pub use http::{start_http_api, stop_http_api};
 pub use server::{broadcast_event, register_event_callback, run_server, EventCallback};
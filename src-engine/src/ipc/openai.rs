@@ -0,0 +1,340 @@
+//! OpenAI-compatible transcription endpoint.
+//!
+//! Serves `POST /v1/audio/transcriptions` on the HTTP API so tools written
+//! against the OpenAI Whisper API can use the local model by pointing their
+//! base URL at `http://127.0.0.1:<port>/v1` and using the API token as the
+//! API key. Supported form fields are `file` (see [`SUPPORTED_FORMATS`]),
+//! `response_format` (`json`, `text`, `srt` or `vtt`), `language` and
+//! `prompt`; others such as `model` and `temperature` are accepted and
+//! ignored.
+//!
+//! Files are queued on the [`TranscriptionQueue`](crate::transcription::TranscriptionQueue)
+//! as jobs, so they never run concurrently with live dictation, and live
+//! segments waiting in the queue are transcribed first. Their results are
+//! only returned to the caller (no history, events or pasting).
+
+use axum::extract::Multipart;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use super::handlers::get_transcription_queue;
+use crate::transcription::queue::QueuedSegment;
+use crate::transcription::{TimedSegment, TranscribeOptions, TranscriptionJob, WHISPER_LANGUAGES};
+
+/// Largest accepted upload, matching the OpenAI API limit
+pub const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+/// Audio file types that can be decoded, in OpenAI's error message order.
+/// Opus (common in `.webm` and `.oga`) is not supported.
+pub const SUPPORTED_FORMATS: [&str; 8] =
+    ["flac", "m4a", "mp3", "mp4", "mpeg", "mpga", "ogg", "wav"];
+
+/// Output format of a transcription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFormat {
+    Json,
+    Text,
+    Srt,
+    Vtt,
+}
+
+impl ResponseFormat {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            "srt" => Ok(Self::Srt),
+            "vtt" => Ok(Self::Vtt),
+            _ => Err(format!(
+                "Unsupported response_format '{}' (expected json, text, srt or vtt)",
+                value
+            )),
+        }
+    }
+
+    /// Render transcribed segments in this format.
+    fn render(self, segments: &[TimedSegment]) -> Response {
+        let text = segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        match self {
+            Self::Json => Json(serde_json::json!({ "text": text })).into_response(),
+            Self::Text => plain_text(format!("{}\n", text)),
+            Self::Srt => plain_text(to_srt(segments)),
+            Self::Vtt => (
+                [(header::CONTENT_TYPE, "text/vtt; charset=utf-8")],
+                to_vtt(segments),
+            )
+                .into_response(),
+        }
+    }
+}
+
+fn plain_text(body: String) -> Response {
+    ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response()
+}
+
+/// Format a time as `HH:MM:SS` plus milliseconds after `separator`
+fn format_timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+fn to_srt(segments: &[TimedSegment]) -> String {
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                format_timestamp(s.start_ms, ','),
+                format_timestamp(s.end_ms, ','),
+                s.text
+            )
+        })
+        .collect()
+}
+
+fn to_vtt(segments: &[TimedSegment]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+    for s in segments {
+        vtt.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(s.start_ms, '.'),
+            format_timestamp(s.end_ms, '.'),
+            s.text
+        ));
+    }
+    vtt
+}
+
+/// Error in the OpenAI API error format.
+fn api_error(status: StatusCode, message: impl Into<String>) -> Response {
+    let kind = if status.is_server_error() {
+        "server_error"
+    } else {
+        "invalid_request_error"
+    };
+    let body = serde_json::json!({
+        "error": {
+            "message": message.into(),
+            "type": kind,
+            "param": null,
+            "code": null,
+        }
+    });
+    (status, Json(body)).into_response()
+}
+
+/// The error OpenAI returns for audio it cannot decode
+fn unsupported_format(detail: &str) -> Response {
+    let formats: Vec<String> = SUPPORTED_FORMATS
+        .iter()
+        .map(|f| format!("'{}'", f))
+        .collect();
+    api_error(
+        StatusCode::BAD_REQUEST,
+        format!(
+            "Invalid file format. Supported formats: [{}] ({})",
+            formats.join(", "),
+            detail
+        ),
+    )
+}
+
+/// Check a requested language against the languages the model knows.
+fn parse_language(value: &str) -> Result<String, String> {
+    let language = value.to_ascii_lowercase();
+    if !WHISPER_LANGUAGES.contains(&language.as_str()) {
+        return Err(format!(
+            "Unsupported language '{}' (expected an ISO 639-1 code such as 'en')",
+            value
+        ));
+    }
+    Ok(language)
+}
+
+/// Fields of a transcription request.
+struct TranscriptionForm {
+    file: Vec<u8>,
+    /// Extension of the uploaded file name, if any
+    extension: Option<String>,
+    format: ResponseFormat,
+    options: TranscribeOptions,
+}
+
+impl TranscriptionForm {
+    async fn read(multipart: &mut Multipart) -> Result<Self, String> {
+        let mut file = None;
+        let mut extension = None;
+        let mut format = ResponseFormat::Json;
+        let mut options = TranscribeOptions::default();
+
+        while let Some(field) = multipart.next_field().await.map_err(|e| e.to_string())? {
+            let name = field.name().unwrap_or_default().to_string();
+            if name == "file" {
+                extension = field
+                    .file_name()
+                    .and_then(|n| std::path::Path::new(n).extension())
+                    .map(|e| e.to_string_lossy().to_ascii_lowercase());
+                file = Some(field.bytes().await.map_err(|e| e.to_string())?.to_vec());
+                continue;
+            }
+            let value = field.text().await.map_err(|e| e.to_string())?;
+            let value = value.trim();
+            match name.as_str() {
+                "response_format" => format = ResponseFormat::parse(value)?,
+                "language" if !value.is_empty() => options.language = Some(parse_language(value)?),
+                "prompt" if !value.is_empty() => options.prompt = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            file: file.ok_or("Missing file")?,
+            extension,
+            format,
+            options,
+        })
+    }
+}
+
+/// `POST /v1/audio/transcriptions`
+pub async fn transcriptions(mut multipart: Multipart) -> Response {
+    let form = match TranscriptionForm::read(&mut multipart).await {
+        Ok(form) => form,
+        Err(e) => return api_error(StatusCode::BAD_REQUEST, e),
+    };
+    // Decoding compressed audio takes a while; keep it off the runtime
+    let TranscriptionForm {
+        file,
+        extension,
+        format,
+        options,
+    } = form;
+    let decoded = tokio::task::spawn_blocking(move || {
+        crate::audio::decode_audio_file(file, extension.as_deref())
+    })
+    .await;
+    let audio = match decoded {
+        Ok(Ok(audio)) => audio,
+        Ok(Err(e)) => return unsupported_format(&e),
+        Err(e) => return api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let queue = get_transcription_queue();
+    if !queue.is_worker_active() {
+        return api_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Transcription worker is not running",
+        );
+    }
+
+    let (reply, result) = oneshot::channel();
+    let queued = queue.enqueue(QueuedSegment::for_job(
+        audio,
+        TranscriptionJob { options, reply },
+    ));
+    if !queued {
+        return api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Transcription queue is full, retry later",
+        );
+    }
+
+    match result.await {
+        Ok(Ok(segments)) => {
+            info!(
+                "[OpenAI API] Transcribed file into {} segments",
+                segments.len()
+            );
+            format.render(&segments)
+        }
+        Ok(Err(e)) => {
+            warn!("[OpenAI API] Transcription failed: {}", e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, e)
+        }
+        // The queue was cleared before the job ran
+        Err(_) => api_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Transcription was cancelled",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments() -> Vec<TimedSegment> {
+        vec![
+            TimedSegment {
+                start_ms: 0,
+                end_ms: 2500,
+                text: "Hello there.".into(),
+            },
+            TimedSegment {
+                start_ms: 3_723_040,
+                end_ms: 3_725_000,
+                text: "Bye.".into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn subtitles() {
+        assert_eq!(
+            to_srt(&segments()),
+            "1\n00:00:00,000 --> 00:00:02,500\nHello there.\n\n\
+             2\n01:02:03,040 --> 01:02:05,000\nBye.\n\n"
+        );
+        assert_eq!(
+            to_vtt(&segments()),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHello there.\n\n\
+             01:02:03.040 --> 01:02:05.000\nBye.\n\n"
+        );
+        assert_eq!(to_vtt(&[]), "WEBVTT\n\n");
+    }
+
+    #[tokio::test]
+    async fn unsupported_files_get_openai_error() {
+        let response = unsupported_format("Unrecognized audio file");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+        assert_eq!(
+            body["error"]["message"],
+            "Invalid file format. Supported formats: ['flac', 'm4a', 'mp3', 'mp4', 'mpeg', \
+             'mpga', 'ogg', 'wav'] (Unrecognized audio file)"
+        );
+    }
+
+    #[test]
+    fn response_formats() {
+        assert_eq!(ResponseFormat::parse("srt"), Ok(ResponseFormat::Srt));
+        assert!(ResponseFormat::parse("verbose_json").is_err());
+    }
+
+    #[test]
+    fn languages_are_checked() {
+        assert_eq!(parse_language("EN"), Ok("en".to_string()));
+        assert_eq!(parse_language("yue"), Ok("yue".to_string()));
+        assert!(parse_language("xx").is_err());
+        assert!(parse_language("english").is_err());
+    }
+}
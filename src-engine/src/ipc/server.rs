@@ -5,14 +5,21 @@
 //! and named pipes (Windows).
 
 use flowstt_common::ipc::{
-    get_socket_path, read_json, write_json, EventType, IpcError, Request, Response,
+    get_socket_path, read_message, write_json, ErrorCode, EventTopic, EventType, IpcError,
+    Request, RequestEnvelope, RequestParseError, Response, ResponseEnvelope, MAX_REQUEST_SIZE,
 };
+use flowstt_common::security::peer_verify::{self, PeerInfo, PeerVerifyError};
+use flowstt_common::security::trust_policy::TrustPolicy;
+use std::collections::{HashMap, HashSet};
+use std::mem::Discriminant;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, oneshot, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
 use tracing::{debug, error, info, warn};
 
 use super::handlers::handle_request;
+use crate::audit;
 use crate::is_shutdown_requested;
 use crate::state::get_service_state;
 
@@ -68,10 +75,13 @@ pub fn register_event_callback(callback: impl EventCallback) {
 /// When no IPC clients are subscribed and no external callback is registered,
 /// log the event instead of silently dropping it.
 pub fn broadcast_event(event: Response) {
-    // First, forward to external callback (Tauri frontend) if registered
+    // First, forward to external callback (Tauri frontend) if registered.
+    // Audio job results belong to the client that queued the job.
     if let Response::Event { event: ref evt } = event {
         if let Some(callback) = EXTERNAL_EVENT_CALLBACK.get() {
-            callback.on_event(evt);
+            if !matches!(evt, EventType::AudioTranscriptionComplete { .. }) {
+                callback.on_event(evt);
+            }
         }
     }
 
@@ -90,6 +100,12 @@ pub fn broadcast_event(event: Response) {
                         // High-frequency event - use debug level
                         debug!("Visualization data generated (no clients)");
                     }
+                    EventType::AecMetrics(metrics) => {
+                        debug!(
+                            "AEC metrics (no clients): delay={:?}ms, erle={:.1}dB, converged={}",
+                            metrics.estimated_delay_ms, metrics.erle_db, metrics.converged
+                        );
+                    }
                     EventType::SpeechStarted => {
                         debug!("Speech started (no clients)");
                     }
@@ -108,6 +124,35 @@ pub fn broadcast_event(event: Response) {
                     EventType::PttReleased => {
                         info!("PTT released (no clients)");
                     }
+                    EventType::ToggleTalkStarted => {
+                        info!("Toggle-to-talk started (no clients)");
+                    }
+                    EventType::ToggleTalkStopped {
+                        reason,
+                        duration_ms,
+                    } => {
+                        info!(
+                            "Toggle-to-talk stopped (no clients): {:?}, {}ms",
+                            reason, duration_ms
+                        );
+                    }
+                    EventType::AudioTranscriptionComplete {
+                        job_id, ref error, ..
+                    } => {
+                        info!(
+                            "Audio transcription {} complete (no clients): error={:?}",
+                            job_id, error
+                        );
+                    }
+                    EventType::TranscriptionError { ref error } => {
+                        info!("Transcription error (no clients): {}", error);
+                    }
+                    EventType::HookFailed {
+                        ref hook,
+                        ref error,
+                    } => {
+                        debug!("Hook '{}' failed (no clients): {}", hook, error);
+                    }
                     EventType::TranscriptionModeChanged { mode } => {
                         info!("Transcription mode changed (no clients): {:?}", mode);
                     }
@@ -144,6 +189,65 @@ pub fn broadcast_event(event: Response) {
     let _ = sender.send(event);
 }
 
+/// Which events a subscriber receives, from its `SubscribeEvents` request.
+pub(super) struct EventFilter {
+    /// Topics to forward; all if empty
+    topics: Vec<EventTopic>,
+    /// Minimum time between visualization events of the same kind
+    min_interval: Option<Duration>,
+    /// When each kind of visualization event was last forwarded
+    last_sent: HashMap<Discriminant<EventType>, Instant>,
+}
+
+impl EventFilter {
+    pub(super) fn new(topics: Vec<EventTopic>, max_rate_hz: Option<f32>) -> Self {
+        Self {
+            topics,
+            min_interval: max_rate_hz.map(|hz| {
+                Duration::try_from_secs_f64(1.0 / f64::from(hz)).unwrap_or(Duration::MAX)
+            }),
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Whether to forward `event` at `now`, dropping visualization events
+    /// that exceed the rate limit.
+    pub(super) fn accepts(&mut self, event: &Response, now: Instant) -> bool {
+        let Response::Event { event } = event else {
+            return true;
+        };
+        if matches!(event, EventType::Shutdown) {
+            return true;
+        }
+        let topic = event.topic();
+        if !self.topics.is_empty() && !self.topics.contains(&topic) {
+            return false;
+        }
+        if let (EventTopic::Visualization, Some(min_interval)) = (topic, self.min_interval) {
+            let kind = std::mem::discriminant(event);
+            if let Some(last) = self.last_sent.get(&kind) {
+                if now.duration_since(*last) < min_interval {
+                    return false;
+                }
+            }
+            self.last_sent.insert(kind, now);
+        }
+        true
+    }
+}
+
+/// Whether a subscriber that queued the audio jobs in `jobs` may receive
+/// `event`: a `TranscribeAudio` result only goes to the client that queued
+/// it. A delivered job is forgotten.
+pub(super) fn job_result_allowed(event: &Response, jobs: &mut HashSet<u64>) -> bool {
+    match event {
+        Response::Event {
+            event: EventType::AudioTranscriptionComplete { job_id, .. },
+        } => jobs.remove(job_id),
+        _ => true,
+    }
+}
+
 /// Run the IPC server until shutdown.
 ///
 /// If `ready_tx` is provided, it is notified once the server is listening and
@@ -214,12 +318,18 @@ pub async fn run_server(ready_tx: Option<oneshot::Sender<()>>) -> Result<(), Ipc
 
 /// Handle a Unix socket client connection.
 #[cfg(unix)]
-async fn handle_unix_client(stream: tokio::net::UnixStream) -> Result<(), IpcError> {
+async fn handle_unix_client(mut stream: tokio::net::UnixStream) -> Result<(), IpcError> {
+    let peer = match verify_client(peer_verify::identify_peer(&stream)).await {
+        Ok(peer) => peer,
+        Err(e) => return reject_client(&mut stream, e).await,
+    };
+    info!("Verified client pid {} ({:?})", peer.pid, peer.executable);
+
     increment_client_count();
     info!("Client connected (total: {})", get_client_count());
     
     let (reader, writer) = stream.into_split();
-    let _ = handle_client_connection(reader, writer).await;
+    let _ = handle_client_connection(reader, writer, Some(peer)).await;
     
     decrement_client_count();
     info!("Client disconnected (remaining: {})", get_client_count());
@@ -299,13 +409,22 @@ pub async fn run_server(mut ready_tx: Option<oneshot::Sender<()>>) -> Result<(),
 /// Handle a Windows named pipe client connection.
 #[cfg(windows)]
 async fn handle_windows_client(
-    pipe: tokio::net::windows::named_pipe::NamedPipeServer,
+    mut pipe: tokio::net::windows::named_pipe::NamedPipeServer,
 ) -> Result<(), IpcError> {
+    use std::os::windows::io::AsRawHandle;
+
+    let handle = windows::Win32::Foundation::HANDLE(pipe.as_raw_handle());
+    let peer = match verify_client(peer_verify::identify_peer(handle)).await {
+        Ok(peer) => peer,
+        Err(e) => return reject_client(&mut pipe, e).await,
+    };
+    info!("Verified client pid {} ({:?})", peer.pid, peer.executable);
+
     increment_client_count();
     info!("Client connected (total: {})", get_client_count());
     
     let (reader, writer) = tokio::io::split(pipe);
-    let _ = handle_client_connection(reader, writer).await;
+    let _ = handle_client_connection(reader, writer, Some(peer)).await;
     
     decrement_client_count();
     info!("Client disconnected (remaining: {})", get_client_count());
@@ -313,106 +432,256 @@ async fn handle_windows_client(
     Ok(())
 }
 
+/// Check an identified client against the trust policy.
+///
+/// An untrusted client is only logged unless the policy sets `enforce`.
+/// Reading the policy and hashing the client's executable are blocking file
+/// I/O, so they run on a blocking thread rather than the accept path.
+async fn verify_client(
+    peer: Result<PeerInfo, PeerVerifyError>,
+) -> Result<PeerInfo, PeerVerifyError> {
+    let peer = peer?;
+    tokio::task::spawn_blocking(move || {
+        let policy = load_trust_policy();
+        match peer_verify::verify_executable(&peer, &policy) {
+            Ok(()) => Ok(peer),
+            Err(e @ PeerVerifyError::Untrusted { .. }) if !policy.enforce => {
+                warn!(
+                    "Allowing untrusted IPC client: {}. Add its path or sha256 to {:?} and set \"enforce\": true to refuse other clients",
+                    e,
+                    TrustPolicy::path()
+                );
+                Ok(peer)
+            }
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| PeerVerifyError::CredentialsFailed(e.to_string()))?
+}
+
+/// Load the user's trust policy. A policy file that cannot be read or parsed
+/// is logged and ignored, and only the built-in clients are allowed until it
+/// is fixed.
+fn load_trust_policy() -> TrustPolicy {
+    TrustPolicy::load().unwrap_or_else(|e| {
+        warn!(
+            "Ignoring invalid trust policy, only built-in clients are trusted: {}",
+            e
+        );
+        TrustPolicy {
+            enforce: true,
+            ..TrustPolicy::default()
+        }
+    })
+}
+
+/// Log why a client was refused and tell it before closing the connection.
+async fn reject_client<W: tokio::io::AsyncWrite + Unpin>(
+    writer: &mut W,
+    error: PeerVerifyError,
+) -> Result<(), IpcError> {
+    if matches!(error, PeerVerifyError::Untrusted { .. }) {
+        warn!(
+            "Rejected IPC client: {}. To allow it, add its path or sha256 to {:?}",
+            error,
+            TrustPolicy::path()
+        );
+    } else {
+        warn!("Rejected IPC client: {}", error);
+    }
+    audit::record_rejected_peer(&error);
+    let response = Response::error_with_code(
+        ErrorCode::PermissionDenied,
+        format!("Client rejected by peer verification: {}", error),
+    );
+    write_json(
+        writer,
+        &ResponseEnvelope {
+            request_id: None,
+            response,
+        },
+    )
+    .await
+}
+
+/// Requests read from a client ahead of the one being handled. At most one
+/// of them may be larger than `MAX_REQUEST_SIZE`.
+const REQUEST_QUEUE_CAPACITY: usize = 4;
+
+/// A request read from a client with its `request_id`.
+struct IncomingRequest {
+    request_id: Option<u64>,
+    parsed: Result<Request, RequestParseError>,
+    /// Held by a request larger than `MAX_REQUEST_SIZE` until it is handled
+    large_message: Option<OwnedSemaphorePermit>,
+}
+
+/// Read requests from a client until the connection fails or closes.
+///
+/// This runs on its own task because reads are not cancel-safe: racing them
+/// against events in a `select!` could drop a partly read message.
+///
+/// Only `TranscribeAudio` requests may exceed `MAX_REQUEST_SIZE`, and after
+/// one is read nothing more is read until it has been handled, so a client
+/// can make the engine buffer at most one large message.
+async fn read_requests<R: tokio::io::AsyncRead + Unpin>(
+    mut reader: R,
+    requests: mpsc::Sender<Result<IncomingRequest, IpcError>>,
+) {
+    let large_message = Arc::new(Semaphore::new(1));
+    loop {
+        let Ok(permit) = Arc::clone(&large_message).acquire_owned().await else {
+            break;
+        };
+        let incoming = read_message(&mut reader).await.map(|data| {
+            let (request_id, mut parsed) = RequestEnvelope::parse(&data);
+            let is_large = data.len() > MAX_REQUEST_SIZE;
+            if is_large && !matches!(parsed, Ok(Request::TranscribeAudio { .. })) {
+                parsed = Err(RequestParseError::Invalid(format!(
+                    "message of {} bytes exceeds the {} byte limit for this request",
+                    data.len(),
+                    MAX_REQUEST_SIZE
+                )));
+            }
+            IncomingRequest {
+                request_id,
+                parsed,
+                large_message: is_large.then_some(permit),
+            }
+        });
+        let failed = incoming.is_err();
+        if requests.send(incoming).await.is_err() || failed {
+            break;
+        }
+    }
+}
+
+/// Response for a message that is framed correctly but is not a valid request.
+///
+/// Replying instead of dropping the connection lets newer clients probe for
+/// requests this engine does not know.
+fn parse_error_response(error: RequestParseError) -> Response {
+    match error {
+        RequestParseError::Unsupported(request_type) => {
+            warn!("Unsupported request type: {}", request_type);
+            Response::UnsupportedRequest { request_type }
+        }
+        RequestParseError::Invalid(message) => {
+            warn!("Invalid request: {}", message);
+            Response::error_with_code(
+                ErrorCode::InvalidArgument,
+                format!("Invalid request: {}", message),
+            )
+        }
+    }
+}
+
+/// Wait for the next event, or forever if the client is not subscribed.
+async fn next_event(
+    receiver: &mut Option<broadcast::Receiver<Response>>,
+) -> Result<Response, broadcast::error::RecvError> {
+    match receiver {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Handle a client connection (platform-agnostic).
-async fn handle_client_connection<R, W>(reader: R, writer: W) -> Result<(), IpcError>
+///
+/// Requests are handled one at a time in the order they arrive, but a client
+/// may send several before reading the responses, and once subscribed it
+/// receives events interleaved with them. Each response echoes the
+/// `request_id` of its request so the client can match them up.
+async fn handle_client_connection<R, W>(
+    reader: R,
+    mut writer: W,
+    peer: Option<PeerInfo>,
+) -> Result<(), IpcError>
 where
     R: tokio::io::AsyncRead + Unpin + Send + 'static,
     W: tokio::io::AsyncWrite + Unpin + Send + 'static,
 {
-    let reader = Arc::new(Mutex::new(reader));
-    let writer = Arc::new(Mutex::new(writer));
+    let (request_tx, mut requests) = mpsc::channel(REQUEST_QUEUE_CAPACITY);
+    let reader_task = tokio::spawn(read_requests(reader, request_tx));
+    let result = serve_client(&mut requests, &mut writer, peer.as_ref()).await;
+    reader_task.abort();
+    result
+}
+
+/// Answer requests and forward events until the client disconnects or the
+/// engine shuts down.
+async fn serve_client<W: tokio::io::AsyncWrite + Unpin>(
+    requests: &mut mpsc::Receiver<Result<IncomingRequest, IpcError>>,
+    writer: &mut W,
+    peer: Option<&PeerInfo>,
+) -> Result<(), IpcError> {
     let mut event_receiver: Option<broadcast::Receiver<Response>> = None;
-    let mut subscribed = false;
+    let mut event_filter = EventFilter::new(Vec::new(), None);
+    // Audio transcription jobs queued by this client
+    let mut audio_jobs = HashSet::new();
 
     loop {
         if is_shutdown_requested() {
             // Notify client of shutdown if subscribed
-            if subscribed {
-                let mut w = writer.lock().await;
+            if event_receiver.is_some() {
                 let _ = write_json(
-                    &mut *w,
+                    writer,
                     &Response::Event {
-                        event: flowstt_common::ipc::EventType::Shutdown,
+                        event: EventType::Shutdown,
                     },
                 )
                 .await;
             }
-            break;
+            return Ok(());
         }
 
-        // If subscribed to events, use select! to handle both events and requests efficiently
-        if subscribed {
-            if let Some(ref mut rx) = event_receiver {
-                let mut r = reader.lock().await;
-
-                tokio::select! {
-                    // Wait for event from broadcast channel
-                    event_result = rx.recv() => {
-                        drop(r); // Release reader lock before writing
-                        match event_result {
-                            Ok(event) => {
-                                let mut w = writer.lock().await;
-                                write_json(&mut *w, &event).await?;
-                            }
-                            Err(broadcast::error::RecvError::Lagged(n)) => {
-                                warn!("Client lagged {} events", n);
-                            }
-                            Err(broadcast::error::RecvError::Closed) => {
-                                // Channel closed, unsubscribe
-                                subscribed = false;
-                                event_receiver = None;
-                            }
-                        }
+        tokio::select! {
+            incoming = requests.recv() => {
+                let IncomingRequest { request_id, parsed, large_message } = match incoming {
+                    Some(Ok(incoming)) => incoming,
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                };
+
+                let request = match parsed {
+                    Ok(request) => request,
+                    Err(e) => {
+                        let response = ResponseEnvelope {
+                            request_id,
+                            response: parse_error_response(e),
+                        };
+                        write_json(writer, &response).await?;
                         continue;
                     }
-                    // Wait for request from client (with longer timeout since events are prioritized)
-                    read_result = tokio::time::timeout(std::time::Duration::from_secs(1), read_json::<_, Request>(&mut *r)) => {
-                        drop(r); // Release reader lock
-                        match read_result {
-                            Ok(Ok(request)) => {
-                                info!("Received request: {:?}", request);
-                                let response = handle_request(request.clone()).await;
-                                info!("Sending response: {:?}", response);
-                                let mut w = writer.lock().await;
-                                write_json(&mut *w, &response).await?;
-                            }
-                            Ok(Err(e)) => {
-                                return Err(e);
-                            }
-                            Err(_) => {
-                                // Timeout, continue loop to check for events/shutdown
-                            }
+                };
+                info!("Received request: {:?}", request);
+
+                // Subscribe before handling so no event is missed. Invalid
+                // subscriptions are rejected by `handle_request`.
+                let mut is_subscribe = false;
+                if let Request::SubscribeEvents { ref topics, max_rate_hz } = request {
+                    if request.validate().is_ok() {
+                        is_subscribe = true;
+                        event_filter = EventFilter::new(topics.clone(), max_rate_hz);
+                        if event_receiver.is_none() {
+                            event_receiver = Some(get_event_sender().subscribe());
                         }
-                        continue;
                     }
                 }
-            }
-        }
-
-        // Not subscribed - just handle requests with timeout
-        let read_result = {
-            let mut r = reader.lock().await;
-            tokio::time::timeout(std::time::Duration::from_millis(100), read_json::<_, Request>(&mut *r)).await
-        };
-
-        match read_result {
-            Ok(Ok(request)) => {
-                info!("Received request: {:?}", request);
 
-                // Check if this is a subscribe request
-                let is_subscribe = matches!(request, Request::SubscribeEvents);
-                if is_subscribe {
-                    subscribed = true;
-                    event_receiver = Some(get_event_sender().subscribe());
+                let audited = request.is_state_changing().then(|| request.type_name());
+                let response = handle_request(request).await;
+                // Let the reader continue with the next large request
+                drop(large_message);
+                if let Some(request_type) = audited {
+                    audit::record_request("ipc", peer, request_type, &response);
+                }
+                if let Response::TranscriptionQueued { job_id } = response {
+                    audio_jobs.insert(job_id);
                 }
-
-                // Handle request
-                let response = handle_request(request.clone()).await;
                 info!("Sending response: {:?}", response);
-
-                // Send response
-                let mut w = writer.lock().await;
-                write_json(&mut *w, &response).await?;
+                write_json(writer, &ResponseEnvelope { request_id, response }).await?;
 
                 // After subscribing, send current capture state so the
                 // client immediately knows whether transcription is active
@@ -426,19 +695,213 @@ where
                         },
                     };
                     drop(state);
-                    write_json(&mut *w, &synthetic).await?;
+                    if event_filter.accepts(&synthetic, Instant::now()) {
+                        write_json(writer, &synthetic).await?;
+                    }
                 }
             }
-            Ok(Err(e)) => {
-                // Read error
-                return Err(e);
-            }
-            Err(_) => {
-                // Timeout, continue loop
-                continue;
+            event_result = next_event(&mut event_receiver) => {
+                match event_result {
+                    Ok(event) => {
+                        if event_filter.accepts(&event, Instant::now())
+                            && job_result_allowed(&event, &mut audio_jobs)
+                        {
+                            write_json(writer, &event).await?;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("Client lagged {} events", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        // Channel closed, unsubscribe
+                        event_receiver = None;
+                    }
+                }
             }
+            // Wake up periodically to check the shutdown flag
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::ipc::{read_json, write_message, MAX_FRAME_SIZE};
+    use tokio::io::{AsyncWriteExt, DuplexStream};
+
+    async fn send(client: &mut DuplexStream, message: &str) {
+        let data = message.as_bytes();
+        client
+            .write_all(&(data.len() as u32).to_le_bytes())
+            .await
+            .unwrap();
+        client.write_all(data).await.unwrap();
+    }
 
-    Ok(())
+    async fn receive(client: &mut DuplexStream) -> ResponseEnvelope {
+        tokio::time::timeout(Duration::from_secs(5), read_json(client))
+            .await
+            .expect("timed out waiting for a message")
+            .unwrap()
+    }
+
+    fn connect() -> DuplexStream {
+        let (client, server) = tokio::io::duplex(MAX_FRAME_SIZE);
+        let (reader, writer) = tokio::io::split(server);
+        tokio::spawn(handle_client_connection(reader, writer, None));
+        client
+    }
+
+    #[tokio::test]
+    async fn pipelined_requests_are_answered_in_order() {
+        let mut client = connect();
+        send(&mut client, r#"{"type":"ping","request_id":1}"#).await;
+        send(&mut client, r#"{"type":"from_the_future","request_id":2}"#).await;
+        send(&mut client, r#"{"type":"ping"}"#).await;
+        send(&mut client, r#"{"type":"set_aec_enabled","request_id":3}"#).await;
+
+        let first = receive(&mut client).await;
+        assert_eq!(first.request_id, Some(1));
+        assert!(matches!(first.response, Response::Pong));
+
+        let second = receive(&mut client).await;
+        assert_eq!(second.request_id, Some(2));
+        assert!(matches!(
+            second.response,
+            Response::UnsupportedRequest { request_type } if request_type == "from_the_future"
+        ));
+
+        let third = receive(&mut client).await;
+        assert_eq!(third.request_id, None);
+        assert!(matches!(third.response, Response::Pong));
+
+        // Invalid requests are answered and keep the connection open
+        let fourth = receive(&mut client).await;
+        assert_eq!(fourth.request_id, Some(3));
+        assert!(fourth.response.is_error());
+        send(&mut client, r#"{"type":"ping","request_id":4}"#).await;
+        assert_eq!(receive(&mut client).await.request_id, Some(4));
+    }
+
+    #[tokio::test]
+    async fn only_audio_requests_may_be_large() {
+        let mut client = connect();
+        let padding = "x".repeat(MAX_REQUEST_SIZE);
+        let ping = format!(
+            r#"{{"type":"ping","request_id":1,"padding":"{}"}}"#,
+            padding
+        );
+        write_message(&mut client, ping.as_bytes()).await.unwrap();
+        let rejected = receive(&mut client).await;
+        assert_eq!(rejected.request_id, Some(1));
+        assert!(matches!(
+            rejected.response,
+            Response::Error { code: ErrorCode::InvalidArgument, ref message }
+                if message.contains("byte limit")
+        ));
+
+        // Over a megabyte of silence, base64-encoded
+        let data = "AAAA".repeat(MAX_REQUEST_SIZE / 3 + 1);
+        let audio = format!(
+            r#"{{"type":"transcribe_audio","request_id":2,"sample_rate":16000,"channels":1,"encoding":"pcm_s16le","data":"{}"}}"#,
+            data
+        );
+        write_message(&mut client, audio.as_bytes()).await.unwrap();
+        send(&mut client, r#"{"type":"ping","request_id":3}"#).await;
+
+        // No model or worker in tests, but the request itself is accepted
+        let accepted = receive(&mut client).await;
+        assert_eq!(accepted.request_id, Some(2));
+        assert!(!matches!(
+            accepted.response,
+            Response::Error { code: ErrorCode::InvalidArgument, .. }
+        ));
+        assert_eq!(receive(&mut client).await.request_id, Some(3));
+    }
+
+    #[test]
+    fn event_filter_topics_and_rate() {
+        let event = |event| Response::Event { event };
+        let start = Instant::now();
+        let visualization = || {
+            event(EventType::AudioLevelUpdate {
+                device_id: "mic".into(),
+                level_db: -20.0,
+            })
+        };
+
+        let mut filter = EventFilter::new(vec![EventTopic::Visualization], Some(10.0));
+        assert!(filter.accepts(&visualization(), start));
+        assert!(!filter.accepts(&visualization(), start + Duration::from_millis(50)));
+        assert!(filter.accepts(&visualization(), start + Duration::from_millis(100)));
+        // Other topics are filtered out, but shutdown always gets through
+        assert!(!filter.accepts(&event(EventType::SpeechStarted), start));
+        assert!(filter.accepts(&event(EventType::Shutdown), start));
+        assert!(filter.accepts(&Response::Pong, start));
+
+        let mut filter = EventFilter::new(Vec::new(), None);
+        assert!(filter.accepts(&event(EventType::SpeechStarted), start));
+        assert!(filter.accepts(&visualization(), start));
+        assert!(filter.accepts(&visualization(), start));
+    }
+
+    #[test]
+    fn job_results_go_to_their_client() {
+        let result = |job_id| Response::Event {
+            event: EventType::AudioTranscriptionComplete {
+                job_id,
+                result: None,
+                error: Some("cancelled".into()),
+            },
+        };
+        let mut jobs = HashSet::from([1]);
+        assert!(!job_result_allowed(&result(2), &mut jobs));
+        assert!(job_result_allowed(&result(1), &mut jobs));
+        assert!(!job_result_allowed(&result(1), &mut jobs));
+        assert!(job_result_allowed(&Response::Pong, &mut jobs));
+    }
+
+    #[tokio::test]
+    async fn events_interleave_with_responses() {
+        let mut client = connect();
+        send(&mut client, r#"{"type":"subscribe_events","request_id":1}"#).await;
+        send(&mut client, r#"{"type":"ping","request_id":2}"#).await;
+
+        let subscribed = receive(&mut client).await;
+        assert_eq!(subscribed.request_id, Some(1));
+        assert!(matches!(subscribed.response, Response::Subscribed));
+
+        // The current capture state follows the subscription
+        let state = receive(&mut client).await;
+        assert_eq!(state.request_id, None);
+        assert!(matches!(
+            state.response,
+            Response::Event {
+                event: EventType::CaptureStateChanged { .. }
+            }
+        ));
+        assert_eq!(receive(&mut client).await.request_id, Some(2));
+
+        // Other tests may broadcast events too, so look for ours by content
+        broadcast_event(Response::Event {
+            event: EventType::SpeechEnded { duration_ms: 4321 },
+        });
+        send(&mut client, r#"{"type":"ping","request_id":3}"#).await;
+        let (mut got_event, mut got_pong) = (false, false);
+        while !(got_event && got_pong) {
+            let message = receive(&mut client).await;
+            match message.response {
+                Response::Event {
+                    event: EventType::SpeechEnded { duration_ms: 4321 },
+                } => got_event = true,
+                Response::Pong => {
+                    assert_eq!(message.request_id, Some(3));
+                    got_pong = true;
+                }
+                Response::Event { .. } => {}
+                other => panic!("unexpected message: {:?}", other),
+            }
+        }
+    }
 }
@@ -4,21 +4,28 @@
 //! transcription, hotkey monitoring, clipboard, history, and IPC server.
 //!
 //! This is a library crate consumed by the Tauri application. The engine runs
-//! in-process with the GUI, and also hosts an IPC socket server for CLI clients.
+//! in-process with the GUI, and also hosts an IPC socket server for CLI clients
+//! and an optional localhost HTTP/WebSocket API for other integrations.
 
 mod audio;
 pub mod audio_loop;
+mod audit;
 pub mod clipboard;
 pub mod config;
+pub mod gain;
 pub mod history;
+pub mod hooks;
 pub mod hotkey;
 pub mod ipc;
+pub mod pipeline;
 pub mod platform;
 pub mod processor;
 pub mod ptt_controller;
 pub mod state;
 pub mod test_capture;
+pub mod text_processing;
 pub mod transcription;
+pub mod voice_commands;
 
 pub use audio_loop::{
     is_audio_loop_active, start_audio_loop, stop_audio_loop, TranscriptionEventBroadcaster,
@@ -95,13 +102,26 @@ pub async fn init() -> Result<tokio::task::JoinHandle<()>, String> {
         state.transcription_mode = loaded_config.transcription_mode;
         state.ptt_hotkeys = loaded_config.ptt_hotkeys.clone();
         state.auto_toggle_hotkeys = loaded_config.auto_toggle_hotkeys.clone();
+        state.toggle_talk_hotkeys = loaded_config.toggle_talk_hotkeys.clone();
+        state.toggle_talk_silence_timeout_ms = loaded_config.toggle_talk_silence_timeout_ms;
+        if state.transcription_mode.is_hotkey_driven() {
+            state.hotkey_mode = state.transcription_mode;
+        }
         info!(
-            "Applied config: transcription_mode={:?}, ptt_hotkeys={} combination(s), auto_toggle_hotkeys={} combination(s)",
+            "Applied config: transcription_mode={:?}, ptt_hotkeys={} combination(s), auto_toggle_hotkeys={} combination(s), toggle_talk_hotkeys={} combination(s)",
             state.transcription_mode,
             state.ptt_hotkeys.len(),
-            state.auto_toggle_hotkeys.len()
+            state.auto_toggle_hotkeys.len(),
+            state.toggle_talk_hotkeys.len()
         );
+        if state.hotkey_mode == flowstt_common::TranscriptionMode::ToggleToTalk && state.toggle_talk_hotkeys.is_empty() {
+            warn!("Toggle-to-talk has no hotkeys configured; recording cannot be started until toggle_talk_hotkeys is set");
+        }
     }
+    gain::set_gain_settings(gain::GainSettings {
+        enabled: loaded_config.agc_enabled,
+        target_level_db: loaded_config.agc_target_level_db,
+    });
 
     // Start the IPC server so CLI clients can connect immediately.
     let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
@@ -116,6 +136,13 @@ pub async fn init() -> Result<tokio::task::JoinHandle<()>, String> {
     // Wait until the IPC server is actually listening before proceeding.
     let _ = ready_rx.await;
 
+    // Start the optional localhost HTTP/WebSocket API
+    if loaded_config.http_api_enabled {
+        if let Err(e) = ipc::start_http_api(loaded_config.http_api_port).await {
+            error!("Failed to start HTTP API: {}", e);
+        }
+    }
+
     // Initialize platform-specific audio backends
     info!("Initializing audio backends...");
     if let Err(e) = platform::init_audio_backend() {
@@ -183,6 +210,9 @@ pub async fn init() -> Result<tokio::task::JoinHandle<()>, String> {
 pub fn cleanup() {
     info!("Engine cleanup...");
 
+    // Write out audit entries recorded just before exit
+    audit::flush();
+
     // Remove socket file on Unix
     #[cfg(unix)]
     {
@@ -0,0 +1,462 @@
+//! Audio processing pipeline shared by all transcription modes.
+//!
+//! Every chunk of captured audio goes through the same stages regardless of
+//! how segments are delimited:
+//! - Automatic gain control
+//! - Mono downmix
+//! - Speech detection (also drives the visualization speech metrics)
+//! - Visualization
+//! - Recording into the segment buffer
+//!
+//! A `SegmentationStrategy` decides who marks segment boundaries: the speech
+//! detector (Automatic mode) or the hotkey (Push-to-Talk and Toggle-to-Talk).
+
+use std::sync::{Arc, Mutex};
+
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::{TranscriptionMode, VisualizationData};
+
+use crate::gain::AutomaticGainControl;
+use crate::ipc::broadcast_event;
+use crate::platform::AudioData;
+use crate::processor::{
+    SpeechDetector, SpeechStateChange, VisualizationCallback, VisualizationPayload,
+    VisualizationProcessor, WordBreakEvent,
+};
+use crate::transcription::TranscribeState;
+
+/// How segment boundaries are decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentationStrategy {
+    /// Voice activity detection starts and ends segments, and long segments
+    /// are split at word breaks
+    Vad,
+    /// The hotkey starts and ends the segment; speech detection only feeds
+    /// visualization
+    PushToTalk,
+    /// A hotkey tap starts and another ends the segment; speech detection
+    /// also ends it after `silence_timeout_ms` without speech (0 = never)
+    ToggleToTalk { silence_timeout_ms: u32 },
+}
+
+impl SegmentationStrategy {
+    /// Transcription mode the strategy implements
+    pub fn mode(&self) -> TranscriptionMode {
+        match self {
+            SegmentationStrategy::Vad => TranscriptionMode::Automatic,
+            SegmentationStrategy::PushToTalk => TranscriptionMode::PushToTalk,
+            SegmentationStrategy::ToggleToTalk { .. } => TranscriptionMode::ToggleToTalk,
+        }
+    }
+}
+
+/// Destination for recorded audio and segment boundaries.
+pub trait SegmentSink {
+    /// Whether audio should currently be recorded.
+    fn is_recording(&self) -> bool;
+
+    /// Record interleaved samples.
+    fn write_samples(&mut self, samples: &[f32]);
+
+    /// A segment started `lookback_samples` mono samples ago.
+    fn begin_segment(&mut self, lookback_samples: usize);
+
+    /// The current segment ended.
+    fn end_segment(&mut self);
+
+    /// A word break was detected inside the current segment.
+    fn word_break(&mut self, offset_ms: u32, gap_duration_ms: u32);
+}
+
+impl SegmentSink for TranscribeState {
+    fn is_recording(&self) -> bool {
+        self.is_active
+    }
+
+    fn write_samples(&mut self, samples: &[f32]) {
+        self.process_samples(samples);
+    }
+
+    fn begin_segment(&mut self, lookback_samples: usize) {
+        self.on_speech_started(lookback_samples);
+    }
+
+    fn end_segment(&mut self) {
+        self.on_speech_ended();
+    }
+
+    fn word_break(&mut self, offset_ms: u32, gap_duration_ms: u32) {
+        self.on_word_break(offset_ms, gap_duration_ms);
+    }
+}
+
+/// Processing state for one capture session.
+pub struct AudioPipeline {
+    strategy: SegmentationStrategy,
+    sample_rate: u32,
+    agc: AutomaticGainControl,
+    speech_detector: SpeechDetector,
+    viz_processor: VisualizationProcessor,
+    /// Mono samples recorded since speech was last detected
+    silent_samples: u64,
+    /// Set once the toggle-to-talk silence timeout elapses
+    silence_timed_out: bool,
+}
+
+impl AudioPipeline {
+    /// Create a pipeline for audio at `sample_rate` using `strategy`.
+    pub fn new(sample_rate: u32, strategy: SegmentationStrategy) -> Self {
+        Self {
+            strategy,
+            sample_rate,
+            agc: AutomaticGainControl::new(
+                sample_rate,
+                crate::gain::current_gain_settings().target_level_db,
+            ),
+            speech_detector: SpeechDetector::new(sample_rate),
+            viz_processor: VisualizationProcessor::new(sample_rate, 256),
+            silent_samples: 0,
+            silence_timed_out: false,
+        }
+    }
+
+    /// Set the callback for visualization data.
+    pub fn set_visualization_callback(&mut self, callback: Arc<dyn VisualizationCallback>) {
+        self.viz_processor.set_callback(callback);
+    }
+
+    /// Get the segmentation strategy.
+    pub fn strategy(&self) -> SegmentationStrategy {
+        self.strategy
+    }
+
+    /// Whether the toggle-to-talk silence timeout has elapsed since the last
+    /// call. Reported once per timeout.
+    pub fn take_silence_timeout(&mut self) -> bool {
+        std::mem::take(&mut self.silence_timed_out)
+    }
+
+    /// Process one chunk of captured audio.
+    ///
+    /// Analysis runs without holding `sink`; the sink is only locked to record
+    /// the chunk, so hotkey handlers finalizing a segment are never blocked by
+    /// visualization work. Returns the speech event to broadcast, if any.
+    pub fn process<S: SegmentSink>(
+        &mut self,
+        data: &mut AudioData,
+        sink: &Mutex<S>,
+    ) -> Option<EventType> {
+        let channels = data.channels as usize;
+
+        // Apply automatic gain control before detection and recording
+        let gain_settings = crate::gain::current_gain_settings();
+        if gain_settings.enabled {
+            self.agc.set_target_level_db(gain_settings.target_level_db);
+            self.agc.process(&mut data.samples, channels);
+        } else {
+            self.agc.reset();
+        }
+
+        // Convert to mono for analysis
+        let mono_samples = convert_to_mono(&data.samples, channels);
+
+        // Speech detection runs in every mode so visualization has metrics
+        self.speech_detector.process(&mono_samples);
+        let mut speech_metrics = self.speech_detector.get_metrics();
+        let is_speaking = speech_metrics.is_speaking;
+        speech_metrics.applied_gain_db = self.agc.gain_db();
+        self.viz_processor.set_speech_metrics(speech_metrics);
+        self.viz_processor.process(&mono_samples);
+
+        let state_change = self.speech_detector.take_state_change();
+        let word_break = self.speech_detector.take_word_break_event();
+
+        let mut sink = sink.try_lock().ok()?;
+        if !sink.is_recording() {
+            return None;
+        }
+
+        sink.write_samples(&data.samples);
+
+        match self.strategy {
+            SegmentationStrategy::Vad => {
+                let event = match state_change {
+                    SpeechStateChange::Started { lookback_samples } => {
+                        sink.begin_segment(lookback_samples);
+                        Some(EventType::SpeechStarted)
+                    }
+                    SpeechStateChange::Ended { duration_ms } => {
+                        sink.end_segment();
+                        Some(EventType::SpeechEnded { duration_ms })
+                    }
+                    SpeechStateChange::None => None,
+                };
+
+                // Handle word breaks for timed segment submission
+                if let Some(WordBreakEvent {
+                    offset_ms,
+                    gap_duration_ms,
+                }) = word_break
+                {
+                    sink.word_break(offset_ms, gap_duration_ms);
+                }
+
+                event
+            }
+            // Boundaries come from the hotkey; the PTT controller reports them
+            SegmentationStrategy::PushToTalk => None,
+            SegmentationStrategy::ToggleToTalk { silence_timeout_ms } => {
+                if is_speaking {
+                    self.silent_samples = 0;
+                } else {
+                    self.silent_samples += mono_samples.len() as u64;
+                }
+                let timeout_samples = silence_timeout_ms as u64 * self.sample_rate as u64 / 1000;
+                if silence_timeout_ms > 0 && self.silent_samples >= timeout_samples {
+                    // The PTT controller ends the segment; start counting again
+                    // in case the recording outlives this report
+                    self.silent_samples = 0;
+                    self.silence_timed_out = true;
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Convert multi-channel audio to mono
+pub fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(channels)
+        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Broadcaster for visualization events
+pub struct VisualizationBroadcaster;
+
+impl VisualizationCallback for VisualizationBroadcaster {
+    fn on_visualization_data(&self, payload: VisualizationPayload) {
+        // Convert processor payload to common VisualizationData
+        let data = VisualizationData {
+            waveform: payload.waveform,
+            spectrogram: payload
+                .spectrogram
+                .map(|s| flowstt_common::SpectrogramColumn { colors: s.colors }),
+            speech_metrics: payload
+                .speech_metrics
+                .map(|m| flowstt_common::SpeechMetrics {
+                    amplitude_db: m.amplitude_db,
+                    zcr: m.zcr,
+                    centroid_hz: m.centroid_hz,
+                    is_speaking: m.is_speaking,
+                    voiced_onset_pending: m.is_voiced_pending,
+                    whisper_onset_pending: m.is_whisper_pending,
+                    is_transient: m.is_transient,
+                    is_lookback_speech: m.is_lookback_speech,
+                    is_word_break: m.is_word_break,
+                    applied_gain_db: m.applied_gain_db,
+                }),
+        };
+        broadcast_event(Response::Event {
+            event: EventType::VisualizationData(data),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::TranscriptionQueue;
+
+    const SAMPLE_RATE: u32 = 48000;
+    /// Backends deliver roughly 10ms of audio per chunk
+    const CHUNK_FRAMES: usize = 480;
+
+    #[derive(Debug, PartialEq)]
+    enum SinkOp {
+        Begin,
+        End,
+        WordBreak,
+    }
+
+    struct RecordingSink {
+        recording: bool,
+        written: usize,
+        ops: Vec<SinkOp>,
+    }
+
+    impl RecordingSink {
+        fn new(recording: bool) -> Mutex<Self> {
+            Mutex::new(Self {
+                recording,
+                written: 0,
+                ops: Vec::new(),
+            })
+        }
+    }
+
+    impl SegmentSink for RecordingSink {
+        fn is_recording(&self) -> bool {
+            self.recording
+        }
+
+        fn write_samples(&mut self, samples: &[f32]) {
+            self.written += samples.len();
+        }
+
+        fn begin_segment(&mut self, _lookback_samples: usize) {
+            self.ops.push(SinkOp::Begin);
+        }
+
+        fn end_segment(&mut self) {
+            self.ops.push(SinkOp::End);
+        }
+
+        fn word_break(&mut self, _offset_ms: u32, _gap_duration_ms: u32) {
+            self.ops.push(SinkOp::WordBreak);
+        }
+    }
+
+    fn silence(ms: usize) -> Vec<f32> {
+        vec![0.0; SAMPLE_RATE as usize * ms / 1000]
+    }
+
+    fn tone(ms: usize) -> Vec<f32> {
+        (0..SAMPLE_RATE as usize * ms / 1000)
+            .map(|i| {
+                0.3 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin()
+            })
+            .collect()
+    }
+
+    /// Feed mono audio through the pipeline as 10ms stereo chunks.
+    fn run<S: SegmentSink>(
+        pipeline: &mut AudioPipeline,
+        mono: &[f32],
+        sink: &Mutex<S>,
+    ) -> Vec<EventType> {
+        mono.chunks(CHUNK_FRAMES)
+            .filter_map(|chunk| {
+                let mut data = AudioData {
+                    samples: chunk.iter().flat_map(|&s| [s, s]).collect(),
+                    channels: 2,
+                    sample_rate: SAMPLE_RATE,
+                };
+                pipeline.process(&mut data, sink)
+            })
+            .collect()
+    }
+
+    fn utterance() -> Vec<f32> {
+        [silence(500), tone(1000), silence(1000)].concat()
+    }
+
+    #[test]
+    fn vad_strategy_segments_speech() {
+        let mut pipeline = AudioPipeline::new(SAMPLE_RATE, SegmentationStrategy::Vad);
+        let sink = RecordingSink::new(true);
+        let audio = utterance();
+
+        let events = run(&mut pipeline, &audio, &sink);
+
+        assert_eq!(events.len(), 2, "events: {:?}", events);
+        assert!(matches!(events[0], EventType::SpeechStarted));
+        match events[1] {
+            EventType::SpeechEnded { duration_ms } => {
+                assert!(
+                    (800..=1500).contains(&duration_ms),
+                    "duration_ms = {}",
+                    duration_ms
+                );
+            }
+            ref other => panic!("expected SpeechEnded, got {:?}", other),
+        }
+
+        let sink = sink.lock().unwrap();
+        assert_eq!(sink.ops, vec![SinkOp::Begin, SinkOp::End]);
+        assert_eq!(sink.written, audio.len() * 2);
+    }
+
+    #[test]
+    fn ptt_strategy_records_without_vad_segmentation() {
+        let mut pipeline = AudioPipeline::new(SAMPLE_RATE, SegmentationStrategy::PushToTalk);
+        let sink = RecordingSink::new(true);
+        let audio = utterance();
+
+        let events = run(&mut pipeline, &audio, &sink);
+
+        assert!(events.is_empty(), "events: {:?}", events);
+        let sink = sink.lock().unwrap();
+        assert!(sink.ops.is_empty(), "ops: {:?}", sink.ops);
+        assert_eq!(sink.written, audio.len() * 2);
+    }
+
+    #[test]
+    fn idle_sink_is_not_recorded() {
+        for strategy in [SegmentationStrategy::Vad, SegmentationStrategy::PushToTalk] {
+            let mut pipeline = AudioPipeline::new(SAMPLE_RATE, strategy);
+            let sink = RecordingSink::new(false);
+
+            let events = run(&mut pipeline, &utterance(), &sink);
+
+            assert!(events.is_empty(), "{:?}: events {:?}", strategy, events);
+            let sink = sink.lock().unwrap();
+            assert_eq!(sink.written, 0);
+            assert!(sink.ops.is_empty());
+        }
+    }
+
+    #[test]
+    fn toggle_strategy_times_out_after_silence_only() {
+        let strategy = SegmentationStrategy::ToggleToTalk {
+            silence_timeout_ms: 1500,
+        };
+        let mut pipeline = AudioPipeline::new(SAMPLE_RATE, strategy);
+        let sink = RecordingSink::new(true);
+
+        // Speech with short pauses keeps the recording going
+        let events = run(
+            &mut pipeline,
+            &[silence(1000), tone(1000), silence(1000), tone(1000)].concat(),
+            &sink,
+        );
+        assert!(events.is_empty(), "events: {:?}", events);
+        assert!(!pipeline.take_silence_timeout());
+
+        run(&mut pipeline, &silence(2500), &sink);
+        assert!(pipeline.take_silence_timeout());
+        assert!(!pipeline.take_silence_timeout());
+        assert!(sink.lock().unwrap().ops.is_empty());
+    }
+
+    #[test]
+    fn toggle_strategy_without_timeout_never_times_out() {
+        let strategy = SegmentationStrategy::ToggleToTalk {
+            silence_timeout_ms: 0,
+        };
+        let mut pipeline = AudioPipeline::new(SAMPLE_RATE, strategy);
+        let sink = RecordingSink::new(true);
+
+        run(&mut pipeline, &silence(5000), &sink);
+        assert!(!pipeline.take_silence_timeout());
+    }
+
+    #[test]
+    fn ptt_segment_duration_tracks_key_hold() {
+        let mut state = TranscribeState::new(Arc::new(TranscriptionQueue::new()));
+        state.init_for_capture(SAMPLE_RATE, 2);
+        state.set_ptt_mode(true);
+        state.activate();
+        state.on_speech_started(0);
+        let state = Mutex::new(state);
+
+        let mut pipeline = AudioPipeline::new(SAMPLE_RATE, SegmentationStrategy::PushToTalk);
+        run(&mut pipeline, &[tone(700), silence(800)].concat(), &state);
+
+        assert_eq!(state.lock().unwrap().segment_duration_ms(), 1500);
+    }
+}
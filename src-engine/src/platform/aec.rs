@@ -0,0 +1,711 @@
+//! Shared acoustic echo cancellation for all platform backends.
+//!
+//! Every backend mixes a capture stream (microphone) with a render stream
+//! (system audio). This module wraps the AEC3 pipeline so each backend's mixer
+//! only needs to hand over render samples and capture frames:
+//! - Runs AEC3 at a supported rate (16/32/48 kHz), converting frames to and
+//!   from the device rate when the device runs at something else (e.g. 44.1 kHz)
+//!   with streaming resamplers that keep their state across frames
+//! - Estimates the render-to-capture delay by correlating energy envelopes of
+//!   both streams, and re-seeds AEC3 with the estimate when it drifts
+//! - Tracks convergence (ERLE) and broadcasts `AecMetrics` events for the GUI
+
+use std::collections::VecDeque;
+
+use aec3::voip::VoipAec3;
+use flowstt_common::ipc::{EventType, Response};
+use flowstt_common::AecMetrics;
+
+use crate::ipc::broadcast_event;
+
+/// Sample rates AEC3 can run at natively
+const AEC_SUPPORTED_RATES: [u32; 3] = [16000, 32000, 48000];
+
+/// AEC3 processes audio in 10ms frames
+const AEC_FRAME_MS: u32 = 10;
+
+/// Resolution of the energy envelopes used for delay estimation
+const ENVELOPE_BLOCKS_PER_FRAME: usize = 5;
+
+/// Length of envelope history used for delay estimation (2 seconds)
+const DELAY_HISTORY_FRAMES: usize = 200;
+
+/// Largest render-to-capture delay the estimator searches for
+const MAX_DELAY_MS: u32 = 500;
+
+/// How often the delay estimate is recomputed (1 second)
+const DELAY_ESTIMATE_INTERVAL_FRAMES: usize = 100;
+
+/// Minimum normalized correlation for a delay estimate to be trusted
+const DELAY_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+/// Re-seed AEC3 when the trusted estimate moves by more than this
+const DELAY_REALIGN_THRESHOLD_MS: u32 = 40;
+
+/// Render frames quieter than this mean energy (~-60 dBFS) count as silence
+const RENDER_ACTIVE_ENERGY: f32 = 1e-6;
+
+/// Smoothing factor for ERLE/ERL tracking
+const METRIC_SMOOTHING: f32 = 0.05;
+
+/// ERLE above which the canceller is considered converged
+const CONVERGED_ERLE_DB: f32 = 6.0;
+
+/// Render-active frames required before convergence is reported (1 second)
+const CONVERGENCE_MIN_FRAMES: u32 = 100;
+
+/// Interval between `AecMetrics` events (500ms)
+const METRICS_INTERVAL_FRAMES: u32 = 50;
+
+/// Anti-aliasing cutoff when downsampling, as a fraction of the output rate
+const ANTI_ALIAS_CUTOFF: f32 = 0.45;
+
+/// Q factors of the two sections of a 4th-order Butterworth low-pass
+const BUTTERWORTH_Q: [f32; 2] = [0.541_196_1, 1.306_563];
+
+/// Number of interleaved samples in one 10ms frame at the given format.
+pub fn frame_size(sample_rate: u32, channels: u16) -> usize {
+    (sample_rate * AEC_FRAME_MS / 1000) as usize * channels as usize
+}
+
+/// Choose the rate AEC3 runs at for a device rate: the lowest supported rate
+/// at or above the device rate, capped at 48 kHz.
+fn processing_rate_for(sample_rate: u32) -> u32 {
+    AEC_SUPPORTED_RATES
+        .iter()
+        .copied()
+        .find(|&rate| rate >= sample_rate)
+        .unwrap_or(48000)
+}
+
+/// Second-order low-pass section (RBJ cookbook), transposed direct form II.
+#[derive(Debug, Clone)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    fn low_pass(sample_rate: u32, cutoff: f32, q: f32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * cutoff / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: (1.0 - cos) / 2.0 / a0,
+            b1: (1.0 - cos) / a0,
+            b2: (1.0 - cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+/// Streaming linear resampler for interleaved 10ms frames.
+///
+/// The last input sample of each frame is carried into the next one, so
+/// output samples stay evenly spaced across frame boundaries (at the cost of
+/// one input sample of latency). When downsampling, the input is low-pass
+/// filtered below the output Nyquist rate first.
+struct FrameResampler {
+    channels: usize,
+    out_frames: usize,
+    /// Last (filtered) input sample of the previous frame, per channel
+    last: Vec<f32>,
+    /// Anti-aliasing filter per channel; empty unless downsampling
+    anti_alias: Vec<[Biquad; 2]>,
+}
+
+impl FrameResampler {
+    fn new(in_rate: u32, out_rate: u32, channels: u16) -> Self {
+        let anti_alias = if out_rate < in_rate {
+            let cutoff = out_rate as f32 * ANTI_ALIAS_CUTOFF;
+            let section = |q| Biquad::low_pass(in_rate, cutoff, q);
+            vec![[section(BUTTERWORTH_Q[0]), section(BUTTERWORTH_Q[1])]; channels as usize]
+        } else {
+            Vec::new()
+        };
+        Self {
+            channels: channels as usize,
+            out_frames: (out_rate * AEC_FRAME_MS / 1000) as usize,
+            last: vec![0.0; channels as usize],
+            anti_alias,
+        }
+    }
+
+    /// Resample one frame to `out_frames` frames.
+    fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let channels = self.channels;
+        let in_frames = input.len() / channels;
+        if in_frames == self.out_frames || in_frames == 0 {
+            return input.to_vec();
+        }
+
+        let mut filtered = input.to_vec();
+        if !self.anti_alias.is_empty() {
+            for frame in filtered.chunks_exact_mut(channels) {
+                for (sample, [first, second]) in frame.iter_mut().zip(&mut self.anti_alias) {
+                    *sample = second.process(first.process(*sample));
+                }
+            }
+        }
+
+        // Position 0 is the carried-over sample, position n is input frame n-1
+        let ratio = in_frames as f64 / self.out_frames as f64;
+        let sample_at = |pos: usize, ch: usize| match pos {
+            0 => self.last[ch],
+            _ => filtered[(pos - 1) * channels + ch],
+        };
+        let mut output = Vec::with_capacity(self.out_frames * channels);
+        for i in 0..self.out_frames {
+            let src_pos = i as f64 * ratio;
+            let idx = src_pos.floor() as usize;
+            let frac = (src_pos - idx as f64) as f32;
+            for ch in 0..channels {
+                let a = sample_at(idx, ch);
+                let b = sample_at(idx + 1, ch);
+                output.push(a + (b - a) * frac);
+            }
+        }
+
+        self.last
+            .copy_from_slice(&filtered[(in_frames - 1) * channels..in_frames * channels]);
+        output
+    }
+}
+
+/// Whether a delay estimate is trusted and far enough from the delay AEC3 was
+/// built with to rebuild it
+fn should_realign(applied_delay_ms: u32, delay_ms: u32, confidence: f32) -> bool {
+    confidence >= DELAY_CONFIDENCE_THRESHOLD
+        && delay_ms.abs_diff(applied_delay_ms) > DELAY_REALIGN_THRESHOLD_MS
+}
+
+/// Mean energy of a block of samples
+fn mean_energy(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32
+}
+
+// ============================================================================
+// Delay Estimation
+// ============================================================================
+
+/// Estimates the render-to-capture delay by cross-correlating the short-term
+/// energy envelopes of both streams.
+struct DelayEstimator {
+    /// Render energy envelope (one value per block)
+    render_envelope: VecDeque<f32>,
+    /// Capture energy envelope (one value per block)
+    capture_envelope: VecDeque<f32>,
+    /// Maximum number of envelope blocks kept per stream
+    history_blocks: usize,
+    /// Maximum lag searched, in blocks
+    max_lag_blocks: usize,
+    /// Duration of one envelope block in milliseconds
+    block_ms: f32,
+    /// Capture frames since the last estimate
+    frames_since_estimate: usize,
+    /// Most recent estimate (delay in ms, confidence)
+    estimate: Option<(u32, f32)>,
+}
+
+impl DelayEstimator {
+    fn new() -> Self {
+        let block_ms = AEC_FRAME_MS as f32 / ENVELOPE_BLOCKS_PER_FRAME as f32;
+        Self {
+            render_envelope: VecDeque::new(),
+            capture_envelope: VecDeque::new(),
+            history_blocks: DELAY_HISTORY_FRAMES * ENVELOPE_BLOCKS_PER_FRAME,
+            max_lag_blocks: (MAX_DELAY_MS as f32 / block_ms) as usize,
+            block_ms,
+            frames_since_estimate: 0,
+            estimate: None,
+        }
+    }
+
+    /// Append the envelope of one frame to a history buffer
+    fn push_envelope(envelope: &mut VecDeque<f32>, frame: &[f32], history_blocks: usize) {
+        for i in 0..ENVELOPE_BLOCKS_PER_FRAME {
+            let start = i * frame.len() / ENVELOPE_BLOCKS_PER_FRAME;
+            let end = (i + 1) * frame.len() / ENVELOPE_BLOCKS_PER_FRAME;
+            envelope.push_back(mean_energy(&frame[start..end]).sqrt());
+        }
+        while envelope.len() > history_blocks {
+            envelope.pop_front();
+        }
+    }
+
+    fn push_render(&mut self, frame: &[f32]) {
+        Self::push_envelope(&mut self.render_envelope, frame, self.history_blocks);
+    }
+
+    /// Push a capture frame; returns a fresh estimate when one was computed.
+    fn push_capture(&mut self, frame: &[f32]) -> Option<(u32, f32)> {
+        Self::push_envelope(&mut self.capture_envelope, frame, self.history_blocks);
+
+        self.frames_since_estimate += 1;
+        if self.frames_since_estimate < DELAY_ESTIMATE_INTERVAL_FRAMES {
+            return None;
+        }
+        self.frames_since_estimate = 0;
+
+        let estimate = self.compute();
+        if estimate.is_some() {
+            self.estimate = estimate;
+        }
+        estimate
+    }
+
+    /// Find the lag (render leading capture) with the highest normalized correlation
+    fn compute(&self) -> Option<(u32, f32)> {
+        let n = self.render_envelope.len().min(self.capture_envelope.len());
+        if n <= self.max_lag_blocks * 2 {
+            return None;
+        }
+
+        // Align both histories on their most recent block
+        let render: Vec<f32> = self
+            .render_envelope
+            .iter()
+            .skip(self.render_envelope.len() - n)
+            .copied()
+            .collect();
+        let capture: Vec<f32> = self
+            .capture_envelope
+            .iter()
+            .skip(self.capture_envelope.len() - n)
+            .copied()
+            .collect();
+
+        let render_mean = render.iter().sum::<f32>() / n as f32;
+        let capture_mean = capture.iter().sum::<f32>() / n as f32;
+        let render: Vec<f32> = render.iter().map(|v| v - render_mean).collect();
+        let capture: Vec<f32> = capture.iter().map(|v| v - capture_mean).collect();
+
+        let render_var: f32 = render.iter().map(|v| v * v).sum();
+        let capture_var: f32 = capture.iter().map(|v| v * v).sum();
+        if render_var < f32::EPSILON || capture_var < f32::EPSILON {
+            // Nothing playing (or nothing captured): no information about delay
+            return None;
+        }
+        let norm = (render_var * capture_var).sqrt();
+
+        let mut best_lag = 0;
+        let mut best_corr = f32::MIN;
+        for lag in 0..=self.max_lag_blocks {
+            let corr: f32 = (lag..n).map(|t| capture[t] * render[t - lag]).sum::<f32>() / norm;
+            if corr > best_corr {
+                best_corr = corr;
+                best_lag = lag;
+            }
+        }
+
+        Some((
+            (best_lag as f32 * self.block_ms).round() as u32,
+            best_corr.clamp(0.0, 1.0),
+        ))
+    }
+}
+
+// ============================================================================
+// Echo Canceller
+// ============================================================================
+
+/// AEC3 pipeline with rate conversion, delay estimation and convergence tracking.
+pub struct EchoCanceller {
+    /// Backend name used in log messages
+    backend: &'static str,
+    /// Device sample rate
+    sample_rate: u32,
+    /// Rate AEC3 runs at
+    processing_rate: u32,
+    /// Channels per stream
+    channels: u16,
+    /// AEC3 pipeline
+    aec: VoipAec3,
+    /// Render samples waiting to be fed to AEC3 (device rate)
+    render_buffer: Vec<f32>,
+    /// Render frames from the device rate to the processing rate
+    render_resampler: FrameResampler,
+    /// Capture frames from the device rate to the processing rate
+    capture_resampler: FrameResampler,
+    /// Processed frames back to the device rate
+    output_resampler: FrameResampler,
+    /// Render-to-capture delay estimator
+    delay_estimator: DelayEstimator,
+    /// Delay hint AEC3 was last built with
+    applied_delay_ms: u32,
+    /// Energy of the most recent render frame
+    last_render_energy: f32,
+    /// Smoothed echo return loss enhancement (dB)
+    erle_db: f32,
+    /// Smoothed echo return loss (dB)
+    echo_return_loss_db: f32,
+    /// Consecutive frames processed while render was active
+    render_active_frames: u32,
+    /// Frames since the last metrics event
+    frames_since_metrics: u32,
+}
+
+impl EchoCanceller {
+    /// Create an echo canceller for streams at the given device format.
+    pub fn new(backend: &'static str, sample_rate: u32, channels: u16) -> Result<Self, String> {
+        if sample_rate < 100 || channels == 0 {
+            return Err(format!(
+                "Unsupported AEC format: {}Hz, {} channels",
+                sample_rate, channels
+            ));
+        }
+
+        let processing_rate = processing_rate_for(sample_rate);
+        let aec = Self::build_aec(processing_rate, channels, 0)?;
+
+        tracing::info!(
+            "{}: AEC3 initialized: device {}Hz, processing {}Hz, {} channels, {}ms frames",
+            backend,
+            sample_rate,
+            processing_rate,
+            channels,
+            AEC_FRAME_MS
+        );
+
+        Ok(Self {
+            backend,
+            sample_rate,
+            processing_rate,
+            channels,
+            aec,
+            render_buffer: Vec::new(),
+            render_resampler: FrameResampler::new(sample_rate, processing_rate, channels),
+            capture_resampler: FrameResampler::new(sample_rate, processing_rate, channels),
+            output_resampler: FrameResampler::new(processing_rate, sample_rate, channels),
+            delay_estimator: DelayEstimator::new(),
+            applied_delay_ms: 0,
+            last_render_energy: 0.0,
+            erle_db: 0.0,
+            echo_return_loss_db: 0.0,
+            render_active_frames: 0,
+            frames_since_metrics: 0,
+        })
+    }
+
+    fn build_aec(processing_rate: u32, channels: u16, delay_ms: u32) -> Result<VoipAec3, String> {
+        VoipAec3::builder(
+            processing_rate as usize,
+            channels as usize,
+            channels as usize,
+        )
+        .enable_high_pass(true)
+        .initial_delay_ms(delay_ms as i32)
+        .build()
+        .map_err(|e| format!("Failed to initialize AEC3: {:?}", e))
+    }
+
+    /// Feed render (system audio) samples. Complete frames are handed to AEC3
+    /// immediately, so the render path always runs ahead of the capture path.
+    pub fn handle_render(&mut self, samples: &[f32]) {
+        self.render_buffer.extend_from_slice(samples);
+
+        let frame_size = frame_size(self.sample_rate, self.channels);
+        while self.render_buffer.len() >= frame_size {
+            let render_frame: Vec<f32> = self.render_buffer.drain(0..frame_size).collect();
+            self.last_render_energy = mean_energy(&render_frame);
+            self.delay_estimator.push_render(&render_frame);
+
+            let aec_frame = self.render_resampler.process(&render_frame);
+            if let Err(e) = self.aec.handle_render_frame(&aec_frame) {
+                tracing::error!("{}: AEC3 handle_render_frame error: {:?}", self.backend, e);
+            }
+        }
+    }
+
+    /// Run one device-rate capture frame through AEC3.
+    ///
+    /// Returns the echo-cancelled frame, or the input unchanged if AEC3 fails.
+    pub fn process_capture_frame(&mut self, capture_frame: &[f32]) -> Vec<f32> {
+        if let Some((delay_ms, confidence)) = self.delay_estimator.push_capture(capture_frame) {
+            self.maybe_realign(delay_ms, confidence);
+        }
+
+        let aec_frame = self.capture_resampler.process(capture_frame);
+        let mut out = vec![0.0f32; aec_frame.len()];
+        let processed = match self.aec.process_capture_frame(&aec_frame, false, &mut out) {
+            Ok(_metrics) => self.output_resampler.process(&out),
+            Err(e) => {
+                tracing::error!(
+                    "{}: AEC3 process_capture_frame error: {:?}",
+                    self.backend,
+                    e
+                );
+                capture_frame.to_vec()
+            }
+        };
+
+        self.update_metrics(capture_frame, &processed);
+        processed
+    }
+
+    /// Rebuild AEC3 with a new delay hint when a trusted estimate has drifted
+    fn maybe_realign(&mut self, delay_ms: u32, confidence: f32) {
+        if !should_realign(self.applied_delay_ms, delay_ms, confidence) {
+            return;
+        }
+
+        match Self::build_aec(self.processing_rate, self.channels, delay_ms) {
+            Ok(aec) => {
+                tracing::info!(
+                    "{}: AEC3 realigned: delay {}ms -> {}ms (confidence {:.2})",
+                    self.backend,
+                    self.applied_delay_ms,
+                    delay_ms,
+                    confidence
+                );
+                self.aec = aec;
+                self.applied_delay_ms = delay_ms;
+                self.erle_db = 0.0;
+                self.render_active_frames = 0;
+            }
+            Err(e) => tracing::error!("{}: {}", self.backend, e),
+        }
+    }
+
+    /// Update ERLE/ERL tracking and periodically broadcast metrics
+    fn update_metrics(&mut self, capture_frame: &[f32], processed: &[f32]) {
+        let capture_energy = mean_energy(capture_frame);
+        let output_energy = mean_energy(processed);
+        let render_active = self.last_render_energy > RENDER_ACTIVE_ENERGY;
+
+        if render_active && capture_energy > f32::EPSILON {
+            let erle = 10.0 * (capture_energy / output_energy.max(1e-12)).log10();
+            let erl = 10.0 * (self.last_render_energy / capture_energy).log10();
+            self.erle_db += (erle - self.erle_db) * METRIC_SMOOTHING;
+            self.echo_return_loss_db += (erl - self.echo_return_loss_db) * METRIC_SMOOTHING;
+            self.render_active_frames = self.render_active_frames.saturating_add(1);
+        }
+
+        self.frames_since_metrics += 1;
+        if self.frames_since_metrics >= METRICS_INTERVAL_FRAMES {
+            self.frames_since_metrics = 0;
+            broadcast_event(Response::Event {
+                event: EventType::AecMetrics(self.metrics(render_active)),
+            });
+        }
+    }
+
+    /// Snapshot of the current convergence metrics
+    fn metrics(&self, render_active: bool) -> AecMetrics {
+        let (estimated_delay_ms, delay_confidence) = match self.delay_estimator.estimate {
+            Some((delay, confidence)) => (Some(delay), confidence),
+            None => (None, 0.0),
+        };
+
+        AecMetrics {
+            sample_rate: self.sample_rate,
+            processing_rate: self.processing_rate,
+            estimated_delay_ms,
+            delay_confidence,
+            erle_db: self.erle_db,
+            echo_return_loss_db: self.echo_return_loss_db,
+            render_active,
+            converged: self.render_active_frames >= CONVERGENCE_MIN_FRAMES
+                && self.erle_db >= CONVERGED_ERLE_DB,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic noise in [-1, 1]
+    struct Noise(u64);
+
+    impl Noise {
+        fn next(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % 20001) as f32 / 10000.0 - 1.0
+        }
+    }
+
+    /// Speech-like render signal: noise whose loudness changes every 2ms block
+    fn render_signal(samples: usize, seed: u64) -> Vec<f32> {
+        let mut noise = Noise(seed);
+        let mut loudness = 0.0;
+        (0..samples)
+            .map(|i| {
+                if i % 32 == 0 {
+                    loudness = noise.next().abs();
+                }
+                noise.next() * loudness
+            })
+            .collect()
+    }
+
+    /// Feed `render` and `capture` (16 kHz mono) frame by frame and return the
+    /// last estimate computed
+    fn estimate(render: &[f32], capture: &[f32]) -> Option<(u32, f32)> {
+        let mut estimator = DelayEstimator::new();
+        let mut estimate = None;
+        for (r, c) in render.chunks(160).zip(capture.chunks(160)) {
+            estimator.push_render(r);
+            if let Some(e) = estimator.push_capture(c) {
+                estimate = Some(e);
+            }
+        }
+        estimate
+    }
+
+    #[test]
+    fn delay_is_estimated_from_delayed_render() {
+        let render = render_signal(16000 * 3, 7);
+        for delay_ms in [0usize, 40, 120, 300] {
+            let delay = delay_ms * 16;
+            let capture: Vec<f32> = (0..render.len())
+                .map(|i| {
+                    if i >= delay {
+                        0.5 * render[i - delay]
+                    } else {
+                        0.0
+                    }
+                })
+                .collect();
+            let (estimated, confidence) = estimate(&render, &capture).unwrap();
+            assert!(
+                estimated.abs_diff(delay_ms as u32) <= 2,
+                "expected {}ms, got {}ms",
+                delay_ms,
+                estimated
+            );
+            assert!(
+                confidence >= DELAY_CONFIDENCE_THRESHOLD,
+                "confidence {}",
+                confidence
+            );
+        }
+    }
+
+    #[test]
+    fn unrelated_or_silent_render_is_not_trusted() {
+        let render = render_signal(16000 * 3, 7);
+        let capture = render_signal(16000 * 3, 99);
+        let (_, confidence) = estimate(&render, &capture).unwrap();
+        assert!(confidence < DELAY_CONFIDENCE_THRESHOLD, "{}", confidence);
+
+        let silence = vec![0.0; render.len()];
+        assert_eq!(estimate(&silence, &capture), None);
+    }
+
+    #[test]
+    fn realignment_is_gated() {
+        // Low confidence never realigns
+        assert!(!should_realign(0, 200, DELAY_CONFIDENCE_THRESHOLD - 0.01));
+        // Small drift is ignored
+        assert!(!should_realign(100, 100 + DELAY_REALIGN_THRESHOLD_MS, 0.9));
+        assert!(!should_realign(100, 100 - DELAY_REALIGN_THRESHOLD_MS, 0.9));
+        // Trusted, large drift in either direction realigns
+        assert!(should_realign(100, 101 + DELAY_REALIGN_THRESHOLD_MS, 0.9));
+        assert!(should_realign(100, 20, DELAY_CONFIDENCE_THRESHOLD));
+    }
+
+    #[test]
+    fn processing_rates() {
+        assert_eq!(processing_rate_for(16000), 16000);
+        assert_eq!(processing_rate_for(22050), 32000);
+        assert_eq!(processing_rate_for(44100), 48000);
+        assert_eq!(processing_rate_for(96000), 48000);
+        assert_eq!(frame_size(44100, 2), 882);
+    }
+
+    /// Stereo tone at `rate`, channels in opposite phase, starting at sample
+    /// position `start` (fractional)
+    fn tone(freq: f32, rate: u32, start: f64, frames: usize, step: f64) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let t = (start + i as f64 * step) / rate as f64;
+                let s = (2.0 * std::f64::consts::PI * freq as f64 * t).sin() as f32;
+                [s, -s]
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        mean_energy(samples).sqrt()
+    }
+
+    #[test]
+    fn frames_are_resampled_continuously() {
+        for (device_rate, processing_rate) in [(44100u32, 48000u32), (16000, 48000)] {
+            let in_frames = (device_rate / 100) as usize;
+            let out_frames = (processing_rate / 100) as usize;
+            let step = in_frames as f64 / out_frames as f64;
+            let mut resampler = FrameResampler::new(device_rate, processing_rate, 2);
+
+            for frame in 0..5 {
+                let start = (frame * in_frames) as f64;
+                let input = tone(200.0, device_rate, start, in_frames, 1.0);
+                let output = resampler.process(&input);
+                assert_eq!(output.len(), out_frames * 2);
+                if frame == 0 {
+                    continue;
+                }
+                // Evenly spaced across frame boundaries, one input sample late
+                let expected = tone(200.0, device_rate, start - 1.0, out_frames, step);
+                let max_error = output
+                    .iter()
+                    .zip(&expected)
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0f32, f32::max);
+                assert!(
+                    max_error < 2e-3,
+                    "{} Hz frame {} error {}",
+                    device_rate,
+                    frame,
+                    max_error
+                );
+            }
+        }
+
+        let frame = vec![0.25; 320];
+        assert_eq!(FrameResampler::new(16000, 16000, 2).process(&frame), frame);
+    }
+
+    #[test]
+    fn downsampling_is_filtered() {
+        let level = |freq: f32| {
+            let mut resampler = FrameResampler::new(48000, 16000, 2);
+            let mut output = Vec::new();
+            for frame in 0..20 {
+                let input = tone(freq, 48000, (frame * 480) as f64, 480, 1.0);
+                let frame_out = resampler.process(&input);
+                assert_eq!(frame_out.len(), 320);
+                if frame >= 5 {
+                    output.extend(frame_out);
+                }
+            }
+            rms(&output) / std::f32::consts::FRAC_1_SQRT_2
+        };
+
+        // Speech band passes, content above the new Nyquist rate does not alias
+        assert!((level(1000.0) - 1.0).abs() < 0.05, "{}", level(1000.0));
+        assert!(level(12000.0) < 0.2, "{}", level(12000.0));
+    }
+}
@@ -1,7 +1,39 @@
 //! Platform-agnostic audio backend trait.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
 use flowstt_common::{AudioDevice, RecordingMode};
 
+/// Capacity (in chunks) of the channel between a backend's mixer and the
+/// audio loop. When the loop falls behind, new chunks are dropped instead of
+/// queueing without bound.
+const AUDIO_CHANNEL_CAPACITY: usize = 64;
+
+/// Chunks dropped because the audio loop was not keeping up
+static DROPPED_CHUNKS: AtomicU64 = AtomicU64::new(0);
+
+/// Create the bounded channel a backend delivers audio through.
+pub fn audio_channel<T>() -> (mpsc::SyncSender<T>, mpsc::Receiver<T>) {
+    mpsc::sync_channel(AUDIO_CHANNEL_CAPACITY)
+}
+
+/// Deliver a chunk to the audio loop without ever blocking the capture thread.
+/// If the channel is full the chunk is dropped and the overflow is logged.
+pub fn send_audio<T>(tx: &mpsc::SyncSender<T>, chunk: T, backend: &str) {
+    if let Err(mpsc::TrySendError::Full(_)) = tx.try_send(chunk) {
+        let dropped = DROPPED_CHUNKS.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped == 1 || dropped.is_multiple_of(100) {
+            tracing::warn!(
+                "{}: audio loop is falling behind, {} chunk(s) dropped so far",
+                backend,
+                dropped
+            );
+        }
+    }
+}
+
 /// Audio data received from capture
 pub struct AudioData {
     /// Interleaved audio samples
@@ -34,8 +66,8 @@ pub trait AudioBackend: Send + Sync {
     /// Stop audio capture.
     fn stop_capture(&self) -> Result<(), String>;
 
-    /// Try to receive audio data (non-blocking).
-    fn try_recv(&self) -> Option<AudioData>;
+    /// Receive audio data, blocking until a chunk arrives or `timeout` elapses.
+    fn recv_timeout(&self, timeout: Duration) -> Option<AudioData>;
 
     /// Set whether AEC is enabled.
     fn set_aec_enabled(&self, enabled: bool);
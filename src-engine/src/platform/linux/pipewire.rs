@@ -24,9 +24,11 @@ use std::rc::Rc;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
-use crate::platform::backend::{AudioBackend, AudioData};
-use aec3::voip::VoipAec3;
+use crate::platform::aec::EchoCanceller;
+use crate::platform::backend::{audio_channel, send_audio, AudioBackend, AudioData};
+use crate::platform::mixing;
 use flowstt_common::{AudioDevice, AudioSourceType, RecordingMode};
 
 /// Commands sent to the PipeWire thread
@@ -74,7 +76,7 @@ impl PipeWireBackend {
         recording_mode: Arc<Mutex<RecordingMode>>,
     ) -> Result<Self, String> {
         let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (audio_tx, audio_rx) = audio_channel();
         let input_devices = Arc::new(Mutex::new(Vec::new()));
         let system_devices = Arc::new(Mutex::new(Vec::new()));
         let sample_rate = Arc::new(Mutex::new(48000u32));
@@ -151,12 +153,12 @@ impl AudioBackend for PipeWireBackend {
             .map_err(|e| format!("Failed to send stop command: {}", e))
     }
 
-    fn try_recv(&self) -> Option<AudioData> {
+    fn recv_timeout(&self, timeout: Duration) -> Option<AudioData> {
         let sample_rate = *self.sample_rate.lock().unwrap();
         self.audio_rx
             .lock()
             .unwrap()
-            .try_recv()
+            .recv_timeout(timeout)
             .ok()
             .map(|pw_samples| AudioData {
                 samples: pw_samples.samples,
@@ -183,43 +185,40 @@ pub fn create_backend(
     Ok(Box::new(backend))
 }
 
-/// AEC3 frame size: 10ms at 48kHz = 480 samples per channel
-const AEC_FRAME_SAMPLES: usize = 480;
-
 /// Mixer state for combining audio from multiple streams
 /// Uses separate render-first AEC processing pattern for proper echo cancellation.
 struct AudioMixer {
     /// Buffer for capture samples (microphone/input)
     capture_buffer: Vec<f32>,
-    /// Buffer for render samples (system audio/reference) - fed to AEC
-    render_buffer: Vec<f32>,
     /// Buffer for render samples to mix with processed capture (for Mixed mode)
     render_mix_buffer: Vec<f32>,
     /// Number of active streams (1 or 2)
     num_streams: usize,
+    /// Sample rate of both streams
+    sample_rate: u32,
     /// Channels per stream
     channels: u16,
     /// Output sender
-    output_tx: mpsc::Sender<PwAudioSamples>,
+    output_tx: mpsc::SyncSender<PwAudioSamples>,
     /// Flag to enable/disable AEC (shared with main thread)
     aec_enabled: Arc<Mutex<bool>>,
-    /// Recording mode - Mixed or EchoCancel (shared with main thread)
+    /// Recording mode - Mixed, EchoCancel or SpeakerSeparated (shared with main thread)
     recording_mode: Arc<Mutex<RecordingMode>>,
-    /// AEC3 pipeline (created when in mixed mode with 2 streams)
-    aec: Option<VoipAec3>,
+    /// Echo canceller (created when we have 2 streams)
+    aec: Option<EchoCanceller>,
 }
 
 impl AudioMixer {
     fn new(
-        output_tx: mpsc::Sender<PwAudioSamples>,
+        output_tx: mpsc::SyncSender<PwAudioSamples>,
         aec_enabled: Arc<Mutex<bool>>,
         recording_mode: Arc<Mutex<RecordingMode>>,
     ) -> Self {
         Self {
             capture_buffer: Vec::new(),
-            render_buffer: Vec::new(),
             render_mix_buffer: Vec::new(),
             num_streams: 0,
+            sample_rate: 48000,
             channels: 2,
             output_tx,
             aec_enabled,
@@ -231,37 +230,38 @@ impl AudioMixer {
     fn set_num_streams(&mut self, num: usize) {
         self.num_streams = num;
         self.capture_buffer.clear();
-        self.render_buffer.clear();
         self.render_mix_buffer.clear();
+        self.configure_aec();
+    }
 
-        // Create AEC3 pipeline when we have 2 streams (mic + system audio)
-        if num == 2 {
-            // Initial delay hint: start with 0ms and let AEC adapt
-            match VoipAec3::builder(48000, self.channels as usize, self.channels as usize)
-                .enable_high_pass(true)
-                .initial_delay_ms(0)
-                .build()
-            {
-                Ok(aec) => {
-                    tracing::info!(
-                        "PipeWire: AEC3 initialized: 48kHz, {} channels, {}ms frames",
-                        self.channels,
-                        AEC_FRAME_SAMPLES * 1000 / 48000
-                    );
-                    self.aec = Some(aec);
-                }
-                Err(e) => {
-                    tracing::error!("PipeWire: Failed to initialize AEC3: {:?}", e);
-                    self.aec = None;
-                }
-            }
-        } else {
+    /// (Re)create the echo canceller for the current stream count and format
+    fn configure_aec(&mut self) {
+        // AEC needs 2 streams (mic + system audio)
+        if self.num_streams != 2 {
             self.aec = None;
+            return;
+        }
+
+        match EchoCanceller::new("PipeWire", self.sample_rate, self.channels) {
+            Ok(aec) => self.aec = Some(aec),
+            Err(e) => {
+                tracing::error!("PipeWire: {}", e);
+                self.aec = None;
+            }
         }
     }
 
-    fn set_channels(&mut self, channels: u16) {
+    /// Update the stream format reported by PipeWire, rebuilding the echo
+    /// canceller if it changed (the graph may run at e.g. 44.1 kHz)
+    fn set_format(&mut self, sample_rate: u32, channels: u16) {
+        if sample_rate == self.sample_rate && channels == self.channels {
+            return;
+        }
+        self.sample_rate = sample_rate;
         self.channels = channels;
+        self.capture_buffer.clear();
+        self.render_mix_buffer.clear();
+        self.configure_aec();
     }
 
     /// Add samples from a stream, routing based on source type
@@ -270,31 +270,33 @@ impl AudioMixer {
     fn push_samples(&mut self, samples: &[f32], is_sink_capture: bool) {
         if self.num_streams == 1 {
             // Only one stream - send directly (no AEC possible)
-            let _ = self.output_tx.send(PwAudioSamples {
-                samples: samples.to_vec(),
-                channels: self.channels,
-            });
+            let recording_mode = *self.recording_mode.lock().unwrap();
+            let samples = if recording_mode == RecordingMode::SpeakerSeparated {
+                mixing::separate_single_stream(samples, is_sink_capture)
+            } else {
+                samples.to_vec()
+            };
+            send_audio(
+                &self.output_tx,
+                PwAudioSamples {
+                    samples,
+                    channels: self.channels,
+                },
+                "PipeWire",
+            );
             return;
         }
 
         // Two streams mode
-        let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
-
         if is_sink_capture {
             // System audio (render) - feed to AEC immediately in frame-sized chunks
             // This is critical: AEC needs to see render BEFORE corresponding capture
-            self.render_buffer.extend_from_slice(samples);
-            // Also keep a copy for mixing in Mixed mode
+            // Keep a copy for mixing in Mixed mode
             self.render_mix_buffer.extend_from_slice(samples);
 
             // Feed render frames to AEC immediately
             if let Some(ref mut aec) = self.aec {
-                while self.render_buffer.len() >= frame_size {
-                    let render_frame: Vec<f32> = self.render_buffer.drain(0..frame_size).collect();
-                    if let Err(e) = aec.handle_render_frame(&render_frame) {
-                        tracing::error!("PipeWire: AEC3 handle_render_frame error: {:?}", e);
-                    }
-                }
+                aec.handle_render(samples);
             }
         } else {
             // Microphone (capture) - buffer and process
@@ -308,7 +310,7 @@ impl AudioMixer {
         let aec_enabled = *self.aec_enabled.lock().unwrap();
         let recording_mode = *self.recording_mode.lock().unwrap();
 
-        let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
+        let frame_size = crate::platform::aec::frame_size(self.sample_rate, self.channels);
 
         // Process capture frames when we have enough data from both sources
         while self.capture_buffer.len() >= frame_size && self.render_mix_buffer.len() >= frame_size
@@ -319,15 +321,7 @@ impl AudioMixer {
             // Apply AEC if enabled and we have an AEC instance
             let processed_capture = if aec_enabled {
                 if let Some(ref mut aec) = self.aec {
-                    let mut out = vec![0.0f32; capture_frame.len()];
-
-                    match aec.process_capture_frame(&capture_frame, false, &mut out) {
-                        Ok(_metrics) => out,
-                        Err(e) => {
-                            tracing::error!("PipeWire: AEC3 process_capture_frame error: {:?}", e);
-                            capture_frame
-                        }
-                    }
+                    aec.process_capture_frame(&capture_frame)
                 } else {
                     capture_frame
                 }
@@ -338,17 +332,19 @@ impl AudioMixer {
             // Generate output based on recording mode
             let output: Vec<f32> = match recording_mode {
                 RecordingMode::Mixed => {
-                    // Mix processed capture with system audio (0.5 gain each to prevent clipping)
-                    processed_capture
-                        .iter()
-                        .zip(render_frame.iter())
-                        .map(|(&s1, &s2)| (s1 + s2) * 0.5)
-                        .collect()
+                    // Mix processed capture with system audio (per-source gains
+                    // were applied upstream; each source contributes half)
+                    mixing::mix_samples(&processed_capture, &render_frame)
                 }
                 RecordingMode::EchoCancel => {
                     // Output only the processed capture signal - no mixing
                     processed_capture
                 }
+                RecordingMode::SpeakerSeparated => {
+                    // Microphone (echo-cancelled when AEC is enabled) and
+                    // system audio on separate channels
+                    mixing::separate_samples(&processed_capture, &render_frame)
+                }
             };
 
             // Debug logging (periodic)
@@ -376,10 +372,14 @@ impl AudioMixer {
             }
 
             // Send output
-            let _ = self.output_tx.send(PwAudioSamples {
-                samples: output,
-                channels: self.channels,
-            });
+            send_audio(
+                &self.output_tx,
+                PwAudioSamples {
+                    samples: output,
+                    channels: self.channels,
+                },
+                "PipeWire",
+            );
         }
     }
 }
@@ -403,7 +403,7 @@ struct PwThreadState {
 /// Run the PipeWire main loop thread
 fn run_pipewire_thread(
     cmd_rx: mpsc::Receiver<PwCommand>,
-    audio_tx: mpsc::Sender<PwAudioSamples>,
+    audio_tx: mpsc::SyncSender<PwAudioSamples>,
     input_devices: Arc<Mutex<Vec<AudioDevice>>>,
     system_devices: Arc<Mutex<Vec<AudioDevice>>>,
     sample_rate: Arc<Mutex<u32>>,
@@ -660,6 +660,7 @@ fn create_capture_stream(
     // Track format info from param_changed
     let format_info: Rc<RefCell<AudioInfoRaw>> = Rc::new(RefCell::new(AudioInfoRaw::default()));
     let format_info_for_param = Rc::clone(&format_info);
+    let format_info_for_process = Rc::clone(&format_info);
     let sample_rate_for_param = Arc::clone(&sample_rate);
     let mixer_for_param = Rc::clone(&mixer);
     let mixer_for_process = mixer;
@@ -692,7 +693,10 @@ fn create_capture_stream(
                         channels
                     );
                     *sample_rate_for_param.lock().unwrap() = rate;
-                    mixer_for_param.borrow_mut().set_channels(channels as u16);
+                    // Streams are converted to the stereo mixer layout in process()
+                    mixer_for_param
+                        .borrow_mut()
+                        .set_format(rate, mixing::MIXER_CHANNELS as u16);
                 }
             }
         })
@@ -722,6 +726,14 @@ fn create_capture_stream(
                         .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
                         .collect();
 
+                    // Apply channel selection, gain and mute for this source
+                    let in_channels = format_info_for_process.borrow().channels() as usize;
+                    if in_channels == 0 {
+                        return;
+                    }
+                    let settings = mixing::source_settings(stream_index);
+                    let samples = mixing::apply_source_settings(&samples, in_channels, &settings);
+
                     if !samples.is_empty() {
                         // Route to appropriate mixer buffer based on source type:
                         // - Sink capture (system audio) goes to reference buffer for AEC
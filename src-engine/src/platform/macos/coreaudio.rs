@@ -6,9 +6,10 @@
 //! - Multi-source capture with mixing
 //! - Echo cancellation using AEC3
 
-use crate::platform::backend::{AudioBackend, AudioData};
+use crate::platform::aec::EchoCanceller;
+use crate::platform::backend::{audio_channel, send_audio, AudioBackend, AudioData};
+use crate::platform::mixing;
 use crate::platform::macos::screencapturekit::{self, SCKAudioCapture};
-use aec3::voip::VoipAec3;
 use coreaudio::audio_unit::macos_helpers::{
     get_audio_device_ids, get_audio_device_supports_scope, get_default_device_id, get_device_name,
 };
@@ -25,13 +26,11 @@ use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 /// Target sample rate for output (matches Linux/Windows backends)
 const TARGET_SAMPLE_RATE: f64 = 48000.0;
 
-/// AEC3 frame size: 10ms at 48kHz = 480 samples per channel
-const AEC_FRAME_SAMPLES: usize = 480;
-
 /// System audio device ID prefix
 const SYSTEM_AUDIO_PREFIX: &str = "sck:";
 
@@ -120,9 +119,10 @@ extern "C" fn input_callback_proc(
         return status;
     }
 
-    // Process the audio data
+    // Process the audio data into interleaved samples with all device channels
     let num_frames = in_number_frames as usize;
-    let mut samples = Vec::with_capacity(num_frames * 2);
+    let num_channels = context.num_channels.max(1);
+    let mut samples = Vec::with_capacity(num_frames * num_channels);
 
     unsafe {
         let buffer_list_ref = &*buffer_list;
@@ -136,36 +136,27 @@ extern "C" fn input_callback_proc(
                 channel_ptrs.push(buffer.mData as *const f32);
             }
 
-            // Interleave to stereo
+            // Interleave channel buffers
             for i in 0..num_frames {
-                let left = *channel_ptrs[0].add(i);
-                let right = if num_buffers > 1 {
-                    *channel_ptrs[1].add(i)
-                } else {
-                    left
-                };
-                samples.push(left);
-                samples.push(right);
+                for ptr in &channel_ptrs {
+                    samples.push(*ptr.add(i));
+                }
             }
         } else {
             let buffer = &buffer_list_ref.mBuffers[0];
             let data_ptr = buffer.mData as *const f32;
 
-            if context.num_channels == 1 {
-                for i in 0..num_frames {
-                    let sample = *data_ptr.add(i);
-                    samples.push(sample);
-                    samples.push(sample);
-                }
-            } else {
-                let total_samples = num_frames * context.num_channels;
-                for i in 0..total_samples {
-                    samples.push(*data_ptr.add(i));
-                }
+            let total_samples = num_frames * num_channels;
+            for i in 0..total_samples {
+                samples.push(*data_ptr.add(i));
             }
         }
     }
 
+    // Apply channel selection, gain and mute, converting to stereo
+    let settings = mixing::source_settings(context.stream_index);
+    let samples = mixing::apply_source_settings(&samples, num_channels, &settings);
+
     // Resample if needed
     let samples = if let Some(ref resampler) = context.resampler {
         resampler.lock().unwrap().process(&samples, 2)
@@ -214,35 +205,35 @@ enum CaptureCommand {
 struct AudioMixer {
     /// Buffer for capture samples (microphone/input)
     capture_buffer: Vec<f32>,
-    /// Buffer for render samples (system audio/reference) - fed to AEC and kept for mixing
-    render_buffer: Vec<f32>,
     /// Buffer for render samples to mix with processed capture (for Mixed mode)
     render_mix_buffer: Vec<f32>,
     /// Number of active streams (1 or 2)
     num_streams: usize,
+    /// Sample rate of both streams
+    sample_rate: u32,
     /// Channels per stream
     channels: u16,
     /// Output sender
-    output_tx: mpsc::Sender<CoreAudioSamples>,
+    output_tx: mpsc::SyncSender<CoreAudioSamples>,
     /// Flag to enable/disable AEC (shared with main thread)
     aec_enabled: Arc<Mutex<bool>>,
-    /// Recording mode - Mixed or EchoCancel (shared with main thread)
+    /// Recording mode - Mixed, EchoCancel or SpeakerSeparated (shared with main thread)
     recording_mode: Arc<Mutex<RecordingMode>>,
-    /// AEC3 pipeline (created when in mixed mode with 2 streams)
-    aec: Option<VoipAec3>,
+    /// Echo canceller (created when we have 2 streams)
+    aec: Option<EchoCanceller>,
 }
 
 impl AudioMixer {
     fn new(
-        output_tx: mpsc::Sender<CoreAudioSamples>,
+        output_tx: mpsc::SyncSender<CoreAudioSamples>,
         aec_enabled: Arc<Mutex<bool>>,
         recording_mode: Arc<Mutex<RecordingMode>>,
     ) -> Self {
         Self {
             capture_buffer: Vec::new(),
-            render_buffer: Vec::new(),
             render_mix_buffer: Vec::new(),
             num_streams: 0,
+            sample_rate: TARGET_SAMPLE_RATE as u32,
             channels: 2,
             output_tx,
             aec_enabled,
@@ -254,31 +245,24 @@ impl AudioMixer {
     fn set_num_streams(&mut self, num: usize) {
         self.num_streams = num;
         self.capture_buffer.clear();
-        self.render_buffer.clear();
         self.render_mix_buffer.clear();
+        self.configure_aec();
+    }
 
-        // Create AEC3 pipeline when we have 2 streams (mic + system audio)
-        if num == 2 {
-            match VoipAec3::builder(48000, self.channels as usize, self.channels as usize)
-                .enable_high_pass(true)
-                .initial_delay_ms(0)
-                .build()
-            {
-                Ok(aec) => {
-                    tracing::info!(
-                        "CoreAudio: AEC3 initialized: 48kHz, {} channels, {}ms frames",
-                        self.channels,
-                        AEC_FRAME_SAMPLES * 1000 / 48000
-                    );
-                    self.aec = Some(aec);
-                }
-                Err(e) => {
-                    tracing::error!("CoreAudio: Failed to initialize AEC3: {:?}", e);
-                    self.aec = None;
-                }
-            }
-        } else {
+    /// (Re)create the echo canceller for the current stream count and format
+    fn configure_aec(&mut self) {
+        // AEC needs 2 streams (mic + system audio)
+        if self.num_streams != 2 {
             self.aec = None;
+            return;
+        }
+
+        match EchoCanceller::new("CoreAudio", self.sample_rate, self.channels) {
+            Ok(aec) => self.aec = Some(aec),
+            Err(e) => {
+                tracing::error!("CoreAudio: {}", e);
+                self.aec = None;
+            }
         }
     }
 
@@ -286,29 +270,32 @@ impl AudioMixer {
     fn push_samples(&mut self, samples: &[f32], is_loopback: bool) {
         if self.num_streams == 1 {
             // Single stream - send directly (no AEC possible)
-            let _ = self.output_tx.send(CoreAudioSamples {
-                samples: samples.to_vec(),
-                channels: self.channels,
-            });
+            let recording_mode = *self.recording_mode.lock().unwrap();
+            let samples = if recording_mode == RecordingMode::SpeakerSeparated {
+                mixing::separate_single_stream(samples, is_loopback)
+            } else {
+                samples.to_vec()
+            };
+            send_audio(
+                &self.output_tx,
+                CoreAudioSamples {
+                    samples,
+                    channels: self.channels,
+                },
+                "CoreAudio",
+            );
             return;
         }
 
         // Two streams mode
-        let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
-
         if is_loopback {
             // System audio (render) - feed to AEC immediately
-            self.render_buffer.extend_from_slice(samples);
+            // Keep a copy for mixing in Mixed mode
             self.render_mix_buffer.extend_from_slice(samples);
 
             // Feed render frames to AEC immediately
             if let Some(ref mut aec) = self.aec {
-                while self.render_buffer.len() >= frame_size {
-                    let render_frame: Vec<f32> = self.render_buffer.drain(0..frame_size).collect();
-                    if let Err(e) = aec.handle_render_frame(&render_frame) {
-                        tracing::error!("CoreAudio: AEC3 handle_render_frame error: {:?}", e);
-                    }
-                }
+                aec.handle_render(samples);
             }
         } else {
             // Microphone (capture) - buffer and process
@@ -322,7 +309,7 @@ impl AudioMixer {
         let aec_enabled = *self.aec_enabled.lock().unwrap();
         let recording_mode = *self.recording_mode.lock().unwrap();
 
-        let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
+        let frame_size = crate::platform::aec::frame_size(self.sample_rate, self.channels);
 
         // Process capture frames when we have enough data from both sources
         while self.capture_buffer.len() >= frame_size && self.render_mix_buffer.len() >= frame_size
@@ -333,15 +320,7 @@ impl AudioMixer {
             // Apply AEC if enabled
             let processed_capture = if aec_enabled {
                 if let Some(ref mut aec) = self.aec {
-                    let mut out = vec![0.0f32; capture_frame.len()];
-
-                    match aec.process_capture_frame(&capture_frame, false, &mut out) {
-                        Ok(_metrics) => out,
-                        Err(e) => {
-                            tracing::error!("CoreAudio: AEC3 process_capture_frame error: {:?}", e);
-                            capture_frame
-                        }
-                    }
+                    aec.process_capture_frame(&capture_frame)
                 } else {
                     capture_frame
                 }
@@ -352,26 +331,19 @@ impl AudioMixer {
             // Generate output based on recording mode
             let output: Vec<f32> = match recording_mode {
                 RecordingMode::Mixed => {
-                    // Mix processed capture with system audio using soft clipping
-                    processed_capture
-                        .iter()
-                        .zip(render_frame.iter())
-                        .map(|(&s1, &s2)| {
-                            let sum = s1 + s2;
-                            if sum > 1.0 {
-                                1.0 - (-2.0 * (sum - 1.0)).exp() * 0.5
-                            } else if sum < -1.0 {
-                                -1.0 + (-2.0 * (-sum - 1.0)).exp() * 0.5
-                            } else {
-                                sum
-                            }
-                        })
-                        .collect()
+                    // Mix processed capture with system audio (per-source gains
+                    // were applied upstream; each source contributes half)
+                    mixing::mix_samples(&processed_capture, &render_frame)
                 }
                 RecordingMode::EchoCancel => {
                     // Output only the processed capture signal
                     processed_capture
                 }
+                RecordingMode::SpeakerSeparated => {
+                    // Microphone (echo-cancelled when AEC is enabled) and
+                    // system audio on separate channels
+                    mixing::separate_samples(&processed_capture, &render_frame)
+                }
             };
 
             // Debug logging (periodic)
@@ -399,10 +371,14 @@ impl AudioMixer {
             }
 
             // Send output
-            let _ = self.output_tx.send(CoreAudioSamples {
-                samples: output,
-                channels: self.channels,
-            });
+            send_audio(
+                &self.output_tx,
+                CoreAudioSamples {
+                    samples: output,
+                    channels: self.channels,
+                },
+                "CoreAudio",
+            );
         }
     }
 }
@@ -417,6 +393,8 @@ struct MultiCaptureManager {
     system_stop_flag: Arc<AtomicBool>,
     /// System audio polling thread
     system_thread: Option<JoinHandle<()>>,
+    /// Stream index (1 or 2) of the system audio source, for per-source settings
+    system_stream_index: usize,
 }
 
 impl MultiCaptureManager {
@@ -431,6 +409,7 @@ impl MultiCaptureManager {
         let mut system_capture: Option<SCKAudioCapture> = None;
         let system_thread = None;
         let system_stop_flag = Arc::new(AtomicBool::new(false));
+        let mut system_stream_index = 1;
 
         // Start stream 1
         if let Some(device_id) = source1_id {
@@ -461,6 +440,7 @@ impl MultiCaptureManager {
                     let capture = SCKAudioCapture::new()?;
                     capture.start()?;
                     system_capture = Some(capture);
+                    system_stream_index = 2;
                 }
             } else {
                 // Input device via CoreAudio
@@ -483,6 +463,7 @@ impl MultiCaptureManager {
             system_capture,
             system_stop_flag,
             system_thread,
+            system_stream_index,
         })
     }
 
@@ -490,7 +471,13 @@ impl MultiCaptureManager {
     fn poll_system_audio(&self) -> Option<Vec<f32>> {
         if let Some(ref capture) = self.system_capture {
             if let Some(samples) = capture.try_recv() {
-                return Some(samples.samples);
+                // Apply channel selection, gain and mute for the system source
+                let settings = mixing::source_settings(self.system_stream_index);
+                return Some(mixing::apply_source_settings(
+                    &samples.samples,
+                    samples.channels as usize,
+                    &settings,
+                ));
             }
         }
         None
@@ -671,7 +658,7 @@ impl CoreAudioBackend {
         recording_mode: Arc<Mutex<RecordingMode>>,
     ) -> Result<Self, String> {
         let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (audio_tx, audio_rx) = audio_channel();
         let input_devices = Arc::new(Mutex::new(Vec::new()));
         let system_devices = Arc::new(Mutex::new(Vec::new()));
         let is_capturing = Arc::new(AtomicBool::new(false));
@@ -765,11 +752,11 @@ impl AudioBackend for CoreAudioBackend {
         Ok(())
     }
 
-    fn try_recv(&self) -> Option<AudioData> {
+    fn recv_timeout(&self, timeout: Duration) -> Option<AudioData> {
         self.audio_rx
             .lock()
             .unwrap()
-            .try_recv()
+            .recv_timeout(timeout)
             .ok()
             .map(|samples| AudioData {
                 samples: samples.samples,
@@ -790,7 +777,7 @@ impl AudioBackend for CoreAudioBackend {
 /// Run the capture thread
 fn run_capture_thread(
     cmd_rx: mpsc::Receiver<CaptureCommand>,
-    audio_tx: mpsc::Sender<CoreAudioSamples>,
+    audio_tx: mpsc::SyncSender<CoreAudioSamples>,
     system_devices: Arc<Mutex<Vec<AudioDevice>>>,
     is_capturing: Arc<AtomicBool>,
     aec_enabled: Arc<Mutex<bool>>,
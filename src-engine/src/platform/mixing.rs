@@ -0,0 +1,246 @@
+//! Per-source gain, mute and channel selection shared by all platform backends.
+//!
+//! Each backend converts a source's native interleaved samples into the
+//! stereo mixer layout with `apply_source_settings`, and combines the two
+//! streams with `mix_samples` (or `separate_samples` in speaker-separated
+//! mode), so every platform treats sources the same way.
+
+use std::sync::{Arc, Mutex};
+
+use flowstt_common::SourceSettings;
+
+/// Channel layout used by all backend mixers
+pub const MIXER_CHANNELS: usize = 2;
+
+/// Output channel carrying the microphone in speaker-separated mode
+pub const SEPARATED_ME_CHANNEL: usize = 0;
+
+/// Output channel carrying system audio in speaker-separated mode
+pub const SEPARATED_REMOTE_CHANNEL: usize = 1;
+
+/// Settings for source1 and source2
+static SOURCE_SETTINGS: std::sync::OnceLock<Arc<Mutex<[SourceSettings; 2]>>> =
+    std::sync::OnceLock::new();
+
+fn get_source_settings() -> Arc<Mutex<[SourceSettings; 2]>> {
+    SOURCE_SETTINGS
+        .get_or_init(|| Arc::new(Mutex::new(Default::default())))
+        .clone()
+}
+
+/// Replace the settings for both sources.
+pub fn set_source_settings(source1: SourceSettings, source2: SourceSettings) {
+    *get_source_settings().lock().unwrap() = [source1, source2];
+}
+
+/// Get the settings for a source by stream index (1 or 2).
+pub fn source_settings(stream_index: usize) -> SourceSettings {
+    let settings = get_source_settings();
+    let settings = settings.lock().unwrap();
+    match stream_index {
+        2 => settings[1].clone(),
+        _ => settings[0].clone(),
+    }
+}
+
+/// Convert a source's native interleaved samples to the stereo mixer layout,
+/// applying channel selection, gain and mute.
+///
+/// Selected channels are kept as-is when they already form a stereo pair; a
+/// single channel is duplicated to both sides; any other selection is
+/// averaged to mono first.
+pub fn apply_source_settings(
+    samples: &[f32],
+    in_channels: usize,
+    settings: &SourceSettings,
+) -> Vec<f32> {
+    if in_channels == 0 {
+        return Vec::new();
+    }
+
+    let num_frames = samples.len() / in_channels;
+    if settings.muted {
+        return vec![0.0; num_frames * MIXER_CHANNELS];
+    }
+
+    // Resolve 1-based channel numbers, ignoring channels the device lacks
+    let mut selected: Vec<usize> = settings
+        .channels
+        .iter()
+        .map(|&c| c as usize)
+        .filter(|&c| c >= 1 && c <= in_channels)
+        .map(|c| c - 1)
+        .collect();
+    if selected.is_empty() {
+        selected = (0..in_channels).collect();
+    }
+
+    let gain = 10.0f32.powf(settings.gain_db / 20.0);
+    let mut output = Vec::with_capacity(num_frames * MIXER_CHANNELS);
+
+    for frame in samples.chunks_exact(in_channels) {
+        if selected.len() == MIXER_CHANNELS {
+            for &ch in &selected {
+                output.push(frame[ch] * gain);
+            }
+        } else {
+            let sum: f32 = selected.iter().map(|&ch| frame[ch]).sum();
+            let sample = sum / selected.len() as f32 * gain;
+            for _ in 0..MIXER_CHANNELS {
+                output.push(sample);
+            }
+        }
+    }
+
+    output
+}
+
+/// Mix two equally sized sample buffers at half gain each, so two
+/// full-scale sources cannot exceed full scale. Sources boosted by their gain
+/// setting are clamped to full scale.
+pub fn mix_samples(a: &[f32], b: &[f32]) -> Vec<f32> {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&s1, &s2)| ((s1 + s2) * 0.5).clamp(-1.0, 1.0))
+        .collect()
+}
+
+/// Place two equally sized stereo buffers on separate output channels: the
+/// microphone (downmixed to mono) on the left, system audio on the right.
+pub fn separate_samples(me: &[f32], remote: &[f32]) -> Vec<f32> {
+    let mut output = Vec::with_capacity(me.len().min(remote.len()));
+    for (me_frame, remote_frame) in me
+        .chunks_exact(MIXER_CHANNELS)
+        .zip(remote.chunks_exact(MIXER_CHANNELS))
+    {
+        let mut frame = [0.0; MIXER_CHANNELS];
+        frame[SEPARATED_ME_CHANNEL] = me_frame.iter().sum::<f32>() / MIXER_CHANNELS as f32;
+        frame[SEPARATED_REMOTE_CHANNEL] = remote_frame.iter().sum::<f32>() / MIXER_CHANNELS as f32;
+        output.extend_from_slice(&frame);
+    }
+    output
+}
+
+/// Speaker-separated output for a single capture stream: the source goes on
+/// its own channel and the other speaker's channel is silent.
+pub fn separate_single_stream(samples: &[f32], is_system_audio: bool) -> Vec<f32> {
+    let silence = vec![0.0; samples.len()];
+    if is_system_audio {
+        separate_samples(&silence, samples)
+    } else {
+        separate_samples(samples, &silence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(gain_db: f32, muted: bool, channels: &[u16]) -> SourceSettings {
+        SourceSettings {
+            gain_db,
+            muted,
+            channels: channels.to_vec(),
+        }
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn source_settings_are_applied() {
+        // Two frames of a 4-channel device; channel N carries N/10 (negated
+        // in the second frame)
+        let quad = [0.1, 0.2, 0.3, 0.4, -0.1, -0.2, -0.3, -0.4];
+        let cases: &[(SourceSettings, &[f32], usize, &[f32])] = &[
+            // Channel 2 only is duplicated to both sides
+            (
+                settings(0.0, false, &[2]),
+                &quad,
+                4,
+                &[0.2, 0.2, -0.2, -0.2],
+            ),
+            // A stereo pair is kept as-is, in selection order
+            (
+                settings(0.0, false, &[4, 2]),
+                &quad,
+                4,
+                &[0.4, 0.2, -0.4, -0.2],
+            ),
+            // Other selections are averaged to mono
+            (
+                settings(0.0, false, &[1, 2, 3]),
+                &quad,
+                4,
+                &[0.2, 0.2, -0.2, -0.2],
+            ),
+            // Channels the device lacks are ignored
+            (
+                settings(0.0, false, &[2, 7]),
+                &quad,
+                4,
+                &[0.2, 0.2, -0.2, -0.2],
+            ),
+            // No valid selection uses all channels
+            (
+                settings(0.0, false, &[0, 9]),
+                &quad,
+                4,
+                &[0.25, 0.25, -0.25, -0.25],
+            ),
+            // Gain in dB
+            (
+                settings(6.0206, false, &[2]),
+                &quad,
+                4,
+                &[0.4, 0.4, -0.4, -0.4],
+            ),
+            (
+                settings(-20.0, false, &[4]),
+                &quad,
+                4,
+                &[0.04, 0.04, -0.04, -0.04],
+            ),
+            // Mute silences every frame regardless of gain
+            (settings(24.0, true, &[2]), &quad, 4, &[0.0; 4]),
+            // Mono sources are duplicated
+            (
+                settings(0.0, false, &[]),
+                &[0.5, -0.5],
+                1,
+                &[0.5, 0.5, -0.5, -0.5],
+            ),
+            // Trailing partial frames are dropped
+            (settings(0.0, false, &[]), &[0.1, 0.2, 0.3], 2, &[0.1, 0.2]),
+            (settings(0.0, false, &[]), &quad, 0, &[]),
+        ];
+        for (settings, samples, in_channels, expected) in cases {
+            assert_close(
+                &apply_source_settings(samples, *in_channels, settings),
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn mix_keeps_half_gain() {
+        // Unity-gain sources mix at half level, as they always have
+        assert_close(
+            &mix_samples(&[0.3, -0.5, 0.0, 1.0], &[0.5, 0.2, -0.8, 1.0]),
+            &[0.4, -0.15, -0.4, 1.0],
+        );
+
+        // Boosted sources are limited to full scale
+        assert_close(&mix_samples(&[4.0, -3.0], &[1.0, -1.0]), &[1.0, -1.0]);
+    }
+}
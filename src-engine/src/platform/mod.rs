@@ -14,9 +14,12 @@ pub mod windows;
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+#[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+mod aec;
 mod backend;
+pub mod mixing;
 
-pub use backend::AudioBackend;
+pub use backend::{AudioBackend, AudioData};
 
 /// Initialize the platform-specific audio backend.
 pub fn init_audio_backend() -> Result<(), String> {
@@ -6,14 +6,16 @@
 //! - Multi-source capture with mixing
 //! - Echo cancellation using AEC3
 
-use crate::platform::backend::{AudioBackend, AudioData};
+use crate::platform::aec::EchoCanceller;
+use crate::platform::backend::{audio_channel, send_audio, AudioBackend, AudioData};
+use crate::platform::mixing;
 use flowstt_common::{AudioDevice, AudioSourceType, RecordingMode};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
-use aec3::voip::VoipAec3;
 use windows::core::{GUID, PCWSTR, PWSTR};
 use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
 use windows::Win32::Media::Audio::{
@@ -44,9 +46,6 @@ const KSDATAFORMAT_SUBTYPE_IEEE_FLOAT: GUID =
 /// Target sample rate for output (matches Linux backend)
 const TARGET_SAMPLE_RATE: u32 = 48000;
 
-/// AEC3 frame size: 10ms at 48kHz = 480 samples per channel
-const AEC_FRAME_SAMPLES: usize = 480;
-
 /// Internal audio samples for channel communication
 struct WasapiAudioSamples {
     samples: Vec<f32>,
@@ -98,7 +97,7 @@ impl WasapiBackend {
         recording_mode: Arc<Mutex<RecordingMode>>,
     ) -> Result<Self, String> {
         let (cmd_tx, cmd_rx) = mpsc::channel();
-        let (audio_tx, audio_rx) = mpsc::channel();
+        let (audio_tx, audio_rx) = audio_channel();
         let input_devices = Arc::new(Mutex::new(Vec::new()));
         let system_devices = Arc::new(Mutex::new(Vec::new()));
         let is_capturing = Arc::new(AtomicBool::new(false));
@@ -207,11 +206,11 @@ impl AudioBackend for WasapiBackend {
         Ok(())
     }
 
-    fn try_recv(&self) -> Option<AudioData> {
+    fn recv_timeout(&self, timeout: Duration) -> Option<AudioData> {
         self.audio_rx
             .lock()
             .unwrap()
-            .try_recv()
+            .recv_timeout(timeout)
             .ok()
             .map(|samples| AudioData {
                 samples: samples.samples,
@@ -345,35 +344,35 @@ fn pwstr_to_string(pwstr: PWSTR) -> String {
 struct AudioMixer {
     /// Buffer for capture samples (microphone/input)
     capture_buffer: Vec<f32>,
-    /// Buffer for render samples (system audio/reference) - fed to AEC and kept for mixing
-    render_buffer: Vec<f32>,
     /// Buffer for render samples to mix with processed capture (for Mixed mode)
     render_mix_buffer: Vec<f32>,
     /// Number of active streams (1 or 2)
     num_streams: usize,
+    /// Sample rate of both streams
+    sample_rate: u32,
     /// Channels per stream
     channels: u16,
     /// Output sender
-    output_tx: mpsc::Sender<WasapiAudioSamples>,
+    output_tx: mpsc::SyncSender<WasapiAudioSamples>,
     /// Flag to enable/disable AEC (shared with main thread)
     aec_enabled: Arc<Mutex<bool>>,
-    /// Recording mode - Mixed or EchoCancel (shared with main thread)
+    /// Recording mode - Mixed, EchoCancel or SpeakerSeparated (shared with main thread)
     recording_mode: Arc<Mutex<RecordingMode>>,
-    /// AEC3 pipeline (created when in mixed mode with 2 streams)
-    aec: Option<VoipAec3>,
+    /// Echo canceller (created when we have 2 streams)
+    aec: Option<EchoCanceller>,
 }
 
 impl AudioMixer {
     fn new(
-        output_tx: mpsc::Sender<WasapiAudioSamples>,
+        output_tx: mpsc::SyncSender<WasapiAudioSamples>,
         aec_enabled: Arc<Mutex<bool>>,
         recording_mode: Arc<Mutex<RecordingMode>>,
     ) -> Self {
         Self {
             capture_buffer: Vec::new(),
-            render_buffer: Vec::new(),
             render_mix_buffer: Vec::new(),
             num_streams: 0,
+            sample_rate: TARGET_SAMPLE_RATE,
             channels: 2,
             output_tx,
             aec_enabled,
@@ -385,31 +384,24 @@ impl AudioMixer {
     fn set_num_streams(&mut self, num: usize) {
         self.num_streams = num;
         self.capture_buffer.clear();
-        self.render_buffer.clear();
         self.render_mix_buffer.clear();
+        self.configure_aec();
+    }
 
-        // Create AEC3 pipeline when we have 2 streams (mic + system audio)
-        if num == 2 {
-            match VoipAec3::builder(48000, self.channels as usize, self.channels as usize)
-                .enable_high_pass(true)
-                .initial_delay_ms(0)
-                .build()
-            {
-                Ok(aec) => {
-                    tracing::info!(
-                        "WASAPI: AEC3 initialized: 48kHz, {} channels, {}ms frames",
-                        self.channels,
-                        AEC_FRAME_SAMPLES * 1000 / 48000
-                    );
-                    self.aec = Some(aec);
-                }
-                Err(e) => {
-                    tracing::error!("WASAPI: Failed to initialize AEC3: {:?}", e);
-                    self.aec = None;
-                }
-            }
-        } else {
+    /// (Re)create the echo canceller for the current stream count and format
+    fn configure_aec(&mut self) {
+        // AEC needs 2 streams (mic + system audio)
+        if self.num_streams != 2 {
             self.aec = None;
+            return;
+        }
+
+        match EchoCanceller::new("WASAPI", self.sample_rate, self.channels) {
+            Ok(aec) => self.aec = Some(aec),
+            Err(e) => {
+                tracing::error!("WASAPI: {}", e);
+                self.aec = None;
+            }
         }
     }
 
@@ -417,29 +409,32 @@ impl AudioMixer {
     fn push_samples(&mut self, samples: &[f32], is_loopback: bool) {
         if self.num_streams == 1 {
             // Single stream - send directly (no AEC possible)
-            let _ = self.output_tx.send(WasapiAudioSamples {
-                samples: samples.to_vec(),
-                channels: self.channels,
-            });
+            let recording_mode = *self.recording_mode.lock().unwrap();
+            let samples = if recording_mode == RecordingMode::SpeakerSeparated {
+                mixing::separate_single_stream(samples, is_loopback)
+            } else {
+                samples.to_vec()
+            };
+            send_audio(
+                &self.output_tx,
+                WasapiAudioSamples {
+                    samples,
+                    channels: self.channels,
+                },
+                "WASAPI",
+            );
             return;
         }
 
         // Two streams mode
-        let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
-
         if is_loopback {
             // System audio (render) - feed to AEC immediately
-            self.render_buffer.extend_from_slice(samples);
+            // Keep a copy for mixing in Mixed mode
             self.render_mix_buffer.extend_from_slice(samples);
 
             // Feed render frames to AEC immediately
             if let Some(ref mut aec) = self.aec {
-                while self.render_buffer.len() >= frame_size {
-                    let render_frame: Vec<f32> = self.render_buffer.drain(0..frame_size).collect();
-                    if let Err(e) = aec.handle_render_frame(&render_frame) {
-                        tracing::error!("WASAPI: AEC3 handle_render_frame error: {:?}", e);
-                    }
-                }
+                aec.handle_render(samples);
             }
         } else {
             // Microphone (capture) - buffer and process
@@ -453,7 +448,7 @@ impl AudioMixer {
         let aec_enabled = *self.aec_enabled.lock().unwrap();
         let recording_mode = *self.recording_mode.lock().unwrap();
 
-        let frame_size = AEC_FRAME_SAMPLES * self.channels as usize;
+        let frame_size = crate::platform::aec::frame_size(self.sample_rate, self.channels);
 
         // Process capture frames when we have enough data from both sources
         while self.capture_buffer.len() >= frame_size && self.render_mix_buffer.len() >= frame_size
@@ -464,15 +459,7 @@ impl AudioMixer {
             // Apply AEC if enabled
             let processed_capture = if aec_enabled {
                 if let Some(ref mut aec) = self.aec {
-                    let mut out = vec![0.0f32; capture_frame.len()];
-
-                    match aec.process_capture_frame(&capture_frame, false, &mut out) {
-                        Ok(_metrics) => out,
-                        Err(e) => {
-                            tracing::error!("WASAPI: AEC3 process_capture_frame error: {:?}", e);
-                            capture_frame
-                        }
-                    }
+                    aec.process_capture_frame(&capture_frame)
                 } else {
                     capture_frame
                 }
@@ -483,26 +470,19 @@ impl AudioMixer {
             // Generate output based on recording mode
             let output: Vec<f32> = match recording_mode {
                 RecordingMode::Mixed => {
-                    // Mix processed capture with system audio using soft clipping
-                    processed_capture
-                        .iter()
-                        .zip(render_frame.iter())
-                        .map(|(&s1, &s2)| {
-                            let sum = s1 + s2;
-                            if sum > 1.0 {
-                                1.0 - (-2.0 * (sum - 1.0)).exp() * 0.5
-                            } else if sum < -1.0 {
-                                -1.0 + (-2.0 * (-sum - 1.0)).exp() * 0.5
-                            } else {
-                                sum
-                            }
-                        })
-                        .collect()
+                    // Mix processed capture with system audio (per-source gains
+                    // were applied upstream; each source contributes half)
+                    mixing::mix_samples(&processed_capture, &render_frame)
                 }
                 RecordingMode::EchoCancel => {
                     // Output only the processed capture signal
                     processed_capture
                 }
+                RecordingMode::SpeakerSeparated => {
+                    // Microphone (echo-cancelled when AEC is enabled) and
+                    // system audio on separate channels
+                    mixing::separate_samples(&processed_capture, &render_frame)
+                }
             };
 
             // Debug logging (periodic)
@@ -530,10 +510,14 @@ impl AudioMixer {
             }
 
             // Send output
-            let _ = self.output_tx.send(WasapiAudioSamples {
-                samples: output,
-                channels: self.channels,
-            });
+            send_audio(
+                &self.output_tx,
+                WasapiAudioSamples {
+                    samples: output,
+                    channels: self.channels,
+                },
+                "WASAPI",
+            );
         }
     }
 }
@@ -541,7 +525,7 @@ impl AudioMixer {
 /// Run the capture thread
 fn run_capture_thread(
     cmd_rx: mpsc::Receiver<CaptureCommand>,
-    audio_tx: mpsc::Sender<WasapiAudioSamples>,
+    audio_tx: mpsc::SyncSender<WasapiAudioSamples>,
     system_devices: Arc<Mutex<Vec<AudioDevice>>>,
     is_capturing: Arc<AtomicBool>,
     aec_enabled: Arc<Mutex<bool>>,
@@ -775,7 +759,9 @@ fn run_stream_capture(
 
                 // Capture loop
                 while !stop_flag.load(Ordering::SeqCst) {
-                    if let Err(e) = process_capture(&mut state, is_loopback, &stream_tx) {
+                    if let Err(e) =
+                        process_capture(&mut state, is_loopback, stream_index, &stream_tx)
+                    {
                         tracing::error!("WASAPI: Stream {} capture error: {}", stream_index, e);
                         break;
                     }
@@ -947,6 +933,7 @@ fn parse_wave_format(format: &WAVEFORMATEX) -> Result<CaptureFormat, String> {
 unsafe fn process_capture(
     state: &mut CaptureState,
     is_loopback: bool,
+    stream_index: usize,
     stream_tx: &mpsc::Sender<StreamSamples>,
 ) -> Result<(), String> {
     let wait_result = WaitForSingleObject(state.event_handle, 10);
@@ -986,12 +973,13 @@ unsafe fn process_capture(
             samples
         };
 
-        // Convert mono to stereo if needed
-        let stereo_samples = if state.format.channels == 1 {
-            mono_to_stereo(&final_samples)
-        } else {
-            final_samples
-        };
+        // Apply channel selection, gain and mute, converting to stereo
+        let settings = mixing::source_settings(stream_index);
+        let stereo_samples = mixing::apply_source_settings(
+            &final_samples,
+            state.format.channels as usize,
+            &settings,
+        );
 
         // Send to mixer thread via channel with loopback flag
         let _ = stream_tx.send(StreamSamples {
@@ -1039,16 +1027,6 @@ unsafe fn convert_to_f32(buffer: *const u8, num_frames: usize, format: &CaptureF
     }
 }
 
-/// Convert mono audio to stereo by duplicating channels
-fn mono_to_stereo(mono: &[f32]) -> Vec<f32> {
-    let mut stereo = Vec::with_capacity(mono.len() * 2);
-    for &sample in mono {
-        stereo.push(sample);
-        stereo.push(sample);
-    }
-    stereo
-}
-
 /// Simple linear resampler
 struct Resampler {
     source_rate: u32,
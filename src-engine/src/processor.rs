@@ -50,6 +50,8 @@ pub struct SpeechMetrics {
     pub lookback_offset_ms: Option<u32>,
     /// Whether a word break (inter-word gap) is currently detected
     pub is_word_break: bool,
+    /// Gain applied by automatic gain control in dB (set by the audio loop)
+    pub applied_gain_db: f32,
 }
 
 /// Event payload for speech detection events
@@ -452,6 +454,7 @@ impl SpeechDetector {
             is_lookback_speech: false,
             lookback_offset_ms: self.last_lookback_offset_ms,
             is_word_break: self.last_is_word_break,
+            applied_gain_db: 0.0,
         }
     }
 
@@ -2,6 +2,8 @@
 //!
 //! This module manages the PTT lifecycle:
 //! - In PTT mode, audio capture is only active while the hotkey is held
+//! - In toggle-to-talk mode, one hotkey tap starts capture and the next tap
+//!   (or a silence timeout reported by the audio loop) stops it
 //! - Polls for hotkey events independently of audio loop
 //! - Starts/stops audio capture on key press/release
 //! - Handles toggle hotkey for switching between modes
@@ -12,21 +14,25 @@ use std::thread;
 use std::time::Duration;
 
 use flowstt_common::ipc::{EventType, Response};
-use flowstt_common::TranscriptionMode;
+use flowstt_common::{HotkeyCombination, ToggleTalkStopReason, TranscriptionMode};
 use tracing::{debug, error, info};
 
 use crate::audio_loop::{self, is_audio_loop_active};
 use crate::hotkey::{self, HotkeyEvent};
 use crate::ipc::broadcast_event;
 use crate::ipc::handlers::{get_transcribe_state, get_transcription_queue};
+use crate::pipeline::SegmentationStrategy;
 use crate::platform;
-use crate::processor::{VisualizationCallback, VisualizationPayload, VisualizationProcessor};
 use crate::state::get_service_state;
 
 /// Global PTT controller state
 static PTT_ACTIVE: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
 static PTT_THREAD_RUNNING: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
 
+/// Set by the audio loop when a toggle-to-talk recording has been silent for
+/// the configured timeout; handled on the controller thread
+static TOGGLE_TALK_STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
 fn get_ptt_active() -> Arc<AtomicBool> {
     PTT_ACTIVE
         .get_or_init(|| Arc::new(AtomicBool::new(false)))
@@ -44,6 +50,12 @@ pub fn is_ptt_controller_running() -> bool {
     get_ptt_thread_running().load(Ordering::SeqCst)
 }
 
+/// Ask the controller to end the current toggle-to-talk recording because of
+/// the silence timeout.
+pub fn request_toggle_talk_stop() {
+    TOGGLE_TALK_STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
 /// Start the PTT controller thread.
 /// This monitors for hotkey events and controls audio capture accordingly.
 pub fn start_ptt_controller() -> Result<(), String> {
@@ -72,10 +84,15 @@ pub fn stop_ptt_controller() {
     info!("[PTT Controller] Stopping...");
     get_ptt_thread_running().store(false, Ordering::SeqCst);
 
-    // If PTT was active, stop capture
+    // If PTT or toggle-to-talk was recording, stop capture
     if get_ptt_active().load(Ordering::SeqCst) {
         get_ptt_active().store(false, Ordering::SeqCst);
         stop_ptt_capture();
+
+        let state_arc = get_service_state();
+        let mut state = futures::executor::block_on(state_arc.lock());
+        state.is_ptt_active = false;
+        state.is_toggle_talk_active = false;
     }
 }
 
@@ -89,12 +106,18 @@ fn ptt_controller_loop() {
             break;
         }
 
-        // Check for hotkey events
+        // Check for hotkey events. In toggle-to-talk mode the backend reports
+        // the toggle-to-talk hotkeys as PTT presses; only the press matters.
         if let Some(event) = hotkey::try_recv_hotkey() {
+            let toggle_to_talk = current_mode() == TranscriptionMode::ToggleToTalk;
             match event {
-                HotkeyEvent::PttPressed => {
-                    handle_ptt_pressed();
+                HotkeyEvent::PttPressed(binding) if toggle_to_talk => {
+                    handle_toggle_talk_tap(binding);
+                }
+                HotkeyEvent::PttPressed(binding) => {
+                    handle_ptt_pressed(binding);
                 }
+                HotkeyEvent::PttReleased if toggle_to_talk => {}
                 HotkeyEvent::PttReleased => {
                     handle_ptt_released();
                 }
@@ -104,6 +127,11 @@ fn ptt_controller_loop() {
             }
         }
 
+        // Check for a toggle-to-talk silence timeout
+        if TOGGLE_TALK_STOP_REQUESTED.swap(false, Ordering::SeqCst) {
+            stop_toggle_talk(ToggleTalkStopReason::SilenceTimeout);
+        }
+
         // Sleep briefly to avoid busy-waiting
         thread::sleep(Duration::from_millis(5));
     }
@@ -112,8 +140,15 @@ fn ptt_controller_loop() {
     get_ptt_thread_running().store(false, Ordering::SeqCst);
 }
 
+/// Current transcription mode
+fn current_mode() -> TranscriptionMode {
+    let state_arc = get_service_state();
+    let state = futures::executor::block_on(state_arc.lock());
+    state.transcription_mode
+}
+
 /// Handle PTT key press - start audio capture
-fn handle_ptt_pressed() {
+fn handle_ptt_pressed(binding: HotkeyCombination) {
     if get_ptt_active().load(Ordering::SeqCst) {
         return;
     }
@@ -133,8 +168,14 @@ fn handle_ptt_pressed() {
         event: EventType::PttPressed,
     });
 
-    // Start capture
-    if let Err(e) = start_ptt_capture() {
+    start_hotkey_recording(SegmentationStrategy::PushToTalk, binding);
+}
+
+/// Start capture for a hotkey-driven recording made with `binding` and
+/// broadcast the outcome. Returns false (with the recording marked inactive)
+/// if capture failed.
+fn start_hotkey_recording(strategy: SegmentationStrategy, binding: HotkeyCombination) -> bool {
+    if let Err(e) = start_ptt_capture(strategy, binding) {
         error!("[PTT] Failed to start recording: {}", e);
         get_ptt_active().store(false, Ordering::SeqCst);
 
@@ -144,6 +185,7 @@ fn handle_ptt_pressed() {
                 error: Some(e),
             },
         });
+        false
     } else {
         // Broadcast speech started
         broadcast_event(Response::Event {
@@ -156,6 +198,7 @@ fn handle_ptt_pressed() {
                 error: None,
             },
         });
+        true
     }
 }
 
@@ -175,7 +218,30 @@ fn handle_ptt_released() {
         state.is_ptt_active = false;
     }
 
+    let duration_ms = end_hotkey_recording();
+
+    // Broadcast events
+    broadcast_event(Response::Event {
+        event: EventType::PttReleased,
+    });
+
+    broadcast_event(Response::Event {
+        event: EventType::SpeechEnded { duration_ms },
+    });
+
+    broadcast_event(Response::Event {
+        event: EventType::CaptureStateChanged {
+            capturing: false,
+            error: None,
+        },
+    });
+}
+
+/// Submit the current hotkey-driven segment for transcription and stop
+/// capture. Returns the segment duration in milliseconds.
+fn end_hotkey_recording() -> u64 {
     // Finalize current segment before stopping - this submits for transcription
+    let mut duration_ms = 0;
     let transcribe_state = get_transcribe_state();
     if let Ok(mut transcribe) = transcribe_state.try_lock() {
         info!(
@@ -183,6 +249,7 @@ fn handle_ptt_released() {
             transcribe.in_speech, transcribe.is_active
         );
         if transcribe.in_speech {
+            duration_ms = transcribe.segment_duration_ms();
             let segment = transcribe.on_speech_ended();
             if let Some(ref s) = segment {
                 info!("[PTT] Extracted {} samples for transcription", s.len());
@@ -197,13 +264,77 @@ fn handle_ptt_released() {
     // Stop capture
     stop_ptt_capture();
 
+    duration_ms
+}
+
+/// Handle a toggle-to-talk hotkey tap - start or stop the recording
+fn handle_toggle_talk_tap(binding: HotkeyCombination) {
+    if get_ptt_active().load(Ordering::SeqCst) {
+        stop_toggle_talk(ToggleTalkStopReason::Hotkey);
+    } else {
+        start_toggle_talk(binding);
+    }
+}
+
+/// Start a toggle-to-talk recording
+fn start_toggle_talk(binding: HotkeyCombination) {
+    info!("[ToggleTalk] Recording STARTED");
+    get_ptt_active().store(true, Ordering::SeqCst);
+    TOGGLE_TALK_STOP_REQUESTED.store(false, Ordering::SeqCst);
+
+    // Update state
+    let silence_timeout_ms = {
+        let state_arc = get_service_state();
+        let mut state = futures::executor::block_on(state_arc.lock());
+        state.is_toggle_talk_active = true;
+        state.toggle_talk_silence_timeout_ms
+    };
+
+    broadcast_event(Response::Event {
+        event: EventType::ToggleTalkStarted,
+    });
+
+    if !start_hotkey_recording(
+        SegmentationStrategy::ToggleToTalk { silence_timeout_ms },
+        binding,
+    ) {
+        let state_arc = get_service_state();
+        let mut state = futures::executor::block_on(state_arc.lock());
+        state.is_toggle_talk_active = false;
+    }
+}
+
+/// End the toggle-to-talk recording and submit it for transcription
+fn stop_toggle_talk(reason: ToggleTalkStopReason) {
+    if !get_ptt_active().load(Ordering::SeqCst) {
+        return;
+    }
+
+    info!(
+        "[ToggleTalk] Recording STOPPED ({:?}) - submitting for transcription",
+        reason
+    );
+    get_ptt_active().store(false, Ordering::SeqCst);
+
+    // Update state
+    {
+        let state_arc = get_service_state();
+        let mut state = futures::executor::block_on(state_arc.lock());
+        state.is_toggle_talk_active = false;
+    }
+
+    let duration_ms = end_hotkey_recording();
+
     // Broadcast events
     broadcast_event(Response::Event {
-        event: EventType::PttReleased,
+        event: EventType::ToggleTalkStopped {
+            reason,
+            duration_ms,
+        },
     });
 
     broadcast_event(Response::Event {
-        event: EventType::SpeechEnded { duration_ms: 0 },
+        event: EventType::SpeechEnded { duration_ms },
     });
 
     broadcast_event(Response::Event {
@@ -214,16 +345,16 @@ fn handle_ptt_released() {
     });
 }
 
-/// Handle toggle hotkey press - switch between Automatic and PTT modes
+/// Handle toggle hotkey press - switch between Automatic and the hotkey-driven
+/// (PTT or toggle-to-talk) mode
 fn handle_toggle_pressed() {
     info!("[Toggle] Toggle hotkey pressed");
 
     let state_arc = get_service_state();
     let (
         current_mode,
+        hotkey_mode,
         auto_mode_active,
-        _ptt_hotkeys,
-        _toggle_hotkeys,
         source1_id,
         source2_id,
         aec_enabled,
@@ -232,9 +363,8 @@ fn handle_toggle_pressed() {
         let state = futures::executor::block_on(state_arc.lock());
         (
             state.transcription_mode,
+            state.hotkey_mode,
             state.auto_mode_active,
-            state.ptt_hotkeys.clone(),
-            state.auto_toggle_hotkeys.clone(),
             state.source1_id.clone(),
             state.source2_id.clone(),
             state.aec_enabled,
@@ -244,10 +374,10 @@ fn handle_toggle_pressed() {
 
     // Determine new mode
     let (new_mode, new_auto_active) = if auto_mode_active {
-        // Currently in auto mode via toggle -> switch to PTT mode
-        (TranscriptionMode::PushToTalk, false)
+        // Currently in auto mode via toggle -> switch back to the hotkey mode
+        (hotkey_mode, false)
     } else {
-        // Currently in PTT mode -> switch to auto mode
+        // Currently in a hotkey mode -> switch to auto mode
         (TranscriptionMode::Automatic, true)
     };
 
@@ -256,9 +386,9 @@ fn handle_toggle_pressed() {
         current_mode, new_mode
     );
 
-    // If currently recording in PTT mode, finalize the segment first
+    // If currently recording in a hotkey mode, finalize the segment first
     if get_ptt_active().load(Ordering::SeqCst) {
-        info!("[Toggle] Finalizing active PTT segment before mode switch");
+        info!("[Toggle] Finalizing active hotkey segment before mode switch");
         get_ptt_active().store(false, Ordering::SeqCst);
 
         let transcribe_state = get_transcribe_state();
@@ -280,9 +410,13 @@ fn handle_toggle_pressed() {
     {
         let state_arc = get_service_state();
         let mut state = futures::executor::block_on(state_arc.lock());
+        if current_mode.is_hotkey_driven() {
+            state.hotkey_mode = current_mode;
+        }
         state.transcription_mode = new_mode;
         state.auto_mode_active = new_auto_active;
         state.is_ptt_active = false;
+        state.is_toggle_talk_active = false;
         state.transcribe_status.capturing = false;
         state.transcribe_status.in_speech = false;
 
@@ -329,7 +463,10 @@ fn handle_toggle_pressed() {
             let transcribe_state = get_transcribe_state();
             let mut transcribe = transcribe_state.lock().unwrap();
             transcribe.init_for_capture(sample_rate, 2);
+            transcribe.set_recording_mode(recording_mode);
             transcribe.activate();
+            // New automatic session - speakers are numbered from 1 again
+            transcribe.transcription_queue.reset_speakers();
         }
 
         // Start capture
@@ -353,7 +490,9 @@ fn handle_toggle_pressed() {
         if !is_audio_loop_active() {
             let queue = get_transcription_queue();
             let transcribe_state = get_transcribe_state();
-            if let Err(e) = audio_loop::start_audio_loop(queue, transcribe_state) {
+            if let Err(e) =
+                audio_loop::start_audio_loop(queue, transcribe_state, SegmentationStrategy::Vad)
+            {
                 error!("[Toggle] Failed to start audio loop: {}", e);
             }
         }
@@ -375,8 +514,11 @@ fn handle_toggle_pressed() {
 
         info!("[Toggle] Automatic mode capture started");
     } else {
-        // PTT mode - just ready state, capture happens on hotkey press
-        info!("[Toggle] PTT mode ready - waiting for hotkey press");
+        // Hotkey mode - just ready state, capture happens on hotkey press
+        info!(
+            "[Toggle] {:?} mode ready - waiting for hotkey press",
+            new_mode
+        );
 
         broadcast_event(Response::Event {
             event: EventType::CaptureStateChanged {
@@ -389,8 +531,11 @@ fn handle_toggle_pressed() {
     info!("[Toggle] Mode switched to {:?}", new_mode);
 }
 
-/// Start audio capture for PTT session
-fn start_ptt_capture() -> Result<(), String> {
+/// Start audio capture for a PTT or toggle-to-talk session
+fn start_ptt_capture(
+    strategy: SegmentationStrategy,
+    binding: HotkeyCombination,
+) -> Result<(), String> {
     let state_arc = get_service_state();
     let (source1_id, source2_id, aec_enabled, recording_mode) = {
         let state = futures::executor::block_on(state_arc.lock());
@@ -417,7 +562,9 @@ fn start_ptt_capture() -> Result<(), String> {
         let transcribe_state = get_transcribe_state();
         let mut transcribe = transcribe_state.lock().unwrap();
         transcribe.init_for_capture(sample_rate, 2);
-        transcribe.set_ptt_mode(true); // Disable automatic segmentation
+        transcribe.set_recording_mode(recording_mode);
+        transcribe.set_capture_mode(strategy.mode()); // Disable automatic segmentation
+        transcribe.set_capture_hotkey(Some(binding));
         transcribe.activate();
         // Immediately start speech segment (no lookback in PTT mode)
         transcribe.on_speech_started(0);
@@ -433,8 +580,12 @@ fn start_ptt_capture() -> Result<(), String> {
         return Err("Audio backend not available".to_string());
     }
 
-    // Start PTT audio processing loop (simpler than the main audio loop - no VAD)
-    start_ptt_audio_loop();
+    // Start the audio loop - the hotkey, not VAD, delimits the segment
+    if let Err(e) =
+        audio_loop::start_audio_loop(get_transcription_queue(), get_transcribe_state(), strategy)
+    {
+        error!("[PTT] Failed to start audio loop: {}", e);
+    }
 
     // Update state
     {
@@ -447,17 +598,18 @@ fn start_ptt_capture() -> Result<(), String> {
     Ok(())
 }
 
-/// Stop audio capture for PTT session
+/// Stop audio capture for a PTT or toggle-to-talk session
 fn stop_ptt_capture() {
-    // Stop PTT audio processing loop
-    stop_ptt_audio_loop();
+    // Stop the audio loop
+    audio_loop::stop_audio_loop();
 
     // Finalize transcribe state and disable PTT mode
     let transcribe_state = get_transcribe_state();
     if let Ok(mut transcribe) = transcribe_state.try_lock() {
         transcribe.finalize();
         transcribe.deactivate();
-        transcribe.set_ptt_mode(false); // Restore automatic segmentation for next use
+        transcribe.set_capture_mode(TranscriptionMode::Automatic); // Restore automatic segmentation for next use
+        transcribe.set_capture_hotkey(None);
     }
 
     // Stop capture
@@ -476,124 +628,6 @@ fn stop_ptt_capture() {
     info!("[PTT Controller] Capture stopped");
 }
 
-/// Global PTT audio loop control
-static PTT_AUDIO_LOOP_ACTIVE: std::sync::OnceLock<Arc<AtomicBool>> = std::sync::OnceLock::new();
-
-fn get_ptt_audio_loop_active() -> Arc<AtomicBool> {
-    PTT_AUDIO_LOOP_ACTIVE
-        .get_or_init(|| Arc::new(AtomicBool::new(false)))
-        .clone()
-}
-
-/// Start PTT audio processing loop (simplified - no VAD, just process audio)
-fn start_ptt_audio_loop() {
-    if get_ptt_audio_loop_active().load(Ordering::SeqCst) {
-        return; // Already running
-    }
-
-    let loop_active = get_ptt_audio_loop_active();
-    loop_active.store(true, Ordering::SeqCst);
-
-    // Get sample rate from backend
-    let sample_rate = platform::get_backend()
-        .map(|b| b.sample_rate())
-        .unwrap_or(48000);
-
-    let transcribe_state = get_transcribe_state();
-
-    thread::spawn(move || {
-        debug!("[PTT AudioLoop] Starting PTT audio processing loop");
-
-        // Create visualization processor
-        let mut viz_processor = VisualizationProcessor::new(sample_rate, 256);
-        viz_processor.set_callback(Arc::new(PttVisualizationBroadcaster));
-
-        let loop_active = get_ptt_audio_loop_active();
-
-        loop {
-            // Check if we should stop
-            if !loop_active.load(Ordering::SeqCst) {
-                break;
-            }
-
-            // Check if service is shutting down
-            if crate::is_shutdown_requested() {
-                break;
-            }
-
-            // Try to receive audio from backend
-            let audio_data = platform::get_backend().and_then(|b| b.try_recv());
-
-            if let Some(data) = audio_data {
-                // Convert to mono for visualization
-                let mono_samples = convert_to_mono(&data.samples, data.channels as usize);
-
-                // Process visualization
-                viz_processor.process(&mono_samples);
-
-                // Write audio to transcribe state (no VAD - PTT controller manages segments)
-                if let Ok(mut transcribe) = transcribe_state.try_lock() {
-                    if transcribe.is_active {
-                        transcribe.process_samples(&data.samples);
-                    }
-                }
-            } else {
-                // No data available, sleep briefly
-                thread::sleep(Duration::from_millis(1));
-            }
-        }
-
-        debug!("[PTT AudioLoop] PTT audio processing loop stopped");
-    });
-}
-
-/// Stop PTT audio processing loop
-fn stop_ptt_audio_loop() {
-    get_ptt_audio_loop_active().store(false, Ordering::SeqCst);
-}
-
-/// Convert multi-channel audio to mono
-fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        return samples.to_vec();
-    }
-    samples
-        .chunks(channels)
-        .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-        .collect()
-}
-
-/// Broadcaster for PTT visualization events
-struct PttVisualizationBroadcaster;
-
-impl VisualizationCallback for PttVisualizationBroadcaster {
-    fn on_visualization_data(&self, payload: VisualizationPayload) {
-        // Convert processor payload to common VisualizationData
-        let data = flowstt_common::VisualizationData {
-            waveform: payload.waveform,
-            spectrogram: payload
-                .spectrogram
-                .map(|s| flowstt_common::SpectrogramColumn { colors: s.colors }),
-            speech_metrics: payload
-                .speech_metrics
-                .map(|m| flowstt_common::SpeechMetrics {
-                    amplitude_db: m.amplitude_db,
-                    zcr: m.zcr,
-                    centroid_hz: m.centroid_hz,
-                    is_speaking: m.is_speaking,
-                    voiced_onset_pending: m.is_voiced_pending,
-                    whisper_onset_pending: m.is_whisper_pending,
-                    is_transient: m.is_transient,
-                    is_lookback_speech: m.is_lookback_speech,
-                    is_word_break: m.is_word_break,
-                }),
-        };
-        broadcast_event(Response::Event {
-            event: EventType::VisualizationData(data),
-        });
-    }
-}
-
 /// Check if PTT is currently active (key held)
 #[allow(dead_code)]
 pub fn is_ptt_active() -> bool {
@@ -22,14 +22,22 @@ pub struct ServiceState {
     pub source1_id: Option<String>,
     /// Secondary audio source ID (optional)
     pub source2_id: Option<String>,
-    /// Current transcription mode (Automatic or PushToTalk)
+    /// Current transcription mode (Automatic, PushToTalk or ToggleToTalk)
     pub transcription_mode: TranscriptionMode,
     /// Configured push-to-talk hotkey combinations
     pub ptt_hotkeys: Vec<HotkeyCombination>,
     /// Configured auto-mode toggle hotkeys
     pub auto_toggle_hotkeys: Vec<HotkeyCombination>,
+    /// Configured toggle-to-talk hotkey combinations
+    pub toggle_talk_hotkeys: Vec<HotkeyCombination>,
+    /// Silence after which a toggle-to-talk recording ends (0 = never)
+    pub toggle_talk_silence_timeout_ms: u32,
     /// Whether PTT key is currently pressed
     pub is_ptt_active: bool,
+    /// Whether a toggle-to-talk recording is in progress
+    pub is_toggle_talk_active: bool,
+    /// Hotkey-driven mode to return to when auto mode is toggled off
+    pub hotkey_mode: TranscriptionMode,
     /// Whether auto mode is currently active (for PTT suppression)
     pub auto_mode_active: bool,
     /// Current runtime mode (development or production)
@@ -37,6 +45,14 @@ pub struct ServiceState {
 }
 
 impl ServiceState {
+    /// Hotkeys that start and stop recording in the current mode
+    pub fn talk_hotkeys(&self) -> Vec<HotkeyCombination> {
+        match self.transcription_mode {
+            TranscriptionMode::ToggleToTalk => self.toggle_talk_hotkeys.clone(),
+            _ => self.ptt_hotkeys.clone(),
+        }
+    }
+
     /// Check if primary audio source is configured
     pub fn has_primary_source(&self) -> bool {
         self.source1_id.is_some()
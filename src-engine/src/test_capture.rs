@@ -11,6 +11,7 @@ use std::thread::JoinHandle;
 
 use flowstt_common::ipc::{EventType, Response};
 
+use crate::audio_loop::AUDIO_RECV_TIMEOUT;
 use crate::ipc::broadcast_event;
 use crate::platform;
 
@@ -97,7 +98,8 @@ fn run_test_capture(device_id: &str, stop_flag: &AtomicBool) -> Result<(), Strin
     let mut accumulated = Vec::with_capacity(samples_per_update);
 
     while !stop_flag.load(Ordering::Relaxed) {
-        if let Some(audio_data) = backend.try_recv() {
+        // Block until audio arrives, waking periodically to check the stop flag
+        if let Some(audio_data) = backend.recv_timeout(AUDIO_RECV_TIMEOUT) {
             // Convert to mono if multi-channel
             let mono: Vec<f32> = if audio_data.channels > 1 {
                 audio_data
@@ -124,9 +126,6 @@ fn run_test_capture(device_id: &str, stop_flag: &AtomicBool) -> Result<(), Strin
 
                 accumulated.clear();
             }
-        } else {
-            // No data available, sleep briefly to avoid busy-spinning
-            std::thread::sleep(std::time::Duration::from_millis(5));
         }
     }
 
@@ -0,0 +1,230 @@
+//! Text post-processing pipeline.
+//!
+//! Transcripts pass through an ordered list of [`TextProcessor`] stages
+//! configured by `Config::text_processing`. The pipeline runs on complete
+//! transcripts (after long-form stitching) and before the wake-word gate and
+//! voice commands. Recordings made in a given transcription mode, or with one
+//! of its hotkey bindings, can use their own stage list (a profile), e.g.
+//! filler removal for push-to-talk only, or number formatting for one PTT key.
+//!
+//! # Stages
+//!
+//! - [`Capitalization`]: sentence starts and the pronoun "I"
+//! - [`FillerRemoval`]: "um", "uh", ...
+//! - [`NumberFormatting`]: spelled-out numbers as digits
+//! - [`SmartPunctuation`]: typographic quotes, dashes and ellipses
+//! - [`Replace`]: custom find/replace rules
+
+mod numbers;
+mod stages;
+
+pub use numbers::NumberFormatting;
+pub use stages::{Capitalization, FillerRemoval, Replace, SmartPunctuation};
+
+use std::ops::Range;
+
+use flowstt_common::{HotkeyCombination, TextProcessingConfig, TextStage, TranscriptionMode};
+
+/// Byte ranges of the whitespace-separated words of `text`. Stages rewrite
+/// only the words they change and keep the whitespace between the others.
+fn word_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                spans.push(s..i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push(s..text.len());
+    }
+    spans
+}
+
+/// A single text transformation stage.
+pub trait TextProcessor: Send + Sync {
+    /// Stage name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Transform a transcript.
+    fn process(&self, text: &str) -> String;
+}
+
+/// Build the processor for a configured stage.
+pub fn create_processor(stage: &TextStage) -> Box<dyn TextProcessor> {
+    match stage {
+        TextStage::Capitalization => Box::new(Capitalization),
+        TextStage::FillerRemoval { words } => Box::new(FillerRemoval::new(words)),
+        TextStage::NumberFormatting => Box::new(NumberFormatting),
+        TextStage::SmartPunctuation => Box::new(SmartPunctuation),
+        TextStage::Replace { find, replace } => Box::new(Replace::new(find, replace)),
+    }
+}
+
+/// An ordered list of text processors.
+#[derive(Default)]
+pub struct TextPipeline {
+    stages: Vec<Box<dyn TextProcessor>>,
+}
+
+impl TextPipeline {
+    /// Create a pipeline from configured stages.
+    pub fn new(stages: &[TextStage]) -> Self {
+        Self {
+            stages: stages.iter().map(create_processor).collect(),
+        }
+    }
+
+    /// Run every stage in order.
+    pub fn process(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for stage in &self.stages {
+            let processed = stage.process(&text);
+            if processed != text {
+                tracing::trace!(
+                    "[TextProcessing] {}: '{}' -> '{}'",
+                    stage.name(),
+                    text,
+                    processed
+                );
+                text = processed;
+            }
+        }
+        text
+    }
+}
+
+/// The default pipeline plus per-mode and per-hotkey profiles.
+pub struct TextPipelines {
+    default: TextPipeline,
+    profiles: Vec<(TranscriptionMode, Option<HotkeyCombination>, TextPipeline)>,
+}
+
+impl TextPipelines {
+    /// Build the pipelines described by a config.
+    pub fn new(config: &TextProcessingConfig) -> Self {
+        Self {
+            default: TextPipeline::new(&config.stages),
+            profiles: config
+                .profiles
+                .iter()
+                .map(|p| (p.mode, p.hotkey.clone(), TextPipeline::new(&p.stages)))
+                .collect(),
+        }
+    }
+
+    /// Process a transcript recorded in `mode`, with `hotkey` if it was
+    /// hotkey-driven. A profile for the binding wins over one for the mode.
+    pub fn process(
+        &self,
+        mode: TranscriptionMode,
+        hotkey: Option<&HotkeyCombination>,
+        text: &str,
+    ) -> String {
+        let profile = |binding: Option<&HotkeyCombination>| {
+            self.profiles
+                .iter()
+                .find(|(m, h, _)| *m == mode && h.as_ref() == binding)
+                .map(|(_, _, pipeline)| pipeline)
+        };
+        hotkey
+            .and_then(|h| profile(Some(h)))
+            .or_else(|| profile(None))
+            .unwrap_or(&self.default)
+            .process(text)
+    }
+}
+
+impl Default for TextPipelines {
+    fn default() -> Self {
+        Self::new(&TextProcessingConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::{default_filler_words, KeyCode, TextProfile};
+
+    #[test]
+    fn stages_run_in_order() {
+        let pipeline = TextPipeline::new(&[
+            TextStage::FillerRemoval {
+                words: default_filler_words(),
+            },
+            TextStage::NumberFormatting,
+            TextStage::Capitalization,
+        ]);
+        assert_eq!(
+            pipeline.process("um, twenty five people came. i counted them"),
+            "25 people came. I counted them"
+        );
+    }
+
+    #[test]
+    fn default_pipeline_keeps_product_spelling() {
+        let pipelines = TextPipelines::default();
+        assert_eq!(
+            pipelines.process(TranscriptionMode::Automatic, None, "Open Flow STT now"),
+            "Open FlowSTT now"
+        );
+    }
+
+    #[test]
+    fn profiles_replace_default_stages() {
+        let config = TextProcessingConfig {
+            stages: vec![TextStage::Capitalization],
+            profiles: vec![TextProfile {
+                mode: TranscriptionMode::ToggleToTalk,
+                hotkey: None,
+                stages: vec![],
+            }],
+        };
+        let pipelines = TextPipelines::new(&config);
+        assert_eq!(
+            pipelines.process(TranscriptionMode::PushToTalk, None, "hello"),
+            "Hello"
+        );
+        assert_eq!(
+            pipelines.process(TranscriptionMode::ToggleToTalk, None, "hello"),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn hotkey_profiles_win_over_mode_profiles() {
+        let notes = HotkeyCombination::single(KeyCode::F13);
+        let config = TextProcessingConfig {
+            stages: vec![],
+            profiles: vec![
+                TextProfile {
+                    mode: TranscriptionMode::PushToTalk,
+                    hotkey: None,
+                    stages: vec![TextStage::Capitalization],
+                },
+                TextProfile {
+                    mode: TranscriptionMode::PushToTalk,
+                    hotkey: Some(notes.clone()),
+                    stages: vec![TextStage::NumberFormatting],
+                },
+            ],
+        };
+        let pipelines = TextPipelines::new(&config);
+        let ptt = TranscriptionMode::PushToTalk;
+        assert_eq!(pipelines.process(ptt, Some(&notes), "twenty one"), "21");
+        let other = HotkeyCombination::single(KeyCode::F14);
+        assert_eq!(
+            pipelines.process(ptt, Some(&other), "twenty one"),
+            "Twenty one"
+        );
+        assert_eq!(
+            pipelines.process(TranscriptionMode::Automatic, Some(&notes), "twenty one"),
+            "twenty one"
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Number formatting (inverse text normalization).
+//!
+//! Whisper usually writes numbers as digits, but short or hesitant phrases
+//! often come out spelled ("twenty five", "two thousand and three"). This
+//! stage rewrites spelled cardinal numbers as digits. Single words below ten
+//! ("one of them") are left alone since they read better as words.
+
+use super::{word_spans, TextProcessor};
+
+/// A number word.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumberWord {
+    /// zero - nine
+    Unit(u64),
+    /// ten - nineteen
+    Teen(u64),
+    /// twenty - ninety
+    Tens(u64),
+    Hundred,
+    /// thousand, million, billion
+    Scale(u64),
+    /// "and" inside a number ("one hundred and five")
+    And,
+}
+
+fn number_word(word: &str) -> Option<NumberWord> {
+    use NumberWord::*;
+    const UNITS: [&str; 10] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    const TEENS: [&str; 10] = [
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 8] = [
+        "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+
+    if let Some(n) = UNITS.iter().position(|w| *w == word) {
+        return Some(Unit(n as u64));
+    }
+    if let Some(n) = TEENS.iter().position(|w| *w == word) {
+        return Some(Teen(10 + n as u64));
+    }
+    if let Some(n) = TENS.iter().position(|w| *w == word) {
+        return Some(Tens(20 + 10 * n as u64));
+    }
+    match word {
+        "hundred" => Some(Hundred),
+        "thousand" => Some(Scale(1_000)),
+        "million" => Some(Scale(1_000_000)),
+        "billion" => Some(Scale(1_000_000_000)),
+        _ => None,
+    }
+}
+
+/// Value of a number phrase being read word by word.
+#[derive(Debug, Clone, Copy)]
+struct NumberReader {
+    /// Sum of completed thousand/million/billion groups
+    total: u64,
+    /// Group below the last scale word
+    current: u64,
+    /// Previous word
+    last: Option<NumberWord>,
+    /// Smallest scale used so far (scales must decrease)
+    last_scale: u64,
+    /// Number words read (excluding "and")
+    words: usize,
+}
+
+impl NumberReader {
+    fn new() -> Self {
+        Self {
+            total: 0,
+            current: 0,
+            last: None,
+            last_scale: u64::MAX,
+            words: 0,
+        }
+    }
+
+    /// Add a word, returning false if it cannot continue the number.
+    fn push(&mut self, word: NumberWord) -> bool {
+        use NumberWord::*;
+        let after_group_start = matches!(self.last, None | Some(Hundred | Scale(_) | And));
+        match word {
+            Unit(0) if self.last.is_some() => return false,
+            Unit(n) if after_group_start || matches!(self.last, Some(Tens(_))) => {
+                self.current += n;
+            }
+            Teen(n) | Tens(n) if after_group_start => self.current += n,
+            // Only a group below a hundred can be multiplied ("nineteen
+            // hundred"); "nine hundred nine hundred" is two numbers
+            Hundred
+                if matches!(self.last, Some(Unit(_) | Teen(_)))
+                    && (1..100).contains(&self.current) =>
+            {
+                self.current *= 100;
+            }
+            Scale(scale)
+                if !matches!(self.last, None | Some(And | Scale(_)))
+                    && self.current > 0
+                    && scale < self.last_scale =>
+            {
+                // A number too large for u64 ends before the scale word
+                let Some(total) = self
+                    .current
+                    .checked_mul(scale)
+                    .and_then(|group| group.checked_add(self.total))
+                else {
+                    return false;
+                };
+                self.total = total;
+                self.current = 0;
+                self.last_scale = scale;
+            }
+            And if matches!(self.last, Some(Hundred | Scale(_))) => {}
+            _ => return false,
+        }
+        if word != And {
+            self.words += 1;
+        }
+        self.last = Some(word);
+        true
+    }
+
+    fn value(&self) -> u64 {
+        self.total + self.current
+    }
+
+    /// Whether `words` belong to the number after this one instead: the
+    /// `next` word multiplies them but cannot continue this number ("nine
+    /// hundred | nine hundred", "one thousand | two thousand").
+    fn ends_before(&self, words: &[NumberWord], next: Option<&Token>) -> bool {
+        let Some(multiplier) = next
+            .filter(|t| t.prefix.is_empty())
+            .and_then(|t| t.number_words())
+            .and_then(|w| w.first().copied())
+            .filter(|w| matches!(w, NumberWord::Hundred | NumberWord::Scale(_)))
+        else {
+            return false;
+        };
+
+        let mut continued = *self;
+        for word in words {
+            continued.push(*word);
+        }
+        if continued.push(multiplier) {
+            return false;
+        }
+        let mut fresh = NumberReader::new();
+        words.iter().all(|w| fresh.push(*w)) && fresh.push(multiplier)
+    }
+}
+
+/// A whitespace-separated token split into punctuation and a lowercase core.
+struct Token<'a> {
+    prefix: &'a str,
+    core: String,
+    suffix: &'a str,
+}
+
+impl<'a> Token<'a> {
+    fn new(token: &'a str) -> Self {
+        let start = token
+            .find(|c: char| c.is_alphanumeric())
+            .unwrap_or(token.len());
+        let end = token
+            .rfind(|c: char| c.is_alphanumeric())
+            .map(|i| i + token[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(start);
+        Self {
+            prefix: &token[..start],
+            core: token[start..end].to_lowercase(),
+            suffix: &token[end..],
+        }
+    }
+
+    /// Number words of the token ("twenty-five" is two), or None
+    fn number_words(&self) -> Option<Vec<NumberWord>> {
+        if self.core.is_empty() {
+            return None;
+        }
+        self.core.split('-').map(number_word).collect()
+    }
+}
+
+/// Format a number with thousands separators from 10,000 up (years stay as-is)
+fn format_number(value: u64) -> String {
+    let digits = value.to_string();
+    if value < 10_000 {
+        return digits;
+    }
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Writes spelled-out numbers as digits.
+pub struct NumberFormatting;
+
+impl NumberFormatting {
+    /// Read the longest number starting at `tokens[start]`. Returns the number
+    /// of tokens consumed and the reader, if a number was read.
+    fn read_number(tokens: &[Token], start: usize) -> Option<(usize, NumberReader)> {
+        let mut reader = NumberReader::new();
+        let mut end = start;
+
+        while end < tokens.len() {
+            let token = &tokens[end];
+            // Punctuation before a word ends the number before it
+            if end > start && !token.prefix.is_empty() {
+                break;
+            }
+            let Some(words) = token.number_words() else {
+                break;
+            };
+            if end > start
+                && token.suffix.is_empty()
+                && reader.ends_before(&words, tokens.get(end + 1))
+            {
+                break;
+            }
+
+            let mut next = reader;
+            if !words.iter().all(|w| next.push(*w)) {
+                break;
+            }
+            reader = next;
+            end += 1;
+
+            if !token.suffix.is_empty() {
+                break;
+            }
+            // "one hundred and five"
+            if end + 1 < tokens.len() && tokens[end].core == "and" && tokens[end].suffix.is_empty()
+            {
+                let mut with_and = reader;
+                let continues = with_and.push(NumberWord::And)
+                    && tokens[end + 1].prefix.is_empty()
+                    && tokens[end + 1].number_words().is_some_and(|words| {
+                        !with_and.ends_before(&words, tokens.get(end + 2))
+                            && with_and.push(words[0])
+                    });
+                if continues {
+                    reader.push(NumberWord::And);
+                    end += 1;
+                }
+            }
+        }
+
+        (end > start).then_some((end - start, reader))
+    }
+}
+
+impl TextProcessor for NumberFormatting {
+    fn name(&self) -> &'static str {
+        "number_formatting"
+    }
+
+    fn process(&self, text: &str) -> String {
+        let spans = word_spans(text);
+        let tokens: Vec<Token> = spans.iter().map(|s| Token::new(&text[s.clone()])).collect();
+        let mut out = String::with_capacity(text.len());
+        // End of the text already copied to `out`
+        let mut copied = 0;
+
+        let mut i = 0;
+        while i < tokens.len() {
+            match Self::read_number(&tokens, i) {
+                Some((count, reader)) if reader.words >= 2 || reader.value() >= 10 => {
+                    out.push_str(&text[copied..spans[i].start]);
+                    let last = &tokens[i + count - 1];
+                    let mut number =
+                        format!("{}{}", tokens[i].prefix, format_number(reader.value()));
+                    let mut suffix = last.suffix;
+                    i += count;
+
+                    // "fifty percent" -> "50%"
+                    if suffix.is_empty()
+                        && i < tokens.len()
+                        && tokens[i].core == "percent"
+                        && tokens[i].prefix.is_empty()
+                    {
+                        number.push('%');
+                        suffix = tokens[i].suffix;
+                        i += 1;
+                    }
+                    number.push_str(suffix);
+                    out.push_str(&number);
+                    copied = spans[i - 1].end;
+                }
+                _ => i += 1,
+            }
+        }
+        out.push_str(&text[copied..]);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(text: &str) -> String {
+        NumberFormatting.process(text)
+    }
+
+    #[test]
+    fn spelled_numbers_become_digits() {
+        assert_eq!(format("I need twenty five chairs."), "I need 25 chairs.");
+        assert_eq!(format("thirty seven"), "37");
+        assert_eq!(format("one hundred and five"), "105");
+        assert_eq!(
+            format("It cost two thousand three hundred forty-two dollars"),
+            "It cost 2342 dollars"
+        );
+        assert_eq!(
+            format("about three million people"),
+            "about 3,000,000 people"
+        );
+        assert_eq!(format("Nineteen hundred"), "1900");
+        assert_eq!(format("ninety nine hundred"), "9900");
+        assert_eq!(
+            format("nine hundred ninety nine billion nine hundred ninety nine"),
+            "999,000,000,999"
+        );
+    }
+
+    #[test]
+    fn repeated_hundreds_are_separate_numbers() {
+        assert_eq!(format("nine hundred nine hundred"), "900 900");
+        assert_eq!(
+            format("nine hundred nine hundred nine hundred"),
+            "900 900 900"
+        );
+        assert_eq!(format("two hundred hundred"), "200 hundred");
+        assert_eq!(format("one thousand two thousand"), "1000 2000");
+        assert_eq!(format("one hundred and five hundred"), "100 and 500");
+
+        // Used to overflow the group and panic
+        let text = "nine hundred ".repeat(12);
+        let formatted = format(&text);
+        assert!(
+            formatted.split_whitespace().all(|w| w == "900"),
+            "{}",
+            formatted
+        );
+    }
+
+    #[test]
+    fn small_single_numbers_stay_words() {
+        assert_eq!(
+            format("one of them said two things"),
+            "one of them said two things"
+        );
+        assert_eq!(format("twelve apostles"), "12 apostles");
+    }
+
+    #[test]
+    fn punctuation_and_percent() {
+        assert_eq!(format("fifty percent, maybe."), "50%, maybe.");
+        assert_eq!(format("(twenty one) items"), "(21) items");
+        assert_eq!(format("twenty, five"), "20, five");
+        assert_eq!(format("bread and butter"), "bread and butter");
+        assert_eq!(format("a hundred and one"), "a hundred and one");
+    }
+
+    #[test]
+    fn whitespace_is_kept() {
+        assert_eq!(
+            format("twenty five\nitems,  thirty one\tleft"),
+            "25\nitems,  31\tleft"
+        );
+        assert_eq!(format(" fifty percent\n"), " 50%\n");
+    }
+}
@@ -0,0 +1,284 @@
+//! Word-level text processing stages.
+
+use std::collections::HashSet;
+
+use flowstt_common::normalize_word;
+
+use super::{word_spans, TextProcessor};
+
+/// Abbreviations whose trailing period does not end a sentence
+const ABBREVIATIONS: &[&str] = &["mr.", "mrs.", "ms.", "dr.", "st.", "vs.", "e.g.", "i.e."];
+
+/// Whether a word ends a sentence (ignoring closing quotes and brackets)
+fn ends_sentence(word: &str) -> bool {
+    if ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+        return false;
+    }
+    word.trim_end_matches(['"', '\'', ')', '”', '’'])
+        .ends_with(['.', '?', '!', '…'])
+}
+
+/// Uppercase the first letter of a word, unless it is deliberately mixed-case
+/// (e.g. "iPhone").
+fn capitalize_first(word: &str) -> String {
+    let Some((i, first)) = word.char_indices().find(|(_, c)| c.is_alphabetic()) else {
+        return word.to_string();
+    };
+    let rest = &word[i + first.len_utf8()..];
+    if !first.is_lowercase() || rest.chars().any(char::is_uppercase) {
+        return word.to_string();
+    }
+    let mut capitalized = String::with_capacity(word.len());
+    capitalized.push_str(&word[..i]);
+    capitalized.extend(first.to_uppercase());
+    capitalized.push_str(rest);
+    capitalized
+}
+
+/// Capitalizes sentence starts and the pronoun "I".
+pub struct Capitalization;
+
+impl TextProcessor for Capitalization {
+    fn name(&self) -> &'static str {
+        "capitalization"
+    }
+
+    fn process(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut sentence_start = true;
+
+        for piece in text.split_inclusive(char::is_whitespace) {
+            let word = piece.trim_end();
+            let whitespace = &piece[word.len()..];
+
+            let pronoun = matches!(
+                normalize_word(word).as_str(),
+                "i" | "i'm" | "i've" | "i'll" | "i'd"
+            );
+            if (sentence_start || pronoun) && !word.is_empty() {
+                out.push_str(&capitalize_first(word));
+            } else {
+                out.push_str(word);
+            }
+            out.push_str(whitespace);
+
+            if word.chars().any(char::is_alphanumeric) {
+                sentence_start = false;
+            }
+            if ends_sentence(word) || whitespace.contains('\n') {
+                sentence_start = true;
+            }
+        }
+        out
+    }
+}
+
+/// Removes filler words, keeping the surrounding punctuation sensible.
+pub struct FillerRemoval {
+    words: HashSet<String>,
+}
+
+impl FillerRemoval {
+    /// Create a stage removing `words` (case-insensitive).
+    pub fn new(words: &[String]) -> Self {
+        Self {
+            words: words.iter().map(|w| normalize_word(w)).collect(),
+        }
+    }
+}
+
+impl TextProcessor for FillerRemoval {
+    fn name(&self) -> &'static str {
+        "filler_removal"
+    }
+
+    fn process(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        // Start in `out` of the last kept word, while nothing follows it
+        let mut prev: Option<usize> = None;
+        // Whether a kept word has been written
+        let mut kept_any = false;
+        let mut capitalize_next = false;
+        // Start of the whitespace before the next word, and whether it
+        // belongs to a removed filler
+        let mut gap_start = 0;
+        let mut drop_gap = false;
+
+        for span in word_spans(text) {
+            let token = &text[span.clone()];
+            let gap = &text[gap_start..span.start];
+            gap_start = span.end;
+
+            if !self.words.contains(&normalize_word(token)) {
+                if !drop_gap {
+                    out.push_str(gap);
+                }
+                drop_gap = false;
+                prev = Some(out.len());
+                kept_any = true;
+                if capitalize_next {
+                    out.push_str(&capitalize_first(token));
+                    capitalize_next = false;
+                } else {
+                    out.push_str(token);
+                }
+                continue;
+            }
+
+            // "Um, so..." - the next word now starts the sentence
+            let at_sentence_start = prev.is_none_or(|i| ends_sentence(&out[i..]));
+            if at_sentence_start && token.starts_with(char::is_uppercase) {
+                capitalize_next = true;
+            }
+
+            // "so, uh." - keep the sentence end the filler carried
+            let end: String = token
+                .chars()
+                .rev()
+                .take_while(|c| matches!(c, '.' | '?' | '!'))
+                .collect();
+            if let Some(i) = prev.filter(|i| !end.is_empty() && !ends_sentence(&out[*i..])) {
+                let trimmed = out[i..].trim_end_matches([',', ';', ':']).len();
+                out.truncate(i + trimmed);
+                out.extend(end.chars().rev());
+            }
+
+            // The filler goes with the space before it, unless that is a
+            // line break or the start of the text
+            if !kept_any || gap.contains('\n') {
+                if !drop_gap {
+                    out.push_str(gap);
+                }
+                drop_gap = true;
+                prev = None;
+            }
+        }
+        if !drop_gap {
+            out.push_str(&text[gap_start..]);
+        }
+        out
+    }
+}
+
+/// Typographic quotes, dashes and ellipses, and no space before punctuation.
+pub struct SmartPunctuation;
+
+impl TextProcessor for SmartPunctuation {
+    fn name(&self) -> &'static str {
+        "smart_punctuation"
+    }
+
+    fn process(&self, text: &str) -> String {
+        let text = text.replace("...", "…").replace("--", "—");
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let prev = out.chars().last();
+            let opens = prev.is_none_or(|p| p.is_whitespace() || "([{—".contains(p));
+            match c {
+                '"' => out.push(if opens { '“' } else { '”' }),
+                '\'' => out.push(if opens { '‘' } else { '’' }),
+                ' ' if chars
+                    .peek()
+                    .is_some_and(|next| matches!(next, ',' | '.' | ';' | ':' | '!' | '?')) =>
+                {
+                    // Drop the space in "word ,"
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+}
+
+/// Replaces every occurrence of a string.
+pub struct Replace {
+    find: String,
+    replace: String,
+}
+
+impl Replace {
+    /// Create a stage replacing `find` with `replace`.
+    pub fn new(find: &str, replace: &str) -> Self {
+        Self {
+            find: find.to_string(),
+            replace: replace.to_string(),
+        }
+    }
+}
+
+impl TextProcessor for Replace {
+    fn name(&self) -> &'static str {
+        "replace"
+    }
+
+    fn process(&self, text: &str) -> String {
+        if self.find.is_empty() {
+            return text.to_string();
+        }
+        text.replace(&self.find, &self.replace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::default_filler_words;
+
+    fn remove_fillers(text: &str) -> String {
+        FillerRemoval::new(&default_filler_words()).process(text)
+    }
+
+    #[test]
+    fn capitalization_fixes_sentences_and_pronoun() {
+        assert_eq!(
+            Capitalization.process("hello there. i think it's fine? yes!\nnext line"),
+            "Hello there. I think it's fine? Yes!\nNext line"
+        );
+        assert_eq!(
+            Capitalization.process("ask dr. smith if i'm late. iPhone sales"),
+            "Ask dr. smith if I'm late. iPhone sales"
+        );
+    }
+
+    #[test]
+    fn filler_removal_keeps_punctuation() {
+        assert_eq!(remove_fillers("Um, so we ship it."), "So we ship it.");
+        assert_eq!(
+            remove_fillers("I think, uh, we should wait, um."),
+            "I think, we should wait."
+        );
+        assert_eq!(remove_fillers("Uh. Hmm."), "");
+        assert_eq!(remove_fillers("Umbrella drums"), "Umbrella drums");
+    }
+
+    #[test]
+    fn filler_removal_keeps_line_breaks() {
+        assert_eq!(
+            remove_fillers("First line, um\nsecond  line\n\nuh third"),
+            "First line,\nsecond  line\n\nthird"
+        );
+    }
+
+    #[test]
+    fn smart_punctuation() {
+        assert_eq!(
+            SmartPunctuation.process(r#"She said "it's done" -- wait..."#),
+            "She said “it’s done” — wait…"
+        );
+        assert_eq!(
+            SmartPunctuation.process("Well , 'maybe' !"),
+            "Well, ‘maybe’!"
+        );
+    }
+
+    #[test]
+    fn replace_rule() {
+        let stage = Replace::new("Flow STT", "FlowSTT");
+        assert_eq!(
+            stage.process("Flow STT and Flow STT"),
+            "FlowSTT and FlowSTT"
+        );
+    }
+}
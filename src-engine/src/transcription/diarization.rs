@@ -0,0 +1,699 @@
+//! Speaker diarization for automatic transcription sessions.
+//!
+//! Each transcribed segment is summarized by a speaker embedding computed from
+//! its 16kHz mono audio: the mean and spread of mel-frequency cepstral
+//! coefficients over voiced frames (vocal tract shape) plus the median pitch.
+//! Embeddings are clustered online across the session - a segment joins the
+//! closest known speaker when it is near enough, otherwise it starts a new one.
+//!
+//! Sources are clustered independently, so in speaker-separated mode the
+//! remote channel gets its own speaker IDs distinct from the microphone.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use flowstt_common::Speaker;
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+/// Sample rate of the audio passed to the diarizer (Whisper input rate)
+const SAMPLE_RATE: usize = 16000;
+
+/// Analysis frame length (25ms)
+const FRAME_LEN: usize = 400;
+
+/// Hop between analysis frames (10ms)
+const HOP_LEN: usize = 160;
+
+/// FFT size for the mel filterbank
+const FFT_SIZE: usize = 512;
+
+/// Number of mel filterbank bands
+const NUM_MEL_BANDS: usize = 26;
+
+/// Cepstral coefficients kept (c1..=c12; c0 is loudness and discarded)
+const NUM_CEPSTRA: usize = 12;
+
+/// Lowest and highest filterbank frequencies
+const MEL_LOW_HZ: f32 = 60.0;
+const MEL_HIGH_HZ: f32 = 7600.0;
+
+/// Frames quieter than this relative to the loudest frame are not voiced
+const VOICED_RANGE_DB: f32 = 30.0;
+
+/// Absolute floor for voiced frames (mean power in dBFS)
+const VOICED_FLOOR_DB: f32 = -60.0;
+
+/// Minimum voiced audio for a usable embedding (ms)
+const MIN_VOICED_MS: usize = 300;
+
+/// Pitch search range
+const MIN_PITCH_HZ: usize = 60;
+const MAX_PITCH_HZ: usize = 400;
+
+/// Pitch analysis window (40ms, long enough for two periods at 60Hz)
+const PITCH_WINDOW: usize = 640;
+
+/// Pitch is estimated on every Nth voiced frame (40ms apart)
+const PITCH_FRAME_STEP: usize = 4;
+
+/// Normalized autocorrelation peak required to treat a frame as pitched
+const PITCH_CLARITY: f32 = 0.5;
+
+/// Weight of the pitch dimension relative to the cepstral dimensions
+const PITCH_WEIGHT: f32 = 8.0;
+
+/// Weight of the cepstral spread dimensions relative to the means
+const SPREAD_WEIGHT: f32 = 0.5;
+
+/// Distance below which a segment is attributed to an existing speaker
+const DEFAULT_DISTANCE_THRESHOLD: f32 = 2.0;
+
+/// Maximum speakers tracked per source; further segments join the closest one
+const DEFAULT_MAX_SPEAKERS: usize = 8;
+
+/// Speaker embedding for a segment of audio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerEmbedding(Vec<f32>);
+
+impl SpeakerEmbedding {
+    /// Euclidean distance to another embedding
+    pub fn distance(&self, other: &SpeakerEmbedding) -> f32 {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/// Computes speaker embeddings from 16kHz mono audio.
+pub struct EmbeddingExtractor {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    mel_filters: Vec<Vec<(usize, f32)>>,
+    dct: Vec<Vec<f32>>,
+}
+
+impl EmbeddingExtractor {
+    /// Create an extractor for 16kHz audio
+    pub fn new() -> Self {
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(FFT_SIZE);
+
+        let window = (0..FRAME_LEN)
+            .map(|i| {
+                0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FRAME_LEN - 1) as f32).cos()
+            })
+            .collect();
+
+        // DCT-II rows for c1..=c12
+        let dct = (1..=NUM_CEPSTRA)
+            .map(|k| {
+                (0..NUM_MEL_BANDS)
+                    .map(|n| {
+                        (std::f32::consts::PI * k as f32 * (n as f32 + 0.5) / NUM_MEL_BANDS as f32)
+                            .cos()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            fft,
+            window,
+            mel_filters: build_mel_filters(),
+            dct,
+        }
+    }
+
+    /// Compute the embedding of a segment, or None if it has too little voiced audio
+    pub fn embed(&self, samples: &[f32]) -> Option<SpeakerEmbedding> {
+        if samples.len() < FRAME_LEN {
+            return None;
+        }
+
+        // Pre-emphasis flattens the spectral tilt of voiced speech
+        let emphasized: Vec<f32> = std::iter::once(samples[0])
+            .chain(samples.windows(2).map(|w| w[1] - 0.97 * w[0]))
+            .collect();
+
+        let num_frames = (samples.len() - FRAME_LEN) / HOP_LEN + 1;
+        let mut frame_power_db = Vec::with_capacity(num_frames);
+        let mut frame_cepstra = Vec::with_capacity(num_frames);
+
+        let mut buffer = vec![Complex::new(0.0f32, 0.0); FFT_SIZE];
+        for frame_idx in 0..num_frames {
+            let start = frame_idx * HOP_LEN;
+            let frame = &samples[start..start + FRAME_LEN];
+            let power = frame.iter().map(|s| s * s).sum::<f32>() / FRAME_LEN as f32;
+            frame_power_db.push(10.0 * power.max(1e-12).log10());
+
+            for (i, slot) in buffer.iter_mut().enumerate() {
+                *slot = if i < FRAME_LEN {
+                    Complex::new(emphasized[start + i] * self.window[i], 0.0)
+                } else {
+                    Complex::new(0.0, 0.0)
+                };
+            }
+            self.fft.process(&mut buffer);
+
+            let log_mel: Vec<f32> = self
+                .mel_filters
+                .iter()
+                .map(|filter| {
+                    let energy: f32 = filter
+                        .iter()
+                        .map(|&(bin, weight)| buffer[bin].norm_sqr() * weight)
+                        .sum();
+                    energy.max(1e-10).ln()
+                })
+                .collect();
+
+            let cepstra: Vec<f32> = self
+                .dct
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(log_mel.iter())
+                        .map(|(c, m)| c * m)
+                        .sum::<f32>()
+                        / NUM_MEL_BANDS as f32
+                })
+                .collect();
+            frame_cepstra.push(cepstra);
+        }
+
+        // Keep frames near the loudest one (voiced speech)
+        let max_db = frame_power_db
+            .iter()
+            .cloned()
+            .fold(f32::NEG_INFINITY, f32::max);
+        let voiced: Vec<usize> = (0..num_frames)
+            .filter(|&i| {
+                frame_power_db[i] > max_db - VOICED_RANGE_DB && frame_power_db[i] > VOICED_FLOOR_DB
+            })
+            .collect();
+
+        let voiced_ms = voiced.len() * HOP_LEN * 1000 / SAMPLE_RATE;
+        if voiced_ms < MIN_VOICED_MS {
+            return None;
+        }
+
+        // Mean and spread of each cepstral coefficient over voiced frames
+        let count = voiced.len() as f32;
+        let mut mean = [0.0f32; NUM_CEPSTRA];
+        for &i in &voiced {
+            for (m, c) in mean.iter_mut().zip(frame_cepstra[i].iter()) {
+                *m += c / count;
+            }
+        }
+        let mut spread = [0.0f32; NUM_CEPSTRA];
+        for &i in &voiced {
+            for ((s, c), m) in spread
+                .iter_mut()
+                .zip(frame_cepstra[i].iter())
+                .zip(mean.iter())
+            {
+                *s += (c - m) * (c - m) / count;
+            }
+        }
+
+        // Median pitch over pitched frames, as octaves relative to 100Hz
+        let mut pitches: Vec<f32> = voiced
+            .iter()
+            .step_by(PITCH_FRAME_STEP)
+            .filter_map(|&i| estimate_pitch(samples, i * HOP_LEN))
+            .collect();
+        let pitch_octaves = if pitches.is_empty() {
+            0.0
+        } else {
+            pitches.sort_by(|a, b| a.total_cmp(b));
+            (pitches[pitches.len() / 2] / 100.0).log2()
+        };
+
+        let mut embedding = Vec::with_capacity(NUM_CEPSTRA * 2 + 1);
+        embedding.extend_from_slice(&mean);
+        embedding.extend(spread.iter().map(|v| v.sqrt() * SPREAD_WEIGHT));
+        embedding.push(pitch_octaves * PITCH_WEIGHT);
+
+        Some(SpeakerEmbedding(embedding))
+    }
+}
+
+impl Default for EmbeddingExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Triangular mel filters as (FFT bin, weight) lists
+fn build_mel_filters() -> Vec<Vec<(usize, f32)>> {
+    let hz_to_mel = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+    let mel_to_hz = |mel: f32| 700.0 * (10f32.powf(mel / 2595.0) - 1.0);
+
+    let low = hz_to_mel(MEL_LOW_HZ);
+    let high = hz_to_mel(MEL_HIGH_HZ);
+    let bin_hz = SAMPLE_RATE as f32 / FFT_SIZE as f32;
+    let edges: Vec<f32> = (0..NUM_MEL_BANDS + 2)
+        .map(|i| mel_to_hz(low + (high - low) * i as f32 / (NUM_MEL_BANDS + 1) as f32) / bin_hz)
+        .collect();
+
+    (0..NUM_MEL_BANDS)
+        .map(|band| {
+            let (left, center, right) = (edges[band], edges[band + 1], edges[band + 2]);
+            (left.floor() as usize..=right.ceil() as usize)
+                .filter_map(|bin| {
+                    let pos = bin as f32;
+                    let weight = if pos < center {
+                        (pos - left) / (center - left)
+                    } else {
+                        (right - pos) / (right - center)
+                    };
+                    (weight > 0.0).then_some((bin, weight))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Estimate the pitch of the window starting at `start` by normalized autocorrelation
+fn estimate_pitch(samples: &[f32], start: usize) -> Option<f32> {
+    let window = samples.get(start..start + PITCH_WINDOW)?;
+    let min_lag = SAMPLE_RATE / MAX_PITCH_HZ;
+    let max_lag = (SAMPLE_RATE / MIN_PITCH_HZ).min(PITCH_WINDOW / 2);
+
+    let correlation: Vec<f32> = (min_lag..=max_lag)
+        .map(|lag| {
+            let (a, b) = (&window[..PITCH_WINDOW - lag], &window[lag..]);
+            let cross: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+            let energy =
+                a.iter().map(|x| x * x).sum::<f32>() * b.iter().map(|y| y * y).sum::<f32>();
+            if energy > 0.0 {
+                cross / energy.sqrt()
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let best = correlation.iter().cloned().fold(0.0f32, f32::max);
+    if best < PITCH_CLARITY {
+        return None;
+    }
+
+    // The first peak close to the best one is the fundamental; later peaks
+    // at multiples of the period would give octave errors
+    (1..correlation.len() - 1)
+        .find(|&i| {
+            correlation[i] >= 0.9 * best
+                && correlation[i] >= correlation[i - 1]
+                && correlation[i] >= correlation[i + 1]
+        })
+        .map(|i| SAMPLE_RATE as f32 / (min_lag + i) as f32)
+}
+
+/// A speaker discovered during the session
+struct SpeakerCluster {
+    /// 1-based speaker ID
+    id: u32,
+    /// Running mean of the member embeddings
+    centroid: SpeakerEmbedding,
+    /// Number of segments assigned
+    count: usize,
+}
+
+/// Online clustering of speaker embeddings across a session.
+pub struct Diarizer {
+    extractor: EmbeddingExtractor,
+    /// Speakers per source (None = mixed/single-source recording)
+    clusters: HashMap<Option<Speaker>, Vec<SpeakerCluster>>,
+    distance_threshold: f32,
+    max_speakers: usize,
+}
+
+impl Diarizer {
+    /// Create a diarizer with default clustering parameters
+    pub fn new() -> Self {
+        Self::with_params(DEFAULT_DISTANCE_THRESHOLD, DEFAULT_MAX_SPEAKERS)
+    }
+
+    /// Create a diarizer with a custom distance threshold and speaker limit
+    pub fn with_params(distance_threshold: f32, max_speakers: usize) -> Self {
+        Self {
+            extractor: EmbeddingExtractor::new(),
+            clusters: HashMap::new(),
+            distance_threshold,
+            max_speakers: max_speakers.max(1),
+        }
+    }
+
+    /// Forget all speakers (start of a new session)
+    pub fn reset(&mut self) {
+        self.clusters.clear();
+    }
+
+    /// Number of speakers discovered for a source
+    pub fn speaker_count(&self, source: Option<Speaker>) -> usize {
+        self.clusters.get(&source).map_or(0, |c| c.len())
+    }
+
+    /// Attribute a segment of 16kHz mono audio to a speaker.
+    /// Returns the 1-based speaker ID, or None if the segment has too little
+    /// voiced audio to tell.
+    pub fn assign(&mut self, source: Option<Speaker>, samples: &[f32]) -> Option<u32> {
+        let embedding = self.extractor.embed(samples)?;
+        Some(self.assign_embedding(source, &embedding))
+    }
+
+    /// Attribute an embedding to the closest speaker, creating one if none is close enough
+    pub fn assign_embedding(
+        &mut self,
+        source: Option<Speaker>,
+        embedding: &SpeakerEmbedding,
+    ) -> u32 {
+        let clusters = self.clusters.entry(source).or_default();
+
+        let closest = clusters
+            .iter()
+            .enumerate()
+            .map(|(idx, c)| (idx, embedding.distance(&c.centroid)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match closest {
+            Some((idx, distance))
+                if distance <= self.distance_threshold || clusters.len() >= self.max_speakers =>
+            {
+                let cluster = &mut clusters[idx];
+                cluster.count += 1;
+                let weight = 1.0 / cluster.count as f32;
+                for (c, e) in cluster.centroid.0.iter_mut().zip(embedding.0.iter()) {
+                    *c += (e - *c) * weight;
+                }
+                tracing::debug!(
+                    "[Diarizer] Segment assigned to speaker {} (distance {:.2})",
+                    cluster.id,
+                    distance
+                );
+                cluster.id
+            }
+            _ => {
+                let id = clusters.len() as u32 + 1;
+                clusters.push(SpeakerCluster {
+                    id,
+                    centroid: embedding.clone(),
+                    count: 1,
+                });
+                tracing::debug!("[Diarizer] New speaker {} ({:?} source)", id, source);
+                id
+            }
+        }
+    }
+}
+
+impl Default for Diarizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{process_recorded_audio, save_to_wav, RawRecordedAudio};
+    use std::path::{Path, PathBuf};
+
+    /// Capture format used for the fixtures (matches live recordings)
+    const FIXTURE_RATE: u32 = 48000;
+    const FIXTURE_CHANNELS: u16 = 2;
+
+    /// Deterministic pseudo-random generator so fixtures are reproducible
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next_f32(&mut self) -> f32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+    }
+
+    /// A synthetic voice: glottal pulse train through vowel formant resonators
+    struct Voice {
+        pitch_hz: f32,
+        /// Vocal tract length factor applied to all formants
+        formant_scale: f32,
+    }
+
+    const MALE: Voice = Voice {
+        pitch_hz: 110.0,
+        formant_scale: 1.0,
+    };
+    const FEMALE: Voice = Voice {
+        pitch_hz: 210.0,
+        formant_scale: 1.17,
+    };
+    /// Similar pitch to MALE but a shorter vocal tract
+    const TENOR: Voice = Voice {
+        pitch_hz: 135.0,
+        formant_scale: 1.12,
+    };
+
+    /// Formants (F1, F2, F3) of a few vowels for an adult male vocal tract
+    const VOWELS: [(f32, f32, f32); 5] = [
+        (730.0, 1090.0, 2440.0),
+        (270.0, 2290.0, 3010.0),
+        (300.0, 870.0, 2240.0),
+        (530.0, 1840.0, 2480.0),
+        (660.0, 1720.0, 2410.0),
+    ];
+
+    /// Synthesize mono speech-like audio: syllables of random vowels with
+    /// pitch jitter and short pauses between them
+    fn synthesize(voice: &Voice, duration_ms: u32, rng: &mut Lcg) -> Vec<f32> {
+        let rate = FIXTURE_RATE as f32;
+        let total = (duration_ms as f32 / 1000.0 * rate) as usize;
+        let mut output = Vec::with_capacity(total);
+
+        while output.len() < total {
+            let syllable_len = ((0.15 + 0.15 * rng.next_f32()) * rate) as usize;
+            let pause_len = ((0.02 + 0.04 * rng.next_f32()) * rate) as usize;
+            let (f1, f2, f3) = VOWELS[(rng.next_f32() * VOWELS.len() as f32) as usize];
+            let pitch = voice.pitch_hz * (0.92 + 0.16 * rng.next_f32());
+
+            let mut resonators: Vec<Resonator> = [(f1, 80.0), (f2, 100.0), (f3, 120.0)]
+                .iter()
+                .map(|&(f, bw)| Resonator::new(f * voice.formant_scale, bw, rate))
+                .collect();
+
+            let mut phase = 0.0f32;
+            for i in 0..syllable_len {
+                // Slow pitch drift within the syllable
+                let f0 = pitch * (1.0 + 0.03 * (i as f32 / syllable_len as f32 - 0.5));
+                phase += f0 / rate;
+                let mut sample = if phase >= 1.0 {
+                    phase -= 1.0;
+                    1.0
+                } else {
+                    0.0
+                };
+                sample += 0.02 * (rng.next_f32() - 0.5);
+                for r in resonators.iter_mut() {
+                    sample = r.process(sample);
+                }
+                let envelope = (std::f32::consts::PI * i as f32 / syllable_len as f32).sin();
+                output.push(sample * envelope);
+            }
+            output.extend(std::iter::repeat_n(0.0, pause_len));
+        }
+        output.truncate(total);
+
+        let peak = output.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        if peak > 0.0 {
+            output.iter_mut().for_each(|s| *s *= 0.5 / peak);
+        }
+        output
+    }
+
+    /// Two-pole resonator modelling one formant
+    struct Resonator {
+        a1: f32,
+        a2: f32,
+        gain: f32,
+        y1: f32,
+        y2: f32,
+    }
+
+    impl Resonator {
+        fn new(freq: f32, bandwidth: f32, rate: f32) -> Self {
+            let r = (-std::f32::consts::PI * bandwidth / rate).exp();
+            let theta = 2.0 * std::f32::consts::PI * freq / rate;
+            Self {
+                a1: 2.0 * r * theta.cos(),
+                a2: -r * r,
+                gain: 1.0 - r,
+                y1: 0.0,
+                y2: 0.0,
+            }
+        }
+
+        fn process(&mut self, x: f32) -> f32 {
+            let y = self.gain * x + self.a1 * self.y1 + self.a2 * self.y2;
+            self.y2 = self.y1;
+            self.y1 = y;
+            y
+        }
+    }
+
+    /// Write a conversation fixture to a stereo WAV file, returning the
+    /// ground-truth (speaker index, start frame, end frame) of each turn
+    fn write_conversation(
+        path: &Path,
+        voices: &[&Voice],
+        turns: &[(usize, u32)],
+        seed: u64,
+    ) -> Vec<(usize, usize, usize)> {
+        let mut rng = Lcg(seed);
+        let mut mono = Vec::new();
+        let mut truth = Vec::new();
+        for &(speaker, duration_ms) in turns {
+            let start = mono.len();
+            mono.extend(synthesize(voices[speaker], duration_ms, &mut rng));
+            truth.push((speaker, start, mono.len()));
+        }
+
+        let interleaved: Vec<f32> = mono
+            .iter()
+            .flat_map(|&s| std::iter::repeat_n(s, FIXTURE_CHANNELS as usize))
+            .collect();
+        save_to_wav(&interleaved, FIXTURE_RATE, FIXTURE_CHANNELS, path).unwrap();
+        truth
+    }
+
+    /// Read a fixture back, cut it into the given turns and convert each to
+    /// the 16kHz mono format the transcription queue produces
+    fn load_segments(path: &Path, truth: &[(usize, usize, usize)]) -> Vec<Vec<f32>> {
+        let mut reader = hound::WavReader::open(path).unwrap();
+        let spec = reader.spec();
+        let samples: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        let channels = spec.channels as usize;
+
+        truth
+            .iter()
+            .map(|&(_, start, end)| {
+                let raw = RawRecordedAudio {
+                    samples: samples[start * channels..end * channels].to_vec(),
+                    sample_rate: spec.sample_rate,
+                    channels: spec.channels,
+                };
+                process_recorded_audio(raw, None).unwrap()
+            })
+            .collect()
+    }
+
+    fn fixture_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("flowstt-diarization-fixtures");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    /// Diarize a fixture and return (speakers found, purity of the clustering)
+    fn evaluate(name: &str, voices: &[&Voice], turns: &[(usize, u32)], seed: u64) -> (usize, f32) {
+        let path = fixture_path(name);
+        let truth = write_conversation(&path, voices, turns, seed);
+        let segments = load_segments(&path, &truth);
+        let _ = std::fs::remove_file(&path);
+
+        let mut diarizer = Diarizer::new();
+        let predicted: Vec<u32> = segments
+            .iter()
+            .map(|s| diarizer.assign(None, s).expect("segment should be voiced"))
+            .collect();
+
+        // Purity: each predicted speaker is credited with its majority true speaker
+        let mut correct = 0;
+        for id in 1..=diarizer.speaker_count(None) as u32 {
+            let mut counts = vec![0; voices.len()];
+            for (p, t) in predicted.iter().zip(truth.iter()) {
+                if *p == id {
+                    counts[t.0] += 1;
+                }
+            }
+            correct += counts.iter().max().copied().unwrap_or(0);
+        }
+
+        (
+            diarizer.speaker_count(None),
+            correct as f32 / predicted.len() as f32,
+        )
+    }
+
+    /// Alternating turns of varied length between speakers 0 and 1
+    const TWO_SPEAKER_TURNS: [(usize, u32); 12] = [
+        (0, 2400),
+        (1, 1800),
+        (0, 1200),
+        (1, 2800),
+        (1, 900),
+        (0, 3000),
+        (1, 1500),
+        (0, 800),
+        (0, 2000),
+        (1, 2200),
+        (0, 1600),
+        (1, 1100),
+    ];
+
+    #[test]
+    fn test_two_speakers_male_female() {
+        let (speakers, purity) =
+            evaluate("male_female.wav", &[&MALE, &FEMALE], &TWO_SPEAKER_TURNS, 1);
+        assert_eq!(speakers, 2, "expected two speakers");
+        assert!(purity >= 0.9, "purity {:.2} below 0.9", purity);
+    }
+
+    #[test]
+    fn test_two_speakers_similar_pitch() {
+        let (speakers, purity) =
+            evaluate("male_tenor.wav", &[&MALE, &TENOR], &TWO_SPEAKER_TURNS, 2);
+        assert_eq!(speakers, 2, "expected two speakers");
+        assert!(purity >= 0.9, "purity {:.2} below 0.9", purity);
+    }
+
+    #[test]
+    fn test_single_speaker_stays_one_cluster() {
+        let turns: Vec<(usize, u32)> = TWO_SPEAKER_TURNS.iter().map(|&(_, d)| (0, d)).collect();
+        let (speakers, _) = evaluate("single.wav", &[&FEMALE], &turns, 3);
+        assert_eq!(speakers, 1, "one voice should not be split");
+    }
+
+    #[test]
+    fn test_silence_has_no_embedding() {
+        let extractor = EmbeddingExtractor::new();
+        assert!(extractor.embed(&vec![0.0; 16000]).is_none());
+        assert!(extractor.embed(&[0.1; 100]).is_none());
+    }
+
+    #[test]
+    fn test_sources_are_clustered_independently() {
+        let mut rng = Lcg(4);
+        let male = synthesize(&MALE, 2000, &mut rng);
+        let raw = RawRecordedAudio {
+            samples: male,
+            sample_rate: FIXTURE_RATE,
+            channels: 1,
+        };
+        let audio = process_recorded_audio(raw, None).unwrap();
+
+        let mut diarizer = Diarizer::new();
+        assert_eq!(diarizer.assign(Some(Speaker::Me), &audio), Some(1));
+        assert_eq!(diarizer.assign(Some(Speaker::Remote), &audio), Some(1));
+        assert_eq!(diarizer.speaker_count(Some(Speaker::Me)), 1);
+        assert_eq!(diarizer.speaker_count(Some(Speaker::Remote)), 1);
+
+        diarizer.reset();
+        assert_eq!(diarizer.speaker_count(Some(Speaker::Me)), 0);
+    }
+}
@@ -8,13 +8,21 @@
 //! - [`transcriber`]: High-level transcription API
 //! - [`queue`]: Async transcription queue with worker thread
 //! - [`transcribe_state`]: State management for continuous transcription mode
+//! - [`diarization`]: Speaker embedding clustering for automatic sessions
+//! - [`stitching`]: Joining the overlapping windows of long-form utterances
+//! - [`wake_word`]: Wake phrase gate for Automatic mode pasting
 
+pub mod diarization;
 pub mod queue;
+pub mod stitching;
 pub mod transcribe_state;
 pub mod transcriber;
+pub mod wake_word;
 pub mod whisper_ffi;
 
 // Re-export main types
-pub use queue::{TranscriptionCallback, TranscriptionQueue};
+pub use queue::{TranscriptionCallback, TranscriptionJob, TranscriptionQueue};
 pub use transcribe_state::TranscribeState;
-pub use transcriber::{download_model, Transcriber};
+pub use transcriber::{
+    download_model, TimedSegment, TranscribeOptions, Transcriber, WHISPER_LANGUAGES,
+};
@@ -1,20 +1,28 @@
 //! Transcription queue for async processing.
 //!
 //! This module provides a bounded queue for audio segments awaiting transcription,
-//! with a worker thread that processes segments sequentially.
+//! with a worker thread that processes segments sequentially. Audio submitted
+//! by clients (files or raw PCM) is queued as jobs so it shares the loaded
+//! model with live dictation.
 
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 
+use flowstt_common::{HotkeyCombination, Speaker, TextProcessingConfig, TranscriptionMode};
+
 use crate::audio::{process_recorded_audio, RawRecordedAudio};
+use crate::text_processing::TextPipelines;
 
-use super::Transcriber;
+use super::diarization::Diarizer;
+use super::stitching::UtteranceStitcher;
+use super::wake_word::WakeWordGate;
+use super::{TimedSegment, TranscribeOptions, Transcriber};
 
-/// Maximum queue size for transcription segments
-const MAX_QUEUE_SIZE: usize = 10;
+/// Default maximum queue size for transcription segments
+const DEFAULT_MAX_QUEUE_SIZE: usize = 10;
 
 /// A segment of audio queued for transcription.
 pub struct QueuedSegment {
@@ -26,6 +34,61 @@ pub struct QueuedSegment {
     pub channels: u16,
     /// Path to saved WAV file (if saved)
     pub wav_path: Option<PathBuf>,
+    /// Speaker whose audio this is (speaker-separated mode only)
+    pub speaker: Option<Speaker>,
+    /// Whether to attribute the segment to a diarized speaker
+    pub diarize: bool,
+    /// Set when the segment is one window of a long-form utterance
+    pub utterance: Option<UtterancePart>,
+    /// Transcription mode the segment was recorded in
+    pub mode: TranscriptionMode,
+    /// Hotkey binding the segment was recorded with, if hotkey-driven
+    pub hotkey: Option<HotkeyCombination>,
+    /// Set for API jobs, whose result goes back to the requester instead of
+    /// the callback (no history, events or pasting)
+    pub job: Option<TranscriptionJob>,
+}
+
+impl QueuedSegment {
+    /// A segment for audio submitted by a client, transcribed as `job`.
+    pub fn for_job(audio: RawRecordedAudio, job: TranscriptionJob) -> Self {
+        Self {
+            samples: audio.samples,
+            sample_rate: audio.sample_rate,
+            channels: audio.channels,
+            wav_path: None,
+            speaker: None,
+            diarize: false,
+            utterance: None,
+            // Not used for jobs
+            mode: TranscriptionMode::default(),
+            hotkey: None,
+            job: Some(job),
+        }
+    }
+}
+
+/// Take the next segment to transcribe: live segments in order, then API jobs.
+fn pop_next(queue: &mut VecDeque<QueuedSegment>) -> Option<QueuedSegment> {
+    let next = queue.iter().position(|s| s.job.is_none()).unwrap_or(0);
+    queue.remove(next)
+}
+
+/// A transcription of client-submitted audio requested through the API.
+pub struct TranscriptionJob {
+    /// Language and prompt for the transcription
+    pub options: TranscribeOptions,
+    /// Receives the timed segments, or an error
+    pub reply: tokio::sync::oneshot::Sender<Result<Vec<TimedSegment>, String>>,
+}
+
+/// Position of a segment within a long-form utterance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtterancePart {
+    /// Utterance the window belongs to
+    pub utterance_id: u64,
+    /// Whether this is the last window of the utterance
+    pub is_final: bool,
 }
 
 /// Callback trait for transcription events.
@@ -36,7 +99,18 @@ pub trait TranscriptionCallback: Send + Sync + 'static {
     fn on_transcription_started(&self);
 
     /// Called when transcription completes successfully.
-    fn on_transcription_complete(&self, text: String, wav_path: Option<String>);
+    /// `speaker` is set when the segment came from speaker-separated recording,
+    /// `speaker_id` when the segment was attributed to a diarized speaker.
+    /// `paste` is false when the text must not be pasted (e.g. the wake word
+    /// was not spoken).
+    fn on_transcription_complete(
+        &self,
+        text: String,
+        wav_path: Option<String>,
+        speaker: Option<Speaker>,
+        speaker_id: Option<u32>,
+        paste: bool,
+    );
 
     /// Called when transcription fails.
     fn on_transcription_error(&self, error: String);
@@ -52,12 +126,20 @@ pub trait TranscriptionCallback: Send + Sync + 'static {
 pub struct TranscriptionQueue {
     /// The queue of segments
     queue: Arc<Mutex<VecDeque<QueuedSegment>>>,
+    /// Signalled when a segment is queued or the worker is stopped
+    available: Arc<Condvar>,
     /// Flag indicating worker should continue running
     worker_active: Arc<AtomicBool>,
     /// Count of segments currently in queue
     queue_count: Arc<AtomicUsize>,
+    /// Maximum number of segments waiting in the queue
+    max_queue_size: AtomicUsize,
     /// Callback for transcription events
     callback: Arc<Mutex<Option<Arc<dyn TranscriptionCallback>>>>,
+    /// Speakers discovered in the current session
+    diarizer: Arc<Mutex<Diarizer>>,
+    /// Text post-processing and wake-word gate
+    post_processing: Arc<Mutex<PostProcessing>>,
 }
 
 impl TranscriptionQueue {
@@ -65,9 +147,13 @@ impl TranscriptionQueue {
     pub fn new() -> Self {
         Self {
             queue: Arc::new(Mutex::new(VecDeque::new())),
+            available: Arc::new(Condvar::new()),
             worker_active: Arc::new(AtomicBool::new(false)),
             queue_count: Arc::new(AtomicUsize::new(0)),
+            max_queue_size: AtomicUsize::new(DEFAULT_MAX_QUEUE_SIZE),
             callback: Arc::new(Mutex::new(None)),
+            diarizer: Arc::new(Mutex::new(Diarizer::new())),
+            post_processing: Arc::new(Mutex::new(PostProcessing::default())),
         }
     }
 
@@ -81,27 +167,57 @@ impl TranscriptionQueue {
         *self.callback.lock().unwrap() = None;
     }
 
+    /// Forget the speakers of the previous session.
+    pub fn reset_speakers(&self) {
+        self.diarizer.lock().unwrap().reset();
+    }
+
+    /// Set the wake phrase for Automatic mode, or None to paste everything.
+    pub fn set_wake_word(&self, phrase: Option<&str>) {
+        self.post_processing.lock().unwrap().wake_word = phrase.and_then(WakeWordGate::new);
+    }
+
+    /// Set the text post-processing pipeline.
+    pub fn set_text_processing(&self, config: &TextProcessingConfig) {
+        self.post_processing.lock().unwrap().pipelines = TextPipelines::new(config);
+    }
+
     /// Get the current queue depth.
     pub fn queue_depth(&self) -> usize {
         self.queue_count.load(Ordering::SeqCst)
     }
 
+    /// Set the maximum number of segments waiting in the queue.
+    /// Segments already queued beyond a lowered limit are still processed.
+    pub fn set_max_queue_size(&self, max_queue_size: usize) {
+        self.max_queue_size.store(max_queue_size, Ordering::SeqCst);
+    }
+
     /// Check if the worker is active.
     pub fn is_worker_active(&self) -> bool {
         self.worker_active.load(Ordering::SeqCst)
     }
 
     /// Enqueue a segment for transcription.
-    /// Returns false if queue is full (segment was not added).
+    /// Returns false if queue is full (segment was not added). API jobs may
+    /// only take half of the queue, leaving the rest to live dictation.
     pub fn enqueue(&self, segment: QueuedSegment) -> bool {
         let mut queue = self.queue.lock().unwrap();
-        if queue.len() >= MAX_QUEUE_SIZE {
+        let max_queue_size = self.max_queue_size.load(Ordering::SeqCst);
+        if queue.len() >= max_queue_size {
             // Queue is full, don't add
             return false;
         }
+        if segment.job.is_some() {
+            let jobs = queue.iter().filter(|s| s.job.is_some()).count();
+            if jobs >= max_queue_size.div_ceil(2) {
+                return false;
+            }
+        }
         queue.push_back(segment);
         let depth = queue.len();
         self.queue_count.store(depth, Ordering::SeqCst);
+        self.available.notify_one();
 
         // Notify callback of queue update
         if let Some(ref cb) = *self.callback.lock().unwrap() {
@@ -111,6 +227,14 @@ impl TranscriptionQueue {
         true
     }
 
+    /// Report a captured segment that could not be transcribed to the
+    /// callback.
+    pub fn report_error(&self, error: String) {
+        if let Some(ref cb) = *self.callback.lock().unwrap() {
+            cb.on_transcription_error(error);
+        }
+    }
+
     /// Start the transcription worker thread.
     pub fn start_worker(&self, model_path: PathBuf) {
         if self.worker_active.load(Ordering::SeqCst) {
@@ -120,12 +244,16 @@ impl TranscriptionQueue {
         self.worker_active.store(true, Ordering::SeqCst);
 
         let queue = Arc::clone(&self.queue);
+        let available = Arc::clone(&self.available);
         let worker_active = Arc::clone(&self.worker_active);
         let queue_count = Arc::clone(&self.queue_count);
         let callback = Arc::clone(&self.callback);
+        let diarizer = Arc::clone(&self.diarizer);
+        let post_processing = Arc::clone(&self.post_processing);
 
         thread::spawn(move || {
             let mut transcriber = Transcriber::new();
+            let mut stitcher = UtteranceStitcher::new();
 
             // Try to load model at start
             if model_path.exists() {
@@ -135,23 +263,14 @@ impl TranscriptionQueue {
             }
 
             loop {
-                // Check if we should stop
-                if !worker_active.load(Ordering::SeqCst) {
-                    // Drain remaining queue before exiting
-                    let remaining = {
-                        let q = queue.lock().unwrap();
-                        q.len()
-                    };
-                    if remaining == 0 {
-                        break;
-                    }
-                    // Continue processing remaining items
-                }
-
-                // Try to get a segment from queue
+                // Sleep until a segment is queued. Once stopped, keep draining
+                // the remaining segments and exit when the queue is empty.
                 let segment = {
                     let mut q = queue.lock().unwrap();
-                    let seg = q.pop_front();
+                    while q.is_empty() && worker_active.load(Ordering::SeqCst) {
+                        q = available.wait(q).unwrap();
+                    }
+                    let seg = pop_next(&mut q);
                     let depth = q.len();
                     queue_count.store(depth, Ordering::SeqCst);
 
@@ -166,10 +285,34 @@ impl TranscriptionQueue {
                 };
 
                 match segment {
-                    Some(seg) => {
+                    Some(mut seg) => {
+                        if let Some(job) = seg.job.take() {
+                            run_job(&mut transcriber, seg, job);
+                            continue;
+                        }
+
+                        // Final window of a long-form utterance with nothing
+                        // left to transcribe - just complete the utterance
+                        if seg.samples.is_empty() {
+                            let wav_path_str = seg
+                                .wav_path
+                                .as_ref()
+                                .map(|p| p.to_string_lossy().to_string());
+                            complete_segment(
+                                &callback,
+                                &post_processing,
+                                &mut stitcher,
+                                &seg,
+                                String::new(),
+                                wav_path_str,
+                                None,
+                            );
+                            continue;
+                        }
+
                         // Process the segment
                         let raw_audio = RawRecordedAudio {
-                            samples: seg.samples,
+                            samples: std::mem::take(&mut seg.samples),
                             sample_rate: seg.sample_rate,
                             channels: seg.channels,
                         };
@@ -179,9 +322,22 @@ impl TranscriptionQueue {
                             .as_ref()
                             .map(|p| p.to_string_lossy().to_string());
 
+                        // Normalize loudness when gain control is enabled
+                        let gain_settings = crate::gain::current_gain_settings();
+                        let normalize_target_db = gain_settings
+                            .enabled
+                            .then_some(gain_settings.target_level_db);
+
                         // Convert to format suitable for Whisper
-                        match process_recorded_audio(raw_audio) {
+                        match process_recorded_audio(raw_audio, normalize_target_db) {
                             Ok(processed) => {
+                                // Attribute the segment to a speaker
+                                let speaker_id = if seg.diarize {
+                                    diarizer.lock().unwrap().assign(seg.speaker, &processed)
+                                } else {
+                                    None
+                                };
+
                                 // Notify that transcription is starting
                                 if let Some(ref cb) = *callback.lock().unwrap() {
                                     cb.on_transcription_started();
@@ -190,14 +346,32 @@ impl TranscriptionQueue {
                                 // Transcribe
                                 match transcriber.transcribe(&processed) {
                                     Ok(text) => {
-                                        if let Some(ref cb) = *callback.lock().unwrap() {
-                                            cb.on_transcription_complete(text, wav_path_str);
-                                        }
+                                        complete_segment(
+                                            &callback,
+                                            &post_processing,
+                                            &mut stitcher,
+                                            &seg,
+                                            text,
+                                            wav_path_str,
+                                            speaker_id,
+                                        );
                                     }
                                     Err(e) => {
                                         if let Some(ref cb) = *callback.lock().unwrap() {
                                             cb.on_transcription_error(e);
                                         }
+                                        // Keep the rest of a long-form utterance
+                                        if seg.utterance.is_some() {
+                                            complete_segment(
+                                                &callback,
+                                                &post_processing,
+                                                &mut stitcher,
+                                                &seg,
+                                                String::new(),
+                                                wav_path_str,
+                                                speaker_id,
+                                            );
+                                        }
                                     }
                                 }
 
@@ -210,13 +384,22 @@ impl TranscriptionQueue {
                                 if let Some(ref cb) = *callback.lock().unwrap() {
                                     cb.on_transcription_error(e);
                                 }
+                                if seg.utterance.is_some() {
+                                    complete_segment(
+                                        &callback,
+                                        &post_processing,
+                                        &mut stitcher,
+                                        &seg,
+                                        String::new(),
+                                        wav_path_str,
+                                        None,
+                                    );
+                                }
                             }
                         }
                     }
-                    None => {
-                        // No segment available, sleep briefly
-                        thread::sleep(std::time::Duration::from_millis(50));
-                    }
+                    // Stopped and fully drained
+                    None => break,
                 }
             }
 
@@ -226,7 +409,11 @@ impl TranscriptionQueue {
 
     /// Stop the transcription worker (will drain remaining queue).
     pub fn stop_worker(&self) {
+        // Hold the queue lock so the worker cannot miss the wakeup between
+        // checking the flag and waiting
+        let _queue = self.queue.lock().unwrap();
         self.worker_active.store(false, Ordering::SeqCst);
+        self.available.notify_all();
     }
 
     /// Clear the queue (discard pending segments).
@@ -242,8 +429,142 @@ impl TranscriptionQueue {
     }
 }
 
+/// Transcribe an API job and send the result back to the requester.
+fn run_job(transcriber: &mut Transcriber, seg: QueuedSegment, job: TranscriptionJob) {
+    let raw_audio = RawRecordedAudio {
+        samples: seg.samples,
+        sample_rate: seg.sample_rate,
+        channels: seg.channels,
+    };
+    let result = process_recorded_audio(raw_audio, None)
+        .and_then(|audio| transcriber.transcribe_segments(&audio, &job.options));
+    // The requester may have disconnected in the meantime
+    let _ = job.reply.send(result);
+}
+
+/// Deliver the transcript of a segment. Windows of a long-form utterance are
+/// held back until the final window arrives and delivered as one transcript.
+fn complete_segment(
+    callback: &Mutex<Option<Arc<dyn TranscriptionCallback>>>,
+    post_processing: &Mutex<PostProcessing>,
+    stitcher: &mut UtteranceStitcher,
+    seg: &QueuedSegment,
+    text: String,
+    wav_path: Option<String>,
+    speaker_id: Option<u32>,
+) {
+    let completed = match seg.utterance {
+        Some(part) => stitcher.push(seg.speaker, part, &text, speaker_id, wav_path),
+        None => {
+            let (text, paste) = post_processing.lock().unwrap().finish(seg, text);
+            if let Some(ref cb) = *callback.lock().unwrap() {
+                cb.on_transcription_complete(text, wav_path, seg.speaker, speaker_id, paste);
+            }
+            return;
+        }
+    };
+
+    for utterance in completed {
+        if utterance.text.is_empty() {
+            continue;
+        }
+        let (text, paste) = post_processing.lock().unwrap().finish(seg, utterance.text);
+        if let Some(ref cb) = *callback.lock().unwrap() {
+            cb.on_transcription_complete(
+                text,
+                utterance.wav_path,
+                seg.speaker,
+                utterance.speaker_id,
+                paste,
+            );
+        }
+    }
+}
+
+/// Processing of complete transcripts before delivery.
+#[derive(Default)]
+struct PostProcessing {
+    /// Text post-processing stages per transcription mode and hotkey
+    pipelines: TextPipelines,
+    /// Wake phrase required before Automatic mode text is pasted
+    wake_word: Option<WakeWordGate>,
+}
+
+impl PostProcessing {
+    /// Post-process a transcript and check it against the wake-word gate.
+    /// Returns the text to deliver and whether it may be pasted. Text without
+    /// the wake phrase is still delivered (for history) but not pasted; with
+    /// it, the phrase is stripped.
+    fn finish(&self, seg: &QueuedSegment, text: String) -> (String, bool) {
+        if crate::clipboard::is_no_speech(&text) {
+            return (text, true);
+        }
+        let text = self.pipelines.process(seg.mode, seg.hotkey.as_ref(), &text);
+
+        // Only Automatic mode is gated, and remote speech is never pasted
+        if seg.mode != TranscriptionMode::Automatic || seg.speaker == Some(Speaker::Remote) {
+            return (text, true);
+        }
+        let Some(ref gate) = self.wake_word else {
+            return (text, true);
+        };
+        match gate.strip(&text) {
+            Some(stripped) if !stripped.is_empty() => (stripped, true),
+            // Wake phrase alone leaves nothing to paste
+            Some(_) => (text, false),
+            None => {
+                tracing::debug!("[TranscriptionQueue] Wake word not detected, not pasting");
+                (text, false)
+            }
+        }
+    }
+}
+
 impl Default for TranscriptionQueue {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(samples: usize, job: bool) -> QueuedSegment {
+        let audio = RawRecordedAudio {
+            samples: vec![0.0; samples],
+            sample_rate: 16000,
+            channels: 1,
+        };
+        let mut segment = QueuedSegment::for_job(
+            audio,
+            TranscriptionJob {
+                options: TranscribeOptions::default(),
+                reply: tokio::sync::oneshot::channel().0,
+            },
+        );
+        if !job {
+            segment.job = None;
+        }
+        segment
+    }
+
+    #[test]
+    fn live_segments_go_before_jobs() {
+        let queue = TranscriptionQueue::new();
+        queue.set_max_queue_size(4);
+        assert!(queue.enqueue(segment(1, true)));
+        assert!(queue.enqueue(segment(2, true)));
+        // Jobs only take half of the queue
+        assert!(!queue.enqueue(segment(3, true)));
+        assert!(queue.enqueue(segment(4, false)));
+        assert!(queue.enqueue(segment(5, false)));
+        assert!(!queue.enqueue(segment(6, false)));
+
+        let mut q = queue.queue.lock().unwrap();
+        let order: Vec<usize> = std::iter::from_fn(|| pop_next(&mut q))
+            .map(|s| s.samples.len())
+            .collect();
+        assert_eq!(order, [4, 5, 1, 2]);
+    }
+}
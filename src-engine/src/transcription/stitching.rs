@@ -0,0 +1,247 @@
+//! Stitching of long-form utterances.
+//!
+//! In long-form mode an utterance is transcribed as a series of overlapping
+//! windows (up to ~30s each). Consecutive windows share a second of audio, so
+//! the end of one transcript usually repeats at the start of the next. This
+//! module removes the repeated words and joins the windows back into a single
+//! transcript.
+
+use std::collections::HashMap;
+
+use flowstt_common::{normalize_word, Speaker};
+
+use super::queue::UtterancePart;
+
+/// Longest run of words considered when matching window boundaries.
+/// One second of overlap rarely holds more than a handful of words.
+const MAX_OVERLAP_WORDS: usize = 8;
+
+/// Append `next` to `previous`, dropping the words at the start of `next`
+/// that repeat the end of `previous`.
+///
+/// The longest run of up to `MAX_OVERLAP_WORDS` words that ends `previous`
+/// and starts `next` is removed from `next`. Comparison ignores case and
+/// punctuation, so "world." and "World" match.
+pub fn merge_overlapping(previous: &str, next: &str) -> String {
+    let previous = previous.trim();
+    let next = next.trim();
+    if previous.is_empty() {
+        return next.to_string();
+    }
+    if next.is_empty() {
+        return previous.to_string();
+    }
+
+    let prev_words: Vec<String> = previous.split_whitespace().map(normalize_word).collect();
+    let next_words: Vec<&str> = next.split_whitespace().collect();
+    let next_normalized: Vec<String> = next_words.iter().map(|w| normalize_word(w)).collect();
+
+    let max_overlap = MAX_OVERLAP_WORDS
+        .min(prev_words.len())
+        .min(next_words.len());
+    let overlap = (1..=max_overlap)
+        .rev()
+        .find(|&k| {
+            let tail = &prev_words[prev_words.len() - k..];
+            let head = &next_normalized[..k];
+            // Words made only of punctuation never anchor an overlap
+            tail.iter().all(|w| !w.is_empty()) && tail == head
+        })
+        .unwrap_or(0);
+
+    let remainder = next_words[overlap..].join(" ");
+    if remainder.is_empty() {
+        previous.to_string()
+    } else {
+        format!("{} {}", previous, remainder)
+    }
+}
+
+/// A completed long-form utterance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StitchedUtterance {
+    /// Transcript of the whole utterance
+    pub text: String,
+    /// Recording of the whole utterance
+    pub wav_path: Option<String>,
+    /// Diarized speaker of the utterance
+    pub speaker_id: Option<u32>,
+}
+
+/// Utterance whose windows are still being transcribed
+struct PendingUtterance {
+    utterance_id: u64,
+    text: String,
+    speaker_id: Option<u32>,
+}
+
+impl PendingUtterance {
+    fn finish(self, wav_path: Option<String>) -> StitchedUtterance {
+        StitchedUtterance {
+            text: self.text,
+            wav_path,
+            speaker_id: self.speaker_id,
+        }
+    }
+}
+
+/// Collects the window transcripts of long-form utterances, per speaker.
+#[derive(Default)]
+pub struct UtteranceStitcher {
+    pending: HashMap<Option<Speaker>, PendingUtterance>,
+}
+
+impl UtteranceStitcher {
+    /// Create an empty stitcher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the transcript of one window.
+    ///
+    /// Returns the utterances completed by this window: the window's own
+    /// utterance when it is the final part, preceded by any unfinished
+    /// utterance it supersedes (e.g. when the final window was dropped).
+    pub fn push(
+        &mut self,
+        speaker: Option<Speaker>,
+        part: UtterancePart,
+        text: &str,
+        speaker_id: Option<u32>,
+        wav_path: Option<String>,
+    ) -> Vec<StitchedUtterance> {
+        let mut completed = Vec::new();
+
+        let mut pending = match self.pending.remove(&speaker) {
+            Some(pending) if pending.utterance_id == part.utterance_id => pending,
+            stale => {
+                if let Some(stale) = stale {
+                    tracing::warn!(
+                        "[Stitcher] Utterance {} ended without a final window",
+                        stale.utterance_id
+                    );
+                    completed.push(stale.finish(None));
+                }
+                PendingUtterance {
+                    utterance_id: part.utterance_id,
+                    text: String::new(),
+                    speaker_id: None,
+                }
+            }
+        };
+
+        pending.text = merge_overlapping(&pending.text, text);
+        pending.speaker_id = pending.speaker_id.or(speaker_id);
+
+        if part.is_final {
+            completed.push(pending.finish(wav_path));
+        } else {
+            self.pending.insert(speaker, pending);
+        }
+
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(utterance_id: u64, is_final: bool) -> UtterancePart {
+        UtterancePart {
+            utterance_id,
+            is_final,
+        }
+    }
+
+    #[test]
+    fn merge_removes_repeated_boundary_words() {
+        assert_eq!(
+            merge_overlapping(
+                "I wanted to follow up on the budget",
+                "on the budget for next quarter."
+            ),
+            "I wanted to follow up on the budget for next quarter."
+        );
+    }
+
+    #[test]
+    fn merge_ignores_case_and_punctuation() {
+        assert_eq!(
+            merge_overlapping("Thanks for joining, everyone.", "Everyone, let's start."),
+            "Thanks for joining, everyone. let's start."
+        );
+    }
+
+    #[test]
+    fn merge_without_overlap_concatenates() {
+        assert_eq!(
+            merge_overlapping("The first sentence.", "The second sentence."),
+            "The first sentence. The second sentence."
+        );
+    }
+
+    #[test]
+    fn merge_handles_empty_sides() {
+        assert_eq!(merge_overlapping("", " hello "), "hello");
+        assert_eq!(merge_overlapping("hello", ""), "hello");
+        assert_eq!(merge_overlapping("hello world", "world"), "hello world");
+    }
+
+    #[test]
+    fn merge_ignores_punctuation_only_overlap() {
+        assert_eq!(merge_overlapping("Wait -", "- what?"), "Wait - - what?");
+    }
+
+    #[test]
+    fn stitcher_joins_windows_of_an_utterance() {
+        let mut stitcher = UtteranceStitcher::new();
+
+        assert!(stitcher
+            .push(None, part(1, false), "one two three", Some(2), None)
+            .is_empty());
+        assert!(stitcher
+            .push(None, part(1, false), "three four five", None, None)
+            .is_empty());
+        let done = stitcher.push(
+            None,
+            part(1, true),
+            "five six",
+            Some(3),
+            Some("a.wav".into()),
+        );
+
+        assert_eq!(
+            done,
+            vec![StitchedUtterance {
+                text: "one two three four five six".into(),
+                wav_path: Some("a.wav".into()),
+                speaker_id: Some(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn stitcher_flushes_utterance_missing_its_final_window() {
+        let mut stitcher = UtteranceStitcher::new();
+
+        stitcher.push(None, part(1, false), "lost ending", None, None);
+        let done = stitcher.push(None, part(2, true), "next", None, None);
+
+        assert_eq!(done.len(), 2);
+        assert_eq!(done[0].text, "lost ending");
+        assert_eq!(done[1].text, "next");
+    }
+
+    #[test]
+    fn stitcher_keeps_speakers_apart() {
+        let mut stitcher = UtteranceStitcher::new();
+
+        stitcher.push(Some(Speaker::Me), part(1, false), "mine", None, None);
+        let remote = stitcher.push(Some(Speaker::Remote), part(1, true), "theirs", None, None);
+        let me = stitcher.push(Some(Speaker::Me), part(1, true), "too", None, None);
+
+        assert_eq!(remote[0].text, "theirs");
+        assert_eq!(me[0].text, "mine too");
+    }
+}
@@ -4,32 +4,49 @@
 //! - `SegmentRingBuffer`: A ring buffer for continuous audio capture
 //! - `TranscribeState`: State management for transcribe mode
 
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::audio::{generate_recording_filename, save_to_wav};
+use flowstt_common::{
+    HotkeyCombination, RecordingMode, SegmentationConfig, Speaker, TranscriptionMode,
+};
 
-use super::queue::{QueuedSegment, TranscriptionQueue};
+use crate::audio::{generate_recording_filename, save_to_wav, WavRecorder};
+use crate::platform::mixing::{MIXER_CHANNELS, SEPARATED_ME_CHANNEL, SEPARATED_REMOTE_CHANNEL};
 
-/// Ring buffer capacity: 30 seconds at 48kHz stereo
+use super::queue::{QueuedSegment, TranscriptionQueue, UtterancePart};
+
+/// Default ring buffer capacity: 30 seconds at 48kHz stereo
 /// 48000 * 30 * 2 = 2,880,000 samples
+/// (the capacity in use follows `SegmentationConfig::ring_buffer_secs`)
 const RING_BUFFER_CAPACITY: usize = 48000 * 30 * 2;
 
 /// Overflow threshold: 90% of buffer capacity
 const OVERFLOW_THRESHOLD_PERCENT: usize = 90;
 
-/// Maximum segment duration before seeking word break
-const MAX_SEGMENT_DURATION_MS: u64 = 4000;
+/// Long-form mode: window duration before seeking a word break. With the
+/// grace period and the overlap, windows stay within Whisper's 30s context.
+const LONG_FORM_MAX_SEGMENT_DURATION_MS: u64 = 28000;
+
+/// Long-form mode: audio from the end of one window repeated at the start of
+/// the next, so words cut at the boundary are heard in full by one of them
+const LONG_FORM_OVERLAP_MS: u64 = 1000;
+
+/// Minimum ring buffer length in long-form mode, so a full window never
+/// reaches the overflow threshold
+const LONG_FORM_MIN_RING_BUFFER_SECS: u32 = 60;
 
-/// Grace period after duration threshold before forcing segment submission (500ms)
-const WORD_BREAK_GRACE_MS: u64 = 750;
+/// Push-to-talk: length of the end of each long-form window searched for
+/// the quietest point to cut at, since PTT audio has no detected word breaks
+const PTT_CUT_SEARCH_MS: u64 = 3000;
 
-/// Minimum segment duration to submit for transcription (200ms)
-/// Segments shorter than this are likely to produce [BLANK_AUDIO] from Whisper
-const MIN_SEGMENT_DURATION_MS: u64 = 500;
+/// Push-to-talk: block length used to measure loudness when choosing a cut
+const PTT_CUT_BLOCK_MS: u64 = 20;
 
-/// Minimum RMS amplitude threshold for non-silent audio (linear scale)
-/// Approximately -40dB
-const MIN_AUDIO_RMS_THRESHOLD: f32 = 0.01;
+/// Push-to-talk: key-hold audio buffered before its completed long-form
+/// windows are queued, so memory stays bounded however long the key is held
+const PTT_MAX_BUFFERED_MS: u64 = 120_000;
 
 /// Safety margin before word break point (ms) - ensures we don't cut into the end of speech
 /// The extraction point will be (gap_start - margin) rather than gap_midpoint
@@ -212,6 +229,34 @@ pub struct TranscribeState {
     callback: Option<Arc<dyn TranscribeStateCallback>>,
     /// PTT mode - disables automatic segmentation
     ptt_mode: bool,
+    /// Mode of the current capture, recorded on queued segments
+    capture_mode: TranscriptionMode,
+    /// Hotkey binding that started the current capture, if hotkey-driven
+    capture_hotkey: Option<HotkeyCombination>,
+    /// Speaker-separated mode - each channel is transcribed as its own speaker
+    speaker_separated: bool,
+    /// Long-form mode - long speech is split into overlapping windows whose
+    /// transcripts are stitched into one utterance
+    long_form: bool,
+    /// Identifier of the current utterance (incremented on speech start)
+    utterance_id: u64,
+    /// Number of long-form windows queued for the current utterance
+    utterance_parts: u32,
+    /// Recording of the current long-form utterance, without the overlaps.
+    /// Written to disk as windows are queued rather than held in memory.
+    utterance_recording: Option<WavRecorder>,
+    /// Number of overlap samples at the start of the current window
+    window_overlap_samples: usize,
+    /// Ring buffer and segment limits
+    segmentation: SegmentationConfig,
+    /// PTT mode - audio of the key-hold not yet queued, which may be far
+    /// longer than the ring buffer (up to `PTT_MAX_BUFFERED_MS`)
+    ptt_audio: Vec<f32>,
+    /// PTT mode - samples at the start of `ptt_audio` already queued as the
+    /// end of an earlier window
+    ptt_queued_samples: usize,
+    /// Directory recorded segments are saved to
+    recordings_dir: PathBuf,
 }
 
 impl TranscribeState {
@@ -231,19 +276,123 @@ impl TranscribeState {
             lookback_sample_count: 0,
             callback: None,
             ptt_mode: false,
+            capture_mode: TranscriptionMode::Automatic,
+            capture_hotkey: None,
+            speaker_separated: false,
+            long_form: false,
+            utterance_id: 0,
+            utterance_parts: 0,
+            utterance_recording: None,
+            window_overlap_samples: 0,
+            segmentation: SegmentationConfig::default(),
+            ptt_audio: Vec::new(),
+            ptt_queued_samples: 0,
+            recordings_dir: crate::history::TranscriptionHistory::recordings_dir(),
         }
     }
 
     /// Enable or disable PTT mode.
     /// In PTT mode, automatic segmentation is disabled - segments are only
-    /// submitted when explicitly ended via on_speech_ended().
+    /// submitted when explicitly ended via on_speech_ended(). The key-hold is
+    /// recorded in full regardless of the ring buffer size, and recordings
+    /// too long for one transcription are split into stitched windows, queued
+    /// while the key is still held once `PTT_MAX_BUFFERED_MS` is buffered.
     pub fn set_ptt_mode(&mut self, enabled: bool) {
-        self.ptt_mode = enabled;
-        if enabled {
+        self.set_capture_mode(if enabled {
+            TranscriptionMode::PushToTalk
+        } else {
+            TranscriptionMode::Automatic
+        });
+    }
+
+    /// Set the mode of the current capture. Hotkey-driven modes enable PTT
+    /// mode (see [`Self::set_ptt_mode`]).
+    pub fn set_capture_mode(&mut self, mode: TranscriptionMode) {
+        self.capture_mode = mode;
+        self.ptt_mode = mode.is_hotkey_driven();
+        if self.ptt_mode {
             tracing::debug!("[TranscribeState] PTT mode enabled - automatic segmentation disabled");
         }
     }
 
+    /// Set the hotkey binding of the current capture, which selects its text
+    /// processing profile.
+    pub fn set_capture_hotkey(&mut self, hotkey: Option<HotkeyCombination>) {
+        self.capture_hotkey = hotkey;
+    }
+
+    /// Set the recording mode of the capture feeding this state.
+    /// In speaker-separated mode, segments are split by channel and each
+    /// speaker is validated, saved and queued independently.
+    pub fn set_recording_mode(&mut self, mode: RecordingMode) {
+        self.speaker_separated = mode == RecordingMode::SpeakerSeparated;
+    }
+
+    /// Enable or disable long-form segmentation.
+    /// Long-form mode keeps segments up to ~30s and splits longer speech into
+    /// overlapping windows that the queue stitches back into one transcript.
+    /// It applies to automatic, non-separated capture; any in-progress
+    /// segment is submitted before switching.
+    pub fn set_long_form(&mut self, enabled: bool) {
+        if self.long_form == enabled {
+            return;
+        }
+        self.finalize();
+        self.long_form = enabled;
+        self.resize_ring_buffer();
+    }
+
+    /// Get the ring buffer and segment limits.
+    pub fn segmentation_config(&self) -> &SegmentationConfig {
+        &self.segmentation
+    }
+
+    /// Replace the ring buffer and segment limits (already validated).
+    /// If the ring buffer changes size, any in-progress segment is submitted
+    /// before it is reallocated.
+    pub fn set_segmentation_config(&mut self, config: SegmentationConfig) {
+        self.segmentation = config;
+        self.resize_ring_buffer();
+    }
+
+    /// Ring buffer capacity for the current limits and capture format
+    fn ring_buffer_capacity(&self) -> usize {
+        let mut secs = self.segmentation.ring_buffer_secs;
+        if self.long_form {
+            secs = secs.max(LONG_FORM_MIN_RING_BUFFER_SECS);
+        }
+        secs as usize * self.sample_rate as usize * self.channels as usize
+    }
+
+    /// Reallocate the ring buffer if its capacity no longer matches
+    fn resize_ring_buffer(&mut self) {
+        let capacity = self.ring_buffer_capacity();
+        if capacity == self.ring_buffer.capacity() {
+            return;
+        }
+        self.finalize();
+        self.ring_buffer = SegmentRingBuffer::new(capacity);
+        self.segment_start_idx = 0;
+        tracing::debug!(
+            "[TranscribeState] Ring buffer resized to {} samples",
+            capacity
+        );
+    }
+
+    /// Whether speech is currently split into stitched long-form windows
+    fn long_form_active(&self) -> bool {
+        self.long_form && !self.ptt_mode && !self.speaker_separated
+    }
+
+    /// Duration after which a segment starts seeking a word break
+    fn max_segment_duration_ms(&self) -> u64 {
+        if self.long_form_active() {
+            LONG_FORM_MAX_SEGMENT_DURATION_MS
+        } else {
+            self.segmentation.max_segment_duration_ms
+        }
+    }
+
     /// Set the callback for state events.
     pub fn set_callback(&mut self, callback: Arc<dyn TranscribeStateCallback>) {
         self.callback = Some(callback);
@@ -258,6 +407,7 @@ impl TranscribeState {
     pub fn init_for_capture(&mut self, sample_rate: u32, channels: u16) {
         self.sample_rate = sample_rate;
         self.channels = channels;
+        self.resize_ring_buffer();
         self.ring_buffer.clear();
         self.in_speech = false;
         self.segment_start_idx = 0;
@@ -265,6 +415,8 @@ impl TranscribeState {
         self.seeking_word_break = false;
         self.word_break_seek_start_samples = 0;
         self.lookback_sample_count = 0;
+        self.ptt_audio = Vec::new();
+        self.ptt_queued_samples = 0;
     }
 
     /// Activate transcribe mode
@@ -283,6 +435,8 @@ impl TranscribeState {
         self.is_active = false;
         self.in_speech = false;
         self.seeking_word_break = false;
+        self.ptt_audio = Vec::new();
+        self.ptt_queued_samples = 0;
     }
 
     /// Process incoming audio samples - writes to ring buffer and checks for overflow/duration
@@ -293,11 +447,18 @@ impl TranscribeState {
             return None;
         }
 
-        // In PTT mode, skip all automatic segmentation - just write samples
+        // In PTT mode, skip all automatic segmentation - just record samples.
+        // The key-hold goes to a growing buffer so it is never cut short by
+        // the ring buffer wrapping.
         if self.ptt_mode {
-            self.ring_buffer.write(samples);
             if self.in_speech {
+                self.ptt_audio.extend_from_slice(samples);
                 self.segment_sample_count += samples.len() as u64;
+                if self.samples_to_ms(self.ptt_audio.len() as u64) >= PTT_MAX_BUFFERED_MS {
+                    self.flush_key_hold();
+                }
+            } else {
+                self.ring_buffer.write(samples);
             }
             return None;
         }
@@ -311,11 +472,9 @@ impl TranscribeState {
             // Extract current segment before it gets overwritten
             let segment = self.ring_buffer.extract_segment(self.segment_start_idx);
 
-            // Update segment start to current write position
-            self.segment_start_idx = self.ring_buffer.write_position();
+            // Continue the segment from the current write position
+            self.continue_segment_at(self.ring_buffer.write_position());
             self.segment_sample_count = 0;
-            self.seeking_word_break = false;
-            self.lookback_sample_count = 0; // No lookback for continuation segments
 
             // Remain in speech state
             tracing::debug!(
@@ -337,7 +496,7 @@ impl TranscribeState {
 
             // Check if we've exceeded max duration and should start seeking word break
             let duration_ms = self.samples_to_ms(self.segment_sample_count);
-            if !self.seeking_word_break && duration_ms >= MAX_SEGMENT_DURATION_MS {
+            if !self.seeking_word_break && duration_ms >= self.max_segment_duration_ms() {
                 self.seeking_word_break = true;
                 self.word_break_seek_start_samples = self.segment_sample_count;
                 tracing::debug!(
@@ -352,7 +511,7 @@ impl TranscribeState {
                     self.segment_sample_count - self.word_break_seek_start_samples;
                 let grace_ms = self.samples_to_ms(samples_since_seek);
 
-                if grace_ms >= WORD_BREAK_GRACE_MS {
+                if grace_ms >= self.segmentation.word_break_grace_ms {
                     // Grace period expired, force extraction
                     let forced = self.force_segment_extraction();
                     if forced.is_some() {
@@ -364,7 +523,7 @@ impl TranscribeState {
 
         // If we extracted a segment due to overflow, queue it
         if let Some(segment) = overflow_segment.clone() {
-            self.queue_segment(segment);
+            self.queue_segment(segment, false);
         }
 
         overflow_segment
@@ -391,6 +550,15 @@ impl TranscribeState {
         self.seeking_word_break = false;
         // Remember lookback count (in stereo samples) for proper word break extraction
         self.lookback_sample_count = lookback_stereo_samples;
+        // Each speech segment is a new utterance
+        self.utterance_id += 1;
+        self.utterance_parts = 0;
+        self.utterance_recording = None;
+        self.window_overlap_samples = 0;
+        if self.ptt_mode {
+            self.ptt_audio = self.ring_buffer.extract_segment(self.segment_start_idx);
+            self.ptt_queued_samples = 0;
+        }
         tracing::debug!(
             "[TranscribeState] Speech started, segment_start_idx={}, lookback={} mono -> {} stereo",
             self.segment_start_idx,
@@ -406,7 +574,11 @@ impl TranscribeState {
         }
 
         // Extract the segment
-        let segment = self.ring_buffer.extract_segment(self.segment_start_idx);
+        let segment = if self.ptt_mode {
+            std::mem::take(&mut self.ptt_audio)
+        } else {
+            self.ring_buffer.extract_segment(self.segment_start_idx)
+        };
 
         self.in_speech = false;
         self.segment_sample_count = 0;
@@ -424,11 +596,89 @@ impl TranscribeState {
         );
 
         // Queue the segment for transcription (will validate before actually queueing)
-        self.queue_segment(segment.clone());
+        if self.ptt_mode {
+            self.queue_key_hold(segment.clone());
+        } else {
+            self.queue_segment(segment.clone(), true);
+        }
 
         Some(segment)
     }
 
+    /// Queue the rest of a PTT key-hold when the key is released.
+    ///
+    /// Recordings longer than a long-form window are split into overlapping
+    /// windows that the queue stitches back into one transcript.
+    /// Speaker-separated recordings are always queued whole, per speaker.
+    fn queue_key_hold(&mut self, samples: Vec<f32>) {
+        let channels = self.channels as usize;
+        let windows = split_long_recording(&samples, channels, self.sample_rate);
+        if self.speaker_separated || (windows.len() <= 1 && self.utterance_parts == 0) {
+            self.queue_segment(samples, true);
+            return;
+        }
+
+        tracing::debug!(
+            "[TranscribeState] Key-hold of {}ms split into {} windows",
+            self.samples_to_ms(samples.len() as u64),
+            windows.len()
+        );
+
+        self.queue_key_hold_windows(&samples, &windows, true);
+        self.ptt_queued_samples = 0;
+        self.notify_queue_update();
+    }
+
+    /// Queue the completed windows of a key-hold that has outgrown
+    /// `PTT_MAX_BUFFERED_MS`, keeping only the last window buffered.
+    /// Speaker-separated recordings are not stitched, so their buffer is
+    /// queued as it is and the key-hold continues as a new segment.
+    fn flush_key_hold(&mut self) {
+        let samples = std::mem::take(&mut self.ptt_audio);
+        if self.speaker_separated {
+            self.queue_segment(samples, false);
+            return;
+        }
+
+        let mut windows = split_long_recording(&samples, self.channels as usize, self.sample_rate);
+        let Some(last) = windows.pop() else {
+            return;
+        };
+
+        tracing::debug!(
+            "[TranscribeState] Key-hold buffer full, queueing {} windows",
+            windows.len()
+        );
+
+        self.queue_key_hold_windows(&samples, &windows, false);
+        self.ptt_queued_samples = self.ptt_queued_samples.saturating_sub(last.start);
+        self.ptt_audio = samples[last.start..].to_vec();
+        self.notify_queue_update();
+    }
+
+    /// Queue windows of buffered key-hold audio as parts of one long-form
+    /// utterance; the last window ends the utterance when `ends_utterance`.
+    fn queue_key_hold_windows(
+        &mut self,
+        samples: &[f32],
+        windows: &[Range<usize>],
+        ends_utterance: bool,
+    ) {
+        let mut previous_end = self.ptt_queued_samples;
+        for (i, window) in windows.iter().enumerate() {
+            self.window_overlap_samples = previous_end.saturating_sub(window.start);
+            previous_end = window.end;
+            let is_final = ends_utterance && i + 1 == windows.len();
+            self.queue_utterance_window(samples[window.clone()].to_vec(), is_final);
+        }
+        self.ptt_queued_samples = previous_end;
+    }
+
+    /// Duration of the current segment in milliseconds, excluding lookback
+    pub fn segment_duration_ms(&self) -> u64 {
+        self.samples_to_ms(self.segment_sample_count)
+    }
+
     /// Convert sample count to milliseconds.
     /// Note: sample count here is raw samples (includes all channels),
     /// so we divide by channels to get frames, then convert to ms.
@@ -481,11 +731,11 @@ impl TranscribeState {
 
         // Don't extract if the segment would be too short
         let extraction_duration_ms = self.samples_to_ms(extraction_length);
-        if extraction_duration_ms < MIN_SEGMENT_DURATION_MS {
+        if extraction_duration_ms < self.segmentation.min_segment_duration_ms {
             tracing::debug!(
                 "[TranscribeState] Word break would create segment too short ({}ms < {}ms), skipping",
                 extraction_duration_ms,
-                MIN_SEGMENT_DURATION_MS
+                self.segmentation.min_segment_duration_ms
             );
             return None;
         }
@@ -521,17 +771,14 @@ impl TranscribeState {
         );
 
         // Queue the segment for transcription (will validate before actually queueing)
-        self.queue_segment(segment.clone());
+        self.queue_segment(segment.clone(), false);
 
         // Update state for next segment - the new segment starts at the extraction point
-        // No lookback for continuation segments (we already have the audio in the buffer)
-        self.segment_start_idx = extraction_end_idx;
-        self.lookback_sample_count = 0;
+        self.continue_segment_at(extraction_end_idx);
         // Remaining samples in the segment: total minus what we extracted (excluding lookback)
         self.segment_sample_count = self
             .segment_sample_count
             .saturating_sub(extraction_point_samples);
-        self.seeking_word_break = false;
 
         Some(segment)
     }
@@ -565,33 +812,49 @@ impl TranscribeState {
         );
 
         // Queue the segment for transcription (will validate before actually queueing)
-        self.queue_segment(segment.clone());
+        self.queue_segment(segment.clone(), false);
 
         // Update state for next segment - remain in speech
-        self.segment_start_idx = self.ring_buffer.write_position();
+        self.continue_segment_at(self.ring_buffer.write_position());
         self.segment_sample_count = 0;
-        self.lookback_sample_count = 0;
-        self.seeking_word_break = false;
 
         Some(segment)
     }
 
+    /// Start the next segment of ongoing speech at ring buffer index `idx`.
+    ///
+    /// Regular continuation segments have no lookback (the audio before `idx`
+    /// was already submitted). Long-form windows instead start with an overlap
+    /// of the previous window, which is treated like lookback.
+    fn continue_segment_at(&mut self, idx: usize) {
+        let overlap = if self.long_form_active() {
+            self.ms_to_samples(LONG_FORM_OVERLAP_MS) as usize
+        } else {
+            0
+        };
+        let capacity = self.ring_buffer.capacity();
+        self.segment_start_idx = (idx + capacity - overlap % capacity) % capacity;
+        self.lookback_sample_count = overlap;
+        self.window_overlap_samples = overlap;
+        self.seeking_word_break = false;
+    }
+
     /// Check if a segment has sufficient audio content for transcription
     /// Returns false if segment is too short or too quiet (likely to produce [BLANK_AUDIO])
-    fn is_segment_valid_for_transcription(&self, samples: &[f32]) -> bool {
+    fn is_segment_valid_for_transcription(&self, samples: &[f32], channels: u16) -> bool {
         if samples.is_empty() {
             return false;
         }
 
         // Check minimum duration
         // samples.len() is raw sample count (stereo), divide by channels to get frames
-        let frames = samples.len() as u64 / self.channels as u64;
+        let frames = samples.len() as u64 / channels as u64;
         let duration_ms = frames * 1000 / self.sample_rate as u64;
-        if duration_ms < MIN_SEGMENT_DURATION_MS {
+        if duration_ms < self.segmentation.min_segment_duration_ms {
             tracing::debug!(
                 "[TranscribeState] Segment too short ({}ms < {}ms), skipping",
                 duration_ms,
-                MIN_SEGMENT_DURATION_MS
+                self.segmentation.min_segment_duration_ms
             );
             return false;
         }
@@ -600,11 +863,11 @@ impl TranscribeState {
         let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
         let rms = (sum_squares / samples.len() as f32).sqrt();
 
-        if rms < MIN_AUDIO_RMS_THRESHOLD {
+        if rms < self.segmentation.min_audio_rms {
             tracing::debug!(
                 "[TranscribeState] Segment too quiet (RMS {:.6} < {:.6}), skipping",
                 rms,
-                MIN_AUDIO_RMS_THRESHOLD
+                self.segmentation.min_audio_rms
             );
             return false;
         }
@@ -612,61 +875,208 @@ impl TranscribeState {
         true
     }
 
-    /// Queue a segment for transcription (saves WAV and enqueues)
-    fn queue_segment(&self, samples: Vec<f32>) {
+    /// Queue a segment for transcription (saves WAV and enqueues).
+    /// `end_of_utterance` is set when the segment ends with the speech.
+    fn queue_segment(&mut self, samples: Vec<f32>, end_of_utterance: bool) {
         if samples.is_empty() {
             return;
         }
 
+        if self.long_form_active() && (self.utterance_parts > 0 || !end_of_utterance) {
+            // Window of a long utterance - stitched by the queue
+            self.queue_utterance_window(samples, end_of_utterance);
+        } else if self.speaker_separated && self.channels as usize == MIXER_CHANNELS {
+            // Each channel carries one speaker - transcribe them independently
+            for (channel, speaker) in [
+                (SEPARATED_ME_CHANNEL, Speaker::Me),
+                (SEPARATED_REMOTE_CHANNEL, Speaker::Remote),
+            ] {
+                let speaker_samples: Vec<f32> = samples
+                    .iter()
+                    .skip(channel)
+                    .step_by(MIXER_CHANNELS)
+                    .copied()
+                    .collect();
+                self.queue_speaker_segment(speaker_samples, 1, Some(speaker));
+            }
+        } else {
+            self.queue_speaker_segment(samples, self.channels, None);
+        }
+
+        self.notify_queue_update();
+    }
+
+    /// Emit queue update via callback
+    fn notify_queue_update(&self) {
+        let depth = self.transcription_queue.queue_depth();
+        if let Some(ref cb) = self.callback {
+            cb.on_queue_update(depth);
+        }
+    }
+
+    /// Validate, save and enqueue the audio of a single speaker (or of the
+    /// whole mix when `speaker` is None)
+    fn queue_speaker_segment(&self, samples: Vec<f32>, channels: u16, speaker: Option<Speaker>) {
         // Validate segment has sufficient content
-        if !self.is_segment_valid_for_transcription(&samples) {
+        if !self.is_segment_valid_for_transcription(&samples, channels) {
             return;
         }
 
-        // Save to WAV file in app data directory
-        let filename = generate_recording_filename();
-        let recordings_dir = crate::history::TranscriptionHistory::recordings_dir();
+        let wav_path = self.save_segment_wav(&samples, channels, speaker);
 
-        // Create directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&recordings_dir) {
-            tracing::error!(
-                "[TranscribeState] Failed to create recordings directory: {}",
-                e
-            );
-        }
+        self.enqueue(QueuedSegment {
+            samples,
+            sample_rate: self.sample_rate,
+            channels,
+            wav_path,
+            speaker,
+            diarize: !self.ptt_mode,
+            mode: self.capture_mode,
+            hotkey: self.capture_hotkey.clone(),
+            utterance: None,
+            job: None,
+        });
+    }
+
+    /// Queue one window of a long-form utterance.
+    ///
+    /// Windows are only transcribed; the final window carries the recording
+    /// of the whole utterance and is always queued so the stitched transcript
+    /// is delivered even when the final window itself is too short or quiet.
+    fn queue_utterance_window(&mut self, samples: Vec<f32>, is_final: bool) {
+        // The overlap was recorded as part of the previous window
+        let overlap = self.window_overlap_samples.min(samples.len());
+        self.record_utterance(&samples[overlap..]);
+        self.utterance_parts += 1;
+
+        let valid = self.is_segment_valid_for_transcription(&samples, self.channels);
+        let utterance = Some(UtterancePart {
+            utterance_id: self.utterance_id,
+            is_final,
+        });
+
+        let (samples, wav_path) = if is_final {
+            let wav_path = self.finish_utterance_recording();
+            (if valid { samples } else { Vec::new() }, wav_path)
+        } else if valid {
+            (samples, None)
+        } else {
+            return;
+        };
+
+        tracing::debug!(
+            "[TranscribeState] Queueing long-form window {} of utterance {}{}",
+            self.utterance_parts,
+            self.utterance_id,
+            if is_final { " (final)" } else { "" }
+        );
+
+        self.enqueue(QueuedSegment {
+            samples,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            wav_path,
+            speaker: None,
+            diarize: !self.ptt_mode,
+            mode: self.capture_mode,
+            hotkey: self.capture_hotkey.clone(),
+            utterance,
+            job: None,
+        });
+    }
 
-        let output_path = recordings_dir.join(&filename);
-        let wav_path = match save_to_wav(&samples, self.sample_rate, self.channels, &output_path) {
+    /// Save segment audio to a WAV file in the recordings directory
+    fn save_segment_wav(
+        &self,
+        samples: &[f32],
+        channels: u16,
+        speaker: Option<Speaker>,
+    ) -> Option<PathBuf> {
+        let output_path = self.recording_path(speaker);
+        match save_to_wav(samples, self.sample_rate, channels, &output_path) {
             Ok(()) => {
-                tracing::info!("[TranscribeState] Saved segment to: {:?}", output_path);
-                if let Some(ref cb) = self.callback {
-                    cb.on_recording_saved(output_path.to_string_lossy().to_string());
-                }
+                self.notify_recording_saved(&output_path);
                 Some(output_path)
             }
             Err(e) => {
                 tracing::error!("[TranscribeState] Failed to save WAV: {}", e);
                 None
             }
-        };
+        }
+    }
 
-        // Create queued segment
-        let queued = QueuedSegment {
-            samples,
-            sample_rate: self.sample_rate,
-            channels: self.channels,
-            wav_path,
-        };
+    /// Append audio to the recording of the current long-form utterance,
+    /// starting the file with the first window
+    fn record_utterance(&mut self, samples: &[f32]) {
+        if self.utterance_parts == 0 {
+            let path = self.recording_path(None);
+            self.utterance_recording = WavRecorder::create(path, self.sample_rate, self.channels)
+                .map_err(|e| tracing::error!("[TranscribeState] Failed to save WAV: {}", e))
+                .ok();
+        }
+        if let Some(recorder) = self.utterance_recording.as_mut() {
+            if let Err(e) = recorder.write(samples) {
+                tracing::error!("[TranscribeState] Failed to save WAV: {}", e);
+                self.utterance_recording = None;
+            }
+        }
+    }
 
-        // Enqueue for transcription
-        if !self.transcription_queue.enqueue(queued) {
-            tracing::warn!("[TranscribeState] Transcription queue is full, segment dropped");
+    /// Complete the recording of the current long-form utterance
+    fn finish_utterance_recording(&mut self) -> Option<PathBuf> {
+        match self.utterance_recording.take()?.finalize() {
+            Ok(path) => {
+                self.notify_recording_saved(&path);
+                Some(path)
+            }
+            Err(e) => {
+                tracing::error!("[TranscribeState] Failed to save WAV: {}", e);
+                None
+            }
         }
+    }
 
-        // Emit queue update via callback
-        let depth = self.transcription_queue.queue_depth();
+    /// Path for a new recording, creating the recordings directory if needed
+    fn recording_path(&self, speaker: Option<Speaker>) -> PathBuf {
+        let mut filename = generate_recording_filename();
+        if let Some(speaker) = speaker {
+            filename = filename.replace(".wav", &format!("-{}.wav", speaker.label()));
+        }
+
+        // Create directory if it doesn't exist
+        if let Err(e) = std::fs::create_dir_all(&self.recordings_dir) {
+            tracing::error!(
+                "[TranscribeState] Failed to create recordings directory: {}",
+                e
+            );
+        }
+
+        self.recordings_dir.join(&filename)
+    }
+
+    fn notify_recording_saved(&self, path: &std::path::Path) {
+        tracing::info!("[TranscribeState] Saved segment to: {:?}", path);
         if let Some(ref cb) = self.callback {
-            cb.on_queue_update(depth);
+            cb.on_recording_saved(path.to_string_lossy().to_string());
+        }
+    }
+
+    /// Enqueue a segment for transcription. A segment the full queue cannot
+    /// take is reported to clients rather than dropped silently.
+    fn enqueue(&self, queued: QueuedSegment) {
+        let frames = queued.samples.len() / queued.channels.max(1) as usize;
+        let secs = frames as f32 / queued.sample_rate.max(1) as f32;
+        let wav_path = queued.wav_path.clone();
+        if !self.transcription_queue.enqueue(queued) {
+            let mut error = format!(
+                "Transcription queue is full, {:.1}s of audio was not transcribed",
+                secs
+            );
+            if let Some(path) = wav_path {
+                error.push_str(&format!(" (recording kept at {})", path.display()));
+            }
+            tracing::warn!("[TranscribeState] {}", error);
+            self.transcription_queue.report_error(error);
         }
     }
 
@@ -680,3 +1090,187 @@ impl TranscribeState {
         }
     }
 }
+
+/// Split a recording into overlapping windows of at most
+/// `LONG_FORM_MAX_SEGMENT_DURATION_MS`.
+///
+/// Each window ends at the quietest `PTT_CUT_BLOCK_MS` block within its last
+/// `PTT_CUT_SEARCH_MS`, so cuts tend to fall between words, and the next
+/// window starts `LONG_FORM_OVERLAP_MS` earlier. Returns sample ranges
+/// aligned to whole frames; a recording that fits in one window is returned
+/// as a single range.
+fn split_long_recording(samples: &[f32], channels: usize, sample_rate: u32) -> Vec<Range<usize>> {
+    let channels = channels.max(1);
+    let ms_to_frames = |ms: u64| (ms * sample_rate as u64 / 1000) as usize;
+    let total_frames = samples.len() / channels;
+    let max_frames = ms_to_frames(LONG_FORM_MAX_SEGMENT_DURATION_MS);
+    let search_frames = ms_to_frames(PTT_CUT_SEARCH_MS);
+    let block_frames = ms_to_frames(PTT_CUT_BLOCK_MS).max(1);
+    let overlap_frames = ms_to_frames(LONG_FORM_OVERLAP_MS);
+
+    let energy = |start: usize| -> f32 {
+        samples[start * channels..(start + block_frames) * channels]
+            .iter()
+            .map(|s| s * s)
+            .sum()
+    };
+
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while total_frames - start > max_frames {
+        let window_end = start + max_frames;
+        let cut = (window_end - search_frames..=window_end - block_frames)
+            .step_by(block_frames)
+            .min_by(|&a, &b| energy(a).total_cmp(&energy(b)))
+            .unwrap_or(window_end);
+        windows.push(start * channels..cut * channels);
+        start = cut - overlap_frames;
+    }
+    windows.push(start * channels..samples.len());
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    fn frames(ms: u64) -> usize {
+        (ms * SAMPLE_RATE as u64 / 1000) as usize
+    }
+
+    #[test]
+    fn short_recording_is_a_single_window() {
+        let samples = vec![0.1; frames(20_000) * 2];
+        assert_eq!(
+            split_long_recording(&samples, 2, SAMPLE_RATE),
+            vec![0..samples.len()]
+        );
+    }
+
+    #[test]
+    fn long_recording_is_split_at_quiet_points_with_overlap() {
+        // 70s of "speech" with a pause 26.5s into the recording
+        let mut samples = vec![0.2; frames(70_000)];
+        let pause = frames(26_500);
+        samples[pause..pause + frames(100)].fill(0.0);
+
+        let windows = split_long_recording(&samples, 1, SAMPLE_RATE);
+
+        assert_eq!(windows.len(), 3, "windows: {:?}", windows);
+        assert_eq!(windows[0].start, 0);
+        assert!((pause..pause + frames(100)).contains(&windows[0].end));
+        assert_eq!(windows.last().unwrap().end, samples.len());
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].end - pair[1].start, frames(LONG_FORM_OVERLAP_MS));
+        }
+        for window in &windows {
+            assert!(window.len() <= frames(LONG_FORM_MAX_SEGMENT_DURATION_MS));
+        }
+    }
+
+    #[test]
+    fn ptt_key_hold_outlasts_ring_buffer() {
+        let mut state = TranscribeState::new(Arc::new(TranscriptionQueue::new()));
+        state.init_for_capture(SAMPLE_RATE, 2);
+        state.set_ptt_mode(true);
+        state.activate();
+        state.on_speech_started(0);
+
+        let ring_secs = state.segmentation_config().ring_buffer_secs as u64;
+        let chunk = vec![0.1; frames(10) * 2];
+        for _ in 0..(ring_secs + 15) * 100 {
+            state.process_samples(&chunk);
+        }
+
+        assert_eq!(state.segment_duration_ms(), (ring_secs + 15) * 1000);
+        assert_eq!(state.ptt_audio.len(), frames((ring_secs + 15) * 1000) * 2);
+    }
+
+    #[test]
+    fn long_ptt_key_hold_is_queued_while_held() {
+        // 16 kHz mono keeps the five-minute recording quick to write
+        let dir = std::env::temp_dir().join(format!("flowstt-keyhold-{}", std::process::id()));
+        let mut state = TranscribeState::new(Arc::new(TranscriptionQueue::new()));
+        state.recordings_dir = dir.clone();
+        state.init_for_capture(16000, 1);
+        state.set_ptt_mode(true);
+        state.activate();
+        state.on_speech_started(0);
+
+        // 10ms chunks of a tone whose level varies, so windows are cut at
+        // varying points
+        let chunks: Vec<Vec<f32>> = (0..37)
+            .map(|i| {
+                let level = 0.05 + i as f32 / 200.0;
+                (0..160).map(|n| level * (n as f32 * 0.05).sin()).collect()
+            })
+            .collect();
+        let hold_ms = PTT_MAX_BUFFERED_MS * 5 / 2;
+        let max_buffered = PTT_MAX_BUFFERED_MS as usize * 16;
+        for chunk in chunks.iter().cycle().take((hold_ms / 10) as usize) {
+            state.process_samples(chunk);
+            assert!(state.ptt_audio.len() < max_buffered);
+        }
+
+        assert_eq!(state.segment_duration_ms(), hold_ms);
+        assert!(state.utterance_parts > 0);
+        assert!(state.transcription_queue.queue_depth() > 0);
+
+        // The recording holds the whole key-hold once, without the overlaps
+        state.on_speech_ended();
+        let recordings: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(recordings.len(), 1);
+        let wav = std::fs::read(recordings[0].as_ref().unwrap().path()).unwrap();
+        let audio = crate::audio::read_wav(&wav).unwrap();
+        assert_eq!(audio.samples.len(), hold_ms as usize * 16);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Records transcription errors
+    #[derive(Default)]
+    struct Errors(std::sync::Mutex<Vec<String>>);
+
+    impl crate::transcription::TranscriptionCallback for Errors {
+        fn on_transcription_started(&self) {}
+        fn on_transcription_complete(
+            &self,
+            _text: String,
+            _wav_path: Option<String>,
+            _speaker: Option<Speaker>,
+            _speaker_id: Option<u32>,
+            _paste: bool,
+        ) {
+        }
+        fn on_transcription_error(&self, error: String) {
+            self.0.lock().unwrap().push(error);
+        }
+        fn on_transcription_finished(&self) {}
+        fn on_queue_update(&self, _depth: usize) {}
+    }
+
+    #[test]
+    fn segment_rejected_by_full_queue_is_reported() {
+        let dir = std::env::temp_dir().join(format!("flowstt-queuefull-{}", std::process::id()));
+        let queue = Arc::new(TranscriptionQueue::new());
+        let errors = Arc::new(Errors::default());
+        queue.set_callback(errors.clone());
+        queue.set_max_queue_size(0);
+        let mut state = TranscribeState::new(queue);
+        state.recordings_dir = dir.clone();
+        state.init_for_capture(16000, 1);
+        state.set_ptt_mode(true);
+        state.activate();
+        state.on_speech_started(0);
+        for _ in 0..100 {
+            state.process_samples(&[0.1; 160]);
+        }
+        state.on_speech_ended();
+
+        let errors = errors.0.lock().unwrap();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].starts_with("Transcription queue is full, 1.0s"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -9,6 +9,7 @@
 //! - Whisper parameter tuning to reduce hallucinations at the source
 //! - Post-processing to detect and remove repetition loops
 
+use std::ffi::CString;
 use std::path::PathBuf;
 
 use super::whisper_ffi::{self, Context, WhisperSamplingStrategy};
@@ -22,6 +23,37 @@ const MIN_REPETITIONS_FOR_LOOP: usize = 3;
 /// Minimum phrase length (in chars) to check for repetition
 const MIN_PHRASE_LENGTH: usize = 10;
 
+/// Language codes Whisper can transcribe (ISO 639-1, plus "haw" and "yue")
+pub const WHISPER_LANGUAGES: [&str; 100] = [
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it",
+    "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur",
+    "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr", "az", "sl", "kn",
+    "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw", "gl", "mr", "pa", "si",
+    "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln",
+    "ha", "ba", "jw", "su", "yue",
+];
+
+/// Options for transcribing a whole file (see [`Transcriber::transcribe_segments`]).
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    /// Spoken language code (e.g. "en"), or None for the model default
+    pub language: Option<String>,
+    /// Text to condition the model on (spelling of names, style)
+    pub prompt: Option<String>,
+}
+
+/// A transcribed segment and its position in the audio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedSegment {
+    /// Segment start in milliseconds
+    pub start_ms: u64,
+    /// Segment end in milliseconds
+    pub end_ms: u64,
+    /// Segment text
+    pub text: String,
+}
+
 /// Wrapper around whisper.cpp for transcription.
 pub struct Transcriber {
     ctx: Option<Context>,
@@ -123,12 +155,10 @@ impl Transcriber {
             }
         }
 
-        // Post-process to remove hallucination loops
+        // Post-process to remove hallucination loops. Other text clean-up
+        // runs on complete transcripts in `crate::text_processing`.
         let result = Self::remove_repetition_loops(&result);
 
-        // Replace "Flow STT" with "FlowSTT" in transcribed text
-        let result = result.replace("Flow STT", "FlowSTT");
-
         if result.is_empty() {
             Ok("(No speech detected)".to_string())
         } else {
@@ -184,12 +214,10 @@ impl Transcriber {
             }
         }
 
-        // Post-process to remove hallucination loops
+        // Post-process to remove hallucination loops. Other text clean-up
+        // runs on complete transcripts in `crate::text_processing`.
         let result = Self::remove_repetition_loops(&result);
 
-        // Replace "Flow STT" with "FlowSTT" in transcribed text
-        let result = result.replace("Flow STT", "FlowSTT");
-
         if result.is_empty() {
             Ok("(No speech detected)".to_string())
         } else {
@@ -197,6 +225,60 @@ impl Transcriber {
         }
     }
 
+    /// Transcribe a whole recording (mono, 16kHz) into timed segments.
+    ///
+    /// Unlike [`Self::transcribe`], silence yields no segments rather than a
+    /// "(No speech detected)" placeholder.
+    pub fn transcribe_segments(
+        &mut self,
+        audio_data: &[f32],
+        options: &TranscribeOptions,
+    ) -> Result<Vec<TimedSegment>, String> {
+        self.load_model()?;
+
+        let ctx = self.ctx.as_ref().unwrap();
+
+        let mut params = whisper_ffi::full_default_params(WhisperSamplingStrategy::Greedy)?;
+        params.configure_with_hallucination_mitigation();
+
+        // The C strings must outlive the call to full()
+        let language = options
+            .language
+            .as_deref()
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| "Invalid language".to_string())?;
+        let prompt = options
+            .prompt
+            .as_deref()
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| "Invalid prompt".to_string())?;
+        if let Some(ref language) = language {
+            params.language = language.as_ptr();
+        }
+        if let Some(ref prompt) = prompt {
+            params.initial_prompt = prompt.as_ptr();
+        }
+
+        ctx.full(&params, audio_data)?;
+
+        let mut segments = Vec::new();
+        for i in 0..ctx.full_n_segments()? {
+            let text = Self::remove_repetition_loops(ctx.full_get_segment_text(i)?.trim());
+            if text.is_empty() {
+                continue;
+            }
+            let (start_ms, end_ms) = ctx.full_get_segment_times_ms(i)?;
+            segments.push(TimedSegment {
+                start_ms,
+                end_ms,
+                text,
+            });
+        }
+        Ok(segments)
+    }
+
     /// Remove repetition loops (hallucinations) from transcribed text.
     ///
     /// Whisper sometimes produces output like:
@@ -0,0 +1,181 @@
+//! Wake-word gate for Automatic mode.
+//!
+//! Automatic mode transcribes everything the VAD hears. When the gate is
+//! enabled, a transcript is only pasted if it starts with the wake phrase
+//! (e.g. "hey flow"), and the phrase is removed from the text. The check runs
+//! on the Whisper transcript, so no separate keyword model is needed.
+
+use flowstt_common::normalize_word;
+
+/// Shortest wake phrase word that may be matched fuzzily
+const FUZZY_MIN_LETTERS: usize = 4;
+
+/// Whether a transcribed word is a near miss of a wake phrase word.
+///
+/// Whisper often misspells the end of uncommon words ("flo", "floe" for
+/// "flow"), so words of four or more letters may differ by one edit at their
+/// last letter. Edits elsewhere, or to short words, mostly produce other
+/// common words ("slow", "low", "flew"; "they" for "hey").
+fn is_near_miss(heard: &str, expected: &str) -> bool {
+    let expected_len = expected.chars().count();
+    let common_prefix = heard
+        .chars()
+        .zip(expected.chars())
+        .take_while(|(h, e)| h == e)
+        .count();
+    expected_len >= FUZZY_MIN_LETTERS
+        && common_prefix + 1 >= expected_len
+        && edit_distance(heard, expected) <= 1
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Checks transcripts for a leading wake phrase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WakeWordGate {
+    /// Normalized words of the wake phrase
+    words: Vec<String>,
+}
+
+impl WakeWordGate {
+    /// Create a gate for `phrase`. Returns None if the phrase has no words.
+    pub fn new(phrase: &str) -> Option<Self> {
+        let words: Vec<String> = phrase
+            .split_whitespace()
+            .map(normalize_word)
+            .filter(|w| !w.is_empty())
+            .collect();
+        if words.is_empty() {
+            None
+        } else {
+            Some(Self { words })
+        }
+    }
+
+    /// If `text` starts with the wake phrase, return the text that follows
+    /// it (with the first letter capitalized). Returns None otherwise.
+    ///
+    /// At most one word of the phrase may be a near miss (see
+    /// [`is_near_miss`]); the others must match exactly.
+    pub fn strip(&self, text: &str) -> Option<String> {
+        let mut rest = text.trim_start();
+        let mut near_miss_used = false;
+        for expected in &self.words {
+            // Skip words made only of punctuation (e.g. a leading "-")
+            let heard = loop {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let (word, after) = rest.split_at(end);
+                if word.is_empty() {
+                    return None;
+                }
+                rest = after.trim_start();
+                let normalized = normalize_word(word);
+                if !normalized.is_empty() {
+                    break normalized;
+                }
+            };
+            if heard != *expected {
+                if near_miss_used || !is_near_miss(&heard, expected) {
+                    return None;
+                }
+                near_miss_used = true;
+            }
+        }
+
+        // Drop punctuation left between the phrase and the command
+        let rest = rest.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let mut chars = rest.chars();
+        Some(match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate() -> WakeWordGate {
+        WakeWordGate::new("Hey Flow").unwrap()
+    }
+
+    #[test]
+    fn strips_leading_wake_phrase() {
+        assert_eq!(
+            gate().strip("Hey, Flow. send the report to Sam."),
+            Some("Send the report to Sam.".into())
+        );
+        assert_eq!(
+            gate().strip("hey flow"),
+            Some(String::new()),
+            "phrase alone is detected but leaves nothing to paste"
+        );
+    }
+
+    #[test]
+    fn tolerates_whisper_misspellings() {
+        assert_eq!(
+            gate().strip("Hey Flo, open the door"),
+            Some("Open the door".into())
+        );
+        assert_eq!(gate().strip("hey floe go"), Some("Go".into()));
+        assert_eq!(gate().strip("hey flows go"), Some("Go".into()));
+    }
+
+    #[test]
+    fn rejects_near_misses_that_are_other_words() {
+        for text in [
+            "They flow into the river",
+            "Hee flow",
+            "Hay flow",
+            "Hey slow down",
+            "Hey, glow sticks",
+            "hey low battery",
+            "Hey flew home",
+            "Hey flowing water",
+        ] {
+            assert_eq!(gate().strip(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn only_one_word_may_be_a_near_miss() {
+        let gate = WakeWordGate::new("hello computer").unwrap();
+        assert_eq!(
+            gate.strip("Hello compute, lights on"),
+            Some("Lights on".into())
+        );
+        assert_eq!(
+            gate.strip("Hell computer, lights on"),
+            Some("Lights on".into())
+        );
+        assert_eq!(gate.strip("Hell compute, lights on"), None);
+    }
+
+    #[test]
+    fn rejects_text_without_leading_phrase() {
+        assert_eq!(gate().strip("Send the report, hey flow."), None);
+        assert_eq!(gate().strip("Hey there, Flow"), None);
+        assert_eq!(gate().strip("Hey"), None);
+        assert_eq!(gate().strip(""), None);
+    }
+
+    #[test]
+    fn empty_phrase_has_no_gate() {
+        assert_eq!(WakeWordGate::new(" , "), None);
+    }
+}
@@ -390,6 +390,8 @@ pub struct WhisperLibrary {
     full_n_segments: unsafe extern "C" fn(ctx: WhisperContext) -> c_int,
     full_get_segment_text:
         unsafe extern "C" fn(ctx: WhisperContext, i_segment: c_int) -> *const c_char,
+    full_get_segment_t0: unsafe extern "C" fn(ctx: WhisperContext, i_segment: c_int) -> i64,
+    full_get_segment_t1: unsafe extern "C" fn(ctx: WhisperContext, i_segment: c_int) -> i64,
     print_system_info: unsafe extern "C" fn() -> *const c_char,
 }
 
@@ -440,6 +442,18 @@ impl WhisperLibrary {
                 )
                 .map_err(|e| format!("Failed to load whisper_full_get_segment_text: {}", e))?;
 
+            let full_get_segment_t0 = *lib
+                .get::<unsafe extern "C" fn(WhisperContext, c_int) -> i64>(
+                    b"whisper_full_get_segment_t0\0",
+                )
+                .map_err(|e| format!("Failed to load whisper_full_get_segment_t0: {}", e))?;
+
+            let full_get_segment_t1 = *lib
+                .get::<unsafe extern "C" fn(WhisperContext, c_int) -> i64>(
+                    b"whisper_full_get_segment_t1\0",
+                )
+                .map_err(|e| format!("Failed to load whisper_full_get_segment_t1: {}", e))?;
+
             let print_system_info = *lib
                 .get::<unsafe extern "C" fn() -> *const c_char>(b"whisper_print_system_info\0")
                 .map_err(|e| format!("Failed to load whisper_print_system_info: {}", e))?;
@@ -452,6 +466,8 @@ impl WhisperLibrary {
                 full,
                 full_n_segments,
                 full_get_segment_text,
+                full_get_segment_t0,
+                full_get_segment_t1,
                 print_system_info,
             })
         }
@@ -746,6 +762,16 @@ impl Context {
             .map(|s| s.to_string())
             .map_err(|e| format!("Invalid UTF-8 in segment: {}", e))
     }
+
+    /// Get the start and end time of a specific segment in milliseconds
+    pub fn full_get_segment_times_ms(&self, i_segment: i32) -> Result<(u64, u64), String> {
+        let lib = get_lib()?;
+
+        // whisper.cpp reports times in 10ms units
+        let t0 = unsafe { (lib.full_get_segment_t0)(self.ptr, i_segment) };
+        let t1 = unsafe { (lib.full_get_segment_t1)(self.ptr, i_segment) };
+        Ok((t0.max(0) as u64 * 10, t1.max(0) as u64 * 10))
+    }
 }
 
 impl Drop for Context {
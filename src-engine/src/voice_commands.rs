@@ -0,0 +1,234 @@
+//! Spoken editing commands.
+//!
+//! Turns a transcript such as "Dear Sam comma new paragraph thanks for the
+//! notes period press enter" into text and keystrokes for
+//! [`crate::clipboard::type_edits`]. The grammar comes from
+//! `Config::voice_commands`; parsing depends only on the transcript text.
+
+use flowstt_common::{normalize_word, EditKey, VoiceCommand, VoiceCommandAction};
+
+/// One step of typing a transcript into the foreground application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditOp {
+    /// Paste text
+    Text(String),
+    /// Press a key or shortcut
+    Key(EditKey),
+    /// Delete the text typed for the previous transcript
+    DeletePrevious,
+}
+
+/// Render the text of a sequence of edits (keystrokes are dropped).
+pub fn edits_text(edits: &[EditOp]) -> String {
+    edits
+        .iter()
+        .filter_map(|op| match op {
+            EditOp::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Punctuation Whisper writes around a spoken punctuation command
+fn is_auto_punctuation(c: char) -> bool {
+    matches!(c, ',' | '.' | ';' | ':' | '!' | '?' | ' ')
+}
+
+/// Matches voice command phrases in transcripts.
+pub struct VoiceCommandParser {
+    /// Normalized phrase words and actions, longest phrases first
+    commands: Vec<(Vec<String>, VoiceCommandAction)>,
+}
+
+impl VoiceCommandParser {
+    /// Create a parser for a grammar.
+    pub fn new(commands: &[VoiceCommand]) -> Self {
+        let mut commands: Vec<(Vec<String>, VoiceCommandAction)> = commands
+            .iter()
+            .map(|c| (c.phrase_words(), c.action.clone()))
+            .filter(|(words, _)| !words.is_empty())
+            .collect();
+        // Prefer "new paragraph" over a shorter phrase that is its prefix
+        commands.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self { commands }
+    }
+
+    /// Split a transcript into text and keystrokes.
+    pub fn parse(&self, text: &str) -> Vec<EditOp> {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let words: Vec<String> = tokens.iter().map(|t| normalize_word(t)).collect();
+
+        let mut builder = EditBuilder::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            let matched = self
+                .commands
+                .iter()
+                .find(|(phrase, _)| words[i..].starts_with(phrase));
+            match matched {
+                Some((phrase, action)) => {
+                    builder.command(action);
+                    // Whisper's punctuation on the command words is dropped
+                    i += phrase.len();
+                }
+                None => {
+                    builder.word(tokens[i]);
+                    i += 1;
+                }
+            }
+        }
+        builder.finish()
+    }
+}
+
+/// Accumulates the edits of one transcript.
+#[derive(Default)]
+struct EditBuilder {
+    ops: Vec<EditOp>,
+    /// Text not yet emitted as an op
+    text: String,
+    /// Where the phrase "delete that" would remove begins in `text`
+    phrase_start: Option<usize>,
+    /// Whether the last thing added was a dictated word or punctuation
+    in_phrase: bool,
+}
+
+impl EditBuilder {
+    fn word(&mut self, token: &str) {
+        if !self.in_phrase {
+            self.phrase_start = Some(self.text.len());
+            self.in_phrase = true;
+        }
+        if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+            self.text.push(' ');
+        }
+        self.text.push_str(token);
+    }
+
+    fn command(&mut self, action: &VoiceCommandAction) {
+        match action {
+            VoiceCommandAction::Punctuation { text } => {
+                let end = self.text.trim_end_matches(is_auto_punctuation).len();
+                self.text.truncate(end);
+                self.text.push_str(text);
+            }
+            VoiceCommandAction::Insert { text } => {
+                let end = self.text.trim_end_matches(' ').len();
+                self.text.truncate(end);
+                self.text.push_str(text);
+                self.in_phrase = false;
+            }
+            VoiceCommandAction::Key { key } => {
+                self.flush();
+                self.ops.push(EditOp::Key(*key));
+                self.phrase_start = None;
+                self.in_phrase = false;
+            }
+            VoiceCommandAction::DeleteThat => {
+                match self.phrase_start.take() {
+                    Some(start) => {
+                        let start = start.min(self.text.len());
+                        self.text.truncate(start);
+                    }
+                    // Spoken first: undo the previous transcript
+                    None if self.text.is_empty() && self.ops.is_empty() => {
+                        self.ops.push(EditOp::DeletePrevious);
+                    }
+                    None => {}
+                }
+                self.in_phrase = false;
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            self.ops.push(EditOp::Text(std::mem::take(&mut self.text)));
+        }
+    }
+
+    fn finish(mut self) -> Vec<EditOp> {
+        self.flush();
+        self.ops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstt_common::default_voice_commands;
+
+    fn parse(text: &str) -> Vec<EditOp> {
+        VoiceCommandParser::new(&default_voice_commands()).parse(text)
+    }
+
+    fn text(s: &str) -> EditOp {
+        EditOp::Text(s.to_string())
+    }
+
+    #[test]
+    fn plain_dictation_is_unchanged() {
+        assert_eq!(parse(" Hello there."), vec![text("Hello there.")]);
+        assert_eq!(parse(""), vec![]);
+    }
+
+    #[test]
+    fn punctuation_replaces_whisper_punctuation() {
+        assert_eq!(
+            parse("Dear Sam, comma, thanks for the notes. Period."),
+            vec![text("Dear Sam, thanks for the notes.")]
+        );
+        assert_eq!(
+            parse("Are you coming question mark"),
+            vec![text("Are you coming?")]
+        );
+    }
+
+    #[test]
+    fn line_breaks_are_inserted() {
+        assert_eq!(
+            parse("Hi all. New paragraph. The build is green. New line. Thanks"),
+            vec![text("Hi all.\n\nThe build is green.\nThanks")]
+        );
+    }
+
+    #[test]
+    fn keys_split_the_text() {
+        assert_eq!(
+            parse("Looks good to me. Press enter. Select all."),
+            vec![
+                text("Looks good to me."),
+                EditOp::Key(EditKey::Enter),
+                EditOp::Key(EditKey::SelectAll),
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_that_removes_the_last_phrase() {
+        assert_eq!(
+            parse("Hello Sam, new line. This is wrong. Delete that. Thanks"),
+            vec![text("Hello Sam,\nThanks")]
+        );
+        assert_eq!(parse("Scratch that."), vec![EditOp::DeletePrevious]);
+        assert_eq!(
+            parse("Oops delete that delete that"),
+            vec![EditOp::DeletePrevious]
+        );
+    }
+
+    #[test]
+    fn custom_grammar() {
+        let commands = vec![VoiceCommand {
+            phrase: "Send it".into(),
+            action: VoiceCommandAction::Key {
+                key: EditKey::Enter,
+            },
+        }];
+        let parser = VoiceCommandParser::new(&commands);
+        assert_eq!(
+            parser.parse("Ship it, comma. Send it!"),
+            vec![text("Ship it, comma."), EditOp::Key(EditKey::Enter)]
+        );
+    }
+}
@@ -8,7 +8,7 @@ mod tray;
 
 use flowstt_common::config::{Config, ThemeMode};
 use flowstt_common::ipc::{EventType, Request, Response};
-use flowstt_common::{runtime_mode, AudioDevice, HotkeyCombination, RecordingMode, RuntimeMode, TranscriptionMode};
+use flowstt_common::{runtime_mode, AudioDevice, HotkeyCombination, RecordingMode, RuntimeMode, SourceSettings, Speaker, ToggleTalkStopReason, TranscriptionMode};
 use std::env;
 use std::time::Instant;
 use tauri::{AppHandle, Emitter, Listener, Manager, State};
@@ -147,6 +147,12 @@ fn forward_event_to_tauri(app_handle: &AppHandle, event: &EventType) {
                 }
             }
         }
+        EventType::TranscriptionError { error } => {
+            let _ = app_handle.emit("transcription-error", error);
+        }
+        EventType::AecMetrics(metrics) => {
+            let _ = app_handle.emit("aec-metrics", metrics);
+        }
         EventType::SpeechStarted => {
             let _ = app_handle.emit("speech-started", ());
         }
@@ -196,6 +202,42 @@ fn forward_event_to_tauri(app_handle: &AppHandle, event: &EventType) {
         EventType::PttReleased => {
             let _ = app_handle.emit("ptt-released", ());
         }
+        EventType::ToggleTalkStarted => {
+            let _ = app_handle.emit("toggle-talk-started", ());
+        }
+        EventType::ToggleTalkStopped {
+            reason,
+            duration_ms,
+        } => {
+            #[derive(serde::Serialize, Clone)]
+            struct ToggleTalkStopped {
+                reason: ToggleTalkStopReason,
+                duration_ms: u64,
+            }
+            let _ = app_handle.emit(
+                "toggle-talk-stopped",
+                ToggleTalkStopped {
+                    reason: *reason,
+                    duration_ms: *duration_ms,
+                },
+            );
+        }
+        // Only sent to the client that queued the job
+        EventType::AudioTranscriptionComplete { .. } => {}
+        EventType::HookFailed { hook, error } => {
+            #[derive(serde::Serialize, Clone)]
+            struct HookFailed {
+                hook: String,
+                error: String,
+            }
+            let _ = app_handle.emit(
+                "hook-failed",
+                HookFailed {
+                    hook: hook.clone(),
+                    error: error.clone(),
+                },
+            );
+        }
         EventType::TranscriptionModeChanged { mode } => {
             let _ = app_handle.emit("transcription-mode-changed", mode);
         }
@@ -222,7 +264,7 @@ async fn list_all_sources() -> Result<Vec<AudioDevice>, String> {
     .await;
     match response {
         Response::Devices { devices } => Ok(devices),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -232,15 +274,19 @@ async fn list_all_sources() -> Result<Vec<AudioDevice>, String> {
 async fn set_sources(
     source1_id: Option<String>,
     source2_id: Option<String>,
+    source1_settings: Option<SourceSettings>,
+    source2_settings: Option<SourceSettings>,
 ) -> Result<(), String> {
     let response = flowstt_engine::ipc::handlers::handle_request(Request::SetSources {
         source1_id,
         source2_id,
+        source1_settings,
+        source2_settings,
     })
     .await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -252,7 +298,7 @@ async fn set_aec_enabled(enabled: bool) -> Result<(), String> {
         flowstt_engine::ipc::handlers::handle_request(Request::SetAecEnabled { enabled }).await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -264,7 +310,7 @@ async fn set_recording_mode(mode: RecordingMode) -> Result<(), String> {
         flowstt_engine::ipc::handlers::handle_request(Request::SetRecordingMode { mode }).await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -286,7 +332,7 @@ async fn check_model_status() -> Result<LocalModelStatus, String> {
             available: status.available,
             path: status.path,
         }),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -298,7 +344,7 @@ async fn download_model() -> Result<(), String> {
         flowstt_engine::ipc::handlers::handle_request(Request::DownloadModel).await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -322,7 +368,7 @@ async fn get_cuda_status() -> Result<LocalCudaStatus, String> {
             runtime_available: status.runtime_available,
             system_info: status.system_info,
         }),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -354,7 +400,7 @@ async fn get_status() -> Result<LocalStatus, String> {
             source2_id: status.source2_id,
             transcription_mode: status.transcription_mode,
         }),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -367,6 +413,8 @@ struct LocalPttStatus {
     auto_toggle_hotkeys: Vec<HotkeyCombination>,
     auto_mode_active: bool,
     is_active: bool,
+    toggle_talk_hotkeys: Vec<HotkeyCombination>,
+    toggle_talk_active: bool,
     available: bool,
     error: Option<String>,
 }
@@ -380,7 +428,7 @@ async fn set_transcription_mode(mode: TranscriptionMode) -> Result<(), String> {
     .await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -394,7 +442,7 @@ async fn set_ptt_hotkeys(hotkeys: Vec<HotkeyCombination>) -> Result<(), String>
     .await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -411,10 +459,12 @@ async fn get_ptt_status() -> Result<LocalPttStatus, String> {
             auto_toggle_hotkeys: status.auto_toggle_hotkeys,
             auto_mode_active: status.auto_mode_active,
             is_active: status.is_active,
+            toggle_talk_hotkeys: status.toggle_talk_hotkeys,
+            toggle_talk_active: status.toggle_talk_active,
             available: status.available,
             error: status.error,
         }),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -428,7 +478,35 @@ async fn set_auto_toggle_hotkeys(hotkeys: Vec<HotkeyCombination>) -> Result<(),
     .await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Set the toggle-to-talk hotkey combinations
+#[tauri::command]
+async fn set_toggle_talk_hotkeys(hotkeys: Vec<HotkeyCombination>) -> Result<(), String> {
+    let response = flowstt_engine::ipc::handlers::handle_request(
+        Request::SetToggleTalkHotkeys { hotkeys },
+    )
+    .await;
+    match response {
+        Response::Ok => Ok(()),
+        Response::Error { message, .. } => Err(message),
+        _ => Err("Unexpected response".into()),
+    }
+}
+
+/// Set the toggle-to-talk silence timeout (0 disables it)
+#[tauri::command]
+async fn set_toggle_talk_silence_timeout(timeout_ms: u32) -> Result<(), String> {
+    let response = flowstt_engine::ipc::handlers::handle_request(
+        Request::SetToggleTalkSilenceTimeout { timeout_ms },
+    )
+    .await;
+    match response {
+        Response::Ok => Ok(()),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -444,11 +522,11 @@ async fn toggle_auto_mode() -> Result<TranscriptionMode, String> {
                 flowstt_engine::ipc::handlers::handle_request(Request::GetPttStatus).await;
             match status_response {
                 Response::PttStatus(status) => Ok(status.mode),
-                Response::Error { message } => Err(message),
+                Response::Error { message, .. } => Err(message),
                 _ => Err("Unexpected response".into()),
             }
         }
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -460,6 +538,8 @@ struct LocalHistoryEntry {
     text: String,
     timestamp: String,
     wav_path: Option<String>,
+    speaker: Option<Speaker>,
+    speaker_id: Option<u32>,
 }
 
 /// Get transcription history
@@ -475,9 +555,11 @@ async fn get_history() -> Result<Vec<LocalHistoryEntry>, String> {
                 text: e.text,
                 timestamp: e.timestamp,
                 wav_path: e.wav_path,
+                speaker: e.speaker,
+                speaker_id: e.speaker_id,
             })
             .collect()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -491,7 +573,7 @@ async fn delete_history_entry(id: String) -> Result<(), String> {
     .await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -568,6 +650,8 @@ async fn complete_setup(
         let _ = flowstt_engine::ipc::handlers::handle_request(Request::SetSources {
             source1_id,
             source2_id,
+            source1_settings: None,
+            source2_settings: None,
         })
         .await;
     }
@@ -596,7 +680,7 @@ async fn test_audio_device(device_id: String) -> Result<(), String> {
     .await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -608,7 +692,7 @@ async fn stop_test_audio_device() -> Result<(), String> {
         flowstt_engine::ipc::handlers::handle_request(Request::StopTestAudioDevice).await;
     match response {
         Response::Ok => Ok(()),
-        Response::Error { message } => Err(message),
+        Response::Error { message, .. } => Err(message),
         _ => Err("Unexpected response".into()),
     }
 }
@@ -788,6 +872,8 @@ pub fn run() {
             set_ptt_hotkeys,
             get_ptt_status,
             set_auto_toggle_hotkeys,
+            set_toggle_talk_hotkeys,
+            set_toggle_talk_silence_timeout,
             toggle_auto_mode,
             get_history,
             delete_history_entry,
@@ -35,7 +35,10 @@ interface CaptureStatus {
 }
 
 // Transcription mode matching backend
-type TranscriptionMode = "automatic" | "push_to_talk";
+type TranscriptionMode = "automatic" | "push_to_talk" | "toggle_to_talk";
+
+// Speaker label for speaker-separated recordings
+type Speaker = "me" | "remote";
 
 // History entry from the service
 interface HistoryEntry {
@@ -43,6 +46,8 @@ interface HistoryEntry {
   text: string;
   timestamp: string;
   wav_path: string | null;
+  speaker?: Speaker | null;
+  speaker_id?: number | null;
 }
 
 // Enriched transcription result payload
@@ -51,6 +56,8 @@ interface TranscriptionCompletePayload {
   text: string;
   timestamp: string | null;
   audio_path: string | null;
+  speaker?: Speaker | null;
+  speaker_id?: number | null;
 }
 
 // DOM elements
@@ -136,11 +143,20 @@ async function setupEventListeners() {
           text: payload.text,
           timestamp: payload.timestamp,
           wav_path: payload.audio_path,
+          speaker: payload.speaker,
+          speaker_id: payload.speaker_id,
         });
       }
     });
   }
 
+  // Segments that could not be transcribed (queue full or Whisper failed)
+  if (!transcriptionErrorUnlisten) {
+    transcriptionErrorUnlisten = await listen<string>("transcription-error", (event) => {
+      console.error("[Transcription] Error:", event.payload);
+    });
+  }
+
   // Capture state changes
   if (!captureStateChangedUnlisten) {
     captureStateChangedUnlisten = await listen<{capturing: boolean, error: string | null}>(
@@ -219,6 +235,14 @@ function formatTimestamp(isoString: string): string {
   }
 }
 
+/** Label for the speaker of a segment, e.g. "me", "remote 2" or "speaker 1" */
+function formatSpeaker(entry: HistoryEntry): string | null {
+  if (entry.speaker === "remote" && entry.speaker_id) return `remote ${entry.speaker_id}`;
+  if (entry.speaker) return entry.speaker;
+  if (entry.speaker_id) return `speaker ${entry.speaker_id}`;
+  return null;
+}
+
 /** Create a DOM element for a history segment */
 function createSegmentElement(entry: HistoryEntry): HTMLElement {
   const row = document.createElement("div");
@@ -231,6 +255,15 @@ function createSegmentElement(entry: HistoryEntry): HTMLElement {
   ts.textContent = formatTimestamp(entry.timestamp);
   row.appendChild(ts);
 
+  // Speaker label (speaker-separated and diarized recordings)
+  const speakerLabel = formatSpeaker(entry);
+  if (speakerLabel) {
+    const speaker = document.createElement("span");
+    speaker.className = `segment-speaker segment-speaker-${entry.speaker ?? "diarized"}`;
+    speaker.textContent = speakerLabel;
+    row.appendChild(speaker);
+  }
+
   // Text
   const text = document.createElement("span");
   text.className = "segment-text";
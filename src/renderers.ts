@@ -18,6 +18,7 @@ export interface SpeechMetrics {
   is_lookback_speech: boolean; // Whether this is lookback-determined speech
   lookback_offset_ms: number | null; // Lookback offset when speech just confirmed
   is_word_break: boolean;    // Whether a word break (inter-word gap) is detected
+  applied_gain_db?: number;  // Gain applied by automatic gain control in dB
 }
 
 export interface VisualizationPayload {
@@ -9,11 +9,25 @@ import {
   VisualizationPayload,
 } from "./renderers";
 
+// Echo cancellation metrics (AecMetrics in flowstt-common)
+interface AecMetricsPayload {
+  sample_rate: number;
+  processing_rate: number;
+  estimated_delay_ms: number | null;
+  delay_confidence: number;
+  erle_db: number;
+  echo_return_loss_db: number;
+  render_active: boolean;
+  converged: boolean;
+}
+
 // DOM elements
 let waveformCanvas: HTMLCanvasElement | null;
 let spectrogramCanvas: HTMLCanvasElement | null;
 let speechActivityCanvas: HTMLCanvasElement | null;
 let closeBtn: HTMLButtonElement | null;
+let aecStatus: HTMLElement | null;
+let aecDetails: HTMLElement | null;
 
 // Renderers
 let waveformRenderer: WaveformRenderer | null = null;
@@ -23,6 +37,12 @@ let speechActivityRenderer: SpeechActivityRenderer | null = null;
 // Event listeners
 let visualizationUnlisten: UnlistenFn | null = null;
 let closeRequestedUnlisten: UnlistenFn | null = null;
+let aecMetricsUnlisten: UnlistenFn | null = null;
+
+// Metrics arrive every 500ms while echo cancellation runs; hide the status
+// once they stop (AEC disabled or capture stopped)
+const AEC_METRICS_TIMEOUT_MS = 2000;
+let aecHideTimer: ReturnType<typeof setTimeout> | null = null;
 
 async function setupVisualizationListener() {
   if (visualizationUnlisten) return;
@@ -43,11 +63,48 @@ async function setupVisualizationListener() {
   });
 }
 
+function showAecMetrics(metrics: AecMetricsPayload) {
+  if (!aecStatus || !aecDetails) return;
+
+  const state = metrics.converged
+    ? "converged"
+    : metrics.render_active
+      ? "adapting"
+      : "idle";
+  const delay =
+    metrics.estimated_delay_ms !== null ? `${metrics.estimated_delay_ms} ms` : "unknown";
+  aecDetails.textContent = `${state} · ERLE ${metrics.erle_db.toFixed(1)} dB · delay ${delay}`;
+  aecStatus.title =
+    `Echo return loss ${metrics.echo_return_loss_db.toFixed(1)} dB, ` +
+    `delay confidence ${(metrics.delay_confidence * 100).toFixed(0)}%, ` +
+    `${metrics.sample_rate} Hz (processing ${metrics.processing_rate} Hz)`;
+  aecStatus.classList.toggle("converged", metrics.converged);
+  aecStatus.hidden = false;
+
+  if (aecHideTimer) clearTimeout(aecHideTimer);
+  aecHideTimer = setTimeout(() => {
+    if (aecStatus) aecStatus.hidden = true;
+    aecHideTimer = null;
+  }, AEC_METRICS_TIMEOUT_MS);
+}
+
+async function setupAecMetricsListener() {
+  if (aecMetricsUnlisten) return;
+
+  aecMetricsUnlisten = await listen<AecMetricsPayload>("aec-metrics", (event) => {
+    showAecMetrics(event.payload);
+  });
+}
+
 function cleanupVisualizationListener() {
   if (visualizationUnlisten) {
     visualizationUnlisten();
     visualizationUnlisten = null;
   }
+  if (aecMetricsUnlisten) {
+    aecMetricsUnlisten();
+    aecMetricsUnlisten = null;
+  }
 }
 
 function startRenderers() {
@@ -87,6 +144,8 @@ window.addEventListener("DOMContentLoaded", async () => {
   spectrogramCanvas = document.querySelector("#spectrogram-canvas");
   speechActivityCanvas = document.querySelector("#speech-activity-canvas");
   closeBtn = document.querySelector("#close-btn");
+  aecStatus = document.querySelector("#aec-status");
+  aecDetails = document.querySelector("#aec-details");
 
   // Initialize renderers
   if (waveformCanvas) {
@@ -126,6 +185,7 @@ window.addEventListener("DOMContentLoaded", async () => {
 
   // Setup visualization event listener and start renderers
   await setupVisualizationListener();
+  await setupAecMetricsListener();
   startRenderers();
 
   // Intercept close requests and hide instead of destroying the window